@@ -1,14 +1,25 @@
-use crate::{bot::Bot, character::Character, level::UpdateContext, player::Player};
+use crate::{
+    bot::{attack_slots::AttackSlots, lod::Observer, Bot},
+    character::Character,
+    item::ItemKind,
+    level::UpdateContext,
+    player::Player,
+};
 use rg3d::{
     core::{
         algebra::Vector3,
+        math::ray::Ray,
         pool::{
             Handle, Pool, PoolIterator, PoolIteratorMut, PoolPairIterator, PoolPairIteratorMut,
         },
         visitor::{Visit, VisitResult, Visitor},
     },
+    physics::geometry::InteractionGroups,
     resource::texture::Texture,
-    scene::Scene,
+    scene::{
+        physics::{Physics, RayCastOptions},
+        Scene,
+    },
 };
 use std::ops::{Deref, DerefMut};
 
@@ -97,27 +108,52 @@ impl Visit for Actor {
     }
 }
 
-// Helper struct that used to hold information about possible target for bots
-// it contains all needed information to select suitable target. This is needed
-// because of borrowing rules that does not allows to have a mutable reference
-// to array element and iterate over array using immutable borrow.
-pub struct TargetDescriptor {
+/// Information about a corpse that still has some loot, it is used by player to show
+/// loot prompt when looking at the corpse.
+pub struct CorpseDescriptor {
     pub handle: Handle<Actor>,
-    pub health: f32,
     pub position: Vector3<f32>,
+    /// First item of the loot and amount of items of the same kind.
+    pub item: ItemKind,
+    pub count: u32,
+}
+
+/// Checks if there is no static geometry between two points. Only trimesh colliders are
+/// treated as obstacles, so actors do not occlude each other. Tiny tolerance at the end of
+/// the ray allows the end point to lie on a surface (e.g. center of an explosion).
+pub fn is_line_of_sight_clear(physics: &Physics, begin: Vector3<f32>, end: Vector3<f32>) -> bool {
+    let ray = Ray::from_two_points(begin, end);
+    let mut query_buffer = Vec::default();
+    physics.cast_ray(
+        RayCastOptions {
+            ray,
+            groups: InteractionGroups::all(),
+            max_len: (ray.dir.norm() - 0.01).max(0.0),
+            sort_results: false,
+        },
+        &mut query_buffer,
+    );
+    query_buffer.iter().all(|hit| {
+        physics
+            .colliders
+            .get(hit.collider.into())
+            .map_or(true, |collider| collider.shape().as_trimesh().is_none())
+    })
 }
 
 #[derive(Default)]
 pub struct ActorContainer {
     pool: Pool<Actor>,
-    target_descriptors: Vec<TargetDescriptor>,
+    corpse_descriptors: Vec<CorpseDescriptor>,
+    attack_slots: AttackSlots,
 }
 
 impl ActorContainer {
     pub fn new() -> Self {
         Self {
             pool: Default::default(),
-            target_descriptors: Default::default(),
+            corpse_descriptors: Default::default(),
+            attack_slots: Default::default(),
         }
     }
 
@@ -133,6 +169,39 @@ impl ActorContainer {
         self.pool.is_valid_handle(actor)
     }
 
+    /// Decides whether damage dealt by `who` to `actor` must be applied. Messages are
+    /// processed in order of sending, so either of them could be removed before the damage
+    /// arrives: damage to a removed actor is ignored, damage from a removed attacker is
+    /// applied without an attacker. Returns the attacker to credit, or `None` if the damage
+    /// must be ignored.
+    pub fn resolve_damage(
+        &self,
+        actor: Handle<Actor>,
+        who: Handle<Actor>,
+        friendly_fire: bool,
+    ) -> Option<Handle<Actor>> {
+        if !self.contains(actor) {
+            return None;
+        }
+
+        let who = if self.contains(who) {
+            who
+        } else {
+            Handle::NONE
+        };
+
+        // Actors still can damage themselves (with grenades for example).
+        if !friendly_fire
+            && who.is_some()
+            && who != actor
+            && self.get(who).faction == self.get(actor).faction
+        {
+            None
+        } else {
+            Some(who)
+        }
+    }
+
     pub fn get_mut(&mut self, actor: Handle<Actor>) -> &mut Actor {
         self.pool.borrow_mut(actor)
     }
@@ -143,6 +212,7 @@ impl ActorContainer {
                 bot.on_actor_removed(actor_handle);
             }
         }
+        self.attack_slots.on_actor_removed(actor_handle);
 
         self.pool.free(actor_handle);
     }
@@ -152,23 +222,72 @@ impl ActorContainer {
     }
 
     pub fn update(&mut self, context: &mut UpdateContext) {
-        self.target_descriptors.clear();
+        self.corpse_descriptors.clear();
         for (handle, actor) in self.pool.pair_iter() {
-            if !actor.is_dead() {
-                self.target_descriptors.push(TargetDescriptor {
-                    handle,
-                    health: actor.health,
-                    position: actor.position(&context.scene.graph),
-                });
+            if let (true, Actor::Bot(bot)) = (actor.is_dead(), actor) {
+                if let Some(&item) = bot.loot().first() {
+                    self.corpse_descriptors.push(CorpseDescriptor {
+                        handle,
+                        position: actor.position(&context.scene.graph),
+                        item,
+                        count: bot.loot().iter().filter(|&&k| k == item).count() as u32,
+                    });
+                }
             }
         }
 
-        for (handle, actor) in self.pool.pair_iter_mut() {
-            match actor {
-                Actor::Bot(bot) => bot.update(handle, context, &self.target_descriptors),
-                Actor::Player(player) => player.update(handle, context),
+        // Bots are updated at reduced rate when they are far from the player's camera.
+        let observer = self.pool.iter().find_map(|actor| match actor {
+            Actor::Player(player) => {
+                Observer::from_camera(&context.scene.graph, player.camera_controller().camera())
+            }
+            Actor::Bot(_) => None,
+        });
+
+        // Each actor is taken out of the pool while it is updated, so bots can query other
+        // actors of the container. Queries do not see the actor that is being updated.
+        let mut attack_slots = std::mem::take(&mut self.attack_slots);
+        let handles = self
+            .pool
+            .pair_iter()
+            .map(|(handle, _)| handle)
+            .collect::<Vec<_>>();
+        for handle in handles {
+            let (ticket, mut actor) = self.pool.take_reserve(handle);
+            match &mut actor {
+                Actor::Bot(bot) => {
+                    bot.update(handle, context, self, &mut attack_slots, observer.as_ref())
+                }
+                Actor::Player(player) => player.update(handle, context, &self.corpse_descriptors),
             }
+            self.pool.put_back(ticket, actor);
         }
+        self.attack_slots = attack_slots;
+    }
+
+    /// Returns handles of alive actors within given radius, optionally filtering out actors
+    /// that are behind static geometry as seen from the center.
+    pub fn actors_in_radius(
+        &self,
+        scene: &Scene,
+        center: Vector3<f32>,
+        radius: f32,
+        line_of_sight: bool,
+    ) -> Vec<Handle<Actor>> {
+        self.pool
+            .pair_iter()
+            .filter(|(_, actor)| !actor.is_dead())
+            .filter_map(|(handle, actor)| {
+                let position = actor.position(&scene.graph);
+                if position.metric_distance(&center) <= radius
+                    && (!line_of_sight || is_line_of_sight_clear(&scene.physics, position, center))
+                {
+                    Some(handle)
+                } else {
+                    None
+                }
+            })
+            .collect()
     }
 
     pub fn iter(&self) -> PoolIterator<Actor> {
@@ -218,3 +337,49 @@ impl Visit for ActorContainer {
         visitor.leave_region()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character::Faction;
+
+    #[test]
+    fn damage_to_removed_actor_is_ignored() {
+        let mut actors = ActorContainer::new();
+        let who = actors.add(Actor::default());
+        let victim = actors.add(Actor::default());
+        actors.free(victim);
+
+        assert_eq!(actors.resolve_damage(victim, who, true), None);
+        assert_eq!(actors.resolve_damage(victim, Handle::NONE, true), None);
+    }
+
+    #[test]
+    fn damage_from_removed_attacker_has_no_attacker() {
+        let mut actors = ActorContainer::new();
+        let who = actors.add(Actor::default());
+        let victim = actors.add(Actor::default());
+        actors.free(who);
+        // Slot of the removed attacker is reused, but stale handle must not alias new actor.
+        let newcomer = actors.add(Actor::default());
+
+        assert_ne!(newcomer, who);
+        assert_eq!(actors.resolve_damage(victim, who, true), Some(Handle::NONE));
+    }
+
+    #[test]
+    fn friendly_fire_is_checked_by_faction() {
+        let mut actors = ActorContainer::new();
+        let who = actors.add(Actor::default());
+        let ally = actors.add(Actor::default());
+        let mut enemy = Actor::default();
+        enemy.faction = Faction::Player;
+        let enemy = actors.add(enemy);
+
+        assert_eq!(actors.resolve_damage(ally, who, false), None);
+        assert_eq!(actors.resolve_damage(ally, who, true), Some(who));
+        assert_eq!(actors.resolve_damage(enemy, who, false), Some(who));
+        // Own grenades hurt regardless of friendly fire.
+        assert_eq!(actors.resolve_damage(who, who, false), Some(who));
+    }
+}
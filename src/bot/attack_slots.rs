@@ -0,0 +1,66 @@
+//! Attack slots limit amount of bots that can attack the same target at a time. A bot must
+//! acquire a slot before it switches to attack state, the rest of the bots in melee range
+//! circle around the target waiting for a free slot. Slots are not saved, bots will acquire
+//! them again on next update after a save is loaded.
+
+use crate::actor::Actor;
+use rg3d::{core::pool::Handle, lazy_static::lazy_static};
+use serde::Deserialize;
+use std::{collections::HashMap, fs::File};
+
+#[derive(Deserialize)]
+pub struct AttackSlotsDefinition {
+    pub max_attackers_per_target: u32,
+}
+
+impl AttackSlotsDefinition {
+    pub fn new() -> Self {
+        let file = File::open("data/configs/attack_slots.ron").unwrap();
+        ron::de::from_reader(file).unwrap()
+    }
+}
+
+lazy_static! {
+    static ref DEFINITION: AttackSlotsDefinition = AttackSlotsDefinition::new();
+}
+
+#[derive(Default)]
+pub struct AttackSlots {
+    /// Target to list of its attackers mapping.
+    slots: HashMap<Handle<Actor>, Vec<Handle<Actor>>>,
+}
+
+impl AttackSlots {
+    pub fn get_definition() -> &'static AttackSlotsDefinition {
+        &DEFINITION
+    }
+
+    /// Tries to occupy a slot of given target, returns true if the attacker already has
+    /// the slot or if there was a free slot.
+    pub fn try_acquire(&mut self, target: Handle<Actor>, attacker: Handle<Actor>) -> bool {
+        let attackers = self.slots.entry(target).or_default();
+        if attackers.contains(&attacker) {
+            true
+        } else if attackers.len() < Self::get_definition().max_attackers_per_target as usize {
+            attackers.push(attacker);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Frees every slot occupied by given attacker.
+    pub fn release(&mut self, attacker: Handle<Actor>) {
+        for attackers in self.slots.values_mut() {
+            attackers.retain(|&a| a != attacker);
+        }
+        self.slots.retain(|_, attackers| !attackers.is_empty());
+    }
+
+    /// Must be called when an actor is removed, it frees its own slots as well as slots
+    /// occupied by others to attack it.
+    pub fn on_actor_removed(&mut self, actor: Handle<Actor>) {
+        self.slots.remove(&actor);
+        self.release(actor);
+    }
+}
@@ -0,0 +1,138 @@
+//! Bots do not detect a target instantly when it enters their field of view, instead their
+//! awareness of the target builds up over time. It builds up faster when the target is close
+//! and in the open, and slowly fades out when the target is out of sight. Rates are taken from
+//! `data/configs/awareness.ron`.
+
+use crate::actor::Actor;
+use rg3d::{
+    core::{
+        pool::Handle,
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    lazy_static::lazy_static,
+};
+use serde::Deserialize;
+use std::fs::File;
+
+#[derive(Deserialize)]
+pub struct AwarenessDefinition {
+    /// Awareness (from 0 to 1) gained per second for a target that stands in the open right
+    /// in front of a bot.
+    pub max_rate: f32,
+    /// Awareness gained per second for a target at the edge of view distance.
+    pub min_rate: f32,
+    pub view_distance: f32,
+    /// Multiplier of the rate for targets that are only partially visible.
+    pub partial_cover_factor: f32,
+    /// Awareness lost per second for targets that are out of sight.
+    pub decay_rate: f32,
+}
+
+impl AwarenessDefinition {
+    pub fn new() -> Self {
+        let file = File::open("data/configs/awareness.ron").unwrap();
+        ron::de::from_reader(file).unwrap()
+    }
+}
+
+lazy_static! {
+    static ref DEFINITION: AwarenessDefinition = AwarenessDefinition::new();
+}
+
+#[derive(Default)]
+struct TargetAwareness {
+    target: Handle<Actor>,
+    value: f32,
+    /// Whether the target was seen on last update, awareness of unseen targets decays.
+    seen: bool,
+}
+
+impl Visit for TargetAwareness {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.target.visit("Target", visitor)?;
+        self.value.visit("Value", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Awareness of a bot about every potential target it has seen recently.
+#[derive(Default)]
+pub struct Awareness {
+    targets: Vec<TargetAwareness>,
+}
+
+impl Awareness {
+    pub fn get_definition() -> &'static AwarenessDefinition {
+        &DEFINITION
+    }
+
+    /// Increases awareness of a target that is visible at given distance, `fully_visible`
+    /// tells whether the target is in the open or partially covered. Returns true if the
+    /// target is fully detected.
+    pub fn notice(
+        &mut self,
+        target: Handle<Actor>,
+        distance: f32,
+        fully_visible: bool,
+        dt: f32,
+    ) -> bool {
+        let definition = Self::get_definition();
+
+        let k = (distance / definition.view_distance).min(1.0);
+        let mut rate = definition.max_rate + (definition.min_rate - definition.max_rate) * k;
+        if !fully_visible {
+            rate *= definition.partial_cover_factor;
+        }
+
+        let entry = self.entry(target);
+        entry.value = (entry.value + rate * dt).min(1.0);
+        entry.seen = true;
+        entry.value >= 1.0
+    }
+
+    /// Instantly makes the bot fully aware of a target, used when the target touches the bot.
+    pub fn alert(&mut self, target: Handle<Actor>) {
+        let entry = self.entry(target);
+        entry.value = 1.0;
+        entry.seen = true;
+    }
+
+    fn entry(&mut self, target: Handle<Actor>) -> &mut TargetAwareness {
+        match self.targets.iter().position(|t| t.target == target) {
+            Some(index) => &mut self.targets[index],
+            None => {
+                self.targets.push(TargetAwareness {
+                    target,
+                    ..Default::default()
+                });
+                self.targets.last_mut().unwrap()
+            }
+        }
+    }
+
+    /// Must be called once after every target was checked, awareness of targets that were
+    /// not noticed fades out.
+    pub fn update(&mut self, dt: f32) {
+        let decay_rate = Self::get_definition().decay_rate;
+        for entry in self.targets.iter_mut() {
+            if !entry.seen {
+                entry.value -= decay_rate * dt;
+            }
+            entry.seen = false;
+        }
+        self.targets.retain(|t| t.value > 0.0);
+    }
+}
+
+impl Visit for Awareness {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.targets.visit("Targets", visitor)?;
+
+        visitor.leave_region()
+    }
+}
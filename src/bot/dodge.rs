@@ -0,0 +1,68 @@
+//! Bots with a dodge reaction run away from grenades that are about to explode nearby.
+//! Projectiles announce imminent explosions with `Message::GrenadeWarning`, a bot dodges
+//! with a chance given in its definition if it has enough time to react.
+
+use rg3d::core::{
+    algebra::Vector3,
+    visitor::{Visit, VisitResult, Visitor},
+};
+use serde::Deserialize;
+
+#[derive(Deserialize, Copy, Clone)]
+pub struct DodgeDefinition {
+    /// Probability (from 0 to 1) to react to a grenade.
+    pub chance: f32,
+    /// Time (in seconds) that a bot needs to react, grenades that explode sooner are
+    /// not dodged.
+    pub reaction_time: f32,
+}
+
+#[derive(Default)]
+pub struct Dodge {
+    /// Position of the grenade.
+    danger: Vector3<f32>,
+    radius: f32,
+    /// Time left until explosion.
+    time_left: f32,
+}
+
+impl Dodge {
+    /// Extra distance (in meters) beyond blast radius that a bot runs to.
+    const SAFETY_MARGIN: f32 = 1.0;
+
+    pub fn new(danger: Vector3<f32>, radius: f32, time: f32) -> Self {
+        Self {
+            danger,
+            radius,
+            time_left: time,
+        }
+    }
+
+    /// Returns a point outside of blast radius in direction away from the grenade.
+    pub fn destination(&self, position: Vector3<f32>) -> Vector3<f32> {
+        let mut away = position - self.danger;
+        away.y = 0.0;
+        let away = away
+            .try_normalize(std::f32::EPSILON)
+            .unwrap_or_else(Vector3::z);
+        self.danger + away.scale(self.radius + Self::SAFETY_MARGIN)
+    }
+
+    /// Returns false when the grenade has exploded and there is nothing to dodge anymore.
+    pub fn update(&mut self, dt: f32) -> bool {
+        self.time_left -= dt;
+        self.time_left > 0.0
+    }
+}
+
+impl Visit for Dodge {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.danger.visit("Danger", visitor)?;
+        self.radius.visit("Radius", visitor)?;
+        self.time_left.visit("TimeLeft", visitor)?;
+
+        visitor.leave_region()
+    }
+}
@@ -0,0 +1,49 @@
+//! Calm bots that hear something suspicious (a scream of another bot, for example) walk to
+//! the place where the noise came from. Once there, they start searching around it as if
+//! they had lost a target at that place.
+
+use rg3d::core::{
+    algebra::Vector3,
+    visitor::{Visit, VisitResult, Visitor},
+};
+
+#[derive(Default)]
+pub struct Investigation {
+    /// Position of the noise.
+    point: Vector3<f32>,
+    /// Time spent walking to the point.
+    timer: f32,
+}
+
+impl Investigation {
+    const REACH_DISTANCE: f32 = 1.5;
+    /// Bot gives up on a point that it cannot reach in this time.
+    const MAX_WALK_TIME: f32 = 15.0;
+
+    pub fn new(point: Vector3<f32>) -> Self {
+        Self { point, timer: 0.0 }
+    }
+
+    pub fn destination(&self) -> Vector3<f32> {
+        self.point
+    }
+
+    /// Returns false when the bot has reached the point or gave up walking to it.
+    pub fn update(&mut self, position: Vector3<f32>, dt: f32) -> bool {
+        self.timer += dt;
+        let mut offset = self.point - position;
+        offset.y = 0.0;
+        offset.norm() > Self::REACH_DISTANCE && self.timer < Self::MAX_WALK_TIME
+    }
+}
+
+impl Visit for Investigation {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.point.visit("Point", visitor)?;
+        self.timer.visit("Timer", visitor)?;
+
+        visitor.leave_region()
+    }
+}
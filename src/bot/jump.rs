@@ -0,0 +1,63 @@
+//! Bots cross gaps in navmesh by jumping along jump links of a level. A jump is a ballistic
+//! flight from the current position of a bot to the end of a link, its arc rises above the
+//! highest of two points by jump height from the definition of the bot. Its state is not
+//! saved, a bot that was saved mid-air simply lands.
+
+use rg3d::core::algebra::Vector3;
+
+#[derive(Default)]
+pub struct Jump {
+    velocity: Vector3<f32>,
+    flight_time: f32,
+    elapsed: f32,
+    launched: bool,
+}
+
+impl Jump {
+    /// Distance (in meters) to the beginning of a link at which a bot starts the jump.
+    pub const REACH_DISTANCE: f32 = 0.5;
+    /// Extra time (in seconds) after expected landing after which a bot stops jumping even
+    /// if it hasn't touched the ground, so it won't hang in the jump forever.
+    const LANDING_TIMEOUT: f32 = 1.0;
+
+    pub fn new(begin: Vector3<f32>, end: Vector3<f32>, height: f32, gravity: f32) -> Self {
+        let gravity = gravity.abs().max(std::f32::EPSILON);
+        let rise = end.y - begin.y;
+        let apex = height + rise.max(0.0);
+
+        let vertical_speed = (2.0 * gravity * apex).sqrt();
+        let fall_time = (2.0 * (apex - rise) / gravity).sqrt();
+        let flight_time = vertical_speed / gravity + fall_time;
+
+        let mut horizontal = end - begin;
+        horizontal.y = 0.0;
+        let mut velocity = horizontal.scale(1.0 / flight_time.max(std::f32::EPSILON));
+        velocity.y = vertical_speed;
+
+        Self {
+            velocity,
+            flight_time,
+            elapsed: 0.0,
+            launched: false,
+        }
+    }
+
+    /// Returns velocity that must be given to the body of a bot on first frame of the jump.
+    pub fn launch(&mut self) -> Option<Vector3<f32>> {
+        if self.launched {
+            None
+        } else {
+            self.launched = true;
+            Some(self.velocity)
+        }
+    }
+
+    /// Returns false when the bot has landed.
+    pub fn update(&mut self, dt: f32, has_ground_contact: bool) -> bool {
+        self.elapsed += dt;
+        // Bot touches the ground at the very beginning of the jump, so ignore contacts
+        // during the first half of the flight.
+        let landed = has_ground_contact && self.elapsed >= self.flight_time * 0.5;
+        !landed && self.elapsed < self.flight_time + Self::LANDING_TIMEOUT
+    }
+}
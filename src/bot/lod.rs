@@ -0,0 +1,104 @@
+//! Update level of detail of bots. Bots that have no target and are far from the player or
+//! out of the player's view do not search for targets each frame, since it requires ray casts
+//! to check line of sight. Thresholds are taken from `data/configs/bot_lod.ron`.
+
+use rg3d::{
+    core::{algebra::Vector3, math::frustum::Frustum, pool::Handle},
+    lazy_static::lazy_static,
+    scene::{graph::Graph, node::Node},
+};
+use serde::Deserialize;
+use std::fs::File;
+
+#[derive(Deserialize)]
+pub struct UpdateLodDefinition {
+    /// Bots closer than this distance to the player are always updated at full rate.
+    pub near_distance: f32,
+    /// Bots further than this distance are updated at the lowest rate even if visible.
+    pub far_distance: f32,
+    /// Interval (in seconds) between AI updates of visible bots between near and far distance.
+    pub reduced_interval: f32,
+    /// Interval (in seconds) between AI updates of far or invisible bots.
+    pub distant_interval: f32,
+}
+
+impl UpdateLodDefinition {
+    pub fn new() -> Self {
+        let file = File::open("data/configs/bot_lod.ron").unwrap();
+        ron::de::from_reader(file).unwrap()
+    }
+}
+
+lazy_static! {
+    static ref DEFINITION: UpdateLodDefinition = UpdateLodDefinition::new();
+}
+
+/// Point of view which is used to select level of detail, usually it is the player's camera.
+pub struct Observer {
+    pub position: Vector3<f32>,
+    pub frustum: Option<Frustum>,
+}
+
+impl Observer {
+    pub fn from_camera(graph: &Graph, camera: Handle<Node>) -> Option<Self> {
+        match &graph[camera] {
+            Node::Camera(camera) => Some(Self {
+                position: camera.global_position(),
+                frustum: Frustum::from(camera.view_projection_matrix()),
+            }),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum UpdateLod {
+    Full,
+    Reduced,
+    Distant,
+}
+
+impl Default for UpdateLod {
+    fn default() -> Self {
+        Self::Full
+    }
+}
+
+impl UpdateLod {
+    pub fn get_definition() -> &'static UpdateLodDefinition {
+        &DEFINITION
+    }
+
+    /// Selects level of detail for a bot at given position, bots are updated at full rate
+    /// if there is no observer.
+    pub fn new(position: Vector3<f32>, observer: Option<&Observer>) -> Self {
+        let observer = match observer {
+            Some(observer) => observer,
+            None => return Self::Full,
+        };
+
+        let definition = Self::get_definition();
+        let distance = position.metric_distance(&observer.position);
+        let visible = observer
+            .frustum
+            .as_ref()
+            .map_or(true, |f| f.is_contains_point(position));
+
+        if distance <= definition.near_distance {
+            Self::Full
+        } else if visible && distance <= definition.far_distance {
+            Self::Reduced
+        } else {
+            Self::Distant
+        }
+    }
+
+    /// Returns time between AI updates.
+    pub fn interval(self) -> f32 {
+        match self {
+            Self::Full => 0.0,
+            Self::Reduced => Self::get_definition().reduced_interval,
+            Self::Distant => Self::get_definition().distant_interval,
+        }
+    }
+}
@@ -18,7 +18,9 @@ use rg3d::{
 #[derive(Default)]
 pub struct LowerBodyMachine {
     pub machine: Machine,
+    pub idle_animation: Handle<Animation>,
     pub walk_animation: Handle<Animation>,
+    pub scream_animation: Handle<Animation>,
     pub dying_animation: Handle<Animation>,
     pub walk_state: Handle<State>,
 }
@@ -38,6 +40,8 @@ impl Visit for LowerBodyMachine {
         self.walk_animation.visit("WalkAnimation", visitor)?;
         self.dying_animation.visit("DyingAnimation", visitor)?;
         self.walk_state.visit("WalkState", visitor)?;
+        self.idle_animation.visit("IdleAnimation", visitor)?;
+        self.scream_animation.visit("ScreamAnimation", visitor)?;
 
         visitor.leave_region()
     }
@@ -74,7 +78,7 @@ impl LowerBodyMachine {
 
         let mut machine = Machine::new();
 
-        let (_, idle_state) = create_play_animation_state(
+        let (idle_animation, idle_state) = create_play_animation_state(
             idle_animation_resource.unwrap(),
             "Idle",
             &mut machine,
@@ -90,7 +94,7 @@ impl LowerBodyMachine {
             model,
         );
 
-        let (_, scream_state) = create_play_animation_state(
+        let (scream_animation, scream_state) = create_play_animation_state(
             scream_animation_resource.unwrap(),
             "Scream",
             &mut machine,
@@ -110,8 +114,7 @@ impl LowerBodyMachine {
             .animations
             .get_mut(dying_animation)
             .set_loop(false)
-            .set_enabled(false)
-            .set_speed(1.0);
+            .set_enabled(false);
 
         scene.animations[walk_animation]
             .add_signal(AnimationSignal::new(Self::STEP_SIGNAL, 0.3))
@@ -172,7 +175,9 @@ impl LowerBodyMachine {
         Self {
             machine,
             walk_state,
+            idle_animation,
             walk_animation,
+            scream_animation,
             dying_animation,
         }
     }
@@ -181,6 +186,15 @@ impl LowerBodyMachine {
         clean_machine(&self.machine, scene)
     }
 
+    /// Sets speeds of animations from the definition multiplied by given scale, speed of
+    /// walking animation is set separately each frame because it depends on movement speed.
+    pub fn set_speed_scale(&self, scene: &mut Scene, definition: &BotDefinition, scale: f32) {
+        let speeds = &definition.animation_speeds;
+        scene.animations[self.idle_animation].set_speed(speeds.idle * scale);
+        scene.animations[self.scream_animation].set_speed(speeds.scream * scale);
+        scene.animations[self.dying_animation].set_speed(speeds.dying * scale);
+    }
+
     pub fn set_walk_animation_speed(&self, scene: &mut Scene, speed: f32) {
         scene.animations[self.walk_animation].set_speed(speed);
     }
@@ -1,15 +1,27 @@
 use crate::{
-    actor::{Actor, TargetDescriptor},
+    actor::{is_line_of_sight_clear, Actor, ActorContainer},
     bot::{
+        attack_slots::AttackSlots,
+        awareness::Awareness,
+        dodge::{Dodge, DodgeDefinition},
+        investigation::Investigation,
+        jump::Jump,
+        lod::{Observer, UpdateLod},
         lower_body::{LowerBodyMachine, LowerBodyMachineInput},
+        navmesh_guard::NavmeshGuard,
+        patrol::Patrol,
+        search::Search,
         upper_body::{UpperBodyMachine, UpperBodyMachineInput},
     },
-    character::{find_hit_boxes, Character},
+    character::{find_hit_boxes, Character, Faction, HitBoxKind},
     item::ItemKind,
-    level::{footstep_ray_check, UpdateContext},
+    level::{footstep_ray_check, jump_link::JumpLinkContainer, UpdateContext},
     message::Message,
-    utils::BodyImpactHandler,
-    weapon::projectile::Damage,
+    utils::{random_direction_in_cone, rng::GameRng, BodyImpactDefinition, BodyImpactHandler},
+    weapon::{
+        projectile::{Damage, DamageType},
+        WeaponContainer, WeaponKind,
+    },
     CollisionGroups, GameTime,
 };
 use rg3d::{
@@ -17,9 +29,9 @@ use rg3d::{
     core::{
         algebra::{Isometry3, Matrix4, Point3, Translation3, UnitQuaternion, Vector3},
         color::Color,
-        math::{frustum::Frustum, ray::Ray, SmoothAngle},
+        math::{self, frustum::Frustum, SmoothAngle},
         pool::Handle,
-        rand::Rng,
+        rand::{seq::SliceRandom, Rng},
         visitor::{Visit, VisitResult, Visitor},
     },
     engine::resource_manager::ResourceManager,
@@ -29,13 +41,8 @@ use rg3d::{
         geometry::{ColliderBuilder, InteractionGroups},
     },
     scene::{
-        self,
-        base::BaseBuilder,
-        graph::Graph,
-        node::Node,
-        physics::{Physics, RayCastOptions},
-        transform::TransformBuilder,
-        ColliderHandle, Scene, SceneDrawingContext,
+        self, base::BaseBuilder, graph::Graph, node::Node, physics::Physics,
+        transform::TransformBuilder, ColliderHandle, Scene, SceneDrawingContext,
     },
     utils::{
         log::{Log, MessageKind},
@@ -47,10 +54,20 @@ use std::{
     collections::HashMap,
     fs::File,
     ops::{Deref, DerefMut},
+    path::PathBuf,
     sync::mpsc::Sender,
 };
 
+pub mod attack_slots;
+pub mod awareness;
+pub mod dodge;
+pub mod investigation;
+pub mod jump;
+pub mod lod;
 mod lower_body;
+pub mod navmesh_guard;
+pub mod patrol;
+pub mod search;
 mod upper_body;
 
 #[derive(Deserialize, Copy, Clone, PartialEq, Eq, Hash, Debug)]
@@ -60,7 +77,15 @@ pub enum BotKind {
     Zombie,
 }
 
+impl Default for BotKind {
+    fn default() -> Self {
+        Self::Zombie
+    }
+}
+
 impl BotKind {
+    pub const ALL: [BotKind; 3] = [BotKind::Mutant, BotKind::Parasite, BotKind::Zombie];
+
     pub fn from_id(id: i32) -> Result<Self, String> {
         match id {
             0 => Ok(BotKind::Mutant),
@@ -85,6 +110,85 @@ impl BotKind {
             BotKind::Zombie => "Zombie",
         }
     }
+
+    pub fn from_description(description: &str) -> Result<Self, String> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|kind| kind.description() == description)
+            .ok_or_else(|| format!("Invalid bot kind name {}", description))
+    }
+}
+
+impl Visit for BotKind {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut id = self.id();
+        id.visit(name, visitor)?;
+        if visitor.is_reading() {
+            *self = Self::from_id(id)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+#[repr(u32)]
+pub enum BotState {
+    Idle = 0,
+    /// Calm bot walks to a random point around the place where it started patrolling.
+    Patrol = 1,
+    /// Calm bot walks to the place of a noise that it has heard.
+    Investigate = 2,
+    Chase = 3,
+    Attack = 4,
+    /// Ranged bot backs off from a target that is too close to regain shooting distance.
+    Flee = 5,
+    Dead = 6,
+    /// Bot is in melee range, but all attack slots of its target are occupied.
+    Circle = 7,
+    /// Bot has lost its target and checks places around last known position of the target.
+    Search = 8,
+    /// Bot runs away from a grenade that is about to explode.
+    Dodge = 9,
+}
+
+impl Default for BotState {
+    fn default() -> Self {
+        Self::Idle
+    }
+}
+
+impl BotState {
+    pub fn id(self) -> u32 {
+        self as u32
+    }
+
+    pub fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(Self::Idle),
+            1 => Ok(Self::Patrol),
+            2 => Ok(Self::Investigate),
+            3 => Ok(Self::Chase),
+            4 => Ok(Self::Attack),
+            5 => Ok(Self::Flee),
+            6 => Ok(Self::Dead),
+            7 => Ok(Self::Circle),
+            8 => Ok(Self::Search),
+            9 => Ok(Self::Dodge),
+            _ => Err(format!("Invalid bot state id {}!", id)),
+        }
+    }
+}
+
+impl Visit for BotState {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut id = self.id();
+        id.visit(name, visitor)?;
+        if visitor.is_reading() {
+            *self = Self::from_id(id)?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
@@ -113,9 +217,80 @@ impl Visit for Target {
     }
 }
 
+/// Detects situations when a bot can't make any progress while moving to its destination
+/// (blocked by another body or stuck on a corner of navmesh). Its state is not saved, it
+/// will be restored in a second after a save is loaded.
+#[derive(Default)]
+struct StuckDetector {
+    last_position: Vector3<f32>,
+    timer: f32,
+    /// Time left for the bot to sidestep.
+    recovery_timer: f32,
+    sidestep: Vector3<f32>,
+}
+
+impl StuckDetector {
+    /// How often bot's progress is checked.
+    const CHECK_INTERVAL: f32 = 1.0;
+    /// Minimal distance that bot must pass during check interval to be considered not stuck.
+    const MIN_PROGRESS: f32 = 0.1;
+    const RECOVERY_TIME: f32 = 0.5;
+
+    fn reset(&mut self, position: Vector3<f32>) {
+        self.last_position = position;
+        self.timer = 0.0;
+    }
+
+    /// Returns sidestep direction if bot is recovering after it got stuck. Progress is checked
+    /// only when the bot thinks.
+    fn update(
+        &mut self,
+        kind: BotKind,
+        position: Vector3<f32>,
+        move_dir: Vector3<f32>,
+        dt: f32,
+        think: bool,
+        rng: &mut GameRng,
+    ) -> Option<Vector3<f32>> {
+        if self.recovery_timer > 0.0 {
+            self.recovery_timer -= dt;
+            if self.recovery_timer <= 0.0 {
+                self.reset(position);
+            }
+            return Some(self.sidestep);
+        }
+
+        self.timer += dt;
+        if think && self.timer >= Self::CHECK_INTERVAL {
+            if position.metric_distance(&self.last_position) < Self::MIN_PROGRESS {
+                Log::writeln(
+                    MessageKind::Warning,
+                    format!(
+                        "{:?} bot is stuck at {:?}, trying to recover.",
+                        kind, position
+                    ),
+                );
+
+                // Step aside of the obstacle, perpendicular to desired direction of movement.
+                let sign = if rng.gen::<bool>() { 1.0 } else { -1.0 };
+                self.sidestep = move_dir
+                    .cross(&Vector3::y())
+                    .try_normalize(std::f32::EPSILON)
+                    .unwrap_or_else(Vector3::x)
+                    .scale(sign);
+                self.recovery_timer = Self::RECOVERY_TIME;
+            }
+            self.reset(position);
+        }
+
+        None
+    }
+}
+
 pub struct Bot {
     target: Option<Target>,
     kind: BotKind,
+    state: BotState,
     model: Handle<Node>,
     character: Character,
     pub definition: &'static BotDefinition,
@@ -126,7 +301,13 @@ pub struct Bot {
     move_target: Vector3<f32>,
     frustum: Frustum,
     last_move_dir: Vector3<f32>,
+    /// Horizontal velocity of the bot without knockback.
+    velocity: Vector3<f32>,
+    jump: Option<Jump>,
     spine: Handle<Node>,
+    /// Whether the bot can use weapons, false if the definition allows weapons but the model
+    /// lacks bones that are required to aim.
+    can_use_weapons: bool,
     yaw: SmoothAngle,
     pitch: SmoothAngle,
     attack_timeout: f32,
@@ -134,6 +315,40 @@ pub struct Bot {
     attack_animation_index: u32,
     agent: NavmeshAgent,
     pub impact_handler: BodyImpactHandler,
+    loot_dropped: bool,
+    /// Items that can be taken from the corpse of a lootable bot.
+    loot: Vec<ItemKind>,
+    attack_windup: f32,
+    /// Direction of circling around a target, either 1.0 or -1.0.
+    circle_direction: f32,
+    stuck_detector: StuckDetector,
+    navmesh_guard: NavmeshGuard,
+    /// Time since dying animation has ended.
+    corpse_time: f32,
+    lod: UpdateLod,
+    /// Time left until next AI update, AI of bots with reduced LOD is not updated each frame.
+    think_timer: f32,
+    /// Time since last AI update.
+    think_elapsed: f32,
+    /// Time since current target was selected.
+    time_on_target: f32,
+    /// Time since current target was seen last time.
+    target_lost_time: f32,
+    awareness: Awareness,
+    search: Option<Search>,
+    /// Noise that the bot goes to check.
+    investigation: Option<Investigation>,
+    /// Wandering of a calm bot, it starts on first update of a bot with a patrol radius.
+    patrol: Option<Patrol>,
+    /// Grenade that the bot runs away from.
+    dodge: Option<Dodge>,
+    /// Time left until the bot is able to scream again.
+    scream_cooldown: f32,
+    /// Time left until the bot stops playing scream animation.
+    scream_time: f32,
+    /// Multiplier of speed of every animation that is currently applied, it is not saved
+    /// so speeds are applied again on the first update after loading.
+    animation_speed_scale: f32,
 }
 
 impl Deref for Bot {
@@ -155,6 +370,7 @@ impl Default for Bot {
         Self {
             character: Default::default(),
             kind: BotKind::Mutant,
+            state: Default::default(),
             model: Default::default(),
             target: Default::default(),
             definition: Self::get_definition(BotKind::Mutant),
@@ -165,7 +381,9 @@ impl Default for Bot {
             move_target: Default::default(),
             frustum: Default::default(),
             last_move_dir: Default::default(),
+            velocity: Default::default(),
             spine: Default::default(),
+            can_use_weapons: false,
             yaw: SmoothAngle {
                 angle: 0.0,
                 target: 0.0,
@@ -181,6 +399,27 @@ impl Default for Bot {
             attack_animation_index: 0,
             agent: Default::default(),
             impact_handler: Default::default(),
+            loot_dropped: false,
+            loot: Default::default(),
+            attack_windup: 0.0,
+            circle_direction: 1.0,
+            stuck_detector: Default::default(),
+            navmesh_guard: Default::default(),
+            corpse_time: 0.0,
+            lod: Default::default(),
+            think_timer: 0.0,
+            think_elapsed: 0.0,
+            time_on_target: 0.0,
+            target_lost_time: 0.0,
+            awareness: Default::default(),
+            search: None,
+            investigation: None,
+            patrol: None,
+            dodge: None,
+            jump: None,
+            scream_cooldown: 0.0,
+            scream_time: 0.0,
+            animation_speed_scale: 0.0,
         }
     }
 }
@@ -188,16 +427,117 @@ impl Default for Bot {
 #[derive(Deserialize)]
 pub struct AttackAnimationDefinition {
     path: String,
+    /// Time (in seconds) of the hit in the animation, target takes damage at this moment
+    /// if it is still in melee range.
     timestamp: f32,
+    /// Base damage of the hit, it is multiplied by the damage scale of current difficulty.
+    /// Only the amount matters, splash radius is ignored for melee attacks.
     damage: Damage,
     speed: f32,
+    /// Time (in seconds) during which the bot holds the first frame of the attack
+    /// before the swing. It is scaled by the speed of the animation.
+    windup: f32,
+    /// Speed (in meters per second) with which the bot moves towards its target during
+    /// the swing, zero means that the bot attacks standing still.
+    #[serde(default)]
+    lunge_speed: f32,
+}
+
+/// Playback speeds of animations of a bot, speeds of attack animations are set per attack.
+#[derive(Deserialize, Copy, Clone)]
+#[serde(default)]
+pub struct AnimationSpeedsDefinition {
+    pub idle: f32,
+    /// Speed of walking animation at full movement speed, it is reduced together with
+    /// movement speed when legs are damaged.
+    pub walk: f32,
+    pub scream: f32,
+    pub aim: f32,
+    pub dying: f32,
+}
+
+impl Default for AnimationSpeedsDefinition {
+    fn default() -> Self {
+        Self {
+            idle: 1.0,
+            walk: 1.0,
+            scream: 1.0,
+            aim: 1.0,
+            dying: 1.0,
+        }
+    }
+}
+
+/// Aim poses for steep angles. Aim pose is blended with them by pitch, so arms follow the
+/// aim instead of relying on rotation of the spine only.
+#[derive(Deserialize, Clone)]
+pub struct FullBodyAimDefinition {
+    pub up_animation: String,
+    pub down_animation: String,
+    /// Pitch (in degrees) at which aim up or aim down pose fully replaces aim pose, rotation
+    /// of the spine covers the rest.
+    pub max_pitch: f32,
+}
+
+/// Accuracy cone of a bot, the direction of every shot is randomly picked inside the cone.
+#[derive(Deserialize, Copy, Clone)]
+pub struct AimSpreadDefinition {
+    /// Half-angle of the cone (in degrees) when target is right in front of the bot.
+    pub near_angle: f32,
+    /// Half-angle of the cone (in degrees) when target is at `far_distance` or further.
+    pub far_angle: f32,
+    pub far_distance: f32,
+}
+
+impl Default for AimSpreadDefinition {
+    fn default() -> Self {
+        Self {
+            near_angle: 1.0,
+            far_angle: 6.0,
+            far_distance: 15.0,
+        }
+    }
+}
+
+/// Limbs of a bot can be severed by heavy damage, the limb disappears from the model and
+/// leaves a gib on the floor.
+#[derive(Deserialize, Clone)]
+pub struct DismembermentDefinition {
+    /// Kinds of hit boxes that can be severed.
+    pub hit_boxes: Vec<HitBoxKind>,
+    /// Total damage that a hit box must take to be severed.
+    pub limb_health: f32,
+    /// Killing hit that deals at least this damage severs the hit box it has hit.
+    pub lethal_damage: f32,
+}
+
+impl AimSpreadDefinition {
+    /// Returns half-angle (in radians) of the cone for given distance to a target.
+    pub fn half_angle(&self, distance: f32) -> f32 {
+        let t = (distance / self.far_distance.max(std::f32::EPSILON)).min(1.0);
+        math::lerpf(self.near_angle, self.far_angle, t).to_radians()
+    }
 }
 
 #[derive(Deserialize)]
 pub struct BotDefinition {
     pub scale: f32,
+    /// Height (in meters) of the cylindrical part of the collision capsule, full height of
+    /// the capsule is `body_height + 2 * body_radius`.
+    pub body_height: f32,
+    /// Radius of the collision capsule, the bot also keeps this distance from corners of
+    /// obstacles when it follows a path.
+    pub body_radius: f32,
     pub health: f32,
+    /// Multipliers of incoming damage by its type, values below 1 are resistances and
+    /// values above 1 are vulnerabilities. Missing types do full damage.
+    #[serde(default)]
+    pub damage_scales: HashMap<DamageType, f32>,
     pub walk_speed: f32,
+    /// Horizontal acceleration (in meters per second squared), `None` means that the bot
+    /// reaches its speed instantly.
+    #[serde(default)]
+    pub acceleration: Option<f32>,
     pub weapon_scale: f32,
     pub model: String,
     pub weapon_hand_name: String,
@@ -207,9 +547,54 @@ pub struct BotDefinition {
     pub hips: String,
     pub v_aim_angle_hack: f32,
     pub can_use_weapons: bool,
+    pub possible_weapons: Vec<WeaponKind>,
+    /// A set of (kind, count, probability) entries, rolled once on death.
+    pub loot_table: Vec<(ItemKind, u32, f32)>,
+    /// Whether loot of the bot stays on its corpse until player takes it, instead of being
+    /// dropped on the ground on death.
+    #[serde(default)]
+    pub lootable: bool,
     pub close_combat_distance: f32,
+    /// Ranged bots back off from a target that is closer than this distance instead of
+    /// attacking it in melee. Zero disables this behavior.
+    #[serde(default)]
+    pub min_engagement_distance: f32,
+    #[serde(default)]
+    pub aim_spread: AimSpreadDefinition,
+    /// Time (in seconds) after taking damage during which the bot cannot aim.
+    pub restoration_time: f32,
+    /// Whether taking damage cancels an attack that is being wound up.
+    pub damage_interrupts_attack: bool,
+    /// Bot switches to another target only if it is closer than current target by this
+    /// distance (in meters).
+    pub target_switch_margin: f32,
+    /// Time (in seconds) during which a bot sticks to a new target before considering others.
+    pub min_time_on_target: f32,
+    /// Time (in seconds) during which target must be out of sight to be considered lost.
+    pub lose_target_time: f32,
+    /// Time (in seconds) that a bot spends searching for a lost target before calming down.
+    pub search_time: f32,
+    /// Radius (in meters) of the area around its starting position where a calm bot wanders,
+    /// zero means that the bot stands still.
+    #[serde(default)]
+    pub patrol_radius: f32,
+    pub faction: Faction,
+    /// Reaction to grenades that are about to explode nearby, bots without it ignore
+    /// grenades.
+    #[serde(default)]
+    pub dodge: Option<DodgeDefinition>,
+    /// Height (in meters) of jumps along jump links of a level, bots without it cannot
+    /// cross gaps in navmesh.
+    #[serde(default)]
+    pub jump_height: Option<f32>,
+    #[serde(default)]
+    pub dismemberment: Option<DismembermentDefinition>,
     pub pain_sounds: Vec<String>,
     pub scream_sounds: Vec<String>,
+    /// Distance (in meters) at which other bots hear the scream of the bot when it spots
+    /// a target, zero means that the bot doesn't scream.
+    #[serde(default)]
+    pub scream_loudness: f32,
     pub idle_sounds: Vec<String>,
 
     // Animations.
@@ -218,12 +603,49 @@ pub struct BotDefinition {
     pub attack_animations: Vec<AttackAnimationDefinition>,
     pub walk_animation: String,
     pub aim_animation: String,
+    /// Only bots that can use weapons aim, `None` means that only the spine is rotated.
+    #[serde(default)]
+    pub full_body_aim: Option<FullBodyAimDefinition>,
     pub dying_animation: String,
+    #[serde(default)]
+    pub animation_speeds: AnimationSpeedsDefinition,
+    /// Defines how long impacts on body parts last, bots with affected legs move slower.
+    #[serde(default)]
+    pub impact: BodyImpactDefinition,
 }
 
 #[derive(Deserialize, Default)]
 pub struct BotDefinitionsContainer {
     map: HashMap<BotKind, BotDefinition>,
+    /// Pairs of bot factions that attack each other, every bot is hostile to the player.
+    #[serde(default)]
+    hostile_factions: Vec<(Faction, Faction)>,
+}
+
+impl BotDefinition {
+    pub fn damage_scale(&self, damage_type: DamageType) -> f32 {
+        self.damage_scales.get(&damage_type).cloned().unwrap_or(1.0)
+    }
+
+    /// Returns paths of every model and animation that is loaded when a bot is created.
+    pub fn resource_paths(&self) -> Vec<&str> {
+        let mut paths = vec![
+            self.model.as_str(),
+            self.idle_animation.as_str(),
+            self.walk_animation.as_str(),
+            self.scream_animation.as_str(),
+            self.dying_animation.as_str(),
+        ];
+        paths.extend(self.attack_animations.iter().map(|a| a.path.as_str()));
+        if self.can_use_weapons && !self.aim_animation.is_empty() {
+            paths.push(&self.aim_animation);
+            if let Some(full_body_aim) = self.full_body_aim.as_ref() {
+                paths.push(&full_body_aim.up_animation);
+                paths.push(&full_body_aim.down_animation);
+            }
+        }
+        paths
+    }
 }
 
 impl BotDefinitionsContainer {
@@ -238,10 +660,33 @@ lazy_static! {
 }
 
 impl Bot {
+    /// Distance (in meters) to a target at which a lunging bot stops, so it won't push into
+    /// the target.
+    const LUNGE_STOP_DISTANCE: f32 = 0.5;
+    /// Minimal time (in seconds) between screams of a bot.
+    const SCREAM_COOLDOWN: f32 = 10.0;
+    const SCREAM_DURATION: f32 = 1.2;
+    /// Distance (in meters) to far plane of the frustum of a bot.
+    const VIEW_DISTANCE: f32 = 20.0;
+
     pub fn get_definition(kind: BotKind) -> &'static BotDefinition {
         DEFINITIONS.map.get(&kind).unwrap()
     }
 
+    /// Checks whether bots of the first faction attack actors of the second one.
+    pub fn is_hostile(faction: Faction, other: Faction) -> bool {
+        if faction == other {
+            false
+        } else if faction == Faction::Player || other == Faction::Player {
+            true
+        } else {
+            DEFINITIONS
+                .hostile_factions
+                .iter()
+                .any(|&(a, b)| (a, b) == (faction, other) || (b, a) == (faction, other))
+        }
+    }
+
     pub async fn new(
         kind: BotKind,
         resource_manager: ResourceManager,
@@ -252,8 +697,8 @@ impl Bot {
     ) -> Self {
         let definition = Self::get_definition(kind);
 
-        let body_height = 0.55;
-        let body_radius = 0.16;
+        let body_height = definition.body_height;
+        let body_radius = definition.body_radius;
 
         let model = resource_manager
             .request_model(&definition.model)
@@ -271,10 +716,37 @@ impl Bot {
             ));
 
         let spine = scene.graph.find_by_name(model, &definition.spine);
-        if spine.is_none() {
+        if spine.is_none() && definition.can_use_weapons {
+            Log::writeln(
+                MessageKind::Warning,
+                format!(
+                    "Spine bone {} not found, bot won't aim or use weapons!",
+                    definition.spine
+                ),
+            );
+        }
+
+        let hips = scene.graph.find_by_name(model, &definition.hips);
+        if hips.is_none() {
+            Log::writeln(
+                MessageKind::Warning,
+                format!(
+                    "Hips bone {} not found, attack animations will rotate whole body!",
+                    definition.hips
+                ),
+            );
+        }
+
+        let hand = scene
+            .graph
+            .find_by_name(model, &definition.weapon_hand_name);
+        if hand.is_none() && definition.can_use_weapons {
             Log::writeln(
                 MessageKind::Warning,
-                "Spine bone not found, bot won't aim vertically!".to_owned(),
+                format!(
+                    "Weapon hand bone {} not found, bot won't use weapons!",
+                    definition.weapon_hand_name
+                ),
             );
         }
 
@@ -305,9 +777,6 @@ impl Bot {
 
         scene.physics_binder.bind(pivot, body);
 
-        let hand = scene
-            .graph
-            .find_by_name(model, &definition.weapon_hand_name);
         let wpn_scale = definition.weapon_scale * (1.0 / definition.scale);
         let weapon_pivot = BaseBuilder::new()
             .with_local_transform(
@@ -324,9 +793,10 @@ impl Bot {
             )
             .build(&mut scene.graph);
 
-        scene.graph.link_nodes(weapon_pivot, hand);
-
-        let hips = scene.graph.find_by_name(model, &definition.hips);
+        // Without a hand the pivot stays attached to the model, so the graph is still valid.
+        scene
+            .graph
+            .link_nodes(weapon_pivot, if hand.is_some() { hand } else { model });
 
         let lower_body_machine =
             LowerBodyMachine::new(resource_manager.clone(), &definition, model, scene).await;
@@ -341,10 +811,13 @@ impl Bot {
                 health: definition.health,
                 sender: Some(sender),
                 hit_boxes: find_hit_boxes(pivot, scene),
+                faction: definition.faction,
                 ..Default::default()
             },
             hips,
             spine,
+            // Bot without aiming bones would shoot from a wrong place or in a wrong direction.
+            can_use_weapons: definition.can_use_weapons && spine.is_some() && hand.is_some(),
             definition,
             last_health: definition.health,
             model,
@@ -366,72 +839,308 @@ impl Bot {
             .has_ended()
     }
 
+    /// Returns time for how long the bot lies dead, `None` if the bot is alive or still
+    /// playing dying animation.
+    pub fn corpse_time(&self) -> Option<f32> {
+        if self.corpse_time > 0.0 {
+            Some(self.corpse_time)
+        } else {
+            None
+        }
+    }
+
+    pub fn state(&self) -> BotState {
+        self.state
+    }
+
+    pub fn kind(&self) -> BotKind {
+        self.kind
+    }
+
+    /// Selects new state of the bot. This is the only place where state transitions happen.
+    fn update_state(
+        &mut self,
+        self_handle: Handle<Actor>,
+        position: Vector3<f32>,
+        attack_slots: &mut AttackSlots,
+        rng: &mut GameRng,
+    ) {
+        let new_state = if self.is_dead() {
+            BotState::Dead
+        } else if self.dodge.is_some() && self.attack_windup <= 0.0 {
+            // Running away from a grenade is more important than anything else.
+            BotState::Dodge
+        } else if let Some(target) = self.target.as_ref() {
+            // Bot must commit to the swing once the windup has started.
+            if self.attack_windup > 0.0 {
+                BotState::Attack
+            } else if self.target_lost_time > 0.0 {
+                // Target is out of sight, go to its last known position.
+                BotState::Chase
+            } else if self.can_use_weapons
+                && position.metric_distance(&target.position)
+                    < self.definition.min_engagement_distance
+            {
+                BotState::Flee
+            } else if is_in_melee_range(
+                position,
+                target.position,
+                self.definition.close_combat_distance,
+            ) {
+                if attack_slots.try_acquire(target.handle, self_handle) {
+                    BotState::Attack
+                } else {
+                    BotState::Circle
+                }
+            } else {
+                BotState::Chase
+            }
+        } else if self.investigation.is_some() {
+            BotState::Investigate
+        } else if self.search.is_some() {
+            BotState::Search
+        } else if self
+            .patrol
+            .as_ref()
+            .map_or(false, |p| p.destination().is_some())
+        {
+            BotState::Patrol
+        } else {
+            BotState::Idle
+        };
+
+        if new_state != BotState::Attack {
+            attack_slots.release(self_handle);
+        }
+
+        if new_state == BotState::Circle && self.state != BotState::Circle {
+            self.circle_direction = if rng.gen::<bool>() { 1.0 } else { -1.0 };
+        }
+
+        self.state = new_state;
+    }
+
+    fn drop_loot(&mut self, graph: &Graph, weapons: &WeaponContainer, rng: &mut GameRng) {
+        let position = self.character.position(graph);
+        let sender = self.character.sender.as_ref().unwrap();
+        let lootable = self.definition.lootable;
+        let loot = &mut self.loot;
+
+        // Loot of lootable bots stays on the corpse, the rest drop it on the ground.
+        let mut drop_item = |kind: ItemKind, position: Vector3<f32>| {
+            if lootable {
+                loot.push(kind);
+            } else {
+                sender
+                    .send(Message::SpawnItem {
+                        kind,
+                        position,
+                        adjust_height: true,
+                    })
+                    .unwrap();
+            }
+        };
+
+        // Weapons of the bot become pickable items in the world.
+        for &weapon in self.character.weapons.iter() {
+            sender
+                .send(Message::ShowWeapon {
+                    weapon,
+                    state: false,
+                })
+                .unwrap();
+            drop_item(weapons[weapon].get_kind().associated_item(), position);
+        }
+
+        for &(kind, count, probability) in self.definition.loot_table.iter() {
+            if rng.gen_range(0.0..1.0) < probability {
+                for _ in 0..count {
+                    // Scatter items a bit, so they won't stack on each other.
+                    let offset =
+                        Vector3::new(rng.gen_range(-0.3..0.3), 0.0, rng.gen_range(-0.3..0.3));
+
+                    drop_item(kind, position + offset);
+                }
+            }
+        }
+
+        self.loot_dropped = true;
+    }
+
+    pub fn loot(&self) -> &[ItemKind] {
+        &self.loot
+    }
+
+    /// Empties the corpse, returns every item that was on it.
+    pub fn take_loot(&mut self) -> Vec<ItemKind> {
+        std::mem::take(&mut self.loot)
+    }
+
+    pub fn can_use_weapons(&self) -> bool {
+        self.can_use_weapons
+    }
+
+    /// Registers damage dealt to a hit box, returns true if the hit box must be severed.
+    /// Must be called after the damage is applied to the bot, so the killing hit is known.
+    pub fn damage_hit_box(&mut self, collider: ColliderHandle, amount: f32) -> bool {
+        let definition = match self.definition.dismemberment.as_ref() {
+            Some(definition) => definition,
+            None => return false,
+        };
+        let dead = self.is_dead();
+
+        match self
+            .character
+            .hit_boxes
+            .iter_mut()
+            .find(|hit_box| hit_box.collider == collider)
+        {
+            Some(hit_box) if definition.hit_boxes.contains(&hit_box.kind) => {
+                hit_box.damage += amount;
+                hit_box.damage >= definition.limb_health
+                    || (dead && amount >= definition.lethal_damage)
+            }
+            _ => false,
+        }
+    }
+
+    /// Collapses the bone of a hit box, so the limb disappears from the skinned mesh, and
+    /// removes the hit box. Returns position of the bone where the limb was severed.
+    pub fn sever_hit_box(
+        &mut self,
+        collider: ColliderHandle,
+        scene: &mut Scene,
+    ) -> Option<Vector3<f32>> {
+        let index = self
+            .character
+            .hit_boxes
+            .iter()
+            .position(|hit_box| hit_box.collider == collider)?;
+        let hit_box = self.character.hit_boxes.remove(index);
+
+        if let Some(collider) = scene.physics.colliders.get_mut(hit_box.collider.into()) {
+            collider.set_collision_groups(InteractionGroups::none());
+        }
+
+        let bone = scene.graph[hit_box.node].parent();
+        if bone.is_none() {
+            return None;
+        }
+
+        // Animations would restore the scale of the bone, so its tracks are disabled.
+        for animation in scene.animations.iter_mut() {
+            animation.set_tracks_enabled_from(bone, false, &scene.graph);
+        }
+        // Zero scale is avoided, it breaks bounds of the skinned mesh.
+        scene.graph[bone]
+            .local_transform_mut()
+            .set_scale(Vector3::new(0.001, 0.001, 0.001));
+
+        Some(scene.graph[bone].global_position())
+    }
+
     pub fn can_shoot(&self) -> bool {
         self.upper_body_machine.machine.active_state() == self.upper_body_machine.aim_state
-            && self.definition.can_use_weapons
+            && self.can_use_weapons
     }
 
     fn select_target(
         &mut self,
         self_handle: Handle<Actor>,
-        scene: &mut Scene,
-        targets: &[TargetDescriptor],
+        scene: &Scene,
+        actors: &ActorContainer,
+        dt: f32,
+        rng: &mut GameRng,
     ) {
+        let position = self.character.position(&scene.graph);
+        let eye = eye_position(position);
+
         // Check if existing target is valid.
+        let mut current_target = None;
+        let mut closest_distance = std::f32::MAX;
         if let Some(target) = self.target.as_mut() {
-            for target_desc in targets {
-                if target_desc.handle != self_handle
-                    && target_desc.handle == target.handle
-                    && target_desc.health > 0.0
-                {
-                    target.position = target_desc.position;
-                    return;
+            if target.handle != self_handle
+                && actors.contains(target.handle)
+                && !actors.get(target.handle).is_dead()
+            {
+                // Bot knows where its target is only while it sees the target, otherwise
+                // it goes to the last known position.
+                let target_position = actors.get(target.handle).position(&scene.graph);
+                let (head, chest) = target_visibility(&scene.physics, eye, target_position);
+                if head || chest {
+                    target.position = target_position;
+                    self.target_lost_time = 0.0;
+                } else {
+                    self.target_lost_time += dt;
                 }
+                current_target = Some(target.handle);
+                closest_distance = position.metric_distance(&target_position);
+            } else {
+                // Target is dead or gone, there is nothing to search for.
+                self.target = None;
             }
         }
 
-        let position = self.character.position(&scene.graph);
-        let mut closest_distance = std::f32::MAX;
-
-        let mut query_buffer = Vec::default();
-        'target_loop: for desc in targets.iter().filter(|desc| desc.handle != self_handle) {
-            let distance = position.metric_distance(&desc.position);
-            if distance != 0.0 && distance < 1.6 || self.frustum.is_contains_point(desc.position) {
-                let ray = Ray::from_two_points(desc.position, position);
-                scene.physics.cast_ray(
-                    RayCastOptions {
-                        ray,
-                        groups: InteractionGroups::all(),
-                        max_len: ray.dir.norm(),
-                        sort_results: true,
-                    },
-                    &mut query_buffer,
-                );
+        if current_target.is_some() && self.target_lost_time >= self.definition.lose_target_time {
+            let last_known_position = self.target.take().unwrap().position;
+            self.search = Some(Search::new(
+                last_known_position,
+                self.definition.search_time,
+                rng,
+            ));
+            current_target = None;
+            closest_distance = std::f32::MAX;
+        }
 
-                'hit_loop: for hit in query_buffer.iter() {
-                    let collider = scene.physics.colliders.get(hit.collider.into()).unwrap();
-                    let body = collider.parent();
+        // Stick to valid target for a while and switch only to a target that is closer by
+        // a margin, otherwise bot will jump between targets at similar distance.
+        if current_target.is_some() {
+            if self.time_on_target < self.definition.min_time_on_target {
+                return;
+            }
+            closest_distance -= self.definition.target_switch_margin;
+        }
+        // Nothing beyond far plane of the frustum can be seen.
+        let radius = closest_distance.min(Self::VIEW_DISTANCE);
+
+        // Bot keeps attacking whoever has hurt it, but picks new targets only among actors
+        // of hostile factions.
+        let faction = self.character.faction;
+        for handle in actors.actors_in_radius(scene, position, radius, false) {
+            let actor = actors.get(handle);
+            if handle == self_handle
+                || Some(handle) == current_target
+                || !Self::is_hostile(faction, actor.faction)
+            {
+                continue;
+            }
 
-                    if collider.shape().as_trimesh().is_some() {
-                        // Target is behind something.
-                        continue 'target_loop;
-                    } else {
-                        // Prevent setting self as target.
-                        if self.character.body == body.into() {
-                            continue 'hit_loop;
-                        }
-                    }
+            let actor_position = actor.position(&scene.graph);
+            let distance = position.metric_distance(&actor_position);
+            let detected = if distance != 0.0 && distance < 1.6 {
+                // Target that touches the bot is detected instantly.
+                if is_line_of_sight_clear(&scene.physics, actor_position, eye) {
+                    self.awareness.alert(handle);
+                    true
+                } else {
+                    false
                 }
+            } else if self.frustum.is_contains_point(actor_position) {
+                // Check both head and chest of the target to find out whether it is in
+                // the open or peeks from behind partial cover.
+                let (head, chest) = target_visibility(&scene.physics, eye, actor_position);
+                (head || chest) && self.awareness.notice(handle, distance, head && chest, dt)
+            } else {
+                false
+            };
 
-                if distance < closest_distance {
-                    self.target = Some(Target {
-                        position: desc.position,
-                        handle: desc.handle,
-                    });
-                    closest_distance = distance;
-                }
+            if detected && distance < closest_distance {
+                self.set_target(handle, actor_position);
+                closest_distance = distance;
             }
         }
+
+        self.awareness.update(dt);
     }
 
     pub fn debug_draw(&self, context: &mut SceneDrawingContext) {
@@ -449,12 +1158,12 @@ impl Bot {
     }
 
     fn update_frustum(&mut self, position: Vector3<f32>, graph: &Graph) {
-        let head_pos = position + Vector3::new(0.0, 0.4, 0.0);
+        let head_pos = eye_position(position);
         let up = graph[self.model].up_vector();
         let look_at = head_pos + graph[self.model].look_vector();
         let view_matrix = Matrix4::look_at_rh(&Point3::from(head_pos), &Point3::from(look_at), &up);
         let projection_matrix =
-            Matrix4::new_perspective(16.0 / 9.0, 90.0f32.to_radians(), 0.1, 20.0);
+            Matrix4::new_perspective(16.0 / 9.0, 90.0f32.to_radians(), 0.1, Self::VIEW_DISTANCE);
         let view_projection_matrix = projection_matrix * view_matrix;
         self.frustum = Frustum::from(view_projection_matrix).unwrap();
     }
@@ -469,9 +1178,32 @@ impl Bot {
             .update(time.delta);
 
         if self.spine.is_some() {
+            let (_, spine_angle) = self.split_pitch(angle);
             graph[self.spine]
                 .local_transform_mut()
-                .set_rotation(UnitQuaternion::from_axis_angle(&Vector3::x_axis(), angle));
+                .set_rotation(UnitQuaternion::from_axis_angle(
+                    &Vector3::x_axis(),
+                    spine_angle,
+                ));
+        }
+    }
+
+    /// Whether aim pose of the bot follows pitch, so the weapon points at targets that are
+    /// far above or below the bot.
+    pub fn uses_full_body_aim(&self) -> bool {
+        self.upper_body_machine.aim_up_animation.is_some()
+    }
+
+    /// Splits pitch into a factor of aim pose in [-1; 1] range (negative is up) and an angle
+    /// that is left for the spine. The spine takes the whole pitch without full-body aim.
+    fn split_pitch(&self, pitch: f32) -> (f32, f32) {
+        match self.definition.full_body_aim.as_ref() {
+            Some(full_body_aim) if self.uses_full_body_aim() => {
+                let max_pitch = full_body_aim.max_pitch.to_radians().max(std::f32::EPSILON);
+                let pose_pitch = pitch.max(-max_pitch).min(max_pitch);
+                (pose_pitch / max_pitch, pitch - pose_pitch)
+            }
+            _ => (0.0, pitch),
         }
     }
 
@@ -487,15 +1219,183 @@ impl Bot {
         body.set_position(position, true);
     }
 
-    fn update_agent(&mut self, navmesh: &mut Navmesh, time: GameTime) {
-        if let Some(target) = self.target.as_ref() {
-            self.agent.set_target(target.position);
+    /// Returns the point that the bot walks to in its current state, if any.
+    fn destination(&self, position: Vector3<f32>) -> Option<Vector3<f32>> {
+        if let Some(dodge) = self.dodge.as_ref() {
+            Some(dodge.destination(position))
+        } else if let Some(target) = self.target.as_ref() {
+            if self.state == BotState::Flee {
+                // Go away from the target to a point that is at minimal engagement distance.
+                let away = (position - target.position)
+                    .try_normalize(std::f32::EPSILON)
+                    .unwrap_or_else(Vector3::z);
+                Some(target.position + away.scale(self.definition.min_engagement_distance))
+            } else {
+                Some(target.position)
+            }
+        } else if let Some(investigation) = self.investigation.as_ref() {
+            Some(investigation.destination())
+        } else if let Some(search) = self.search.as_ref() {
+            Some(search.destination())
+        } else {
+            self.patrol.as_ref().and_then(|p| p.destination())
+        }
+    }
+
+    /// Moves the agent along its path. Destination and path are recalculated only when the
+    /// bot thinks, in between the agent follows the last path.
+    fn update_agent(
+        &mut self,
+        position: Vector3<f32>,
+        navmesh: &mut Navmesh,
+        jump_links: &JumpLinkContainer,
+        gravity: f32,
+        time: GameTime,
+        think: bool,
+    ) {
+        let destination = match self.destination(position) {
+            Some(destination) => destination,
+            None => return,
+        };
+        if !think {
             let _ = self.agent.update(time.delta, navmesh);
+            return;
+        }
+
+        let destination = self.navmesh_guard.destination(navmesh, destination);
+        self.agent.set_target(destination);
+        let _ = self.agent.update(time.delta, navmesh);
+
+        // Destination may lie on another part of navmesh, in this case go to a jump link
+        // that leads closer to it.
+        if let Some(height) = self.definition.jump_height {
+            let reachable = self.agent.path().last().cloned().unwrap_or(position);
+            if reachable.metric_distance(&destination) > JumpLinkContainer::UNREACHABLE_DISTANCE {
+                if let Some(link) = jump_links.find_route(position, reachable, destination) {
+                    if position.metric_distance(&link.begin) <= Jump::REACH_DISTANCE {
+                        self.jump = Some(Jump::new(position, link.end, height, gravity));
+                    } else {
+                        self.agent.set_target(link.begin);
+                        let _ = self.agent.update(time.delta, navmesh);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Whether the bot is fighting, searching for a target or checking a noise.
+    pub fn is_alerted(&self) -> bool {
+        !self.is_dead()
+            && (self.target.is_some() || self.search.is_some() || self.investigation.is_some())
+    }
+
+    /// Whether the bot walks to some point (not circling around a target).
+    fn is_walking(&self) -> bool {
+        match self.state {
+            BotState::Chase
+            | BotState::Flee
+            | BotState::Dodge
+            | BotState::Investigate
+            | BotState::Patrol => true,
+            BotState::Search => self.search.as_ref().map_or(false, |s| !s.is_scanning()),
+            _ => false,
+        }
+    }
+
+    /// Changes horizontal velocity of the bot towards desired one, no faster than acceleration
+    /// from the definition allows. Returns new velocity.
+    fn accelerate(&mut self, desired: Vector3<f32>, dt: f32) -> Vector3<f32> {
+        self.velocity = match self.definition.acceleration {
+            Some(acceleration) => {
+                let delta = desired - self.velocity;
+                let max_delta = acceleration * dt;
+                if delta.norm() > max_delta {
+                    self.velocity + delta.normalize().scale(max_delta)
+                } else {
+                    desired
+                }
+            }
+            None => desired,
+        };
+        self.velocity
+    }
+
+    /// Reacts to a grenade that explodes in given time, the bot may run away from it
+    /// depending on its definition.
+    pub fn on_grenade_warning(
+        &mut self,
+        graph: &Graph,
+        position: Vector3<f32>,
+        time: f32,
+        radius: f32,
+        rng: &mut GameRng,
+    ) {
+        if let Some(definition) = self.definition.dodge.as_ref() {
+            if !self.is_dead()
+                && time > definition.reaction_time
+                && self.character.position(graph).metric_distance(&position) < radius
+                && rng.gen_range(0.0..1.0) < definition.chance
+            {
+                self.dodge = Some(Dodge::new(position, radius, time));
+            }
+        }
+    }
+
+    /// Screams when the bot spots a target, the scream alerts bots of the same faction
+    /// nearby.
+    fn scream(&mut self, self_handle: Handle<Actor>, position: Vector3<f32>, rng: &mut GameRng) {
+        let target = match self.target.as_ref() {
+            Some(target) => target,
+            None => return,
+        };
+        if self.definition.scream_loudness <= 0.0 || self.scream_cooldown > 0.0 {
+            return;
+        }
+
+        self.scream_cooldown = Self::SCREAM_COOLDOWN;
+        self.scream_time = Self::SCREAM_DURATION;
+
+        let sender = self.character.sender.as_ref().unwrap();
+        if let Some(scream_sound) = self.definition.scream_sounds.choose(rng) {
+            sender
+                .send(Message::PlaySound {
+                    path: PathBuf::from(scream_sound.clone()),
+                    position,
+                    gain: 1.0,
+                    rolloff_factor: 1.0,
+                    radius: 2.0,
+                    caption: Some(format!("[{} screams]", self.kind.description())),
+                })
+                .unwrap();
+        }
+        sender
+            .send(Message::BotScream {
+                bot: self_handle,
+                position,
+                loudness: self.definition.scream_loudness,
+                target_position: target.position,
+            })
+            .unwrap();
+    }
+
+    /// Reacts to a scream of another bot in range of the scream, calm bots of the same faction
+    /// go to investigate the position of the target of the screamer. Hearing a scream does not
+    /// make a bot scream itself, so alert spreads further only when investigating bots
+    /// actually spot the target.
+    pub fn on_scream(&mut self, faction: Faction, target_position: Vector3<f32>) {
+        if !self.is_dead() && !self.is_alerted() && self.character.faction == faction {
+            self.investigation = Some(Investigation::new(target_position));
         }
     }
 
     pub fn set_target(&mut self, handle: Handle<Actor>, position: Vector3<f32>) {
+        if self.target.as_ref().map_or(true, |t| t.handle != handle) {
+            self.time_on_target = 0.0;
+        }
         self.target = Some(Target { position, handle });
+        self.target_lost_time = 0.0;
+        self.search = None;
+        self.investigation = None;
     }
 
     fn calculate_movement_speed_factor(&self, physics: &Physics) -> f32 {
@@ -508,7 +1408,10 @@ impl Bot {
                 .get(hitbox.collider.into())
                 .unwrap()
                 .parent();
-            if self.impact_handler.is_affected(body.into()) {
+            if self
+                .impact_handler
+                .is_affected(body.into(), &self.definition.impact)
+            {
                 k = hitbox.movement_speed_factor.min(k);
             }
         }
@@ -520,14 +1423,54 @@ impl Bot {
         &mut self,
         self_handle: Handle<Actor>,
         context: &mut UpdateContext,
-        targets: &[TargetDescriptor],
+        actors: &ActorContainer,
+        attack_slots: &mut AttackSlots,
+        observer: Option<&Observer>,
     ) {
-        let mut is_moving = false;
         let mut can_aim = false;
-        let mut in_close_combat = false;
         let mut movement_speed_factor = 1.0;
 
+        // Difficulty can be changed at any time, so keep speeds of animations in sync.
+        let animation_speed_scale = context.difficulty.bot_animation_speed_scale();
+        if self.animation_speed_scale != animation_speed_scale {
+            self.animation_speed_scale = animation_speed_scale;
+            self.upper_body_machine.set_speed_scale(
+                context.scene,
+                self.definition,
+                animation_speed_scale,
+            );
+            self.lower_body_machine.set_speed_scale(
+                context.scene,
+                self.definition,
+                animation_speed_scale,
+            );
+        }
+
+        // Laser sight telegraphs where the bot is about to shoot.
+        let weapon = self.character.current_weapon();
+        if context.weapons.contains(weapon) {
+            context.weapons[weapon].show_laser_sight_for_bot(
+                !self.is_dead() && self.target.is_some() && self.can_shoot(),
+                &mut context.scene.graph,
+            );
+        }
+
         if self.is_dead() {
+            self.update_state(
+                self_handle,
+                self.character.position(&context.scene.graph),
+                attack_slots,
+                context.rng,
+            );
+
+            if !self.loot_dropped {
+                self.drop_loot(&context.scene.graph, context.weapons, context.rng);
+            }
+
+            if self.can_be_removed(context.scene) {
+                self.corpse_time += context.time.delta;
+            }
+
             for &animation in &[
                 self.upper_body_machine.dying_animation,
                 self.lower_body_machine.dying_animation,
@@ -552,11 +1495,53 @@ impl Bot {
                 self.body = Default::default();
             }
         } else {
-            movement_speed_factor = self.calculate_movement_speed_factor(&context.scene.physics);
+            self.character.update_weapon_switch(context.time.delta);
+            self.character
+                .update_status_effects(self_handle, context.time.delta);
+            self.character.update_knockback(context.time.delta);
+
+            movement_speed_factor = self.calculate_movement_speed_factor(&context.scene.physics)
+                * self.character.movement_speed_factor();
             self.agent
                 .set_speed(self.definition.walk_speed * movement_speed_factor);
 
-            self.select_target(self_handle, context.scene, targets);
+            // Alerted bots are always updated at full rate.
+            self.lod = if self.is_alerted() {
+                UpdateLod::Full
+            } else {
+                UpdateLod::new(self.character.position(&context.scene.graph), observer)
+            };
+            // Snap to the rate of new LOD if the bot became more important.
+            self.think_timer = self.think_timer.min(self.lod.interval()) - context.time.delta;
+            self.think_elapsed += context.time.delta;
+            self.time_on_target += context.time.delta;
+            let think = self.think_timer <= 0.0;
+            if think {
+                let had_target = self.target.is_some();
+                self.think_timer = self.lod.interval();
+                self.select_target(
+                    self_handle,
+                    context.scene,
+                    actors,
+                    self.think_elapsed,
+                    context.rng,
+                );
+                self.think_elapsed = 0.0;
+                if !had_target {
+                    self.scream(
+                        self_handle,
+                        self.character.position(&context.scene.graph),
+                        context.rng,
+                    );
+                }
+            }
+            self.scream_cooldown -= context.time.delta;
+            self.scream_time -= context.time.delta;
+
+            let knockback = self.character.knockback_velocity();
+            let gravity = context.scene.physics.gravity.y;
+            let has_ground_contact =
+                self.jump.is_some() && self.has_ground_contact(&context.scene.physics);
 
             let body = context
                 .scene
@@ -564,47 +1549,200 @@ impl Bot {
                 .bodies
                 .get_mut(self.character.body.into())
                 .unwrap();
+            let position = body.position().translation.vector;
             let look_dir = match self.target.as_ref() {
-                None => {
-                    in_close_combat = false;
-                    Vector3::z()
-                }
-                Some(target) => {
-                    let d = target.position - body.position().translation.vector;
-                    in_close_combat = d.norm() <= self.definition.close_combat_distance;
-                    d
-                }
+                None => Vector3::z(),
+                Some(target) => target.position - position,
             };
 
-            let position = body.position().translation.vector;
+            self.update_state(self_handle, position, attack_slots, context.rng);
+
+            if let Some(dodge) = self.dodge.as_mut() {
+                if !dodge.update(context.time.delta) {
+                    self.dodge = None;
+                }
+            }
+
             let navmesh = &mut context.scene.navmeshes[context.navmesh];
+
+            if let Some(search) = self.search.as_mut() {
+                search.update(position, navmesh, context.time.delta);
+                if search.is_over() {
+                    self.search = None;
+                }
+            }
+
+            // Look around the place of the noise once the bot has got there.
+            if let Some(investigation) = self.investigation.as_mut() {
+                if !investigation.update(position, context.time.delta) {
+                    self.search = Some(Search::new(
+                        investigation.destination(),
+                        self.definition.search_time,
+                        context.rng,
+                    ));
+                    self.investigation = None;
+                }
+            }
+
+            if self.definition.patrol_radius > 0.0 {
+                let calm = !self.is_alerted() && self.dodge.is_none();
+                let patrol = self
+                    .patrol
+                    .get_or_insert_with(|| Patrol::new(position, context.rng));
+                if calm {
+                    patrol.update(
+                        position,
+                        self.definition.patrol_radius,
+                        context.time.delta,
+                        context.rng,
+                    );
+                }
+            }
+
             self.agent.warp(position);
-            self.update_agent(navmesh, context.time);
-            self.move_target = self.agent.position();
+            if self.jump.is_none() {
+                self.update_agent(
+                    position,
+                    navmesh,
+                    context.jump_links,
+                    gravity,
+                    context.time,
+                    think,
+                );
+            }
+            let clearance = self.navmesh_guard.clearance(
+                navmesh,
+                self.agent.path(),
+                self.definition.body_radius,
+            );
+            self.move_target = keep_clearance(self.agent.path(), self.agent.position(), clearance);
 
-            self.update_frustum(position, &context.scene.graph);
+            if think {
+                self.update_frustum(position, &context.scene.graph);
+            }
 
             let was_damaged = self.character.health < self.last_health;
             if was_damaged {
-                self.restoration_time = 0.8;
+                self.restoration_time = self.definition.restoration_time;
+
+                if self.definition.damage_interrupts_attack && self.attack_windup > 0.0 {
+                    self.attack_windup = 0.0;
+                    context
+                        .scene
+                        .animations
+                        .get_mut(
+                            self.upper_body_machine.attack_animations
+                                [self.attack_animation_index as usize],
+                        )
+                        .set_enabled(false);
+                }
             }
 
             can_aim = self.restoration_time <= 0.0;
             self.last_health = self.character.health;
 
-            if !self.is_dead() && !in_close_combat && self.target.is_some() {
-                let mut vel = (self.move_target - position).scale(1.0 / context.time.delta);
+            let is_walking = self.is_walking();
+
+            if !is_walking {
+                // Bot is standing still intentionally.
+                self.stuck_detector.reset(position);
+            }
+
+            if let Some(jump) = self.jump.as_mut() {
+                // Bot is in the air, physics moves it along the arc of the jump.
+                if let Some(velocity) = jump.launch() {
+                    body.set_linvel(velocity, true);
+                }
+                if !jump.update(context.time.delta, has_ground_contact) {
+                    self.jump = None;
+                }
+            } else if is_walking {
+                let desired_dir = self.move_target - position;
+                let mut vel = match self.stuck_detector.update(
+                    self.kind,
+                    position,
+                    desired_dir,
+                    context.time.delta,
+                    think,
+                    context.rng,
+                ) {
+                    Some(sidestep) => sidestep.scale(self.definition.walk_speed),
+                    // Agent can't move faster than its speed.
+                    None => context.time.velocity_from_offset(
+                        desired_dir,
+                        self.definition.walk_speed * movement_speed_factor,
+                    ),
+                };
+                vel.y = 0.0;
+                let mut vel = self.accelerate(vel, context.time.delta);
                 vel.y = body.linvel().y;
-                body.set_linvel(vel, true);
                 self.last_move_dir = vel;
-                is_moving = true;
+                body.set_linvel(vel + knockback, true);
+            } else if let (BotState::Attack, Some(target)) = (self.state, self.target.as_ref()) {
+                // Lunge at the target during the swing, so it is harder to step out of reach.
+                let lunge_speed = self.definition.attack_animations
+                    [self.attack_animation_index as usize]
+                    .lunge_speed;
+                let mut to_target = target.position - position;
+                to_target.y = 0.0;
+                let desired = if self.attack_windup <= 0.0
+                    && lunge_speed > 0.0
+                    && to_target.norm() > Self::LUNGE_STOP_DISTANCE
+                {
+                    to_target
+                        .try_normalize(std::f32::EPSILON)
+                        .unwrap_or_default()
+                        .scale(lunge_speed)
+                } else {
+                    Vector3::default()
+                };
+                let mut vel = self.accelerate(desired, context.time.delta);
+                vel.y = body.linvel().y;
+                body.set_linvel(vel + knockback, true);
+            } else if let (BotState::Circle, Some(target)) = (self.state, self.target.as_ref()) {
+                // Walk sideways around the target while waiting for a free attack slot.
+                let to_target = target.position - position;
+                let mut vel = to_target
+                    .cross(&Vector3::y())
+                    .try_normalize(std::f32::EPSILON)
+                    .unwrap_or_default()
+                    .scale(
+                        self.circle_direction * self.definition.walk_speed * movement_speed_factor,
+                    );
+                vel.y = 0.0;
+                // Turn around instead of strafing off the edge of the navmesh.
+                if let Some(dir) = vel.try_normalize(std::f32::EPSILON) {
+                    if self.navmesh_guard.is_edge_ahead(
+                        navmesh,
+                        position,
+                        dir,
+                        self.definition.body_radius,
+                    ) {
+                        self.circle_direction = -self.circle_direction;
+                        vel = -vel;
+                    }
+                }
+                let mut vel = self.accelerate(vel, context.time.delta);
+                vel.y = body.linvel().y;
+                self.last_move_dir = vel;
+                body.set_linvel(vel + knockback, true);
             } else {
-                body.set_linvel(Vector3::new(0.0, body.linvel().y, 0.0), true);
+                let mut vel = self.accelerate(Vector3::default(), context.time.delta);
+                vel.y = body.linvel().y;
+                body.set_linvel(vel + knockback, true);
             }
 
             let sender = self.character.sender.clone().unwrap();
 
-            if !in_close_combat && can_aim && self.can_shoot() && self.target.is_some() {
+            if matches!(
+                self.state,
+                BotState::Chase | BotState::Flee | BotState::Dodge
+            ) && self.target.is_some()
+                && self.target_lost_time <= 0.0
+                && can_aim
+                && self.can_shoot()
+                && !self.character.is_switching_weapon()
+            {
                 if let Some(weapon) = self
                     .character
                     .weapons
@@ -621,10 +1759,17 @@ impl Bot {
                             .try_extract_exact_items(ItemKind::Ammo, ammo_per_shot)
                             == ammo_per_shot
                     {
+                        let half_angle = self.definition.aim_spread.half_angle(look_dir.norm())
+                            * context.difficulty.bot_aim_spread_scale();
                         sender
                             .send(Message::ShootWeapon {
                                 weapon,
-                                direction: Some(look_dir),
+                                direction: Some(random_direction_in_cone(
+                                    look_dir,
+                                    half_angle,
+                                    context.rng,
+                                )),
+                                damage_scale: 1.0,
                             })
                             .unwrap();
                     }
@@ -636,21 +1781,40 @@ impl Bot {
 
             // Apply damage to target from melee attack
             if let Some(target) = self.target.as_ref() {
+                // Use the most recent position of the target, the target might have stepped
+                // back while the bot was swinging.
+                let target_position = if actors.contains(target.handle) {
+                    actors.get(target.handle).position(&context.scene.graph)
+                } else {
+                    target.position
+                };
+
                 while let Some(event) = context
                     .scene
                     .animations
                     .get_mut(current_attack_animation)
                     .pop_event()
                 {
-                    if event.signal_id == UpperBodyMachine::HIT_SIGNAL && in_close_combat {
+                    if event.signal_id == UpperBodyMachine::HIT_SIGNAL
+                        && self.state == BotState::Attack
+                        && is_in_melee_range(
+                            position,
+                            target_position,
+                            self.definition.close_combat_distance,
+                        )
+                    {
                         sender
                             .send(Message::DamageActor {
                                 actor: target.handle,
-                                who: Default::default(),
+                                who: self_handle,
                                 amount: self.definition.attack_animations
                                     [self.attack_animation_index as usize]
                                     .damage
-                                    .amount(),
+                                    .amount()
+                                    * context.difficulty.bot_damage_scale(),
+                                damage_type: DamageType::Melee,
+                                critical: false,
+                                hit_box: None,
                             })
                             .unwrap();
                     }
@@ -687,15 +1851,23 @@ impl Bot {
             let attack_animation = context.scene.animations.get_mut(current_attack_animation);
             let attack_animation_ended = attack_animation.has_ended();
 
-            if in_close_combat
+            if self.state == BotState::Attack
                 && self.attack_timeout <= 0.0
                 && (attack_animation_ended || !attack_animation.is_enabled())
             {
                 attack_animation.set_enabled(true).rewind();
-                self.attack_animation_index = rg3d::core::rand::thread_rng()
+                self.attack_animation_index = context
+                    .rng
                     .gen_range(0..self.upper_body_machine.attack_animations.len())
                     as u32;
 
+                // Hold the first frame of the attack for a while, so the player is able to
+                // read the attack and dodge it.
+                let attack_definition =
+                    &self.definition.attack_animations[self.attack_animation_index as usize];
+                self.attack_windup = attack_definition.windup
+                    / (attack_definition.speed * self.animation_speed_scale);
+
                 context
                     .scene
                     .animations
@@ -704,7 +1876,30 @@ impl Bot {
                             [self.attack_animation_index as usize],
                     )
                     .set_enabled(true)
-                    .rewind();
+                    .rewind()
+                    .set_speed(if self.attack_windup > 0.0 {
+                        0.0
+                    } else {
+                        attack_definition.speed * self.animation_speed_scale
+                    });
+            }
+
+            if self.attack_windup > 0.0 {
+                self.attack_windup -= context.time.delta;
+                if self.attack_windup <= 0.0 {
+                    context
+                        .scene
+                        .animations
+                        .get_mut(
+                            self.upper_body_machine.attack_animations
+                                [self.attack_animation_index as usize],
+                        )
+                        .set_speed(
+                            self.definition.attack_animations[self.attack_animation_index as usize]
+                                .speed
+                                * self.animation_speed_scale,
+                        );
+                }
             }
 
             if self.attack_timeout < 0.0 && attack_animation_ended {
@@ -718,17 +1913,45 @@ impl Bot {
                     self.aim_vertically(look_dir, &mut context.scene.graph, context.time);
                     self.aim_horizontally(look_dir, &mut context.scene.physics, context.time);
                 }
+            } else if let Some(search) = self.search.as_ref() {
+                // Look where the bot goes and look around on each point, frustum follows
+                // the body, so the bot scans the area.
+                let mut move_dir = self.last_move_dir;
+                move_dir.y = 0.0;
+                let look_dir = move_dir.try_normalize(std::f32::EPSILON).map(|move_dir| {
+                    if search.is_scanning() {
+                        search.scan_direction(move_dir)
+                    } else {
+                        move_dir
+                    }
+                });
+                if let Some(look_dir) = look_dir {
+                    self.aim_horizontally(look_dir, &mut context.scene.physics, context.time);
+                }
+            } else if self.is_walking() {
+                // Look where the bot goes when it patrols, investigates or dodges.
+                let mut move_dir = self.last_move_dir;
+                move_dir.y = 0.0;
+                if let Some(look_dir) = move_dir.try_normalize(std::f32::EPSILON) {
+                    self.aim_horizontally(look_dir, &mut context.scene.physics, context.time);
+                }
             }
         }
 
-        self.lower_body_machine
-            .set_walk_animation_speed(context.scene, movement_speed_factor);
+        let is_moving = self.is_walking() || self.state == BotState::Circle;
+
+        self.lower_body_machine.set_walk_animation_speed(
+            context.scene,
+            movement_speed_factor
+                * self.definition.animation_speeds.walk
+                * self.animation_speed_scale,
+        );
         self.lower_body_machine.apply(
             context.scene,
             context.time.delta,
             LowerBodyMachineInput {
                 walk: is_moving,
-                scream: false,
+                scream: self.scream_time > 0.0 && !self.is_dead(),
                 dead: self.is_dead(),
             },
         );
@@ -736,16 +1959,20 @@ impl Bot {
             context.scene,
             context.time,
             UpperBodyMachineInput {
-                attack: in_close_combat && self.attack_timeout <= 0.0,
+                attack: self.state == BotState::Attack && self.attack_timeout <= 0.0,
                 walk: is_moving,
-                scream: false,
+                scream: self.scream_time > 0.0 && !self.is_dead(),
                 dead: self.is_dead(),
-                aim: self.definition.can_use_weapons && can_aim,
+                aim: self.can_use_weapons && can_aim,
+                aim_pitch_factor: self.split_pitch(self.pitch.angle()).0,
                 attack_animation_index: self.attack_animation_index,
             },
         );
-        self.impact_handler
-            .update_and_apply(context.time.delta, context.scene);
+        self.impact_handler.update_and_apply(
+            context.time.delta,
+            context.scene,
+            &self.definition.impact,
+        );
     }
 
     pub fn clean_up(&mut self, scene: &mut Scene) {
@@ -763,6 +1990,83 @@ impl Bot {
     }
 }
 
+/// Navmesh agent has no size, so paths go right through corners of obstacles. Pushes given
+/// point on the path away from nearby corners of the path by up to `clearance` of each
+/// corner, so the body of
+/// a bot goes around obstacles instead of rubbing against them. Corners are offset to the
+/// outer side of the turn, because obstacles that make a path turn are on its inner side.
+fn keep_clearance(path: &[Vector3<f32>], point: Vector3<f32>, clearance: &[f32]) -> Vector3<f32> {
+    let mut offset = Vector3::default();
+    for (pts, &radius) in path.windows(3).zip(clearance) {
+        let (prev, corner, next) = (pts[0], pts[1], pts[2]);
+        let mut to_point = point - corner;
+        to_point.y = 0.0;
+        let distance = to_point.norm();
+        if distance >= radius {
+            continue;
+        }
+        if let Some(inner) = corner_inner_direction(prev, corner, next) {
+            offset -= inner.scale(radius - distance);
+        }
+    }
+    point + offset
+}
+
+/// Returns horizontal direction to the inner side of the turn of a path at given corner.
+fn corner_inner_direction(
+    prev: Vector3<f32>,
+    corner: Vector3<f32>,
+    next: Vector3<f32>,
+) -> Option<Vector3<f32>> {
+    let mut inner = (prev - corner)
+        .try_normalize(std::f32::EPSILON)
+        .unwrap_or_default()
+        + (next - corner)
+            .try_normalize(std::f32::EPSILON)
+            .unwrap_or_default();
+    inner.y = 0.0;
+    inner.try_normalize(std::f32::EPSILON)
+}
+
+/// Height of the eyes of a bot above its position.
+const EYE_HEIGHT: f32 = 0.4;
+/// Offsets of the head and chest of a target above its position, these are the parts that
+/// are usually visible when a target peeks from behind cover.
+const TARGET_HEAD_OFFSET: f32 = 0.5;
+const TARGET_CHEST_OFFSET: f32 = 0.2;
+
+fn eye_position(position: Vector3<f32>) -> Vector3<f32> {
+    position + Vector3::new(0.0, EYE_HEIGHT, 0.0)
+}
+
+/// Checks visibility of head and chest of a target from given eye position, returns
+/// `(head, chest)` pair.
+fn target_visibility(
+    physics: &Physics,
+    eye: Vector3<f32>,
+    target_position: Vector3<f32>,
+) -> (bool, bool) {
+    let head = is_line_of_sight_clear(
+        physics,
+        target_position + Vector3::new(0.0, TARGET_HEAD_OFFSET, 0.0),
+        eye,
+    );
+    let chest = is_line_of_sight_clear(
+        physics,
+        target_position + Vector3::new(0.0, TARGET_CHEST_OFFSET, 0.0),
+        eye,
+    );
+    (head, chest)
+}
+
+fn is_in_melee_range(
+    position: Vector3<f32>,
+    target_position: Vector3<f32>,
+    close_combat_distance: f32,
+) -> bool {
+    position.metric_distance(&target_position) <= close_combat_distance
+}
+
 fn clean_machine(machine: &Machine, scene: &mut Scene) {
     for node in machine.nodes() {
         if let PoseNode::PlayAnimation(node) = node {
@@ -785,6 +2089,7 @@ impl Visit for Bot {
         self.character.visit("Character", visitor)?;
         self.model.visit("Model", visitor)?;
         self.target.visit("Target", visitor)?;
+        self.state.visit("State", visitor)?;
         self.lower_body_machine
             .visit("LocomotionMachine", visitor)?;
         self.upper_body_machine.visit("AimMachine", visitor)?;
@@ -792,10 +2097,210 @@ impl Visit for Bot {
         self.yaw.visit("Yaw", visitor)?;
         self.pitch.visit("Pitch", visitor)?;
         self.hips.visit("Hips", visitor)?;
+        self.spine.visit("Spine", visitor)?;
+        self.can_use_weapons.visit("CanUseWeapons", visitor)?;
         self.attack_animation_index
             .visit("AttackAnimationIndex", visitor)?;
         self.agent.visit("Agent", visitor)?;
+        self.loot_dropped.visit("LootDropped", visitor)?;
+        self.loot.visit("Loot", visitor)?;
+        self.attack_windup.visit("AttackWindup", visitor)?;
+        self.corpse_time.visit("CorpseTime", visitor)?;
+        self.move_target.visit("MoveTarget", visitor)?;
+        self.last_move_dir.visit("LastMoveDir", visitor)?;
+        self.velocity.visit("Velocity", visitor)?;
+        self.last_health.visit("LastHealth", visitor)?;
+        self.attack_timeout.visit("AttackTimeout", visitor)?;
+        self.awareness.visit("Awareness", visitor)?;
+        self.time_on_target.visit("TimeOnTarget", visitor)?;
+        self.target_lost_time.visit("TargetLostTime", visitor)?;
+        self.search.visit("Search", visitor)?;
+        self.investigation.visit("Investigation", visitor)?;
+        self.patrol.visit("Patrol", visitor)?;
+        self.dodge.visit("Dodge", visitor)?;
+        self.scream_cooldown.visit("ScreamCooldown", visitor)?;
+        self.scream_time.visit("ScreamTime", visitor)?;
+
+        // Frustum and LOD are not saved, they are re-calculated on each update.
 
         visitor.leave_region()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::visit_round_trip;
+
+    #[test]
+    fn melee_hit_is_skipped_if_target_stepped_back() {
+        let position = Vector3::new(1.0, 0.0, 1.0);
+        let close_combat_distance = 1.2;
+
+        // Target was in range when the swing started.
+        assert!(is_in_melee_range(
+            position,
+            Vector3::new(1.0, 0.0, 2.0),
+            close_combat_distance
+        ));
+        // Target stepped back before the hit signal.
+        assert!(!is_in_melee_range(
+            position,
+            Vector3::new(1.0, 0.0, 2.5),
+            close_combat_distance
+        ));
+    }
+
+    /// Creates a scene with a wall across X axis at given `x`, the top of the wall is at
+    /// given height.
+    fn scene_with_wall(x: f32, top: f32) -> Scene {
+        let mut scene = Scene::new();
+        let body = scene
+            .physics
+            .add_body(RigidBodyBuilder::new(BodyStatus::Static).build());
+        scene.physics.add_collider(
+            ColliderBuilder::trimesh(
+                vec![
+                    Point3::new(x, -5.0, -5.0),
+                    Point3::new(x, -5.0, 5.0),
+                    Point3::new(x, top, 5.0),
+                    Point3::new(x, top, -5.0),
+                ],
+                vec![[0, 1, 2], [0, 2, 3]],
+            )
+            .build(),
+            body,
+        );
+        scene
+    }
+
+    #[test]
+    fn target_peeking_over_cover_is_partially_visible() {
+        let target = Vector3::new(0.0, 0.0, 0.0);
+        let eye = Vector3::new(5.0, TARGET_HEAD_OFFSET, 0.0);
+
+        let open = Scene::new();
+        assert_eq!(target_visibility(&open.physics, eye, target), (true, true));
+
+        // Low wall covers chest of the target, but its head is exposed.
+        let partial_cover = scene_with_wall(2.0, 0.4);
+        assert_eq!(
+            target_visibility(&partial_cover.physics, eye, target),
+            (true, false)
+        );
+
+        let full_cover = scene_with_wall(2.0, 1.0);
+        assert_eq!(
+            target_visibility(&full_cover.physics, eye, target),
+            (false, false)
+        );
+    }
+
+    fn calm_bot() -> Bot {
+        let mut bot = Bot {
+            kind: BotKind::Zombie,
+            definition: Bot::get_definition(BotKind::Zombie),
+            ..Default::default()
+        };
+        bot.character.health = 100.0;
+        bot
+    }
+
+    #[test]
+    fn bot_investigates_scream_and_then_searches() {
+        let mut bot = calm_bot();
+        let mut attack_slots = AttackSlots::default();
+        let mut rng = GameRng::new(1);
+        let point = Vector3::new(10.0, 0.0, 0.0);
+
+        bot.on_scream(bot.character.faction, point);
+        bot.update_state(
+            Handle::NONE,
+            Vector3::default(),
+            &mut attack_slots,
+            &mut rng,
+        );
+        assert_eq!(bot.state(), BotState::Investigate);
+        assert_eq!(bot.destination(Vector3::default()), Some(point));
+
+        // Investigation is over once the bot has got to the noise.
+        let investigation = bot.investigation.as_mut().unwrap();
+        assert!(investigation.update(Vector3::default(), 0.1));
+        assert!(!investigation.update(point, 0.1));
+    }
+
+    #[test]
+    fn dodging_bot_is_not_fleeing() {
+        let mut bot = calm_bot();
+        let mut attack_slots = AttackSlots::default();
+        let mut rng = GameRng::new(1);
+
+        bot.dodge = Some(Dodge::new(Vector3::new(1.0, 0.0, 0.0), 3.0, 2.0));
+        bot.update_state(
+            Handle::NONE,
+            Vector3::default(),
+            &mut attack_slots,
+            &mut rng,
+        );
+        assert_eq!(bot.state(), BotState::Dodge);
+    }
+
+    #[test]
+    fn calm_bot_patrols_around_home() {
+        let mut bot = calm_bot();
+        let mut attack_slots = AttackSlots::default();
+        let mut rng = GameRng::new(1);
+        let home = Vector3::new(2.0, 0.0, 2.0);
+        let radius = 4.0;
+
+        bot.patrol = Some(Patrol::new(home, &mut rng));
+        bot.update_state(Handle::NONE, home, &mut attack_slots, &mut rng);
+        assert_eq!(bot.state(), BotState::Idle);
+
+        // Wait time at the first point is over.
+        bot.patrol
+            .as_mut()
+            .unwrap()
+            .update(home, radius, 10.0, &mut rng);
+        bot.update_state(Handle::NONE, home, &mut attack_slots, &mut rng);
+        assert_eq!(bot.state(), BotState::Patrol);
+        let destination = bot.destination(home).unwrap();
+        assert!(destination.metric_distance(&home) <= radius);
+    }
+
+    #[test]
+    fn chasing_bot_survives_save_and_load() {
+        let mut bot = Bot {
+            kind: BotKind::Zombie,
+            definition: Bot::get_definition(BotKind::Zombie),
+            state: BotState::Chase,
+            target: Some(Target {
+                position: Vector3::new(3.0, 0.0, 4.0),
+                handle: Handle::new(1, 2),
+            }),
+            move_target: Vector3::new(1.0, 0.0, 2.0),
+            last_move_dir: Vector3::new(0.0, 0.0, 1.5),
+            last_health: 80.0,
+            attack_timeout: 0.3,
+            restoration_time: 0.7,
+            time_on_target: 2.0,
+            ..Default::default()
+        };
+        bot.character.health = 80.0;
+
+        let loaded = visit_round_trip(&mut bot, "Bot");
+
+        assert_eq!(loaded.kind, BotKind::Zombie);
+        assert_eq!(loaded.state, BotState::Chase);
+        let target = loaded.target.as_ref().unwrap();
+        assert_eq!(target.position, Vector3::new(3.0, 0.0, 4.0));
+        assert_eq!(target.handle, Handle::new(1, 2));
+        assert_eq!(loaded.move_target, bot.move_target);
+        assert_eq!(loaded.last_move_dir, bot.last_move_dir);
+        // Bot must not think that it was damaged right after loading.
+        assert_eq!(loaded.last_health, loaded.character.health);
+        assert_eq!(loaded.attack_timeout, 0.3);
+        assert_eq!(loaded.restoration_time, 0.7);
+        assert_eq!(loaded.time_on_target, 2.0);
+    }
+}
@@ -0,0 +1,189 @@
+//! Keeps bots on the navmesh. Destinations that are computed as offsets (fleeing, dodging,
+//! search points, clearance around corners, strafing) may land off the navmesh, over a pit
+//! for example, and a bot would walk right into it. Snapping a point to the navmesh checks
+//! every triangle of the navmesh, so a bot caches results of the checks and repeats them
+//! only when their input has noticeably changed.
+
+use crate::bot::corner_inner_direction;
+use rg3d::{core::algebra::Vector3, utils::navmesh::Navmesh};
+use std::cmp::Ordering;
+
+/// Snaps given point to the closest point of the navmesh. Point is returned as is if the
+/// navmesh is empty or the point is not finite.
+pub fn clamp_to_navmesh(navmesh: &Navmesh, point: Vector3<f32>) -> Vector3<f32> {
+    if !point.iter().all(|c| c.is_finite()) {
+        return point;
+    }
+
+    let vertices = navmesh.vertices();
+    navmesh
+        .triangles()
+        .iter()
+        .map(|triangle| {
+            let [a, b, c] = triangle.0;
+            let closest = closest_point_on_triangle(
+                point,
+                vertices[a as usize].position(),
+                vertices[b as usize].position(),
+                vertices[c as usize].position(),
+            );
+            (closest, closest.metric_distance(&point))
+        })
+        .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+        .map_or(point, |(closest, _)| closest)
+}
+
+/// Closest point of the triangle `(a, b, c)` to `p`, it is found by checking in which
+/// region of the triangle (vertex, edge or face) the point projects.
+fn closest_point_on_triangle(
+    p: Vector3<f32>,
+    a: Vector3<f32>,
+    b: Vector3<f32>,
+    c: Vector3<f32>,
+) -> Vector3<f32> {
+    let ab = b - a;
+    let ac = c - a;
+    let ap = p - a;
+    let d1 = ab.dot(&ap);
+    let d2 = ac.dot(&ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+
+    let bp = p - b;
+    let d3 = ab.dot(&bp);
+    let d4 = ac.dot(&bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab.scale(d1 / (d1 - d3));
+    }
+
+    let cp = p - c;
+    let d5 = ab.dot(&cp);
+    let d6 = ac.dot(&cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac.scale(d2 / (d2 - d6));
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        return b + (c - b).scale((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+
+    let denom = va + vb + vc;
+    if denom.abs() <= std::f32::EPSILON {
+        // Degenerate triangle.
+        return a;
+    }
+    a + ab.scale(vb / denom) + ac.scale(vc / denom)
+}
+
+/// Horizontal distance between two points.
+fn horizontal_distance(a: Vector3<f32>, b: Vector3<f32>) -> f32 {
+    let mut offset = a - b;
+    offset.y = 0.0;
+    offset.norm()
+}
+
+/// Cached results of navmesh checks of a bot. It is not saved, checks are just repeated on
+/// first use after loading.
+#[derive(Default)]
+pub struct NavmeshGuard {
+    /// Last snapped destination, as `(requested, snapped)` pair.
+    destination: Option<(Vector3<f32>, Vector3<f32>)>,
+    /// Corners of the path for which clearance was calculated.
+    corners: Vec<Vector3<f32>>,
+    /// Clearance (in meters) of each corner.
+    clearance: Vec<f32>,
+    /// Last checked point ahead of a strafing bot, as `(point, off_navmesh)` pair.
+    edge_check: Option<(Vector3<f32>, bool)>,
+}
+
+impl NavmeshGuard {
+    /// Destination is snapped again only when it moves further than this distance (in meters)
+    /// from the last snapped one.
+    const DESTINATION_TOLERANCE: f32 = 0.25;
+    /// Distance (in meters) ahead of a strafing bot at which it checks for the edge of the
+    /// navmesh.
+    const EDGE_LOOKAHEAD: f32 = 1.0;
+    /// Edge check is repeated when the point ahead moves further than this distance.
+    const EDGE_CHECK_TOLERANCE: f32 = 0.5;
+
+    /// Returns given destination snapped to the navmesh. Small moves of a destination (a
+    /// target walking around) reuse correction of the last snap.
+    pub fn destination(&mut self, navmesh: &Navmesh, destination: Vector3<f32>) -> Vector3<f32> {
+        match self.destination {
+            Some((requested, snapped))
+                if requested.metric_distance(&destination) <= Self::DESTINATION_TOLERANCE =>
+            {
+                destination + (snapped - requested)
+            }
+            _ => {
+                let snapped = clamp_to_navmesh(navmesh, destination);
+                self.destination = Some((destination, snapped));
+                snapped
+            }
+        }
+    }
+
+    /// Returns clearance (in meters) of each corner of given path. Corners at the edge of the
+    /// navmesh get less clearance than `radius`, so the body of a bot won't be pushed off the
+    /// navmesh. Clearance is recalculated only when corners of the path change.
+    pub fn clearance(&mut self, navmesh: &Navmesh, path: &[Vector3<f32>], radius: f32) -> &[f32] {
+        let corners: &[Vector3<f32>] = if path.len() > 2 {
+            &path[1..path.len() - 1]
+        } else {
+            &[]
+        };
+        if self.corners != corners {
+            self.corners = corners.to_vec();
+            self.clearance = path
+                .windows(3)
+                .map(|pts| match corner_inner_direction(pts[0], pts[1], pts[2]) {
+                    Some(inner) => {
+                        let probe = pts[1] - inner.scale(radius);
+                        let off_navmesh =
+                            horizontal_distance(clamp_to_navmesh(navmesh, probe), probe);
+                        (radius - off_navmesh).max(0.0)
+                    }
+                    None => radius,
+                })
+                .collect();
+        }
+        &self.clearance
+    }
+
+    /// Checks whether a bot at given position that moves in given direction is about to
+    /// leave the navmesh.
+    pub fn is_edge_ahead(
+        &mut self,
+        navmesh: &Navmesh,
+        position: Vector3<f32>,
+        direction: Vector3<f32>,
+        radius: f32,
+    ) -> bool {
+        let ahead = position + direction.scale(Self::EDGE_LOOKAHEAD);
+        match self.edge_check {
+            Some((checked, off_navmesh))
+                if horizontal_distance(checked, ahead) <= Self::EDGE_CHECK_TOLERANCE =>
+            {
+                off_navmesh
+            }
+            _ => {
+                let off_navmesh =
+                    horizontal_distance(clamp_to_navmesh(navmesh, ahead), ahead) > radius;
+                self.edge_check = Some((ahead, off_navmesh));
+                off_navmesh
+            }
+        }
+    }
+}
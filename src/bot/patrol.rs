@@ -0,0 +1,83 @@
+//! Calm bots do not stand still all the time. Bots with a patrol radius in their definition
+//! wander between random points around the place where they started patrolling, waiting
+//! for a while at each point.
+
+use crate::utils::rng::GameRng;
+use rg3d::core::{
+    algebra::Vector3,
+    rand::Rng,
+    visitor::{Visit, VisitResult, Visitor},
+};
+
+#[derive(Default)]
+pub struct Patrol {
+    /// Center of the patrolled area.
+    home: Vector3<f32>,
+    point: Vector3<f32>,
+    /// Whether the bot walks to the point, otherwise it waits.
+    walking: bool,
+    /// Time left to wait or time spent walking to the point.
+    timer: f32,
+}
+
+impl Patrol {
+    const REACH_DISTANCE: f32 = 0.8;
+    /// Bot gives up on a point that it cannot reach in this time.
+    const MAX_WALK_TIME: f32 = 10.0;
+    /// Range of time (in seconds) that the bot waits at each point.
+    const MIN_WAIT_TIME: f32 = 3.0;
+    const MAX_WAIT_TIME: f32 = 8.0;
+
+    pub fn new(home: Vector3<f32>, rng: &mut GameRng) -> Self {
+        Self {
+            home,
+            point: home,
+            walking: false,
+            timer: rng.gen_range(Self::MIN_WAIT_TIME..Self::MAX_WAIT_TIME),
+        }
+    }
+
+    /// Returns the point that the bot walks to, `None` if the bot waits.
+    pub fn destination(&self) -> Option<Vector3<f32>> {
+        if self.walking {
+            Some(self.point)
+        } else {
+            None
+        }
+    }
+
+    pub fn update(&mut self, position: Vector3<f32>, radius: f32, dt: f32, rng: &mut GameRng) {
+        if self.walking {
+            self.timer += dt;
+            let mut offset = self.point - position;
+            offset.y = 0.0;
+            if offset.norm() <= Self::REACH_DISTANCE || self.timer >= Self::MAX_WALK_TIME {
+                self.walking = false;
+                self.timer = rng.gen_range(Self::MIN_WAIT_TIME..Self::MAX_WAIT_TIME);
+            }
+        } else {
+            self.timer -= dt;
+            if self.timer <= 0.0 {
+                let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+                let distance = rng.gen_range(0.0..radius);
+                self.point =
+                    self.home + Vector3::new(angle.cos() * distance, 0.0, angle.sin() * distance);
+                self.walking = true;
+                self.timer = 0.0;
+            }
+        }
+    }
+}
+
+impl Visit for Patrol {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.home.visit("Home", visitor)?;
+        self.point.visit("Point", visitor)?;
+        self.walking.visit("Walking", visitor)?;
+        self.timer.visit("Timer", visitor)?;
+
+        visitor.leave_region()
+    }
+}
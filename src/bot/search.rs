@@ -0,0 +1,125 @@
+//! Bots that lost sight of their target do not calm down instantly. Instead they walk to the
+//! last known position of the target and then check a few random points around it, looking
+//! around at each point, until search time is over.
+
+use crate::{bot::navmesh_guard::clamp_to_navmesh, utils::rng::GameRng};
+use rg3d::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        rand::Rng,
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    utils::navmesh::Navmesh,
+};
+
+#[derive(Default)]
+pub struct Search {
+    /// Points to check, the first one is the last known position of the target.
+    points: Vec<Vector3<f32>>,
+    current_point: u32,
+    /// Time left until the bot calms down.
+    timer: f32,
+    /// Time spent on current point, either walking to it or looking around.
+    point_timer: f32,
+    /// Whether the bot has reached current point and is looking around.
+    scanning: bool,
+    /// Whether the points were snapped to the navmesh, random points around the last known
+    /// position may be off the navmesh and the bot would never reach them. It is not saved,
+    /// points are snapped again after loading which does not change them.
+    snapped: bool,
+}
+
+impl Search {
+    /// Amount of random points around last known position of the target.
+    const EXTRA_POINTS: usize = 3;
+    const RADIUS: f32 = 4.0;
+    const REACH_DISTANCE: f32 = 0.8;
+    /// Time (in seconds) that the bot spends looking around at each point.
+    const SCAN_TIME: f32 = 2.5;
+    /// Bot gives up on a point that it cannot reach in this time.
+    const MAX_WALK_TIME: f32 = 6.0;
+    /// Maximum angle (in degrees) of head turn to each side while looking around.
+    const SCAN_ANGLE: f32 = 70.0;
+
+    pub fn new(last_known_position: Vector3<f32>, duration: f32, rng: &mut GameRng) -> Self {
+        let mut points = vec![last_known_position];
+        for _ in 0..Self::EXTRA_POINTS {
+            let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+            let distance = rng.gen_range(Self::RADIUS * 0.5..Self::RADIUS);
+            points.push(
+                last_known_position
+                    + Vector3::new(angle.cos() * distance, 0.0, angle.sin() * distance),
+            );
+        }
+
+        Self {
+            points,
+            current_point: 0,
+            timer: duration,
+            point_timer: 0.0,
+            scanning: false,
+            snapped: false,
+        }
+    }
+
+    pub fn destination(&self) -> Vector3<f32> {
+        self.points[self.current_point as usize]
+    }
+
+    pub fn is_scanning(&self) -> bool {
+        self.scanning
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.timer <= 0.0
+    }
+
+    /// Returns direction in which the bot should look while standing on a point, it sweeps
+    /// left and right from the direction in which the bot came to the point.
+    pub fn scan_direction(&self, base: Vector3<f32>) -> Vector3<f32> {
+        let phase = self.point_timer / Self::SCAN_TIME * std::f32::consts::TAU;
+        let angle = Self::SCAN_ANGLE.to_radians() * phase.sin();
+        UnitQuaternion::from_axis_angle(&Vector3::y_axis(), angle).transform_vector(&base)
+    }
+
+    pub fn update(&mut self, position: Vector3<f32>, navmesh: &Navmesh, dt: f32) {
+        if !self.snapped {
+            for point in self.points.iter_mut() {
+                *point = clamp_to_navmesh(navmesh, *point);
+            }
+            self.snapped = true;
+        }
+
+        self.timer -= dt;
+        self.point_timer += dt;
+
+        if self.scanning {
+            if self.point_timer >= Self::SCAN_TIME {
+                self.current_point = (self.current_point + 1) % self.points.len() as u32;
+                self.point_timer = 0.0;
+                self.scanning = false;
+            }
+        } else {
+            let mut offset = self.destination() - position;
+            offset.y = 0.0;
+            if offset.norm() <= Self::REACH_DISTANCE || self.point_timer >= Self::MAX_WALK_TIME {
+                self.point_timer = 0.0;
+                self.scanning = true;
+            }
+        }
+    }
+}
+
+impl Visit for Search {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.points.visit("Points", visitor)?;
+        self.current_point.visit("CurrentPoint", visitor)?;
+        self.timer.visit("Timer", visitor)?;
+        self.point_timer.visit("PointTimer", visitor)?;
+        self.scanning.visit("Scanning", visitor)?;
+
+        visitor.leave_region()
+    }
+}
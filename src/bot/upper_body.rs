@@ -5,7 +5,8 @@ use crate::{
 use rg3d::{
     animation::{
         machine::{
-            blend_nodes::IndexedBlendInput, Machine, Parameter, PoseNode, State, Transition,
+            blend_nodes::{BlendPose, IndexedBlendInput},
+            Machine, Parameter, PoseNode, PoseWeight, State, Transition,
         },
         Animation, AnimationSignal, PoseEvaluationFlags,
     },
@@ -23,6 +24,14 @@ pub struct UpperBodyMachine {
     pub machine: Machine,
     pub attack_animations: Vec<Handle<Animation>>,
     pub aim_state: Handle<State>,
+    pub idle_animation: Handle<Animation>,
+    pub walk_animation: Handle<Animation>,
+    pub scream_animation: Handle<Animation>,
+    /// Handle is none for bots that can't use weapons.
+    pub aim_animation: Handle<Animation>,
+    /// Handles are none for bots without full-body aim.
+    pub aim_up_animation: Handle<Animation>,
+    pub aim_down_animation: Handle<Animation>,
     pub dying_animation: Handle<Animation>,
 }
 
@@ -33,6 +42,9 @@ pub struct UpperBodyMachineInput {
     pub scream: bool,
     pub dead: bool,
     pub aim: bool,
+    /// Blend factor of aim up (negative) and aim down (positive) poses in [-1; 1] range, it
+    /// is used only by bots with full-body aim.
+    pub aim_pitch_factor: f32,
     pub attack_animation_index: u32,
 }
 
@@ -65,14 +77,14 @@ pub fn make_attack_state(
                     UpperBodyMachine::HIT_SIGNAL,
                     desc.timestamp,
                 ))
-                .set_speed(desc.speed)
-                .track_of_mut(hips)
-                .unwrap()
-                .set_flags(PoseEvaluationFlags {
+                .set_speed(desc.speed);
+            if let Some(track) = scene.animations[animation].track_of_mut(hips) {
+                track.set_flags(PoseEvaluationFlags {
                     ignore_position: false,
                     ignore_rotation: true,
                     ignore_scale: false,
                 });
+            }
             animation
         })
         .collect::<Vec<_>>();
@@ -96,6 +108,50 @@ pub fn make_attack_state(
     )
 }
 
+fn retarget_animation(
+    resource: Model,
+    model: Handle<Node>,
+    scene: &mut Scene,
+) -> Handle<Animation> {
+    *resource.retarget_animations(model, scene).get(0).unwrap()
+}
+
+/// Makes aim state that blends aim pose with aim up and aim down poses by pitch. Returns the
+/// state and animations of aim, aim up and aim down poses.
+fn make_full_body_aim_state(
+    machine: &mut Machine,
+    scene: &mut Scene,
+    model: Handle<Node>,
+    aim_resource: Model,
+    aim_up_resource: Model,
+    aim_down_resource: Model,
+) -> (Handle<State>, [Handle<Animation>; 3]) {
+    let animations = [
+        retarget_animation(aim_resource, model, scene),
+        retarget_animation(aim_up_resource, model, scene),
+        retarget_animation(aim_down_resource, model, scene),
+    ];
+
+    let poses = animations
+        .iter()
+        .zip(&[
+            UpperBodyMachine::AIM_FORWARD_WEIGHT,
+            UpperBodyMachine::AIM_UP_WEIGHT,
+            UpperBodyMachine::AIM_DOWN_WEIGHT,
+        ])
+        .map(|(&animation, &weight)| {
+            BlendPose::new(
+                PoseWeight::Parameter(weight.to_owned()),
+                machine.add_node(PoseNode::make_play_animation(animation)),
+            )
+        })
+        .collect();
+
+    let aim_node = machine.add_node(PoseNode::make_blend_animations(poses));
+
+    (machine.add_state(State::new("Aim", aim_node)), animations)
+}
+
 impl UpperBodyMachine {
     pub const HIT_SIGNAL: u64 = 1;
 
@@ -117,6 +173,10 @@ impl UpperBodyMachine {
 
     const ATTACK_INDEX: &'static str = "AttackIndex";
 
+    const AIM_FORWARD_WEIGHT: &'static str = "AimForwardWeight";
+    const AIM_UP_WEIGHT: &'static str = "AimUpWeight";
+    const AIM_DOWN_WEIGHT: &'static str = "AimDownWeight";
+
     pub async fn new(
         resource_manager: ResourceManager,
         definition: &BotDefinition,
@@ -144,15 +204,43 @@ impl UpperBodyMachine {
                 None
             };
 
+        let full_body_aim_resources = match (&aim_animation_resource, &definition.full_body_aim) {
+            (Some(_), Some(full_body_aim)) => {
+                let (up, down) = rg3d::futures::join!(
+                    resource_manager.request_model(&full_body_aim.up_animation),
+                    resource_manager.request_model(&full_body_aim.down_animation),
+                );
+                up.ok().zip(down.ok())
+            }
+            _ => None,
+        };
+
         let mut machine = Machine::new();
 
-        let (aim_animation, aim_state) = if let Some(aim_animation_resource) =
-            aim_animation_resource.clone()
-        {
-            create_play_animation_state(aim_animation_resource, "Aim", &mut machine, scene, model)
-        } else {
-            (Handle::NONE, Handle::NONE)
-        };
+        let (aim_state, [aim_animation, aim_up_animation, aim_down_animation]) =
+            match (aim_animation_resource.clone(), full_body_aim_resources) {
+                (Some(aim_resource), Some((aim_up_resource, aim_down_resource))) => {
+                    make_full_body_aim_state(
+                        &mut machine,
+                        scene,
+                        model,
+                        aim_resource,
+                        aim_up_resource,
+                        aim_down_resource,
+                    )
+                }
+                (Some(aim_resource), None) => {
+                    let (aim_animation, aim_state) = create_play_animation_state(
+                        aim_resource,
+                        "Aim",
+                        &mut machine,
+                        scene,
+                        model,
+                    );
+                    (aim_state, [aim_animation, Handle::NONE, Handle::NONE])
+                }
+                _ => (Handle::NONE, [Handle::NONE; 3]),
+            };
 
         let (idle_animation, idle_state) = create_play_animation_state(
             resources[&definition.idle_animation].clone(),
@@ -216,6 +304,8 @@ impl UpperBodyMachine {
                 idle_animation,
                 walk_animation,
                 aim_animation,
+                aim_up_animation,
+                aim_down_animation,
                 scream_animation,
                 dying_animation,
             ] {
@@ -352,6 +442,12 @@ impl UpperBodyMachine {
             machine,
             attack_animations,
             aim_state,
+            idle_animation,
+            walk_animation,
+            scream_animation,
+            aim_animation,
+            aim_up_animation,
+            aim_down_animation,
             dying_animation,
         }
     }
@@ -360,6 +456,32 @@ impl UpperBodyMachine {
         clean_machine(&self.machine, scene)
     }
 
+    /// Sets speeds of animations from the definition multiplied by given scale. Hit signals
+    /// are bound to time of attack animations, so hits stay in sync with the swings.
+    pub fn set_speed_scale(&self, scene: &mut Scene, definition: &BotDefinition, scale: f32) {
+        let speeds = &definition.animation_speeds;
+        for &(animation, speed) in &[
+            (self.idle_animation, speeds.idle),
+            (self.walk_animation, speeds.walk),
+            (self.scream_animation, speeds.scream),
+            (self.aim_animation, speeds.aim),
+            (self.aim_up_animation, speeds.aim),
+            (self.aim_down_animation, speeds.aim),
+            (self.dying_animation, speeds.dying),
+        ] {
+            if animation.is_some() {
+                scene.animations[animation].set_speed(speed * scale);
+            }
+        }
+        for (&animation, attack) in self
+            .attack_animations
+            .iter()
+            .zip(definition.attack_animations.iter())
+        {
+            scene.animations[animation].set_speed(attack.speed * scale);
+        }
+    }
+
     pub fn apply(&mut self, scene: &mut Scene, time: GameTime, input: UpperBodyMachineInput) {
         let attack_animation_ended = scene.animations
             [self.attack_animations[input.attack_animation_index as usize]]
@@ -388,6 +510,18 @@ impl UpperBodyMachine {
             .set_parameter(Self::IDLE_TO_AIM, Parameter::Rule(input.aim))
             .set_parameter(Self::AIM_TO_IDLE, Parameter::Rule(!input.aim))
             .set_parameter(Self::AIM_TO_WALK, Parameter::Rule(input.walk && !input.aim))
+            .set_parameter(
+                Self::AIM_FORWARD_WEIGHT,
+                Parameter::Weight(1.0 - input.aim_pitch_factor.abs()),
+            )
+            .set_parameter(
+                Self::AIM_UP_WEIGHT,
+                Parameter::Weight((-input.aim_pitch_factor).max(0.0)),
+            )
+            .set_parameter(
+                Self::AIM_DOWN_WEIGHT,
+                Parameter::Weight(input.aim_pitch_factor.max(0.0)),
+            )
             .set_parameter(Self::ATTACK_TO_DYING, Parameter::Rule(input.dead))
             .set_parameter(Self::WALK_TO_DYING, Parameter::Rule(input.dead))
             .set_parameter(Self::IDLE_TO_DYING, Parameter::Rule(input.dead))
@@ -404,6 +538,12 @@ impl Visit for UpperBodyMachine {
         self.attack_animations.visit("AttackAnimations", visitor)?;
         self.dying_animation.visit("DyingAnimation", visitor)?;
         self.aim_state.visit("AimState", visitor)?;
+        self.idle_animation.visit("IdleAnimation", visitor)?;
+        self.walk_animation.visit("WalkAnimation", visitor)?;
+        self.scream_animation.visit("ScreamAnimation", visitor)?;
+        self.aim_animation.visit("AimAnimation", visitor)?;
+        self.aim_up_animation.visit("AimUpAnimation", visitor)?;
+        self.aim_down_animation.visit("AimDownAnimation", visitor)?;
 
         visitor.leave_region()
     }
@@ -1,19 +1,77 @@
 use crate::inventory::Inventory;
 use crate::{
+    actor::Actor,
     message::Message,
-    weapon::{Weapon, WeaponContainer, WeaponKind},
+    status_effect::{StatusEffect, StatusEffectKind},
+    weapon::{projectile::DamageType, Weapon, WeaponContainer, WeaponKind},
 };
 use rg3d::scene::ColliderHandle;
 use rg3d::{
     core::{
         algebra::Vector3,
+        math::ray::Ray,
         pool::Handle,
         visitor::{Visit, VisitResult, Visitor},
     },
-    scene::{graph::Graph, node::Node, physics::Physics, RigidBodyHandle, Scene},
+    scene::{
+        graph::Graph,
+        node::Node,
+        physics::{Physics, RayCastOptions},
+        RigidBodyHandle, Scene,
+    },
 };
+use serde::Deserialize;
 use std::sync::mpsc::Sender;
 
+/// Side of an actor, actors of the same faction do not damage each other unless friendly
+/// fire is enabled. Factions of bots and hostility between them are defined in
+/// `data/configs/bots.ron`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Deserialize)]
+pub enum Faction {
+    Player,
+    Mutants,
+    Parasites,
+    Zombies,
+}
+
+impl Default for Faction {
+    fn default() -> Self {
+        Self::Mutants
+    }
+}
+
+impl Faction {
+    fn id(self) -> u32 {
+        match self {
+            Faction::Player => 0,
+            Faction::Mutants => 1,
+            Faction::Parasites => 2,
+            Faction::Zombies => 3,
+        }
+    }
+
+    fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(Faction::Player),
+            1 => Ok(Faction::Mutants),
+            2 => Ok(Faction::Parasites),
+            3 => Ok(Faction::Zombies),
+            _ => Err(format!("Invalid faction {}", id)),
+        }
+    }
+}
+
+impl Visit for Faction {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut id = self.id();
+        id.visit(name, visitor)?;
+        if visitor.is_reading() {
+            *self = Self::from_id(id)?;
+        }
+        Ok(())
+    }
+}
+
 pub struct Character {
     pub pivot: Handle<Node>,
     pub body: RigidBodyHandle,
@@ -24,6 +82,14 @@ pub struct Character {
     pub sender: Option<Sender<Message>>,
     pub hit_boxes: Vec<HitBox>,
     pub inventory: Inventory,
+    /// Maximum amount of weapons the character is able to carry.
+    pub weapon_slots: u32,
+    pub faction: Faction,
+    weapon_switch_timer: f32,
+    status_effects: Vec<StatusEffect>,
+    /// Velocity (in m/s) of a push from explosions and heavy hits, it is added on top of
+    /// movement velocity and quickly fades out.
+    knockback: Vector3<f32>,
 }
 
 impl Default for Character {
@@ -38,6 +104,11 @@ impl Default for Character {
             sender: None,
             hit_boxes: Default::default(),
             inventory: Default::default(),
+            weapon_slots: 4,
+            faction: Default::default(),
+            weapon_switch_timer: 0.0,
+            status_effects: Default::default(),
+            knockback: Default::default(),
         }
     }
 }
@@ -53,6 +124,12 @@ impl Visit for Character {
         self.current_weapon.visit("CurrentWeapon", visitor)?;
         self.weapon_pivot.visit("WeaponPivot", visitor)?;
         self.inventory.visit("Inventory", visitor)?;
+        self.weapon_slots.visit("WeaponSlots", visitor)?;
+        self.faction.visit("Faction", visitor)?;
+        self.weapon_switch_timer
+            .visit("WeaponSwitchTimer", visitor)?;
+        self.status_effects.visit("StatusEffects", visitor)?;
+        self.knockback.visit("Knockback", visitor)?;
 
         visitor.leave_region()
     }
@@ -78,23 +155,39 @@ pub fn find_hit_boxes(from: Handle<Node>, scene: &Scene) -> Vec<HitBox> {
             match node.tag() {
                 "HitBoxArm" => hit_boxes.push(HitBox {
                     collider,
+                    node: descendant,
+                    kind: HitBoxKind::Arm,
                     damage_factor: 0.25,
+                    critical: false,
                     movement_speed_factor: 1.0,
+                    damage: 0.0,
                 }),
                 "HitBoxLeg" => hit_boxes.push(HitBox {
                     collider,
+                    node: descendant,
+                    kind: HitBoxKind::Leg,
                     damage_factor: 0.35,
+                    critical: false,
                     movement_speed_factor: 0.5,
+                    damage: 0.0,
                 }),
                 "HitBoxBody" => hit_boxes.push(HitBox {
                     collider,
+                    node: descendant,
+                    kind: HitBoxKind::Body,
                     damage_factor: 0.60,
+                    critical: false,
                     movement_speed_factor: 0.75,
+                    damage: 0.0,
                 }),
                 "HitBoxHead" => hit_boxes.push(HitBox {
                     collider,
+                    node: descendant,
+                    kind: HitBoxKind::Head,
                     damage_factor: 1.0,
+                    critical: true,
                     movement_speed_factor: 0.1,
+                    damage: 0.0,
                 }),
                 _ => (),
             }
@@ -105,6 +198,17 @@ pub fn find_hit_boxes(from: Handle<Node>, scene: &Scene) -> Vec<HitBox> {
 }
 
 impl Character {
+    /// Time (in seconds) after weapon switch during which character cannot shoot.
+    pub const WEAPON_SWITCH_DELAY: f32 = 0.35;
+    /// Knockback speed is capped, so a character won't be launched across the map when it
+    /// is pushed by multiple explosions at once.
+    pub const MAX_KNOCKBACK_SPEED: f32 = 8.0;
+    /// Fraction of knockback speed that is lost per second.
+    const KNOCKBACK_DAMPING: f32 = 5.0;
+    /// Maximum distance (in meters) between the bottom of a body and the ground at which
+    /// a character is still considered standing on the ground.
+    const GROUND_CHECK_TOLERANCE: f32 = 0.1;
+
     pub fn get_body(&self) -> RigidBodyHandle {
         self.body
     }
@@ -123,6 +227,36 @@ impl Character {
         false
     }
 
+    /// Checks whether there is ground right below the character using a ray cast down from
+    /// the center of its body. Unlike `has_ground_contact` it does not count contacts with
+    /// ceilings, so it is used to decide whether the character can jump.
+    pub fn is_on_ground(&self, physics: &Physics) -> bool {
+        let body = physics.bodies.get(self.body.into()).unwrap();
+        let self_collider = body.colliders()[0];
+        let begin = body.position().translation.vector;
+        let max_len = match physics.colliders.get(self_collider) {
+            Some(collider) => {
+                begin.y - collider.compute_aabb().mins.y + Self::GROUND_CHECK_TOLERANCE
+            }
+            None => return false,
+        };
+
+        let mut query_buffer = Vec::new();
+        physics.cast_ray(
+            RayCastOptions {
+                ray: Ray::from_two_points(begin, begin - Vector3::new(0.0, max_len, 0.0)),
+                max_len,
+                groups: Default::default(),
+                sort_results: false,
+            },
+            &mut query_buffer,
+        );
+
+        query_buffer
+            .iter()
+            .any(|i| i.collider != ColliderHandle::from(self_collider))
+    }
+
     pub fn get_health(&self) -> f32 {
         self.health
     }
@@ -140,6 +274,9 @@ impl Character {
 
     pub fn damage(&mut self, amount: f32) {
         self.health -= amount.abs();
+
+        self.status_effects
+            .retain(|e| !e.kind.is_interrupted_by_damage());
     }
 
     pub fn heal(&mut self, amount: f32) {
@@ -211,7 +348,104 @@ impl Character {
         }
     }
 
-    fn request_current_weapon_visible(&self, state: bool) {
+    pub fn has_free_weapon_slot(&self) -> bool {
+        (self.weapons.len() as u32) < self.weapon_slots
+    }
+
+    pub fn is_switching_weapon(&self) -> bool {
+        self.weapon_switch_timer > 0.0
+    }
+
+    pub fn update_weapon_switch(&mut self, dt: f32) {
+        self.weapon_switch_timer -= dt;
+    }
+
+    /// Pushes the character with given velocity, only horizontal part is used.
+    pub fn apply_knockback(&mut self, velocity: Vector3<f32>) {
+        self.knockback += Vector3::new(velocity.x, 0.0, velocity.z);
+        let speed = self.knockback.norm();
+        if speed > Self::MAX_KNOCKBACK_SPEED {
+            self.knockback = self.knockback.scale(Self::MAX_KNOCKBACK_SPEED / speed);
+        }
+    }
+
+    pub fn knockback_velocity(&self) -> Vector3<f32> {
+        self.knockback
+    }
+
+    pub fn update_knockback(&mut self, dt: f32) {
+        self.knockback = self
+            .knockback
+            .scale((1.0 - Self::KNOCKBACK_DAMPING * dt).max(0.0));
+    }
+
+    /// Applies new status effect, effect of the same kind is refreshed instead of stacking.
+    pub fn apply_status_effect(&mut self, effect: StatusEffect) {
+        if let Some(existing) = self
+            .status_effects
+            .iter_mut()
+            .find(|e| e.kind == effect.kind)
+        {
+            existing.refresh(&effect);
+        } else {
+            self.status_effects.push(effect);
+        }
+    }
+
+    pub fn remove_status_effect(&mut self, kind: StatusEffectKind) {
+        self.status_effects.retain(|e| e.kind != kind);
+    }
+
+    /// Returns movement speed multiplier from slowing effects, strongest one wins.
+    pub fn movement_speed_factor(&self) -> f32 {
+        self.status_effects
+            .iter()
+            .filter(|e| e.kind == StatusEffectKind::Slow)
+            .fold(1.0, |k, e| e.strength.min(k))
+    }
+
+    /// Applies every active status effect, must be called once per frame.
+    pub fn update_status_effects(&mut self, self_handle: Handle<Actor>, dt: f32) {
+        if self.is_dead() {
+            self.status_effects.clear();
+            return;
+        }
+
+        for i in 0..self.status_effects.len() {
+            let effect = &mut self.status_effects[i];
+            if let Some(step) = effect.update(dt) {
+                let effect = *effect;
+                match effect.kind {
+                    StatusEffectKind::Heal => self.heal(effect.strength * step),
+                    StatusEffectKind::Poison | StatusEffectKind::Burning => {
+                        // Damage goes through the level, so it is handled the same way as
+                        // any other damage (kill credit, hit markers, etc.)
+                        if let Some(sender) = self.sender.as_ref() {
+                            sender
+                                .send(Message::DamageActor {
+                                    actor: self_handle,
+                                    who: effect.source,
+                                    amount: effect.strength * step,
+                                    damage_type: DamageType::Environment,
+                                    critical: false,
+                                    hit_box: None,
+                                })
+                                .unwrap();
+                        }
+                    }
+                    StatusEffectKind::Slow => (),
+                }
+            }
+        }
+
+        self.status_effects.retain(|e| !e.is_expired());
+    }
+
+    fn request_current_weapon_visible(&mut self, state: bool) {
+        if state {
+            self.weapon_switch_timer = Self::WEAPON_SWITCH_DELAY;
+        }
+
         if let Some(sender) = self.sender.as_ref() {
             if let Some(current_weapon) = self.weapons.get(self.current_weapon as usize) {
                 sender
@@ -225,20 +459,24 @@ impl Character {
     }
 
     pub fn next_weapon(&mut self) {
-        if !self.weapons.is_empty() && (self.current_weapon as usize) < self.weapons.len() - 1 {
+        if self.weapons.len() > 1 {
             self.request_current_weapon_visible(false);
 
-            self.current_weapon += 1;
+            self.current_weapon = (self.current_weapon + 1) % self.weapons.len() as u32;
 
             self.request_current_weapon_visible(true);
         }
     }
 
     pub fn prev_weapon(&mut self) {
-        if self.current_weapon > 0 {
+        if self.weapons.len() > 1 {
             self.request_current_weapon_visible(false);
 
-            self.current_weapon -= 1;
+            self.current_weapon = if self.current_weapon == 0 {
+                self.weapons.len() as u32 - 1
+            } else {
+                self.current_weapon - 1
+            };
 
             self.request_current_weapon_visible(true);
         }
@@ -282,9 +520,30 @@ impl Character {
     }
 }
 
+#[derive(Deserialize, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum HitBoxKind {
+    Arm,
+    Leg,
+    Body,
+    Head,
+}
+
+impl Default for HitBoxKind {
+    fn default() -> Self {
+        Self::Body
+    }
+}
+
 #[derive(Default, Clone, Copy, PartialEq)]
 pub struct HitBox {
     pub collider: ColliderHandle,
+    /// Node that is bound to the body of the hit box, its parent is a bone of the model.
+    pub node: Handle<Node>,
+    pub kind: HitBoxKind,
+    /// Total damage that was dealt to the hit box, it is not saved.
+    pub damage: f32,
     pub damage_factor: f32,
     pub movement_speed_factor: f32,
+    /// Hits to such box are critical (head shots).
+    pub critical: bool,
 }
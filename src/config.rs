@@ -1,10 +1,23 @@
-use crate::{control_scheme::ControlScheme, GameEngine};
-use rg3d::renderer::QualitySettings;
+use crate::{
+    control_scheme::ControlScheme,
+    effects::ParticleBudget,
+    gui::{crosshair::CrosshairStyle, palette::ColorBlindMode},
+    localization::Language,
+    GameEngine,
+};
+use rg3d::{
+    core::color::Color,
+    dpi::PhysicalSize,
+    renderer::QualitySettings,
+    utils::log::{Log, MessageKind},
+    window::{Fullscreen, Window},
+};
 use ron::ser::PrettyConfig;
 use serde::{Deserialize, Serialize};
 use std::fs::File;
 
 #[derive(Deserialize, Serialize)]
+#[serde(default)]
 pub struct LevelSoundConfig {
     pub music_volume: f32,
     pub use_hrtf: bool,
@@ -20,6 +33,7 @@ impl Default for LevelSoundConfig {
 }
 
 #[derive(Deserialize, Serialize)]
+#[serde(default)]
 pub struct SoundConfig {
     pub volume: f32,
     pub level: LevelSoundConfig,
@@ -34,11 +48,282 @@ impl Default for SoundConfig {
     }
 }
 
-#[derive(Deserialize, Serialize, Default)]
+#[derive(Deserialize, Serialize, Copy, Clone)]
+#[serde(default)]
+pub struct AccessibilityConfig {
+    pub color_blind_mode: ColorBlindMode,
+    pub subtitles: bool,
+    pub hit_markers: bool,
+    /// Player's health slowly regenerates after some time without taking damage.
+    pub health_regen: bool,
+    /// Camera shakes on explosions and heavy weapon fire nearby.
+    pub camera_shake: bool,
+    /// Multiplier of camera shake strength in [0; 1] range.
+    pub camera_shake_intensity: f32,
+    /// Blood effects and severing of limbs of bots, neutral impact effects are used instead
+    /// when disabled.
+    pub gore: bool,
+    /// Player inspects the weapon after some time without input.
+    pub weapon_inspect: bool,
+    /// Language of UI, it is applied after restart.
+    pub language: Language,
+}
+
+impl Default for AccessibilityConfig {
+    fn default() -> Self {
+        Self {
+            color_blind_mode: Default::default(),
+            subtitles: false,
+            hit_markers: true,
+            health_regen: false,
+            camera_shake: true,
+            camera_shake_intensity: 1.0,
+            gore: true,
+            weapon_inspect: true,
+            language: Default::default(),
+        }
+    }
+}
+
+impl AccessibilityConfig {
+    /// Returns resulting strength multiplier of camera shake, zero if shake is disabled.
+    pub fn camera_shake_scale(&self) -> f32 {
+        if self.camera_shake {
+            self.camera_shake_intensity
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Copy, Clone)]
+#[serde(default)]
+pub struct HudConfig {
+    pub crosshair_style: CrosshairStyle,
+    /// RGB components of crosshair color.
+    pub crosshair_color: [u8; 3],
+    /// Allows to show performance overlay using its control.
+    pub performance_overlay: bool,
+    /// Shows amount of damage over hit bots.
+    pub damage_numbers: bool,
+    /// Multiplier of size of HUD elements, large values are useful on high resolution screens.
+    pub hud_scale: f32,
+    /// Shows compass strip with nearby enemies and objectives.
+    pub compass: bool,
+}
+
+impl Default for HudConfig {
+    fn default() -> Self {
+        Self {
+            crosshair_style: Default::default(),
+            crosshair_color: [255, 255, 255],
+            performance_overlay: false,
+            damage_numbers: false,
+            hud_scale: 1.0,
+            compass: true,
+        }
+    }
+}
+
+impl HudConfig {
+    pub fn crosshair_color(&self) -> Color {
+        let [r, g, b] = self.crosshair_color;
+        Color::opaque(r, g, b)
+    }
+}
+
+#[derive(Deserialize, Serialize, Copy, Clone)]
+#[serde(default)]
+pub struct EffectsConfig {
+    /// Approximate amount of particles that can be alive at the same time, new effects are
+    /// spawned with less particles when the budget is exceeded.
+    pub particle_budget: u32,
+}
+
+impl Default for EffectsConfig {
+    fn default() -> Self {
+        Self {
+            particle_budget: ParticleBudget::DEFAULT_BUDGET,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Copy, Clone)]
+#[serde(default)]
+pub struct GameplayConfig {
+    /// Whether actors of the same faction (bots) can damage each other with splash damage,
+    /// melee attacks and stray shots.
+    pub friendly_fire: bool,
+    /// Whether the game briefly slows down when the player kills the last bot of a fight.
+    pub final_kill_slow_motion: bool,
+    pub difficulty: Difficulty,
+    /// Allows commands of debug console that change gameplay (spawning, god mode, etc.)
+    pub cheats: bool,
+    /// Maximum amount of live bots on a level, requests to spawn more bots are rejected.
+    pub max_bots: usize,
+}
+
+impl Default for GameplayConfig {
+    fn default() -> Self {
+        Self {
+            friendly_fire: false,
+            final_kill_slow_motion: false,
+            difficulty: Default::default(),
+            cheats: false,
+            max_bots: 64,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+impl Difficulty {
+    /// Multiplier of damage of bot melee attacks.
+    pub fn bot_damage_scale(self) -> f32 {
+        match self {
+            Self::Easy => 0.5,
+            Self::Normal => 1.0,
+            Self::Hard => 1.5,
+        }
+    }
+
+    /// Multiplier of speed of every animation of bots, attacks of bots are faster on hard
+    /// difficulty and slower on easy.
+    pub fn bot_animation_speed_scale(self) -> f32 {
+        match self {
+            Self::Easy => 0.85,
+            Self::Normal => 1.0,
+            Self::Hard => 1.15,
+        }
+    }
+
+    /// Multiplier of half-angle of the accuracy cone of bots, bots miss more on easy
+    /// difficulty.
+    pub fn bot_aim_spread_scale(self) -> f32 {
+        match self {
+            Self::Easy => 1.5,
+            Self::Normal => 1.0,
+            Self::Hard => 0.6,
+        }
+    }
+
+    /// Whether a bot is shown on the compass. Bots are shown only when they have noticed
+    /// the player on normal difficulty and never shown on hard.
+    pub fn shows_on_compass(self, alerted: bool) -> bool {
+        match self {
+            Self::Easy => true,
+            Self::Normal => alerted,
+            Self::Hard => false,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DisplayMode {
+    Windowed,
+    /// Borderless window that covers current monitor.
+    Borderless,
+    /// Exclusive fullscreen mode of primary monitor.
+    Exclusive {
+        width: u32,
+        height: u32,
+        refresh_rate: u16,
+    },
+}
+
+impl Default for DisplayMode {
+    fn default() -> Self {
+        Self::Windowed
+    }
+}
+
+impl DisplayMode {
+    pub fn from_window(window: &Window) -> Self {
+        match window.fullscreen() {
+            None => Self::Windowed,
+            Some(Fullscreen::Borderless(_)) => Self::Borderless,
+            Some(Fullscreen::Exclusive(video_mode)) => Self::Exclusive {
+                width: video_mode.size().width,
+                height: video_mode.size().height,
+                refresh_rate: video_mode.refresh_rate(),
+            },
+        }
+    }
+
+    pub fn apply(self, window: &Window) {
+        match self {
+            Self::Windowed => window.set_fullscreen(None),
+            Self::Borderless => window.set_fullscreen(Some(Fullscreen::Borderless(None))),
+            Self::Exclusive {
+                width,
+                height,
+                refresh_rate,
+            } => {
+                // Video mode could be unavailable if monitor was changed, stay windowed then.
+                let video_mode = window.primary_monitor().and_then(|monitor| {
+                    monitor.video_modes().find(|vm| {
+                        vm.size() == PhysicalSize::new(width, height)
+                            && vm.refresh_rate() == refresh_rate
+                    })
+                });
+                window.set_fullscreen(video_mode.map(Fullscreen::Exclusive));
+            }
+        }
+    }
+}
+
+/// User settings, stored in `data/configs/settings.ron`. Every field that is missing in the
+/// file is filled with its default value, so configs of older versions can still be loaded.
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
 pub struct Config {
+    /// Version of the config format, configs written before versioning was introduced have
+    /// no such field and are treated as version 1.
+    pub version: u32,
     pub graphics_settings: QualitySettings,
     pub controls: ControlScheme,
     pub sound: SoundConfig,
+    pub accessibility: AccessibilityConfig,
+    pub hud: HudConfig,
+    pub effects: EffectsConfig,
+    pub gameplay: GameplayConfig,
+    /// Size of the window (in physical pixels) in windowed mode, default size is used if not set.
+    pub window_width: Option<u32>,
+    pub window_height: Option<u32>,
+    /// Position of top-left corner of the window (in physical pixels).
+    pub window_position: Option<(i32, i32)>,
+    pub display_mode: DisplayMode,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            // Default is used for missing fields too, so missing version means version 1.
+            version: 1,
+            graphics_settings: Default::default(),
+            controls: Default::default(),
+            sound: Default::default(),
+            accessibility: Default::default(),
+            hud: Default::default(),
+            effects: Default::default(),
+            gameplay: Default::default(),
+            window_width: None,
+            window_height: None,
+            window_position: None,
+            display_mode: Default::default(),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -61,27 +346,159 @@ impl From<ron::Error> for ConfigError {
 
 impl Config {
     const PATH: &'static str = "data/configs/settings.ron";
+    /// Current version of the config format, must be increased each time when a migration
+    /// step is added to `migrate`.
+    pub const VERSION: u32 = 2;
 
+    /// Loads config and migrates it to current version, migrated config is written back.
     pub fn load() -> Result<Self, ConfigError> {
         let file = File::open(Self::PATH)?;
-        Ok(ron::de::from_reader(file)?)
+        let mut config: Self = ron::de::from_reader(file)?;
+        if config.version < Self::VERSION {
+            let version = config.version;
+            config.migrate();
+            Log::writeln(
+                MessageKind::Information,
+                format!(
+                    "Config migrated from version {} to {}",
+                    version, config.version
+                ),
+            );
+            config.write()?;
+        }
+        Ok(config)
+    }
+
+    /// Upgrades config of an older version step by step, fields that were added in newer
+    /// versions are already filled with defaults at this point, so only settings whose
+    /// meaning has changed need a migration step.
+    fn migrate(&mut self) {
+        if self.version == 1 {
+            // Version 1 had a single mouse sensitivity for both axes, keep it for vertical
+            // axis explicitly.
+            if self.controls.mouse_vertical_sens.is_none() {
+                self.controls.mouse_vertical_sens = Some(self.controls.mouse_sens);
+            }
+            self.version = 2;
+        }
     }
 
     pub fn save(
         engine: &GameEngine,
         control_scheme: ControlScheme,
+        master_gain: f32,
         level_sound_config: LevelSoundConfig,
+        accessibility: AccessibilityConfig,
+        hud: HudConfig,
+        effects: EffectsConfig,
     ) -> Result<(), ConfigError> {
+        // Window state is saved separately and gameplay settings can be changed only in
+        // the file, keep them as is.
+        let previous = Self::load().unwrap_or_default();
+
         let config = Self {
+            version: Self::VERSION,
             graphics_settings: engine.renderer.get_quality_settings(),
             controls: control_scheme,
             sound: SoundConfig {
-                volume: engine.sound_engine.lock().unwrap().master_gain(),
+                volume: master_gain,
                 level: level_sound_config,
             },
+            accessibility,
+            hud,
+            effects,
+            gameplay: previous.gameplay,
+            window_width: previous.window_width,
+            window_height: previous.window_height,
+            window_position: previous.window_position,
+            display_mode: DisplayMode::from_window(engine.get_window()),
         };
+        config.write()
+    }
+
+    pub fn save_window_state(
+        width: u32,
+        height: u32,
+        position: Option<(i32, i32)>,
+    ) -> Result<(), ConfigError> {
+        let mut config = Self::load().unwrap_or_default();
+        config.window_width = Some(width);
+        config.window_height = Some(height);
+        config.window_position = position;
+        config.version = Self::VERSION;
+        config.write()
+    }
+
+    pub fn window_size(&self) -> Option<PhysicalSize<u32>> {
+        Some(PhysicalSize::new(self.window_width?, self.window_height?))
+    }
+
+    fn write(&self) -> Result<(), ConfigError> {
         let file = File::create(Self::PATH)?;
-        ron::ser::to_writer_pretty(file, &config, PrettyConfig::default())?;
+        ron::ser::to_writer_pretty(file, self, PrettyConfig::default())?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Config as it was written before versioning, it has neither version field nor settings
+    /// that were added later.
+    const V1_CONFIG: &str = r#"(
+    controls: (
+        move_forward: (
+            description: "Move Forward",
+            button: Key(W),
+        ),
+        mouse_sens: 0.4,
+        mouse_y_inverse: true,
+    ),
+    sound: (
+        volume: 0.7,
+        level: (
+            music_volume: 0.2,
+            use_hrtf: false,
+        ),
+    ),
+)"#;
+
+    #[test]
+    fn v1_config_is_migrated_to_current_version() {
+        let mut config: Config = ron::de::from_str(V1_CONFIG).unwrap();
+        assert_eq!(config.version, 1);
+
+        config.migrate();
+
+        assert_eq!(config.version, Config::VERSION);
+        // Single sensitivity of version 1 is kept for vertical axis.
+        assert_eq!(config.controls.mouse_sens, 0.4);
+        assert_eq!(config.controls.mouse_vertical_sens, Some(0.4));
+        assert!(config.controls.mouse_y_inverse);
+        assert_eq!(config.sound.volume, 0.7);
+        assert_eq!(config.sound.level.music_volume, 0.2);
+        assert!(!config.sound.level.use_hrtf);
+        // Controls that were added later get their default bindings.
+        assert_eq!(
+            config.controls.toggle_performance_overlay.description,
+            ControlScheme::default()
+                .toggle_performance_overlay
+                .description
+        );
+    }
+
+    #[test]
+    fn current_config_is_not_migrated() {
+        let mut config = Config {
+            version: Config::VERSION,
+            ..Default::default()
+        };
+        config.controls.mouse_vertical_sens = None;
+
+        config.migrate();
+
+        assert_eq!(config.version, Config::VERSION);
+        assert_eq!(config.controls.mouse_vertical_sens, None);
+    }
+}
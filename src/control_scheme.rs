@@ -1,7 +1,7 @@
-use rg3d::event::VirtualKeyCode;
+use rg3d::event::{ElementState, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent};
 use serde::{Deserialize, Serialize};
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Deserialize, Serialize)]
 pub enum ControlButton {
     Mouse(u16),
     Key(VirtualKeyCode),
@@ -25,6 +25,72 @@ impl ControlButton {
             ControlButton::WheelDown => "Wheel Down",
         }
     }
+
+    /// Returns a button of a window event together with its state. Wheel has no released
+    /// state, so each scroll presses its button.
+    pub fn from_window_event(event: &WindowEvent) -> Option<(Self, ElementState)> {
+        match event {
+            WindowEvent::KeyboardInput { input, .. } => input
+                .virtual_keycode
+                .map(|code| (ControlButton::Key(code), input.state)),
+            WindowEvent::MouseInput { button, state, .. } => {
+                let index = match button {
+                    MouseButton::Left => 1,
+                    MouseButton::Right => 2,
+                    MouseButton::Middle => 3,
+                    MouseButton::Other(i) => *i,
+                };
+                Some((ControlButton::Mouse(index), *state))
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let y = match delta {
+                    MouseScrollDelta::LineDelta(_, y) => *y as f64,
+                    MouseScrollDelta::PixelDelta(delta) => delta.y,
+                };
+                if y > 0.0 {
+                    Some((ControlButton::WheelUp, ElementState::Pressed))
+                } else if y < 0.0 {
+                    Some((ControlButton::WheelDown, ElementState::Pressed))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Defines how a held action (aiming, running) reacts to its button.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Deserialize, Serialize)]
+pub enum ActivationMode {
+    /// Action is active while the button is held.
+    Hold,
+    /// Each press of the button switches the action on or off.
+    Toggle,
+}
+
+impl Default for ActivationMode {
+    fn default() -> Self {
+        Self::Hold
+    }
+}
+
+impl ActivationMode {
+    /// Returns new state of an action after its button was pressed or released. `was_pressed`
+    /// is the previous state of the button, key auto-repeat sends press events while a key is
+    /// held and only the first one of them must switch a toggled action.
+    pub fn apply(self, active: bool, was_pressed: bool, pressed: bool) -> bool {
+        match self {
+            ActivationMode::Hold => pressed,
+            ActivationMode::Toggle => {
+                if pressed && !was_pressed {
+                    !active
+                } else {
+                    active
+                }
+            }
+        }
+    }
 }
 
 #[derive(Deserialize, Serialize, Clone)]
@@ -33,7 +99,10 @@ pub struct ControlButtonDefinition {
     pub button: ControlButton,
 }
 
+/// Controls that are missing in a config (added in later versions of the game) get their
+/// default bindings.
 #[derive(Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct ControlScheme {
     pub move_forward: ControlButtonDefinition,
     pub move_backward: ControlButtonDefinition,
@@ -44,9 +113,12 @@ pub struct ControlScheme {
     pub next_weapon: ControlButtonDefinition,
     pub prev_weapon: ControlButtonDefinition,
     pub run: ControlButtonDefinition,
+    pub run_mode: ActivationMode,
     pub aim: ControlButtonDefinition,
+    pub aim_mode: ActivationMode,
     pub toss_grenade: ControlButtonDefinition,
     pub flash_light: ControlButtonDefinition,
+    pub laser_sight: ControlButtonDefinition,
     pub grab_ak47: ControlButtonDefinition,
     pub grab_m4: ControlButtonDefinition,
     pub grab_pistol: ControlButtonDefinition,
@@ -58,8 +130,27 @@ pub struct ControlScheme {
     pub cursor_down: ControlButtonDefinition,
     pub cursor_left: ControlButtonDefinition,
     pub cursor_right: ControlButtonDefinition,
+    pub toggle_performance_overlay: ControlButtonDefinition,
+    pub toggle_console: ControlButtonDefinition,
+    /// Snaps aim to the nearest visible enemy, works only if aim snap is enabled.
+    pub snap_aim: ControlButtonDefinition,
+    /// Opens pause menu, also closes main menu when a level is loaded.
+    pub pause: ControlButtonDefinition,
+    /// Horizontal mouse sensitivity, also used for vertical axis if there is no
+    /// separate value for it (old configs have single sensitivity for both axes).
     pub mouse_sens: f32,
+    pub mouse_vertical_sens: Option<f32>,
     pub mouse_y_inverse: bool,
+    /// Averages two last mouse movements to reduce jitter.
+    pub mouse_smoothing: bool,
+    /// Makes fast mouse movements turn camera further than slow ones.
+    pub mouse_acceleration: bool,
+    /// Strength of aim assist in [0; 1] range, zero means that aim assist is disabled.
+    pub aim_assist: f32,
+    /// Accessibility option that allows to snap aim to the nearest visible enemy by a button.
+    pub aim_snap: bool,
+    /// Time (in seconds) after snapping aim until it can be snapped again.
+    pub aim_snap_cooldown: f32,
 }
 
 impl Default for ControlScheme {
@@ -101,10 +192,12 @@ impl Default for ControlScheme {
                 description: "Run".to_string(),
                 button: ControlButton::Key(VirtualKeyCode::LShift),
             },
+            run_mode: ActivationMode::Hold,
             aim: ControlButtonDefinition {
                 description: "Aim".to_string(),
                 button: ControlButton::Mouse(3),
             },
+            aim_mode: ActivationMode::Hold,
             toss_grenade: ControlButtonDefinition {
                 description: "Toss Grenade".to_string(),
                 button: ControlButton::Key(VirtualKeyCode::G),
@@ -113,6 +206,10 @@ impl Default for ControlScheme {
                 description: "Flash Light".to_string(),
                 button: ControlButton::Key(VirtualKeyCode::F),
             },
+            laser_sight: ControlButtonDefinition {
+                description: "Laser Sight".to_string(),
+                button: ControlButton::Key(VirtualKeyCode::L),
+            },
             grab_pistol: ControlButtonDefinition {
                 description: "Grab Pistol".to_string(),
                 button: ControlButton::Key(VirtualKeyCode::Key1),
@@ -157,14 +254,36 @@ impl Default for ControlScheme {
                 description: "Cursor Right".to_string(),
                 button: ControlButton::Key(VirtualKeyCode::Right),
             },
+            toggle_performance_overlay: ControlButtonDefinition {
+                description: "Performance Overlay".to_string(),
+                button: ControlButton::Key(VirtualKeyCode::F3),
+            },
+            toggle_console: ControlButtonDefinition {
+                description: "Console".to_string(),
+                button: ControlButton::Key(VirtualKeyCode::Grave),
+            },
+            snap_aim: ControlButtonDefinition {
+                description: "Snap Aim".to_string(),
+                button: ControlButton::Key(VirtualKeyCode::Q),
+            },
+            pause: ControlButtonDefinition {
+                description: "Pause".to_string(),
+                button: ControlButton::Key(VirtualKeyCode::Escape),
+            },
             mouse_sens: 0.3,
+            mouse_vertical_sens: None,
             mouse_y_inverse: false,
+            mouse_smoothing: false,
+            mouse_acceleration: false,
+            aim_assist: 0.0,
+            aim_snap: false,
+            aim_snap_cooldown: 2.0,
         }
     }
 }
 
 impl ControlScheme {
-    pub fn buttons_mut(&mut self) -> [&mut ControlButtonDefinition; 23] {
+    pub fn buttons_mut(&mut self) -> [&mut ControlButtonDefinition; 28] {
         [
             &mut self.move_forward,
             &mut self.move_backward,
@@ -181,6 +300,7 @@ impl ControlScheme {
             &mut self.inventory,
             &mut self.toss_grenade,
             &mut self.flash_light,
+            &mut self.laser_sight,
             &mut self.grab_pistol,
             &mut self.grab_ak47,
             &mut self.grab_m4,
@@ -189,10 +309,14 @@ impl ControlScheme {
             &mut self.cursor_down,
             &mut self.cursor_left,
             &mut self.cursor_right,
+            &mut self.toggle_performance_overlay,
+            &mut self.toggle_console,
+            &mut self.snap_aim,
+            &mut self.pause,
         ]
     }
 
-    pub fn buttons(&self) -> [&ControlButtonDefinition; 23] {
+    pub fn buttons(&self) -> [&ControlButtonDefinition; 28] {
         [
             &self.move_forward,
             &self.move_backward,
@@ -209,6 +333,7 @@ impl ControlScheme {
             &self.inventory,
             &self.toss_grenade,
             &self.flash_light,
+            &self.laser_sight,
             &self.grab_pistol,
             &self.grab_ak47,
             &self.grab_m4,
@@ -217,10 +342,100 @@ impl ControlScheme {
             &self.cursor_down,
             &self.cursor_left,
             &self.cursor_right,
+            &self.toggle_performance_overlay,
+            &self.toggle_console,
+            &self.snap_aim,
+            &self.pause,
         ]
     }
 
+    /// Binds a button to an action with given index in [`Self::buttons`]. An action that
+    /// was bound to the same button gets previous button of the rebound action, so a button
+    /// never triggers two actions. Returns index of that action.
+    pub fn rebind(&mut self, index: usize, button: ControlButton) -> Option<usize> {
+        let mut buttons = self.buttons_mut();
+        let old_button = std::mem::replace(&mut buttons[index].button, button);
+        let conflict = (0..buttons.len()).find(|&i| i != index && buttons[i].button == button);
+        if let Some(conflict) = conflict {
+            buttons[conflict].button = old_button;
+        }
+        conflict
+    }
+
+    pub fn mouse_vertical_sens(&self) -> f32 {
+        self.mouse_vertical_sens.unwrap_or(self.mouse_sens)
+    }
+
     pub fn reset(&mut self) {
         *self = Default::default();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buttons_and_buttons_mut_list_same_actions_in_same_order() {
+        let mut scheme = ControlScheme::default();
+        let buttons = scheme
+            .buttons()
+            .iter()
+            .map(|def| *def as *const ControlButtonDefinition)
+            .collect::<Vec<_>>();
+        let buttons_mut = scheme
+            .buttons_mut()
+            .iter()
+            .map(|def| &**def as *const ControlButtonDefinition)
+            .collect::<Vec<_>>();
+        assert_eq!(buttons, buttons_mut);
+    }
+
+    #[test]
+    fn buttons_list_every_action() {
+        let scheme = ControlScheme::default();
+        let serialized = ron::to_string(&scheme).unwrap();
+        assert_eq!(
+            serialized.matches("description:").count(),
+            scheme.buttons().len()
+        );
+    }
+
+    #[test]
+    fn default_bindings_do_not_conflict() {
+        let scheme = ControlScheme::default();
+        let buttons = scheme.buttons();
+        for (i, a) in buttons.iter().enumerate() {
+            for b in buttons[i + 1..].iter() {
+                assert!(
+                    a.button != b.button,
+                    "{} and {}",
+                    a.description,
+                    b.description
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn rebind_to_used_button_swaps_bindings() {
+        let mut scheme = ControlScheme::default();
+        let pause = scheme
+            .buttons()
+            .iter()
+            .position(|def| def.button == scheme.pause.button)
+            .unwrap();
+        let jump = scheme.jump.button;
+
+        assert_eq!(
+            scheme.rebind(pause, ControlButton::Key(VirtualKeyCode::P)),
+            None
+        );
+        assert_eq!(scheme.pause.button, ControlButton::Key(VirtualKeyCode::P));
+
+        let conflict = scheme.rebind(pause, jump);
+        assert!(conflict.is_some());
+        assert_eq!(scheme.pause.button, jump);
+        assert_eq!(scheme.jump.button, ControlButton::Key(VirtualKeyCode::P));
+    }
+}
@@ -160,6 +160,7 @@ impl DoorContainer {
                             gain: 1.0,
                             rolloff_factor: 1.0,
                             radius: 1.0,
+                            caption: None,
                         })
                         .unwrap();
                 } else if door.state == DoorState::Locked
@@ -173,6 +174,7 @@ impl DoorContainer {
                             gain: 1.0,
                             rolloff_factor: 1.0,
                             radius: 1.0,
+                            caption: Some("[Door is locked]".to_owned()),
                         })
                         .unwrap();
                 }
@@ -186,6 +188,7 @@ impl DoorContainer {
                         gain: 1.0,
                         rolloff_factor: 1.0,
                         radius: 1.0,
+                        caption: None,
                     })
                     .unwrap();
             }
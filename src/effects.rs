@@ -7,8 +7,10 @@ use rg3d::{
         color_gradient::{ColorGradient, GradientPoint},
         numeric_range::NumericRange,
         pool::Handle,
+        visitor::{Visit, VisitResult, Visitor},
     },
     engine::resource_manager::ResourceManager,
+    lazy_static::lazy_static,
     scene::{
         base::BaseBuilder,
         graph::Graph,
@@ -17,34 +19,360 @@ use rg3d::{
         transform::TransformBuilder,
     },
 };
-use std::path::Path;
+use serde::Deserialize;
+use std::{collections::HashMap, fs::File, path::Path};
 
 /// TODO: These effects are legacy from rusty-shooter, at that moment, particle system editor
 /// didn't exist and there was just no other options, only to create effects by hand. Effects
 /// should be re-made in rusty-editor and loaded as resources.
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Deserialize)]
 pub enum EffectKind {
     BulletImpact,
     BloodSpray,
     Smoke,
+    PlasmaScorch,
+}
+
+impl Default for EffectKind {
+    fn default() -> Self {
+        Self::BulletImpact
+    }
+}
+
+impl EffectKind {
+    fn id(self) -> u32 {
+        match self {
+            EffectKind::BulletImpact => 0,
+            EffectKind::BloodSpray => 1,
+            EffectKind::Smoke => 2,
+            EffectKind::PlasmaScorch => 3,
+        }
+    }
+
+    fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(EffectKind::BulletImpact),
+            1 => Ok(EffectKind::BloodSpray),
+            2 => Ok(EffectKind::Smoke),
+            3 => Ok(EffectKind::PlasmaScorch),
+            _ => Err(format!("Invalid effect kind {}", id)),
+        }
+    }
+
+    /// Returns neutral substitute of a blood effect.
+    fn without_gore(self) -> Self {
+        match self {
+            EffectKind::BloodSpray => EffectKind::BulletImpact,
+            _ => self,
+        }
+    }
+
+    /// Emission parameters of an effect at full quality.
+    fn parameters(self) -> EffectParameters {
+        match self {
+            EffectKind::BulletImpact => EffectParameters {
+                max_particles: 200,
+                spawn_rate: 3000,
+                lifetime: 0.2,
+                light_lifetime: 0.1,
+            },
+            EffectKind::BloodSpray => EffectParameters {
+                max_particles: 200,
+                spawn_rate: 2000,
+                lifetime: 0.4,
+                light_lifetime: 0.0,
+            },
+            EffectKind::Smoke => EffectParameters {
+                max_particles: 100,
+                spawn_rate: 50,
+                lifetime: 5.0,
+                light_lifetime: 0.0,
+            },
+            EffectKind::PlasmaScorch => EffectParameters {
+                max_particles: 150,
+                spawn_rate: 1500,
+                lifetime: 0.6,
+                light_lifetime: 0.3,
+            },
+        }
+    }
+}
+
+impl Visit for EffectKind {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut id = self.id();
+        id.visit(name, visitor)?;
+        if visitor.is_reading() {
+            *self = Self::from_id(id)?;
+        }
+        Ok(())
+    }
+}
+
+struct EffectParameters {
+    max_particles: u32,
+    spawn_rate: u32,
+    lifetime: f32,
+    /// Lifetime of the flash light of an effect, zero if there is no light.
+    light_lifetime: f32,
+}
+
+struct ActiveEffect {
+    particles: u32,
+    time_left: f32,
+}
+
+/// Tracks approximate amount of alive particles of spawned effects. When there are more
+/// particles than the budget allows, new effects are created with less particles, so heavy
+/// fights won't tank frame rate. Budget is not saved, effects that were alive at the moment
+/// of saving are just not counted after loading.
+pub struct ParticleBudget {
+    budget: u32,
+    active: Vec<ActiveEffect>,
+}
+
+impl Default for ParticleBudget {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_BUDGET)
+    }
+}
+
+impl ParticleBudget {
+    pub const DEFAULT_BUDGET: u32 = 5000;
+    /// Effects are never scaled down below this fraction, otherwise they'd be invisible.
+    const MIN_SCALE: f32 = 0.1;
+
+    pub fn new(budget: u32) -> Self {
+        Self {
+            budget,
+            active: Default::default(),
+        }
+    }
+
+    pub fn set_budget(&mut self, budget: u32) {
+        self.budget = budget;
+    }
+
+    fn active_particles(&self) -> u32 {
+        self.active.iter().map(|e| e.particles).sum()
+    }
+
+    /// Returns multiplier for amount of particles of new effects.
+    fn scale(&self) -> f32 {
+        let active = self.active_particles();
+        if active > self.budget {
+            (self.budget as f32 / active as f32).max(Self::MIN_SCALE)
+        } else {
+            1.0
+        }
+    }
+
+    fn register(&mut self, particles: u32, lifetime: f32) {
+        self.active.push(ActiveEffect {
+            particles,
+            time_left: lifetime,
+        });
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for effect in self.active.iter_mut() {
+            effect.time_left -= dt;
+        }
+        self.active.retain(|e| e.time_left > 0.0);
+    }
+}
+
+fn scaled(value: u32, scale: f32) -> u32 {
+    ((value as f32 * scale).ceil() as u32).max(1)
+}
+
+#[derive(Deserialize, Default)]
+pub struct EffectPoolDefinition {
+    /// Maximum amount of reusable nodes of each effect kind, effects of kinds that are not
+    /// listed here are always created from scratch.
+    pool_sizes: HashMap<EffectKind, u32>,
+}
+
+impl EffectPoolDefinition {
+    pub fn new() -> Self {
+        let file = File::open("data/configs/effects.ron").unwrap();
+        ron::de::from_reader(file).unwrap()
+    }
+}
+
+lazy_static! {
+    static ref POOL_DEFINITION: EffectPoolDefinition = EffectPoolDefinition::new();
+}
+
+#[derive(Default)]
+struct PooledEffect {
+    kind: EffectKind,
+    node: Handle<Node>,
+    /// Time left until the effect can be reused, zero or less if the effect is free.
+    timer: f32,
+    light_timer: f32,
+}
+
+impl Visit for PooledEffect {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.kind.visit("Kind", visitor)?;
+        self.node.visit("Node", visitor)?;
+        self.timer.visit("Timer", visitor)?;
+        self.light_timer.visit("LightTimer", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Short effects like impacts are spawned many times per second during heavy fights, instead
+/// of creating and destroying a node for each of them, the pool keeps effect nodes without
+/// lifetime and restarts them at a new place. Nodes are created on demand until pool size
+/// for the kind is reached, then effects are created as usual and die after their lifetime.
+#[derive(Default)]
+pub struct EffectPool {
+    effects: Vec<PooledEffect>,
+}
+
+impl EffectPool {
+    fn pool_size(kind: EffectKind) -> usize {
+        POOL_DEFINITION
+            .pool_sizes
+            .get(&kind)
+            .map_or(0, |&size| size as usize)
+    }
+
+    fn try_reuse(
+        &mut self,
+        kind: EffectKind,
+        graph: &mut Graph,
+        pos: Vector3<f32>,
+        orientation: UnitQuaternion<f32>,
+        scale: f32,
+    ) -> Option<Handle<Node>> {
+        let effect = self
+            .effects
+            .iter_mut()
+            .find(|e| e.kind == kind && e.timer <= 0.0)?;
+
+        let parameters = kind.parameters();
+        effect.timer = parameters.lifetime;
+        effect.light_timer = parameters.light_lifetime;
+
+        let node = &mut graph[effect.node];
+        node.local_transform_mut().set_position(pos);
+        node.local_transform_mut().set_rotation(orientation);
+        if let Node::ParticleSystem(particle_system) = node {
+            particle_system.clear_particles();
+            for emitter in particle_system.emitters.iter_mut() {
+                emitter.set_max_particles(scaled(parameters.max_particles, scale));
+                emitter.set_spawn_rate(scaled(parameters.spawn_rate, scale));
+            }
+        }
+        set_light_visibility(graph, effect.node, true);
+
+        Some(effect.node)
+    }
+
+    fn has_room(&self, kind: EffectKind) -> bool {
+        self.effects.iter().filter(|e| e.kind == kind).count() < Self::pool_size(kind)
+    }
+
+    fn add(&mut self, kind: EffectKind, node: Handle<Node>) {
+        let parameters = kind.parameters();
+        self.effects.push(PooledEffect {
+            kind,
+            node,
+            timer: parameters.lifetime,
+            light_timer: parameters.light_lifetime,
+        });
+    }
+
+    pub fn update(&mut self, graph: &mut Graph, dt: f32) {
+        for effect in self.effects.iter_mut() {
+            effect.timer -= dt;
+            if effect.light_timer > 0.0 {
+                effect.light_timer -= dt;
+                if effect.light_timer <= 0.0 {
+                    set_light_visibility(graph, effect.node, false);
+                }
+            }
+        }
+    }
+}
+
+impl Visit for EffectPool {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.effects.visit("Effects", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+fn set_light_visibility(graph: &mut Graph, effect: Handle<Node>, visibility: bool) {
+    for &child in graph[effect].children().to_vec().iter() {
+        if let Node::Light(_) = graph[child] {
+            graph[child].set_visibility(visibility);
+        }
+    }
+}
+
+/// Pooled effects must not die, so lifetime is set only for ones that are not pooled.
+fn with_lifetime(builder: BaseBuilder, lifetime: f32, pooled: bool) -> BaseBuilder {
+    if pooled {
+        builder
+    } else {
+        builder.with_lifetime(lifetime)
+    }
 }
 
 /// # Notes
 ///
-/// Each effect is Z-oriented and rotated using given orientation.
+/// Each effect is Z-oriented and rotated using given orientation. When `gore` is disabled,
+/// blood effects are replaced with neutral ones.
 pub fn create(
     kind: EffectKind,
     graph: &mut Graph,
     resource_manager: ResourceManager,
     pos: Vector3<f32>,
     orientation: UnitQuaternion<f32>,
+    gore: bool,
+    budget: &mut ParticleBudget,
+    pool: &mut EffectPool,
 ) -> Handle<Node> {
-    match kind {
-        EffectKind::BulletImpact => create_bullet_impact(graph, resource_manager, pos, orientation),
-        EffectKind::BloodSpray => create_blood_spray(graph, resource_manager, pos, orientation),
-        EffectKind::Smoke => create_smoke(graph, resource_manager, pos, orientation),
+    let kind = if gore { kind } else { kind.without_gore() };
+    let scale = budget.scale();
+    let parameters = kind.parameters();
+    budget.register(scaled(parameters.max_particles, scale), parameters.lifetime);
+
+    if let Some(effect) = pool.try_reuse(kind, graph, pos, orientation, scale) {
+        return effect;
     }
+
+    let pooled = pool.has_room(kind);
+
+    let effect = match kind {
+        EffectKind::BulletImpact => {
+            create_bullet_impact(graph, resource_manager, pos, orientation, scale, pooled)
+        }
+        EffectKind::BloodSpray => {
+            create_blood_spray(graph, resource_manager, pos, orientation, scale, pooled)
+        }
+        EffectKind::Smoke => create_smoke(graph, resource_manager, pos, orientation, scale, pooled),
+        EffectKind::PlasmaScorch => {
+            create_plasma_scorch(graph, resource_manager, pos, orientation, scale, pooled)
+        }
+    };
+
+    if pooled {
+        pool.add(kind, effect);
+    }
+
+    effect
 }
 
 fn create_bullet_impact(
@@ -52,30 +380,36 @@ fn create_bullet_impact(
     resource_manager: ResourceManager,
     pos: Vector3<f32>,
     orientation: UnitQuaternion<f32>,
+    scale: f32,
+    pooled: bool,
 ) -> Handle<Node> {
+    let parameters = EffectKind::BulletImpact.parameters();
     ParticleSystemBuilder::new(
-        BaseBuilder::new()
-            .with_children(&[PointLightBuilder::new(
+        with_lifetime(
+            BaseBuilder::new().with_children(&[PointLightBuilder::new(
                 BaseLightBuilder::new(
-                    BaseBuilder::new().with_lifetime(0.1).with_local_transform(
-                        TransformBuilder::new()
-                            .with_local_position(Vector3::new(0.0, 0.0, 0.05))
-                            .build(),
-                    ),
+                    with_lifetime(BaseBuilder::new(), parameters.light_lifetime, pooled)
+                        .with_local_transform(
+                            TransformBuilder::new()
+                                .with_local_position(Vector3::new(0.0, 0.0, 0.05))
+                                .build(),
+                        ),
                 )
                 .with_color(Color::opaque(255, 255, 200))
                 .with_scatter_enabled(false)
                 .cast_shadows(false),
             )
             .with_radius(0.5)
-            .build(graph)])
-            .with_lifetime(0.2)
-            .with_local_transform(
-                TransformBuilder::new()
-                    .with_local_position(pos)
-                    .with_local_rotation(orientation)
-                    .build(),
-            ),
+            .build(graph)]),
+            parameters.lifetime,
+            pooled,
+        )
+        .with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(pos)
+                .with_local_rotation(orientation)
+                .build(),
+        ),
     )
     .with_acceleration(Vector3::new(0.0, 0.0, 0.0))
     .with_color_over_lifetime_gradient({
@@ -88,8 +422,8 @@ fn create_bullet_impact(
     })
     .with_emitters(vec![SphereEmitterBuilder::new(
         BaseEmitterBuilder::new()
-            .with_max_particles(200)
-            .with_spawn_rate(3000)
+            .with_max_particles(scaled(parameters.max_particles, scale))
+            .with_spawn_rate(scaled(parameters.spawn_rate, scale))
             .with_size_modifier_range(NumericRange::new(-0.01, -0.0125))
             .with_size_range(NumericRange::new(0.0075, 0.015))
             .with_lifetime_range(NumericRange::new(0.05, 0.2))
@@ -104,14 +438,83 @@ fn create_bullet_impact(
     .build(graph)
 }
 
+fn create_plasma_scorch(
+    graph: &mut Graph,
+    resource_manager: ResourceManager,
+    pos: Vector3<f32>,
+    orientation: UnitQuaternion<f32>,
+    scale: f32,
+    pooled: bool,
+) -> Handle<Node> {
+    let parameters = EffectKind::PlasmaScorch.parameters();
+    ParticleSystemBuilder::new(
+        with_lifetime(
+            BaseBuilder::new().with_children(&[PointLightBuilder::new(
+                BaseLightBuilder::new(
+                    with_lifetime(BaseBuilder::new(), parameters.light_lifetime, pooled)
+                        .with_local_transform(
+                            TransformBuilder::new()
+                                .with_local_position(Vector3::new(0.0, 0.0, 0.1))
+                                .build(),
+                        ),
+                )
+                .with_color(Color::opaque(0, 162, 232))
+                .with_scatter_enabled(false)
+                .cast_shadows(false),
+            )
+            .with_radius(1.0)
+            .build(graph)]),
+            parameters.lifetime,
+            pooled,
+        )
+        .with_local_transform(
+            TransformBuilder::new()
+                .with_local_position(pos)
+                .with_local_rotation(orientation)
+                .build(),
+        ),
+    )
+    .with_acceleration(Vector3::new(0.0, 0.0, 0.0))
+    .with_color_over_lifetime_gradient({
+        let mut gradient = ColorGradient::new();
+        gradient.add_point(GradientPoint::new(
+            0.00,
+            Color::from_rgba(200, 240, 255, 255),
+        ));
+        gradient.add_point(GradientPoint::new(0.20, Color::from_rgba(0, 162, 232, 255)));
+        gradient.add_point(GradientPoint::new(0.60, Color::from_rgba(40, 40, 40, 200)));
+        gradient.add_point(GradientPoint::new(1.00, Color::from_rgba(20, 20, 20, 0)));
+        gradient
+    })
+    .with_emitters(vec![SphereEmitterBuilder::new(
+        BaseEmitterBuilder::new()
+            .with_max_particles(scaled(parameters.max_particles, scale))
+            .with_spawn_rate(scaled(parameters.spawn_rate, scale))
+            .with_size_modifier_range(NumericRange::new(0.0005, 0.001))
+            .with_size_range(NumericRange::new(0.02, 0.04))
+            .with_lifetime_range(NumericRange::new(0.2, 0.6))
+            .with_x_velocity_range(NumericRange::new(-0.003, 0.003))
+            .with_y_velocity_range(NumericRange::new(-0.003, 0.003))
+            .with_z_velocity_range(NumericRange::new(0.002, 0.006))
+            .resurrect_particles(false),
+    )
+    .with_radius(0.03)
+    .build()])
+    .with_texture(resource_manager.request_texture(Path::new("data/particles/light_01.png")))
+    .build(graph)
+}
+
 fn create_blood_spray(
     graph: &mut Graph,
     resource_manager: ResourceManager,
     pos: Vector3<f32>,
     orientation: UnitQuaternion<f32>,
+    scale: f32,
+    pooled: bool,
 ) -> Handle<Node> {
+    let parameters = EffectKind::BloodSpray.parameters();
     ParticleSystemBuilder::new(
-        BaseBuilder::new().with_lifetime(0.4).with_local_transform(
+        with_lifetime(BaseBuilder::new(), parameters.lifetime, pooled).with_local_transform(
             TransformBuilder::new()
                 .with_local_position(pos)
                 .with_local_rotation(orientation)
@@ -128,8 +531,8 @@ fn create_blood_spray(
     })
     .with_emitters(vec![SphereEmitterBuilder::new(
         BaseEmitterBuilder::new()
-            .with_max_particles(200)
-            .with_spawn_rate(2000)
+            .with_max_particles(scaled(parameters.max_particles, scale))
+            .with_spawn_rate(scaled(parameters.spawn_rate, scale))
             .with_size_modifier_range(NumericRange::new(-0.01, -0.0125))
             .with_lifetime_range(NumericRange::new(0.1, 0.4))
             .with_size_range(NumericRange::new(0.0075, 0.015))
@@ -144,45 +547,191 @@ fn create_blood_spray(
     .build(graph)
 }
 
+struct SmokeParameters {
+    max_particles: u32,
+    spawn_rate: u32,
+    /// Maximum horizontal speed of particles.
+    spread: f32,
+    rise_speed: NumericRange,
+    radius: f32,
+}
+
 fn create_smoke(
     graph: &mut Graph,
     resource_manager: ResourceManager,
     pos: Vector3<f32>,
     orientation: UnitQuaternion<f32>,
+    scale: f32,
+    pooled: bool,
 ) -> Handle<Node> {
-    ParticleSystemBuilder::new(
-        BaseBuilder::new().with_lifetime(5.0).with_local_transform(
+    let parameters = EffectKind::Smoke.parameters();
+    build_smoke(
+        with_lifetime(BaseBuilder::new(), parameters.lifetime, pooled).with_local_transform(
             TransformBuilder::new()
                 .with_local_position(pos)
                 .with_local_rotation(orientation)
                 .build(),
         ),
+        SmokeParameters {
+            max_particles: scaled(parameters.max_particles, scale),
+            spawn_rate: scaled(parameters.spawn_rate, scale),
+            spread: 0.01,
+            rise_speed: NumericRange::new(0.02, 0.03),
+            radius: 0.01,
+        },
+        graph,
+        resource_manager,
     )
-    .with_acceleration(Vector3::new(0.0, 0.0, 0.0))
-    .with_color_over_lifetime_gradient({
-        let mut gradient = ColorGradient::new();
-        gradient.add_point(GradientPoint::new(0.00, Color::from_rgba(150, 150, 150, 0)));
-        gradient.add_point(GradientPoint::new(
-            0.05,
-            Color::from_rgba(150, 150, 150, 220),
-        ));
-        gradient.add_point(GradientPoint::new(
-            0.85,
-            Color::from_rgba(255, 255, 255, 180),
-        ));
-        gradient.add_point(GradientPoint::new(1.00, Color::from_rgba(255, 255, 255, 0)));
-        gradient
-    })
-    .with_emitters(vec![SphereEmitterBuilder::new(
-        BaseEmitterBuilder::new()
-            .with_max_particles(100)
-            .with_spawn_rate(50)
-            .with_x_velocity_range(NumericRange::new(-0.01, 0.01))
-            .with_y_velocity_range(NumericRange::new(0.02, 0.03))
-            .with_z_velocity_range(NumericRange::new(-0.01, 0.01)),
+}
+
+/// Creates looping thin smoke that is left behind by projectiles, it has no lifetime and must
+/// be managed by [`SmokeTrailPool`].
+fn create_smoke_trail(
+    graph: &mut Graph,
+    resource_manager: ResourceManager,
+    pos: Vector3<f32>,
+) -> Handle<Node> {
+    build_smoke(
+        BaseBuilder::new()
+            .with_local_transform(TransformBuilder::new().with_local_position(pos).build()),
+        SmokeParameters {
+            max_particles: 150,
+            spawn_rate: SmokeTrailPool::SPAWN_RATE,
+            spread: 0.002,
+            rise_speed: NumericRange::new(0.002, 0.004),
+            radius: 0.005,
+        },
+        graph,
+        resource_manager,
     )
-    .with_radius(0.01)
-    .build()])
-    .with_texture(resource_manager.request_texture(Path::new("data/particles/smoke_04.tga")))
-    .build(graph)
+}
+
+fn build_smoke(
+    base_builder: BaseBuilder,
+    parameters: SmokeParameters,
+    graph: &mut Graph,
+    resource_manager: ResourceManager,
+) -> Handle<Node> {
+    ParticleSystemBuilder::new(base_builder)
+        .with_acceleration(Vector3::new(0.0, 0.0, 0.0))
+        .with_color_over_lifetime_gradient({
+            let mut gradient = ColorGradient::new();
+            gradient.add_point(GradientPoint::new(0.00, Color::from_rgba(150, 150, 150, 0)));
+            gradient.add_point(GradientPoint::new(
+                0.05,
+                Color::from_rgba(150, 150, 150, 220),
+            ));
+            gradient.add_point(GradientPoint::new(
+                0.85,
+                Color::from_rgba(255, 255, 255, 180),
+            ));
+            gradient.add_point(GradientPoint::new(1.00, Color::from_rgba(255, 255, 255, 0)));
+            gradient
+        })
+        .with_emitters(vec![SphereEmitterBuilder::new(
+            BaseEmitterBuilder::new()
+                .with_max_particles(parameters.max_particles)
+                .with_spawn_rate(parameters.spawn_rate)
+                .with_x_velocity_range(NumericRange::new(-parameters.spread, parameters.spread))
+                .with_y_velocity_range(parameters.rise_speed)
+                .with_z_velocity_range(NumericRange::new(-parameters.spread, parameters.spread)),
+        )
+        .with_radius(parameters.radius)
+        .build()])
+        .with_texture(resource_manager.request_texture(Path::new("data/particles/smoke_04.tga")))
+        .build(graph)
+}
+
+#[derive(Default)]
+struct FadingTrail {
+    node: Handle<Node>,
+    /// Time left until every particle of the trail disappears.
+    timer: f32,
+}
+
+impl Visit for FadingTrail {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.node.visit("Node", visitor)?;
+        self.timer.visit("Timer", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Smoke trails are not destroyed when their projectile dies, instead they stop emitting
+/// particles, wait until existing particles fade out and then can be reused by another
+/// projectile. This way rapid fire does not create and destroy particle systems each shot.
+#[derive(Default)]
+pub struct SmokeTrailPool {
+    free: Vec<Handle<Node>>,
+    fading: Vec<FadingTrail>,
+}
+
+impl SmokeTrailPool {
+    const SPAWN_RATE: u32 = 60;
+    /// Must not be less than lifetime of trail particles.
+    const FADE_TIME: f32 = 3.0;
+
+    /// Returns a trail that emits particles at given position, either a reused one or a new one.
+    pub fn acquire(
+        &mut self,
+        graph: &mut Graph,
+        resource_manager: ResourceManager,
+        position: Vector3<f32>,
+    ) -> Handle<Node> {
+        match self.free.pop() {
+            Some(trail) => {
+                graph[trail].local_transform_mut().set_position(position);
+                set_spawn_rate(graph, trail, Self::SPAWN_RATE);
+                trail
+            }
+            None => create_smoke_trail(graph, resource_manager, position),
+        }
+    }
+
+    /// Stops emission of a trail, it will be available for reuse once its particles fade out.
+    pub fn release(&mut self, graph: &mut Graph, trail: Handle<Node>) {
+        set_spawn_rate(graph, trail, 0);
+        self.fading.push(FadingTrail {
+            node: trail,
+            timer: Self::FADE_TIME,
+        });
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for trail in self.fading.iter_mut() {
+            trail.timer -= dt;
+        }
+
+        let free = &mut self.free;
+        self.fading.retain(|trail| {
+            if trail.timer <= 0.0 {
+                free.push(trail.node);
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+impl Visit for SmokeTrailPool {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.free.visit("Free", visitor)?;
+        self.fading.visit("Fading", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+fn set_spawn_rate(graph: &mut Graph, particle_system: Handle<Node>, spawn_rate: u32) {
+    if let Node::ParticleSystem(particle_system) = &mut graph[particle_system] {
+        for emitter in particle_system.emitters.iter_mut() {
+            emitter.set_spawn_rate(spawn_rate);
+        }
+    }
 }
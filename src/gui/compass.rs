@@ -0,0 +1,263 @@
+//! Compass strip at the top of the screen. It shows cardinal directions and markers of nearby
+//! enemies and level objectives relative to the facing of the player's camera, north is +Z
+//! axis of the world. Markers behind the player are not shown, the strip covers only
+//! directions in front of the camera.
+
+use crate::gui::{palette::HudPalette, Gui, UiNode};
+use rg3d::{
+    core::{
+        algebra::{Vector2, Vector3},
+        color::Color,
+        pool::Handle,
+    },
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        canvas::CanvasBuilder,
+        message::{MessageDirection, TextMessage, WidgetMessage},
+        text::TextBuilder,
+        ttf::SharedFont,
+        widget::WidgetBuilder,
+        HorizontalAlignment, VerticalAlignment,
+    },
+    scene::camera::Camera,
+};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum CompassMarkerKind {
+    Enemy,
+    Objective,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct CompassMarker {
+    pub position: Vector3<f32>,
+    pub kind: CompassMarkerKind,
+}
+
+pub struct Compass {
+    canvas: Handle<UiNode>,
+    strip: Handle<UiNode>,
+    /// Labels of north, east, south and west.
+    cardinals: [Handle<UiNode>; 4],
+    markers: Vec<Handle<UiNode>>,
+    enabled: bool,
+    palette: HudPalette,
+    scale: f32,
+}
+
+impl Compass {
+    const WIDTH: f32 = 400.0;
+    const HEIGHT: f32 = 36.0;
+    /// Distance from the top of the screen, so the strip won't cover horde wave counter.
+    const TOP_MARGIN: f32 = 80.0;
+    const LABEL_WIDTH: f32 = 40.0;
+    const MARKER_SIZE: f32 = 8.0;
+    /// Angle (in radians) between the left and the right edges of the strip.
+    const SPAN: f32 = std::f32::consts::PI;
+    /// Markers beyond this amount are not shown.
+    const MAX_MARKERS: usize = 16;
+    /// Distance (in meters) at which enemies are shown on the compass.
+    pub const ENEMY_RANGE: f32 = 30.0;
+
+    pub fn new(ui: &mut Gui, font: SharedFont, enabled: bool, scale: f32) -> Self {
+        let ctx = &mut ui.build_ctx();
+
+        let strip = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_background(Brush::Solid(Color::from_rgba(0, 0, 0, 120)))
+                .with_foreground(Brush::Solid(Color::opaque(120, 120, 120))),
+        )
+        .build(ctx);
+
+        let mut make_label = |text: &str| {
+            TextBuilder::new(WidgetBuilder::new())
+                .with_text(text)
+                .with_font(font.clone())
+                .with_horizontal_text_alignment(HorizontalAlignment::Center)
+                .with_vertical_text_alignment(VerticalAlignment::Center)
+                .build(ctx)
+        };
+        let cardinals = [
+            make_label("N"),
+            make_label("E"),
+            make_label("S"),
+            make_label("W"),
+        ];
+
+        // Size and color of markers are set in `sync_sizes` and `sync_to_model`.
+        let markers = (0..Self::MAX_MARKERS)
+            .map(|_| {
+                BorderBuilder::new(
+                    WidgetBuilder::new()
+                        .with_visibility(false)
+                        .with_foreground(Brush::Solid(Color::TRANSPARENT)),
+                )
+                .build(ctx)
+            })
+            .collect::<Vec<_>>();
+
+        let canvas = CanvasBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_child(strip)
+                .with_children(&cardinals)
+                .with_children(&markers),
+        )
+        .build(ctx);
+
+        let compass = Self {
+            canvas,
+            strip,
+            cardinals,
+            markers,
+            enabled,
+            palette: Default::default(),
+            scale,
+        };
+        compass.sync_sizes(ui);
+        compass
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn set_palette(&mut self, palette: &HudPalette) {
+        self.palette = *palette;
+    }
+
+    pub fn set_scale(&mut self, ui: &Gui, font: SharedFont, scale: f32) {
+        self.scale = scale;
+        for &label in self.cardinals.iter() {
+            ui.send_message(TextMessage::font(
+                label,
+                MessageDirection::ToWidget,
+                font.clone(),
+            ));
+        }
+        self.sync_sizes(ui);
+    }
+
+    fn sync_sizes(&self, ui: &Gui) {
+        let set_size = |widget: Handle<UiNode>, width: f32, height: f32| {
+            ui.send_message(WidgetMessage::width(
+                widget,
+                MessageDirection::ToWidget,
+                width * self.scale,
+            ));
+            ui.send_message(WidgetMessage::height(
+                widget,
+                MessageDirection::ToWidget,
+                height * self.scale,
+            ));
+        };
+
+        set_size(self.strip, Self::WIDTH, Self::HEIGHT);
+        for &label in self.cardinals.iter() {
+            set_size(label, Self::LABEL_WIDTH, Self::HEIGHT);
+        }
+        for &marker in self.markers.iter() {
+            set_size(marker, Self::MARKER_SIZE, Self::MARKER_SIZE);
+        }
+    }
+
+    /// Shows compass if there is a camera of the player and moves labels and markers
+    /// according to orientation of the camera.
+    pub fn sync_to_model(&self, ui: &Gui, camera: Option<&Camera>, markers: &[CompassMarker]) {
+        let camera = camera.filter(|_| self.enabled);
+
+        ui.send_message(WidgetMessage::visibility(
+            self.canvas,
+            MessageDirection::ToWidget,
+            camera.is_some(),
+        ));
+
+        let camera = match camera {
+            Some(camera) => camera,
+            None => return,
+        };
+
+        // Horizontal basis of the camera, side vector stays horizontal when the camera looks
+        // up or down, so it is used to get forward direction too.
+        let mut right = -camera.side_vector();
+        right.y = 0.0;
+        let right = match right.try_normalize(std::f32::EPSILON) {
+            Some(right) => right,
+            None => return,
+        };
+        let forward = Vector3::y().cross(&right);
+        let position = camera.global_position();
+
+        let width = Self::WIDTH * self.scale;
+        let height = Self::HEIGHT * self.scale;
+        let origin = Vector2::new(
+            (ui.screen_size().x - width) * 0.5,
+            Self::TOP_MARGIN * self.scale,
+        );
+
+        // Returns horizontal offset from the left edge of the strip for a direction, or
+        // `None` if the direction is out of the strip.
+        let offset_of = |direction: Vector3<f32>| {
+            let angle = direction.dot(&right).atan2(direction.dot(&forward));
+            if angle.abs() <= Self::SPAN * 0.5 {
+                Some((0.5 + angle / Self::SPAN) * width)
+            } else {
+                None
+            }
+        };
+
+        let place = |widget: Handle<UiNode>, offset: Option<f32>, size: Vector2<f32>| {
+            ui.send_message(WidgetMessage::visibility(
+                widget,
+                MessageDirection::ToWidget,
+                offset.is_some(),
+            ));
+            if let Some(offset) = offset {
+                ui.send_message(WidgetMessage::desired_position(
+                    widget,
+                    MessageDirection::ToWidget,
+                    origin + Vector2::new(offset - size.x * 0.5, (height - size.y) * 0.5),
+                ));
+            }
+        };
+
+        ui.send_message(WidgetMessage::desired_position(
+            self.strip,
+            MessageDirection::ToWidget,
+            origin,
+        ));
+
+        let directions = [Vector3::z(), -Vector3::x(), -Vector3::z(), Vector3::x()];
+        let label_size = Vector2::new(Self::LABEL_WIDTH * self.scale, height);
+        for (&label, &direction) in self.cardinals.iter().zip(directions.iter()) {
+            place(label, offset_of(direction), label_size);
+        }
+
+        let marker_size = Vector2::new(
+            Self::MARKER_SIZE * self.scale,
+            Self::MARKER_SIZE * self.scale,
+        );
+        for (i, &widget) in self.markers.iter().enumerate() {
+            let marker = markers.get(i);
+            let offset = marker.and_then(|marker| {
+                let mut direction = marker.position - position;
+                direction.y = 0.0;
+                offset_of(direction)
+            });
+            place(widget, offset, marker_size);
+
+            if let Some(marker) = marker {
+                let color = match marker.kind {
+                    CompassMarkerKind::Enemy => self.palette.danger,
+                    CompassMarkerKind::Objective => self.palette.accent,
+                };
+                ui.send_message(WidgetMessage::background(
+                    widget,
+                    MessageDirection::ToWidget,
+                    Brush::Solid(color),
+                ));
+            }
+        }
+    }
+}
@@ -0,0 +1,245 @@
+//! Debug console at the top of the screen, it is toggled by a key and accepts text commands
+//! that are translated to regular game messages by the game. Commands that change gameplay
+//! are cheats, they work only if cheats are enabled in gameplay section of the config.
+
+use crate::{
+    bot::BotKind,
+    gui::{Gui, GuiMessage, UiNode},
+    item::ItemKind,
+    weapon::WeaponKind,
+};
+use rg3d::{
+    core::{algebra::Vector3, color::Color, pool::Handle},
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        grid::{Column, GridBuilder, Row},
+        message::{MessageDirection, TextBoxMessage, TextMessage, UiMessageData, WidgetMessage},
+        text::TextBuilder,
+        text_box::TextBoxBuilder,
+        ttf::SharedFont,
+        widget::WidgetBuilder,
+        Thickness, VerticalAlignment,
+    },
+};
+use serde::de::DeserializeOwned;
+use std::collections::VecDeque;
+
+const HELP: &str = "Commands: help, clear, spawn_bot <Mutant|Parasite|Zombie>, \
+    spawn_item <kind>, give <M4|Ak47|PlasmaRifle|Glock|Shotgun>, god, noclip, \
+    teleport <x> <y> <z>, time_scale <scale>, shadow_culling";
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ConsoleCommand {
+    SpawnBot(BotKind),
+    SpawnItem(ItemKind),
+    GiveWeapon(WeaponKind),
+    /// Toggles god mode of the player.
+    GodMode,
+    /// Toggles noclip mode of the player.
+    Noclip,
+    Teleport(Vector3<f32>),
+    TimeScale(f32),
+    /// Toggles culling of shadows of distant and off-screen lights.
+    ShadowCulling,
+}
+
+impl ConsoleCommand {
+    fn parse(name: &str, args: &[&str]) -> Result<Self, String> {
+        match name {
+            "spawn_bot" => Ok(Self::SpawnBot(parse_kind(args)?)),
+            "spawn_item" => Ok(Self::SpawnItem(parse_kind(args)?)),
+            "give" => Ok(Self::GiveWeapon(parse_kind(args)?)),
+            "god" => Ok(Self::GodMode),
+            "noclip" => Ok(Self::Noclip),
+            "shadow_culling" => Ok(Self::ShadowCulling),
+            "teleport" => match args {
+                [x, y, z] => Ok(Self::Teleport(Vector3::new(
+                    parse_number(x)?,
+                    parse_number(y)?,
+                    parse_number(z)?,
+                ))),
+                _ => Err("Usage: teleport <x> <y> <z>".to_owned()),
+            },
+            "time_scale" => match args {
+                [scale] => Ok(Self::TimeScale(parse_number(scale)?.max(0.0))),
+                _ => Err("Usage: time_scale <scale>".to_owned()),
+            },
+            _ => Err(format!(
+                "Unknown command {}, type help to list commands",
+                name
+            )),
+        }
+    }
+}
+
+/// Parses kind of a bot, an item or a weapon by its name in configs.
+fn parse_kind<T: DeserializeOwned>(args: &[&str]) -> Result<T, String> {
+    match args {
+        [kind] => ron::de::from_str(kind).map_err(|_| format!("Unknown kind {}", kind)),
+        _ => Err("Expected a single kind".to_owned()),
+    }
+}
+
+fn parse_number(arg: &str) -> Result<f32, String> {
+    arg.parse().map_err(|_| format!("Invalid number {}", arg))
+}
+
+pub struct Console {
+    root: Handle<UiNode>,
+    log: Handle<UiNode>,
+    input: Handle<UiNode>,
+    lines: VecDeque<String>,
+    /// Text of the input box, it is updated by messages from the box.
+    command: String,
+    cheats: bool,
+}
+
+impl Console {
+    const HEIGHT: f32 = 260.0;
+    const MAX_LINES: usize = 10;
+
+    pub fn new(ui: &mut Gui, font: SharedFont, cheats: bool) -> Self {
+        let ctx = &mut ui.build_ctx();
+
+        let log = TextBuilder::new(
+            WidgetBuilder::new()
+                .on_row(0)
+                .with_margin(Thickness::uniform(4.0))
+                .with_vertical_alignment(VerticalAlignment::Bottom),
+        )
+        .with_font(font.clone())
+        .build(ctx);
+
+        let input = TextBoxBuilder::new(
+            WidgetBuilder::new()
+                .on_row(1)
+                .with_margin(Thickness::uniform(2.0)),
+        )
+        .with_font(font)
+        .build(ctx);
+
+        let root = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_width(ui.screen_size().x)
+                .with_height(Self::HEIGHT)
+                .with_background(Brush::Solid(Color::from_rgba(0, 0, 0, 200)))
+                .with_child(
+                    GridBuilder::new(WidgetBuilder::new().with_child(log).with_child(input))
+                        .add_row(Row::stretch())
+                        .add_row(Row::strict(30.0))
+                        .add_column(Column::stretch())
+                        .build(ctx),
+                ),
+        )
+        .build(ctx);
+
+        Self {
+            root,
+            log,
+            input,
+            lines: Default::default(),
+            command: Default::default(),
+            cheats,
+        }
+    }
+
+    pub fn is_visible(&self, ui: &Gui) -> bool {
+        ui.node(self.root).visibility()
+    }
+
+    pub fn toggle(&mut self, ui: &Gui) {
+        let visible = !self.is_visible(ui);
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            visible,
+        ));
+        ui.send_message(WidgetMessage::width(
+            self.root,
+            MessageDirection::ToWidget,
+            ui.screen_size().x,
+        ));
+        if visible {
+            self.clear_input(ui);
+            ui.send_message(WidgetMessage::focus(self.input, MessageDirection::ToWidget));
+        }
+    }
+
+    fn clear_input(&mut self, ui: &Gui) {
+        self.command.clear();
+        ui.send_message(TextBoxMessage::text(
+            self.input,
+            MessageDirection::ToWidget,
+            String::new(),
+        ));
+    }
+
+    pub fn handle_ui_message(&mut self, message: &GuiMessage) {
+        if let UiMessageData::TextBox(TextBoxMessage::Text(text)) = message.data() {
+            if message.destination() == self.input
+                && message.direction() == MessageDirection::FromWidget
+            {
+                self.command = text.clone();
+            }
+        }
+    }
+
+    pub fn print<S: Into<String>>(&mut self, ui: &Gui, line: S) {
+        self.lines.push_back(line.into());
+        while self.lines.len() > Self::MAX_LINES {
+            self.lines.pop_front();
+        }
+        self.sync_log(ui);
+    }
+
+    fn sync_log(&self, ui: &Gui) {
+        ui.send_message(TextMessage::text(
+            self.log,
+            MessageDirection::ToWidget,
+            self.lines.iter().cloned().collect::<Vec<_>>().join("\n"),
+        ));
+    }
+
+    /// Executes entered command, built-in commands are handled by the console itself and
+    /// every other command is returned for the game to execute.
+    pub fn submit(&mut self, ui: &Gui) -> Option<ConsoleCommand> {
+        // The key that toggles the console may end up in the input box too.
+        let line = self.command.trim().trim_matches('`').trim().to_owned();
+        self.clear_input(ui);
+        if line.is_empty() {
+            return None;
+        }
+        self.print(ui, format!("> {}", line));
+
+        let mut words = line.split_whitespace();
+        let name = words.next().unwrap_or_default();
+        let args = words.collect::<Vec<_>>();
+        match name {
+            "help" => {
+                self.print(ui, HELP);
+                None
+            }
+            "clear" => {
+                self.lines.clear();
+                self.sync_log(ui);
+                None
+            }
+            _ => match ConsoleCommand::parse(name, &args) {
+                Ok(_) if !self.cheats => {
+                    self.print(
+                        ui,
+                        "Cheats are disabled, enable them in gameplay section of the config",
+                    );
+                    None
+                }
+                Ok(command) => Some(command),
+                Err(e) => {
+                    self.print(ui, e);
+                    None
+                }
+            },
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! Crosshair in the center of the screen. It is made of a dot and four lines, which of them
+//! are visible depends on selected style. Dynamic crosshair moves its lines apart when player's
+//! accuracy drops (while running or shooting).
+
+use crate::gui::{Gui, UiNode};
+use rg3d::{
+    core::{algebra::Vector2, color::Color, pool::Handle},
+    gui::{
+        border::BorderBuilder, brush::Brush, canvas::CanvasBuilder, message::MessageDirection,
+        message::WidgetMessage, widget::WidgetBuilder,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum CrosshairStyle {
+    Dot,
+    Cross,
+    Dynamic,
+}
+
+impl Default for CrosshairStyle {
+    fn default() -> Self {
+        Self::Cross
+    }
+}
+
+impl CrosshairStyle {
+    pub const ALL: [CrosshairStyle; 3] = [
+        CrosshairStyle::Dot,
+        CrosshairStyle::Cross,
+        CrosshairStyle::Dynamic,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CrosshairStyle::Dot => "Dot",
+            CrosshairStyle::Cross => "Cross",
+            CrosshairStyle::Dynamic => "Dynamic Spread",
+        }
+    }
+
+    pub fn index(self) -> usize {
+        Self::ALL.iter().position(|s| *s == self).unwrap()
+    }
+}
+
+pub struct Crosshair {
+    canvas: Handle<UiNode>,
+    dot: Handle<UiNode>,
+    /// Top, bottom, left and right lines.
+    lines: [Handle<UiNode>; 4],
+    style: CrosshairStyle,
+    spread: f32,
+    screen_size: Vector2<f32>,
+    scale: f32,
+}
+
+impl Crosshair {
+    const DOT_SIZE: f32 = 4.0;
+    const LINE_LENGTH: f32 = 10.0;
+    const LINE_THICKNESS: f32 = 2.0;
+    const MIN_GAP: f32 = 4.0;
+    const MAX_GAP: f32 = 30.0;
+
+    pub fn new(ui: &mut Gui, style: CrosshairStyle, color: Color, scale: f32) -> Self {
+        let ctx = &mut ui.build_ctx();
+
+        // Size of parts is set in `sync_parts`.
+        let mut make_part = || {
+            BorderBuilder::new(
+                WidgetBuilder::new()
+                    .with_background(Brush::Solid(color))
+                    .with_foreground(Brush::Solid(Color::TRANSPARENT)),
+            )
+            .build(ctx)
+        };
+
+        let dot = make_part();
+        let lines = [make_part(), make_part(), make_part(), make_part()];
+
+        let canvas = CanvasBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_child(dot)
+                .with_children(&lines),
+        )
+        .build(ctx);
+
+        let crosshair = Self {
+            canvas,
+            dot,
+            lines,
+            style,
+            spread: 0.0,
+            screen_size: ui.screen_size(),
+            scale,
+        };
+        crosshair.sync_parts(ui);
+        crosshair
+    }
+
+    pub fn set_style(&mut self, ui: &Gui, style: CrosshairStyle) {
+        self.style = style;
+        self.sync_parts(ui);
+    }
+
+    pub fn set_scale(&mut self, ui: &Gui, scale: f32) {
+        self.scale = scale;
+        self.sync_parts(ui);
+    }
+
+    pub fn set_color(&self, ui: &Gui, color: Color) {
+        for &part in std::iter::once(&self.dot).chain(self.lines.iter()) {
+            ui.send_message(WidgetMessage::background(
+                part,
+                MessageDirection::ToWidget,
+                Brush::Solid(color),
+            ));
+        }
+    }
+
+    /// Shows crosshair if there is a player and moves lines of dynamic crosshair according
+    /// to given accuracy spread (in [0; 1] range).
+    pub fn sync_to_model(&mut self, ui: &Gui, spread: Option<f32>) {
+        ui.send_message(WidgetMessage::visibility(
+            self.canvas,
+            MessageDirection::ToWidget,
+            spread.is_some(),
+        ));
+
+        if let Some(spread) = spread {
+            let spread = if self.style == CrosshairStyle::Dynamic {
+                spread
+            } else {
+                0.0
+            };
+
+            if (spread - self.spread).abs() > f32::EPSILON || self.screen_size != ui.screen_size() {
+                self.spread = spread;
+                self.screen_size = ui.screen_size();
+                self.sync_parts(ui);
+            }
+        }
+    }
+
+    fn sync_parts(&self, ui: &Gui) {
+        let center = self.screen_size.scale(0.5);
+        let gap = (Self::MIN_GAP + (Self::MAX_GAP - Self::MIN_GAP) * self.spread) * self.scale;
+        let dot_size = Self::DOT_SIZE * self.scale;
+        let length = Self::LINE_LENGTH * self.scale;
+        let thickness = Self::LINE_THICKNESS * self.scale;
+        let half_thickness = thickness * 0.5;
+
+        let set_part = |part: Handle<UiNode>, position: Vector2<f32>, size: Vector2<f32>| {
+            ui.send_message(WidgetMessage::desired_position(
+                part,
+                MessageDirection::ToWidget,
+                position,
+            ));
+            ui.send_message(WidgetMessage::width(
+                part,
+                MessageDirection::ToWidget,
+                size.x,
+            ));
+            ui.send_message(WidgetMessage::height(
+                part,
+                MessageDirection::ToWidget,
+                size.y,
+            ));
+        };
+
+        let vertical = Vector2::new(thickness, length);
+        let horizontal = Vector2::new(length, thickness);
+
+        set_part(
+            self.dot,
+            center - Vector2::new(dot_size * 0.5, dot_size * 0.5),
+            Vector2::new(dot_size, dot_size),
+        );
+        set_part(
+            self.lines[0],
+            center - Vector2::new(half_thickness, gap + length),
+            vertical,
+        );
+        set_part(
+            self.lines[1],
+            center + Vector2::new(-half_thickness, gap),
+            vertical,
+        );
+        set_part(
+            self.lines[2],
+            center - Vector2::new(gap + length, half_thickness),
+            horizontal,
+        );
+        set_part(
+            self.lines[3],
+            center + Vector2::new(gap, -half_thickness),
+            horizontal,
+        );
+
+        ui.send_message(WidgetMessage::visibility(
+            self.dot,
+            MessageDirection::ToWidget,
+            self.style == CrosshairStyle::Dot,
+        ));
+        for &line in self.lines.iter() {
+            ui.send_message(WidgetMessage::visibility(
+                line,
+                MessageDirection::ToWidget,
+                self.style != CrosshairStyle::Dot,
+            ));
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! Floating numbers that pop up over bots damaged by the player. Each number is anchored to
+//! a point in the world, it is projected to the screen every frame while it rises and fades
+//! out. Numbers of critical hits (head shots) use danger color of the palette.
+
+use crate::gui::{palette::HudPalette, Gui, UiNode};
+use rg3d::{
+    core::{
+        algebra::{Vector2, Vector3, Vector4},
+        color::Color,
+        pool::Handle,
+    },
+    gui::{
+        brush::Brush,
+        message::{MessageDirection, WidgetMessage},
+        text::TextBuilder,
+        ttf::SharedFont,
+        widget::WidgetBuilder,
+        HorizontalAlignment,
+    },
+    scene::camera::Camera,
+};
+
+struct DamageNumber {
+    text: Handle<UiNode>,
+    position: Vector3<f32>,
+    color: Color,
+    timer: f32,
+}
+
+pub struct DamageNumbers {
+    numbers: Vec<DamageNumber>,
+    font: SharedFont,
+    enabled: bool,
+    palette: HudPalette,
+    scale: f32,
+}
+
+impl DamageNumbers {
+    const LIFETIME: f32 = 1.0;
+    /// Distance (in meters) that a number rises during its lifetime.
+    const RISE_HEIGHT: f32 = 0.6;
+    const WIDTH: f32 = 100.0;
+    const HEIGHT: f32 = 30.0;
+    /// Oldest numbers are removed when there are too many of them, this happens with
+    /// fast-firing weapons.
+    const MAX_NUMBERS: usize = 32;
+
+    pub fn new(font: SharedFont, enabled: bool, scale: f32) -> Self {
+        Self {
+            numbers: Default::default(),
+            font,
+            enabled,
+            palette: Default::default(),
+            scale,
+        }
+    }
+
+    /// Changes font and size of new numbers, numbers that are already shown keep their size.
+    pub fn set_scale(&mut self, font: SharedFont, scale: f32) {
+        self.font = font;
+        self.scale = scale;
+    }
+
+    pub fn set_enabled(&mut self, ui: &Gui, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.clear(ui);
+        }
+    }
+
+    pub fn set_palette(&mut self, palette: &HudPalette) {
+        self.palette = *palette;
+    }
+
+    pub fn show(&mut self, ui: &mut Gui, position: Vector3<f32>, amount: f32, critical: bool) {
+        if !self.enabled {
+            return;
+        }
+
+        if self.numbers.len() >= Self::MAX_NUMBERS {
+            let oldest = self.numbers.remove(0);
+            ui.send_message(WidgetMessage::remove(
+                oldest.text,
+                MessageDirection::ToWidget,
+            ));
+        }
+
+        let color = if critical {
+            self.palette.danger
+        } else {
+            Color::WHITE
+        };
+
+        let string = if critical {
+            format!("{:.0}!", amount)
+        } else {
+            format!("{:.0}", amount)
+        };
+
+        // Text is hidden until its first projection to the screen.
+        let text = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_width(Self::WIDTH * self.scale)
+                .with_height(Self::HEIGHT * self.scale)
+                .with_foreground(Brush::Solid(color)),
+        )
+        .with_horizontal_text_alignment(HorizontalAlignment::Center)
+        .with_text(&string)
+        .with_font(self.font.clone())
+        .build(&mut ui.build_ctx());
+
+        self.numbers.push(DamageNumber {
+            text,
+            position,
+            color,
+            timer: 0.0,
+        });
+    }
+
+    /// Removes every number, must be called when a level is destroyed.
+    pub fn clear(&mut self, ui: &Gui) {
+        for number in self.numbers.drain(..) {
+            ui.send_message(WidgetMessage::remove(
+                number.text,
+                MessageDirection::ToWidget,
+            ));
+        }
+    }
+
+    pub fn update(&mut self, ui: &Gui, camera: Option<&Camera>, dt: f32) {
+        let screen_size = ui.screen_size();
+
+        let mut i = 0;
+        while i < self.numbers.len() {
+            let number = &mut self.numbers[i];
+            number.timer += dt;
+            if number.timer >= Self::LIFETIME {
+                ui.send_message(WidgetMessage::remove(
+                    number.text,
+                    MessageDirection::ToWidget,
+                ));
+                self.numbers.remove(i);
+                continue;
+            }
+
+            let k = number.timer / Self::LIFETIME;
+            let position = number.position + Vector3::new(0.0, Self::RISE_HEIGHT * k, 0.0);
+            let screen_position = camera.and_then(|c| project(c, position, screen_size));
+
+            ui.send_message(WidgetMessage::visibility(
+                number.text,
+                MessageDirection::ToWidget,
+                screen_position.is_some(),
+            ));
+            if let Some(screen_position) = screen_position {
+                ui.send_message(WidgetMessage::desired_position(
+                    number.text,
+                    MessageDirection::ToWidget,
+                    screen_position
+                        - Vector2::new(Self::WIDTH * 0.5, Self::HEIGHT * 0.5).scale(self.scale),
+                ));
+                ui.send_message(WidgetMessage::foreground(
+                    number.text,
+                    MessageDirection::ToWidget,
+                    Brush::Solid(Color::from_rgba(
+                        number.color.r,
+                        number.color.g,
+                        number.color.b,
+                        ((1.0 - k) * 255.0) as u8,
+                    )),
+                ));
+            }
+
+            i += 1;
+        }
+    }
+}
+
+/// Projects a point in world space to screen space, returns `None` if the point is behind
+/// the camera.
+fn project(
+    camera: &Camera,
+    position: Vector3<f32>,
+    screen_size: Vector2<f32>,
+) -> Option<Vector2<f32>> {
+    let clip =
+        camera.view_projection_matrix() * Vector4::new(position.x, position.y, position.z, 1.0);
+    if clip.w <= 0.0 {
+        return None;
+    }
+    let ndc = Vector2::new(clip.x / clip.w, clip.y / clip.w);
+    Some(Vector2::new(
+        (ndc.x + 1.0) * 0.5 * screen_size.x,
+        (1.0 - ndc.y) * 0.5 * screen_size.y,
+    ))
+}
@@ -0,0 +1,141 @@
+//! Progress ring around the crosshair that is shown while the player holds action control
+//! at an objective that isn't completed instantly. The ring is made of segments that light up
+//! one by one as holding progresses.
+
+use crate::gui::{palette::HudPalette, Gui, UiNode};
+use rg3d::{
+    core::{algebra::Vector2, color::Color, pool::Handle},
+    gui::{
+        border::BorderBuilder, brush::Brush, canvas::CanvasBuilder, message::MessageDirection,
+        message::WidgetMessage, widget::WidgetBuilder,
+    },
+};
+
+fn unlit_color() -> Color {
+    Color::from_rgba(255, 255, 255, 80)
+}
+
+pub struct HoldProgress {
+    canvas: Handle<UiNode>,
+    segments: Vec<Handle<UiNode>>,
+    /// Amount of lit segments.
+    lit: usize,
+    /// Color of lit segments.
+    color: Color,
+    screen_size: Vector2<f32>,
+    scale: f32,
+}
+
+impl HoldProgress {
+    const SEGMENT_COUNT: usize = 32;
+    const SEGMENT_SIZE: f32 = 4.0;
+    const RADIUS: f32 = 40.0;
+
+    pub fn new(ui: &mut Gui, scale: f32) -> Self {
+        let ctx = &mut ui.build_ctx();
+
+        let segments = (0..Self::SEGMENT_COUNT)
+            .map(|_| {
+                BorderBuilder::new(
+                    WidgetBuilder::new()
+                        .with_background(Brush::Solid(unlit_color()))
+                        .with_foreground(Brush::Solid(Color::TRANSPARENT)),
+                )
+                .build(ctx)
+            })
+            .collect::<Vec<_>>();
+
+        let canvas = CanvasBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_children(&segments),
+        )
+        .build(ctx);
+
+        let progress = Self {
+            canvas,
+            segments,
+            lit: 0,
+            color: HudPalette::default().accent,
+            screen_size: ui.screen_size(),
+            scale,
+        };
+        progress.sync_segments(ui);
+        progress
+    }
+
+    pub fn set_scale(&mut self, ui: &Gui, scale: f32) {
+        self.scale = scale;
+        self.sync_segments(ui);
+    }
+
+    pub fn set_palette(&mut self, ui: &Gui, palette: &HudPalette) {
+        self.color = palette.accent;
+        self.sync_colors(ui);
+    }
+
+    /// Shows the ring filled according to given progress (in [0; 1] range), hides it if
+    /// there is no progress.
+    pub fn sync_to_model(&mut self, ui: &Gui, progress: Option<f32>) {
+        ui.send_message(WidgetMessage::visibility(
+            self.canvas,
+            MessageDirection::ToWidget,
+            progress.is_some(),
+        ));
+
+        if let Some(progress) = progress {
+            let lit = (progress * Self::SEGMENT_COUNT as f32).round() as usize;
+            if lit != self.lit {
+                self.lit = lit;
+                self.sync_colors(ui);
+            }
+
+            if self.screen_size != ui.screen_size() {
+                self.screen_size = ui.screen_size();
+                self.sync_segments(ui);
+            }
+        }
+    }
+
+    fn sync_colors(&self, ui: &Gui) {
+        for (i, &segment) in self.segments.iter().enumerate() {
+            let color = if i < self.lit {
+                self.color
+            } else {
+                unlit_color()
+            };
+            ui.send_message(WidgetMessage::background(
+                segment,
+                MessageDirection::ToWidget,
+                Brush::Solid(color),
+            ));
+        }
+    }
+
+    fn sync_segments(&self, ui: &Gui) {
+        let center = self.screen_size.scale(0.5);
+        let radius = Self::RADIUS * self.scale;
+        let size = Self::SEGMENT_SIZE * self.scale;
+
+        for (i, &segment) in self.segments.iter().enumerate() {
+            // Clockwise from the top.
+            let angle = 2.0 * std::f32::consts::PI * i as f32 / Self::SEGMENT_COUNT as f32;
+            let position = center + Vector2::new(angle.sin(), -angle.cos()).scale(radius);
+            ui.send_message(WidgetMessage::desired_position(
+                segment,
+                MessageDirection::ToWidget,
+                position - Vector2::new(size * 0.5, size * 0.5),
+            ));
+            ui.send_message(WidgetMessage::width(
+                segment,
+                MessageDirection::ToWidget,
+                size,
+            ));
+            ui.send_message(WidgetMessage::height(
+                segment,
+                MessageDirection::ToWidget,
+                size,
+            ));
+        }
+    }
+}
@@ -18,8 +18,8 @@ use rg3d::{
         grid::{Column, GridBuilder, Row},
         image::ImageBuilder,
         message::{
-            ButtonState, MessageDirection, OsEvent, ScrollViewerMessage, TextMessage, UiMessage,
-            UiMessageData, WidgetMessage,
+            MessageDirection, OsEvent, ScrollViewerMessage, TextMessage, UiMessage, UiMessageData,
+            WidgetMessage,
         },
         scroll_viewer::ScrollViewerBuilder,
         stack_panel::StackPanelBuilder,
@@ -410,87 +410,68 @@ impl InventoryInterface {
         }
     }
 
-    pub fn process_os_event(
+    pub fn process_os_event(&mut self, os_event: &OsEvent) {
+        self.ui.process_os_event(os_event);
+    }
+
+    /// Moves selection and uses or drops selected item by bound buttons.
+    pub fn process_button(
         &mut self,
-        os_event: &OsEvent,
+        button: ControlButton,
         control_scheme: &ControlScheme,
         player_handle: Handle<Actor>,
         player: &mut Player,
     ) {
-        self.ui.process_os_event(os_event);
-
         if self.is_enabled {
-            if let OsEvent::KeyboardInput { button, state } = *os_event {
-                if state == ButtonState::Pressed {
-                    // TODO: Add support for other input bindings.
-                    if let ControlButton::Key(key) = control_scheme.cursor_up.button {
-                        if rg3d::utils::translate_key(key) == button {
-                            self.try_move_selection(MoveDirection::Up);
-                        }
-                    }
-                    if let ControlButton::Key(key) = control_scheme.cursor_down.button {
-                        if rg3d::utils::translate_key(key) == button {
-                            self.try_move_selection(MoveDirection::Down);
-                        }
-                    }
-                    if let ControlButton::Key(key) = control_scheme.cursor_left.button {
-                        if rg3d::utils::translate_key(key) == button {
-                            self.try_move_selection(MoveDirection::Left);
-                        }
-                    }
-                    if let ControlButton::Key(key) = control_scheme.cursor_right.button {
-                        if rg3d::utils::translate_key(key) == button {
-                            self.try_move_selection(MoveDirection::Right);
-                        }
-                    }
-                    if let ControlButton::Key(key) = control_scheme.action.button {
-                        if rg3d::utils::translate_key(key) == button {
-                            let selection = self.selection();
-                            if selection.is_some() {
-                                if let UiNode::User(CustomUiNode::InventoryItem(item)) =
-                                    self.ui.node(selection)
-                                {
-                                    let definition = Item::get_definition(item.item);
-                                    if definition.consumable
-                                        && player
-                                            .inventory_mut()
-                                            .try_extract_exact_items(item.item, 1)
-                                            == 1
-                                    {
-                                        self.sender
-                                            .send(Message::UseItem {
-                                                actor: player_handle,
-                                                kind: item.item,
-                                            })
-                                            .unwrap();
-                                        self.sender.send(Message::SyncInventory).unwrap();
-                                    }
-                                } else {
-                                    unreachable!()
-                                }
-                            }
+            if button == control_scheme.cursor_up.button {
+                self.try_move_selection(MoveDirection::Up);
+            }
+            if button == control_scheme.cursor_down.button {
+                self.try_move_selection(MoveDirection::Down);
+            }
+            if button == control_scheme.cursor_left.button {
+                self.try_move_selection(MoveDirection::Left);
+            }
+            if button == control_scheme.cursor_right.button {
+                self.try_move_selection(MoveDirection::Right);
+            }
+            if button == control_scheme.action.button {
+                let selection = self.selection();
+                if selection.is_some() {
+                    if let UiNode::User(CustomUiNode::InventoryItem(item)) = self.ui.node(selection)
+                    {
+                        let definition = Item::get_definition(item.item);
+                        if definition.consumable
+                            && player.inventory_mut().try_extract_exact_items(item.item, 1) == 1
+                        {
+                            self.sender
+                                .send(Message::UseItem {
+                                    actor: player_handle,
+                                    kind: item.item,
+                                })
+                                .unwrap();
+                            self.sender.send(Message::SyncInventory).unwrap();
                         }
+                    } else {
+                        unreachable!()
                     }
-                    if let ControlButton::Key(key) = control_scheme.drop_item.button {
-                        if rg3d::utils::translate_key(key) == button {
-                            let selection = self.selection();
-                            if selection.is_some() {
-                                if let UiNode::User(CustomUiNode::InventoryItem(item)) =
-                                    self.ui.node(selection)
-                                {
-                                    self.sender
-                                        .send(Message::DropItems {
-                                            actor: player_handle,
-                                            item: item.item,
-                                            count: 1,
-                                        })
-                                        .unwrap();
-                                    self.sender.send(Message::SyncInventory).unwrap();
-                                } else {
-                                    unreachable!()
-                                }
-                            }
-                        }
+                }
+            }
+            if button == control_scheme.drop_item.button {
+                let selection = self.selection();
+                if selection.is_some() {
+                    if let UiNode::User(CustomUiNode::InventoryItem(item)) = self.ui.node(selection)
+                    {
+                        self.sender
+                            .send(Message::DropItems {
+                                actor: player_handle,
+                                item: item.item,
+                                count: 1,
+                            })
+                            .unwrap();
+                        self.sender.send(Message::SyncInventory).unwrap();
+                    } else {
+                        unreachable!()
                     }
                 }
             }
@@ -4,6 +4,9 @@
 
 use crate::{
     gui::inventory::{InventoryItem, InventoryItemMessage},
+    gui::palette::HudPalette,
+    leader_board::PersonalScore,
+    level::horde::HordeController,
     message::Message,
 };
 use rg3d::{
@@ -17,15 +20,15 @@ use rg3d::{
         draw::DrawingContext,
         grid::{Column, GridBuilder, Row},
         message::{
-            ButtonMessage, MessageData, MessageDirection, OsEvent, UiMessage, UiMessageData,
-            WidgetMessage,
+            ButtonMessage, MessageData, MessageDirection, OsEvent, TextMessage, UiMessage,
+            UiMessageData, WidgetMessage,
         },
         node::UINode,
         scroll_bar::ScrollBarBuilder,
         scroll_viewer::ScrollViewerBuilder,
         stack_panel::StackPanelBuilder,
         text::TextBuilder,
-        ttf::SharedFont,
+        ttf::{Font, SharedFont},
         widget::WidgetBuilder,
         Control, HorizontalAlignment, NodeHandleMapping, Orientation, Thickness, UserInterface,
         VerticalAlignment,
@@ -33,13 +36,38 @@ use rg3d::{
 };
 use std::{
     ops::{Deref, DerefMut},
-    sync::mpsc::Sender,
+    path::Path,
+    sync::{mpsc::Sender, Arc, Mutex},
 };
 
+pub mod compass;
+pub mod console;
+pub mod crosshair;
+pub mod damage_numbers;
+pub mod hold_progress;
 pub mod inventory;
 pub mod item_display;
+pub mod objective_marker;
+pub mod palette;
+pub mod perf_overlay;
 pub mod weapon_display;
 
+/// Size of HUD font when HUD scale is 1.0.
+const HUD_FONT_SIZE: f32 = 31.0;
+
+/// Creates font for HUD text, fonts are rasterized with fixed size so each HUD scale needs
+/// its own font.
+pub fn make_hud_font(scale: f32) -> SharedFont {
+    SharedFont(Arc::new(Mutex::new(
+        Font::from_file(
+            Path::new("data/ui/SquaresBold.ttf"),
+            HUD_FONT_SIZE * scale,
+            Font::default_char_set(),
+        )
+        .unwrap(),
+    )))
+}
+
 #[derive(Debug, Clone)]
 pub enum CustomUiNode {
     InventoryItem(InventoryItem),
@@ -187,7 +215,10 @@ pub fn create_scroll_viewer(ctx: &mut BuildContext) -> Handle<UiNode> {
 
 pub struct DeathScreen {
     root: Handle<UiNode>,
-    load_game: Handle<UiNode>,
+    title: Handle<UiNode>,
+    score: Handle<UiNode>,
+    restart: Handle<UiNode>,
+    respawn: Handle<UiNode>,
     exit_to_menu: Handle<UiNode>,
     exit_game: Handle<UiNode>,
     sender: Sender<Message>,
@@ -195,7 +226,10 @@ pub struct DeathScreen {
 
 impl DeathScreen {
     pub fn new(ui: &mut Gui, font: SharedFont, sender: Sender<Message>) -> Self {
-        let load_game;
+        let title;
+        let score;
+        let restart;
+        let respawn;
         let exit_to_menu;
         let exit_game;
         let root = BorderBuilder::new(
@@ -207,10 +241,10 @@ impl DeathScreen {
                 .with_child(
                     GridBuilder::new(
                         WidgetBuilder::new()
-                            .with_child(
-                                TextBuilder::new(
+                            .with_child({
+                                title = TextBuilder::new(
                                     WidgetBuilder::new()
-                                        .with_foreground(Brush::Solid(Color::opaque(255, 0, 0)))
+                                        .with_foreground(Brush::Solid(HudPalette::default().danger))
                                         .on_row(0)
                                         .on_column(1)
                                         .with_horizontal_alignment(HorizontalAlignment::Center)
@@ -218,8 +252,9 @@ impl DeathScreen {
                                 )
                                 .with_text("You Died")
                                 .with_font(font.clone())
-                                .build(&mut ui.build_ctx()),
-                            )
+                                .build(&mut ui.build_ctx());
+                                title
+                            })
                             .with_child(
                                 StackPanelBuilder::new(
                                     WidgetBuilder::new()
@@ -227,14 +262,36 @@ impl DeathScreen {
                                         .on_row(1)
                                         .on_column(1)
                                         .with_child({
-                                            load_game = ButtonBuilder::new(
+                                            score = TextBuilder::new(
+                                                WidgetBuilder::new()
+                                                    .with_margin(Thickness::uniform(2.0))
+                                                    .with_horizontal_alignment(
+                                                        HorizontalAlignment::Center,
+                                                    ),
+                                            )
+                                            .with_font(font.clone())
+                                            .build(&mut ui.build_ctx());
+                                            score
+                                        })
+                                        .with_child({
+                                            respawn = ButtonBuilder::new(
+                                                WidgetBuilder::new()
+                                                    .with_margin(Thickness::uniform(2.0)),
+                                            )
+                                            .with_text("Respawn")
+                                            .with_font(font.clone())
+                                            .build(&mut ui.build_ctx());
+                                            respawn
+                                        })
+                                        .with_child({
+                                            restart = ButtonBuilder::new(
                                                 WidgetBuilder::new()
                                                     .with_margin(Thickness::uniform(2.0)),
                                             )
-                                            .with_text("Load Game")
+                                            .with_text("Restart")
                                             .with_font(font.clone())
                                             .build(&mut ui.build_ctx());
-                                            load_game
+                                            restart
                                         })
                                         .with_child({
                                             exit_to_menu = ButtonBuilder::new(
@@ -272,7 +329,10 @@ impl DeathScreen {
 
         Self {
             root,
-            load_game,
+            title,
+            score,
+            restart,
+            respawn,
             exit_to_menu,
             exit_game,
             sender,
@@ -281,8 +341,10 @@ impl DeathScreen {
 
     pub fn handle_ui_message(&mut self, message: &GuiMessage) {
         if let UiMessageData::Button(ButtonMessage::Click) = message.data() {
-            if message.destination() == self.load_game {
-                self.sender.send(Message::LoadGame).unwrap();
+            if message.destination() == self.restart {
+                self.sender.send(Message::RestartLevel).unwrap();
+            } else if message.destination() == self.respawn {
+                self.sender.send(Message::RespawnPlayer).unwrap();
             } else if message.destination() == self.exit_to_menu {
                 self.sender.send(Message::ToggleMainMenu).unwrap();
             } else if message.destination() == self.exit_game {
@@ -291,6 +353,447 @@ impl DeathScreen {
         }
     }
 
+    pub fn set_palette(&self, ui: &Gui, palette: &HudPalette) {
+        ui.send_message(WidgetMessage::foreground(
+            self.title,
+            MessageDirection::ToWidget,
+            Brush::Solid(palette.danger),
+        ));
+    }
+
+    /// Fills score board with player's results of the match, `time` is a duration
+    /// of the match in seconds.
+    pub fn sync_to_model(&self, ui: &Gui, score: PersonalScore, time: f32) {
+        let time = time as u32;
+        ui.send_message(TextMessage::text(
+            self.score,
+            MessageDirection::ToWidget,
+            format!(
+                "Kills: {}\nDeaths: {}\nTime: {:02}:{:02}",
+                score.kills,
+                score.deaths,
+                time / 60,
+                time % 60
+            ),
+        ));
+    }
+
+    pub fn set_visible(&self, ui: &Gui, state: bool) {
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            state,
+        ));
+    }
+
+    pub fn is_visible(&self, ui: &Gui) -> bool {
+        ui.node(self.root).visibility()
+    }
+}
+
+/// Shows current wave and amount of remaining enemies in horde mode.
+pub struct HordeHud {
+    text: Handle<UiNode>,
+}
+
+impl HordeHud {
+    const MARGIN: f32 = 10.0;
+
+    pub fn new(ui: &mut Gui, font: SharedFont, scale: f32) -> Self {
+        let text = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_width(ui.screen_size().x)
+                .with_margin(Thickness::uniform(Self::MARGIN * scale))
+                .with_foreground(Brush::Solid(HudPalette::default().danger)),
+        )
+        .with_horizontal_text_alignment(HorizontalAlignment::Center)
+        .with_font(font)
+        .build(&mut ui.build_ctx());
+
+        Self { text }
+    }
+
+    pub fn set_palette(&self, ui: &Gui, palette: &HudPalette) {
+        ui.send_message(WidgetMessage::foreground(
+            self.text,
+            MessageDirection::ToWidget,
+            Brush::Solid(palette.danger),
+        ));
+    }
+
+    pub fn set_scale(&self, ui: &Gui, font: SharedFont, scale: f32) {
+        ui.send_message(TextMessage::font(
+            self.text,
+            MessageDirection::ToWidget,
+            font,
+        ));
+        ui.send_message(WidgetMessage::margin(
+            self.text,
+            MessageDirection::ToWidget,
+            Thickness::uniform(Self::MARGIN * scale),
+        ));
+    }
+
+    pub fn sync_to_model(&self, ui: &Gui, horde: Option<&HordeController>) {
+        ui.send_message(WidgetMessage::visibility(
+            self.text,
+            MessageDirection::ToWidget,
+            horde.is_some(),
+        ));
+
+        if let Some(horde) = horde {
+            let text = if horde.is_breather() {
+                format!(
+                    "Wave {} in {:.0}s",
+                    horde.wave_number(),
+                    horde.breather_time_left().ceil()
+                )
+            } else {
+                format!(
+                    "Wave {}\nEnemies: {}",
+                    horde.wave_number(),
+                    horde.remaining_enemies()
+                )
+            };
+            ui.send_message(TextMessage::text(
+                self.text,
+                MessageDirection::ToWidget,
+                text,
+            ));
+        }
+    }
+}
+
+/// Shows text captions of important sounds at the bottom of the screen.
+pub struct SubtitleDisplay {
+    text: Handle<UiNode>,
+    /// Pairs of caption and time left to show it.
+    captions: Vec<(String, f32)>,
+    enabled: bool,
+}
+
+impl SubtitleDisplay {
+    const CAPTION_LIFETIME: f32 = 3.0;
+    const MAX_CAPTIONS: usize = 4;
+    /// Distance from the bottom of the screen, so captions won't cover weapon display.
+    const BOTTOM_MARGIN: f32 = 60.0;
+
+    pub fn new(ui: &mut Gui, font: SharedFont, enabled: bool, scale: f32) -> Self {
+        let text = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_width(ui.screen_size().x)
+                .with_height(ui.screen_size().y)
+                .with_margin(Self::margin(scale)),
+        )
+        .with_horizontal_text_alignment(HorizontalAlignment::Center)
+        .with_vertical_text_alignment(VerticalAlignment::Bottom)
+        .with_font(font)
+        .build(&mut ui.build_ctx());
+
+        Self {
+            text,
+            captions: Default::default(),
+            enabled,
+        }
+    }
+
+    fn margin(scale: f32) -> Thickness {
+        Thickness {
+            left: 0.0,
+            top: 0.0,
+            right: 0.0,
+            bottom: Self::BOTTOM_MARGIN * scale,
+        }
+    }
+
+    pub fn set_enabled(&mut self, ui: &Gui, enabled: bool) {
+        self.enabled = enabled;
+        self.captions.clear();
+        self.sync_text(ui);
+    }
+
+    pub fn set_scale(&self, ui: &Gui, font: SharedFont, scale: f32) {
+        ui.send_message(TextMessage::font(
+            self.text,
+            MessageDirection::ToWidget,
+            font,
+        ));
+        ui.send_message(WidgetMessage::margin(
+            self.text,
+            MessageDirection::ToWidget,
+            Self::margin(scale),
+        ));
+    }
+
+    pub fn add_caption(&mut self, ui: &Gui, caption: &str) {
+        if !self.enabled {
+            return;
+        }
+
+        // Do not spam with the same caption, just prolong existing one.
+        if let Some((_, time_left)) = self.captions.iter_mut().find(|(c, _)| c == caption) {
+            *time_left = Self::CAPTION_LIFETIME;
+        } else {
+            self.captions
+                .push((caption.to_owned(), Self::CAPTION_LIFETIME));
+            if self.captions.len() > Self::MAX_CAPTIONS {
+                self.captions.remove(0);
+            }
+            self.sync_text(ui);
+        }
+    }
+
+    pub fn update(&mut self, ui: &Gui, dt: f32) {
+        let count = self.captions.len();
+        for (_, time_left) in self.captions.iter_mut() {
+            *time_left -= dt;
+        }
+        self.captions.retain(|(_, time_left)| *time_left > 0.0);
+        if self.captions.len() != count {
+            self.sync_text(ui);
+        }
+    }
+
+    fn sync_text(&self, ui: &Gui) {
+        let text = self
+            .captions
+            .iter()
+            .map(|(caption, _)| caption.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        ui.send_message(TextMessage::text(
+            self.text,
+            MessageDirection::ToWidget,
+            text,
+        ));
+    }
+}
+
+/// Marker in the center of the screen that tells the player that a shot has hit a target.
+pub struct HitMarker {
+    text: Handle<UiNode>,
+    timer: f32,
+    enabled: bool,
+    palette: HudPalette,
+}
+
+impl HitMarker {
+    const HIT_LIFETIME: f32 = 0.15;
+    const KILL_LIFETIME: f32 = 0.4;
+
+    pub fn new(ui: &mut Gui, font: SharedFont, enabled: bool) -> Self {
+        let text = TextBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_width(ui.screen_size().x)
+                .with_height(ui.screen_size().y),
+        )
+        .with_horizontal_text_alignment(HorizontalAlignment::Center)
+        .with_vertical_text_alignment(VerticalAlignment::Center)
+        .with_text("X")
+        .with_font(font)
+        .build(&mut ui.build_ctx());
+
+        Self {
+            text,
+            timer: 0.0,
+            enabled,
+            palette: Default::default(),
+        }
+    }
+
+    pub fn set_font(&self, ui: &Gui, font: SharedFont) {
+        ui.send_message(TextMessage::font(
+            self.text,
+            MessageDirection::ToWidget,
+            font,
+        ));
+    }
+
+    pub fn set_enabled(&mut self, ui: &Gui, enabled: bool) {
+        self.enabled = enabled;
+        self.timer = 0.0;
+        ui.send_message(WidgetMessage::visibility(
+            self.text,
+            MessageDirection::ToWidget,
+            false,
+        ));
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_palette(&mut self, palette: &HudPalette) {
+        self.palette = *palette;
+    }
+
+    /// Shows marker for a short period of time, kills are shown longer and with
+    /// different color.
+    pub fn show(&mut self, ui: &Gui, kill: bool) {
+        if !self.enabled {
+            return;
+        }
+
+        let (color, lifetime) = if kill {
+            (self.palette.danger, Self::KILL_LIFETIME)
+        } else {
+            (Color::WHITE, Self::HIT_LIFETIME)
+        };
+        // Do not let regular hit to shorten kill marker.
+        self.timer = self.timer.max(lifetime);
+        if kill || self.timer <= lifetime {
+            ui.send_message(WidgetMessage::foreground(
+                self.text,
+                MessageDirection::ToWidget,
+                Brush::Solid(color),
+            ));
+        }
+        ui.send_message(WidgetMessage::visibility(
+            self.text,
+            MessageDirection::ToWidget,
+            true,
+        ));
+    }
+
+    pub fn update(&mut self, ui: &Gui, dt: f32) {
+        if self.timer > 0.0 {
+            self.timer -= dt;
+            if self.timer <= 0.0 {
+                self.timer = 0.0;
+                ui.send_message(WidgetMessage::visibility(
+                    self.text,
+                    MessageDirection::ToWidget,
+                    false,
+                ));
+            }
+        }
+    }
+}
+
+pub struct PauseMenu {
+    root: Handle<UiNode>,
+    resume: Handle<UiNode>,
+    restart_level: Handle<UiNode>,
+    main_menu: Handle<UiNode>,
+    quit_to_main_menu: Handle<UiNode>,
+    sender: Sender<Message>,
+}
+
+impl PauseMenu {
+    pub fn new(ui: &mut Gui, font: SharedFont, sender: Sender<Message>) -> Self {
+        let resume;
+        let restart_level;
+        let main_menu;
+        let quit_to_main_menu;
+        let root = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_width(ui.screen_size().x)
+                .with_height(ui.screen_size().y)
+                .with_background(Brush::Solid(Color::opaque(10, 10, 20)))
+                .with_child(
+                    GridBuilder::new(
+                        WidgetBuilder::new()
+                            .with_child(
+                                TextBuilder::new(
+                                    WidgetBuilder::new()
+                                        .on_row(0)
+                                        .on_column(1)
+                                        .with_horizontal_alignment(HorizontalAlignment::Center)
+                                        .with_vertical_alignment(VerticalAlignment::Bottom),
+                                )
+                                .with_text("Paused")
+                                .with_font(font.clone())
+                                .build(&mut ui.build_ctx()),
+                            )
+                            .with_child(
+                                StackPanelBuilder::new(
+                                    WidgetBuilder::new()
+                                        .with_vertical_alignment(VerticalAlignment::Top)
+                                        .on_row(1)
+                                        .on_column(1)
+                                        .with_child({
+                                            resume = ButtonBuilder::new(
+                                                WidgetBuilder::new()
+                                                    .with_margin(Thickness::uniform(2.0)),
+                                            )
+                                            .with_text("Resume")
+                                            .with_font(font.clone())
+                                            .build(&mut ui.build_ctx());
+                                            resume
+                                        })
+                                        .with_child({
+                                            restart_level = ButtonBuilder::new(
+                                                WidgetBuilder::new()
+                                                    .with_margin(Thickness::uniform(2.0)),
+                                            )
+                                            .with_text("Restart Level")
+                                            .with_font(font.clone())
+                                            .build(&mut ui.build_ctx());
+                                            restart_level
+                                        })
+                                        .with_child({
+                                            main_menu = ButtonBuilder::new(
+                                                WidgetBuilder::new()
+                                                    .with_margin(Thickness::uniform(2.0)),
+                                            )
+                                            .with_text("Main Menu")
+                                            .with_font(font.clone())
+                                            .build(&mut ui.build_ctx());
+                                            main_menu
+                                        })
+                                        .with_child({
+                                            quit_to_main_menu = ButtonBuilder::new(
+                                                WidgetBuilder::new()
+                                                    .with_margin(Thickness::uniform(2.0)),
+                                            )
+                                            .with_text("Quit To Main Menu")
+                                            .with_font(font)
+                                            .build(&mut ui.build_ctx());
+                                            quit_to_main_menu
+                                        }),
+                                )
+                                .build(&mut ui.build_ctx()),
+                            ),
+                    )
+                    .add_row(Row::stretch())
+                    .add_row(Row::stretch())
+                    .add_column(Column::stretch())
+                    .add_column(Column::strict(300.0))
+                    .add_column(Column::stretch())
+                    .build(&mut ui.build_ctx()),
+                ),
+        )
+        .build(&mut ui.build_ctx());
+
+        Self {
+            root,
+            resume,
+            restart_level,
+            main_menu,
+            quit_to_main_menu,
+            sender,
+        }
+    }
+
+    pub fn handle_ui_message(&mut self, message: &GuiMessage) {
+        if let UiMessageData::Button(ButtonMessage::Click) = message.data() {
+            if message.destination() == self.resume {
+                self.sender
+                    .send(Message::SetPaused { paused: false })
+                    .unwrap();
+            } else if message.destination() == self.restart_level {
+                self.sender.send(Message::RestartLevel).unwrap();
+            } else if message.destination() == self.main_menu {
+                self.sender.send(Message::ToggleMainMenu).unwrap();
+            } else if message.destination() == self.quit_to_main_menu {
+                self.sender.send(Message::QuitToMainMenu).unwrap();
+            }
+        }
+    }
+
     pub fn set_visible(&self, ui: &Gui, state: bool) {
         ui.send_message(WidgetMessage::visibility(
             self.root,
@@ -0,0 +1,193 @@
+//! Screen space marker of current objective of a level. It shows description of the
+//! objective and distance to it, the marker sticks to the edges of the screen when the
+//! objective is out of view, so it always points towards the objective.
+
+use crate::{
+    gui::{palette::HudPalette, Gui, UiNode},
+    level::objective::Objective,
+};
+use rg3d::{
+    core::{
+        algebra::{Vector2, Vector3, Vector4},
+        color::Color,
+        pool::Handle,
+    },
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        canvas::CanvasBuilder,
+        message::{MessageDirection, TextMessage, WidgetMessage},
+        text::TextBuilder,
+        ttf::SharedFont,
+        widget::WidgetBuilder,
+        HorizontalAlignment,
+    },
+    scene::camera::Camera,
+};
+
+pub struct ObjectiveMarker {
+    canvas: Handle<UiNode>,
+    icon: Handle<UiNode>,
+    text: Handle<UiNode>,
+    scale: f32,
+}
+
+impl ObjectiveMarker {
+    const ICON_SIZE: f32 = 12.0;
+    const TEXT_WIDTH: f32 = 300.0;
+    const TEXT_HEIGHT: f32 = 70.0;
+    /// Minimal distance from the edges of the screen to the icon.
+    const EDGE_MARGIN: f32 = 20.0;
+
+    pub fn new(ui: &mut Gui, font: SharedFont, scale: f32) -> Self {
+        let ctx = &mut ui.build_ctx();
+
+        let icon = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_background(Brush::Solid(HudPalette::default().accent))
+                .with_foreground(Brush::Solid(Color::WHITE)),
+        )
+        .build(ctx);
+
+        let text = TextBuilder::new(
+            WidgetBuilder::new().with_foreground(Brush::Solid(HudPalette::default().accent)),
+        )
+        .with_horizontal_text_alignment(HorizontalAlignment::Center)
+        .with_font(font)
+        .build(ctx);
+
+        let canvas = CanvasBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_child(icon)
+                .with_child(text),
+        )
+        .build(ctx);
+
+        let marker = Self {
+            canvas,
+            icon,
+            text,
+            scale,
+        };
+        marker.sync_sizes(ui);
+        marker
+    }
+
+    pub fn set_palette(&self, ui: &Gui, palette: &HudPalette) {
+        ui.send_message(WidgetMessage::background(
+            self.icon,
+            MessageDirection::ToWidget,
+            Brush::Solid(palette.accent),
+        ));
+        ui.send_message(WidgetMessage::foreground(
+            self.text,
+            MessageDirection::ToWidget,
+            Brush::Solid(palette.accent),
+        ));
+    }
+
+    pub fn set_scale(&mut self, ui: &Gui, font: SharedFont, scale: f32) {
+        self.scale = scale;
+        ui.send_message(TextMessage::font(
+            self.text,
+            MessageDirection::ToWidget,
+            font,
+        ));
+        self.sync_sizes(ui);
+    }
+
+    fn sync_sizes(&self, ui: &Gui) {
+        let set_size = |widget: Handle<UiNode>, width: f32, height: f32| {
+            ui.send_message(WidgetMessage::width(
+                widget,
+                MessageDirection::ToWidget,
+                width * self.scale,
+            ));
+            ui.send_message(WidgetMessage::height(
+                widget,
+                MessageDirection::ToWidget,
+                height * self.scale,
+            ));
+        };
+
+        set_size(self.icon, Self::ICON_SIZE, Self::ICON_SIZE);
+        set_size(self.text, Self::TEXT_WIDTH, Self::TEXT_HEIGHT);
+    }
+
+    /// Shows marker if there is a camera of the player and an objective that isn't
+    /// completed yet.
+    pub fn sync_to_model(&self, ui: &Gui, camera: Option<&Camera>, objective: Option<&Objective>) {
+        let (camera, objective) = match (camera, objective) {
+            (Some(camera), Some(objective)) => (camera, objective),
+            _ => {
+                ui.send_message(WidgetMessage::visibility(
+                    self.canvas,
+                    MessageDirection::ToWidget,
+                    false,
+                ));
+                return;
+            }
+        };
+
+        ui.send_message(WidgetMessage::visibility(
+            self.canvas,
+            MessageDirection::ToWidget,
+            true,
+        ));
+
+        let screen_size = ui.screen_size();
+        let half_size = screen_size.scale(0.5);
+        let icon_size = Self::ICON_SIZE * self.scale;
+        let text_size = Vector2::new(Self::TEXT_WIDTH, Self::TEXT_HEIGHT).scale(self.scale);
+        let margin = Self::EDGE_MARGIN * self.scale;
+
+        let (ndc, behind) = project(camera, objective.position);
+        // Leave enough space for the text below the icon, so it won't go off-screen.
+        let extent = Vector2::new(
+            (half_size.x - margin - text_size.x * 0.5).max(std::f32::EPSILON),
+            (half_size.y - margin - text_size.y).max(std::f32::EPSILON),
+        );
+        let mut offset = Vector2::new(ndc.x * half_size.x, -ndc.y * half_size.y);
+        let k = (offset.x.abs() / extent.x).max(offset.y.abs() / extent.y);
+        if behind && k <= std::f32::EPSILON {
+            // Exactly behind the camera, point down.
+            offset = Vector2::new(0.0, extent.y);
+        } else if behind || k > 1.0 {
+            // Push the marker to the edge along direction to the objective.
+            offset = offset.scale(1.0 / k);
+        }
+        let center = half_size + offset;
+
+        ui.send_message(WidgetMessage::desired_position(
+            self.icon,
+            MessageDirection::ToWidget,
+            center - Vector2::new(icon_size * 0.5, icon_size * 0.5),
+        ));
+        ui.send_message(WidgetMessage::desired_position(
+            self.text,
+            MessageDirection::ToWidget,
+            center + Vector2::new(-text_size.x * 0.5, icon_size),
+        ));
+
+        let distance = objective
+            .position
+            .metric_distance(&camera.global_position());
+        ui.send_message(TextMessage::text(
+            self.text,
+            MessageDirection::ToWidget,
+            format!("{}\n{:.0} m", objective.description, distance),
+        ));
+    }
+}
+
+/// Projects a point in world space to normalized device coordinates, also returns whether
+/// the point is behind the camera.
+fn project(camera: &Camera, position: Vector3<f32>) -> (Vector2<f32>, bool) {
+    let clip =
+        camera.view_projection_matrix() * Vector4::new(position.x, position.y, position.z, 1.0);
+    // Dividing by negative w flips directions of points behind the camera, so divide by its
+    // absolute value to keep them on the correct side of the screen.
+    let ndc = Vector2::new(clip.x, clip.y).scale(1.0 / clip.w.abs().max(std::f32::EPSILON));
+    (ndc, clip.w <= 0.0)
+}
@@ -0,0 +1,88 @@
+//! HUD colors for different kinds of color blindness. Palettes are based on the Okabe-Ito
+//! set of colors which stays distinguishable for most kinds of color vision deficiency.
+
+use rg3d::core::color::Color;
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ColorBlindMode {
+    None,
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+}
+
+impl Default for ColorBlindMode {
+    fn default() -> Self {
+        Self::None
+    }
+}
+
+impl ColorBlindMode {
+    pub const ALL: [ColorBlindMode; 4] = [
+        ColorBlindMode::None,
+        ColorBlindMode::Protanopia,
+        ColorBlindMode::Deuteranopia,
+        ColorBlindMode::Tritanopia,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ColorBlindMode::None => "None",
+            ColorBlindMode::Protanopia => "Protanopia",
+            ColorBlindMode::Deuteranopia => "Deuteranopia",
+            ColorBlindMode::Tritanopia => "Tritanopia",
+        }
+    }
+
+    pub fn index(self) -> usize {
+        Self::ALL.iter().position(|m| *m == self).unwrap()
+    }
+
+    pub fn palette(self) -> HudPalette {
+        match self {
+            ColorBlindMode::None => HudPalette {
+                health_empty: Color::opaque(255, 0, 0),
+                health_full: Color::opaque(0, 255, 0),
+                accent: Color::opaque(0, 162, 232),
+                danger: Color::opaque(200, 0, 0),
+            },
+            ColorBlindMode::Protanopia => HudPalette {
+                health_empty: Color::opaque(240, 228, 66),
+                health_full: Color::opaque(0, 114, 178),
+                accent: Color::opaque(86, 180, 233),
+                danger: Color::opaque(240, 228, 66),
+            },
+            ColorBlindMode::Deuteranopia => HudPalette {
+                health_empty: Color::opaque(213, 94, 0),
+                health_full: Color::opaque(0, 114, 178),
+                accent: Color::opaque(86, 180, 233),
+                danger: Color::opaque(230, 159, 0),
+            },
+            ColorBlindMode::Tritanopia => HudPalette {
+                health_empty: Color::opaque(213, 94, 0),
+                health_full: Color::opaque(0, 158, 115),
+                accent: Color::opaque(204, 121, 167),
+                danger: Color::opaque(213, 94, 0),
+            },
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct HudPalette {
+    /// Color of health indicator when actor is almost dead.
+    pub health_empty: Color,
+    /// Color of health indicator when actor is at full health.
+    pub health_full: Color,
+    /// Color of informational text (ammo, grenades, etc.)
+    pub accent: Color,
+    /// Color of messages that warns player about threats (enemies, death, etc.)
+    pub danger: Color,
+}
+
+impl Default for HudPalette {
+    fn default() -> Self {
+        ColorBlindMode::None.palette()
+    }
+}
@@ -0,0 +1,214 @@
+//! Performance overlay in the top right corner of the screen. It shows FPS, frame time, rolling
+//! graph of last frame times and amount of active entities on the level. It is useful to find
+//! out what causes stutter.
+
+use crate::gui::{Gui, UiNode};
+use rg3d::{
+    core::{algebra::Vector2, color::Color, pool::Handle},
+    gui::{
+        border::BorderBuilder,
+        brush::Brush,
+        canvas::CanvasBuilder,
+        message::{MessageDirection, TextMessage, WidgetMessage},
+        stack_panel::StackPanelBuilder,
+        text::TextBuilder,
+        widget::WidgetBuilder,
+        Thickness,
+    },
+};
+use std::{collections::VecDeque, fmt::Write, time::Instant};
+
+/// Amount of active entities on a level.
+#[derive(Default, Copy, Clone)]
+pub struct PerformanceCounters {
+    /// Amount of live bots, including the ones waiting to be spawned.
+    pub bots: usize,
+    pub max_bots: usize,
+    pub projectiles: usize,
+    pub particle_systems: usize,
+}
+
+pub struct PerformanceOverlay {
+    root: Handle<UiNode>,
+    text: Handle<UiNode>,
+    bars: Vec<Handle<UiNode>>,
+    /// Frame times (in seconds) of last frames, newest are at the back.
+    frame_times: VecDeque<f32>,
+    last_frame: Instant,
+    /// Whether overlay can be shown at all, controlled by config flag.
+    enabled: bool,
+    visible: bool,
+    string: String,
+}
+
+impl PerformanceOverlay {
+    const WIDTH: f32 = 240.0;
+    const GRAPH_HEIGHT: f32 = 60.0;
+    const BAR_COUNT: usize = 120;
+    /// Frame time that corresponds to full height of the graph, 20 FPS.
+    const GRAPH_MAX_FRAME_TIME: f32 = 0.05;
+    const MARGIN: f32 = 10.0;
+
+    pub fn new(ui: &mut Gui, enabled: bool) -> Self {
+        let ctx = &mut ui.build_ctx();
+
+        let bar_width = Self::WIDTH / Self::BAR_COUNT as f32;
+        let bars = (0..Self::BAR_COUNT)
+            .map(|i| {
+                BorderBuilder::new(
+                    WidgetBuilder::new()
+                        .with_width(bar_width)
+                        .with_height(0.0)
+                        .with_desired_position(Vector2::new(
+                            i as f32 * bar_width,
+                            Self::GRAPH_HEIGHT,
+                        ))
+                        .with_foreground(Brush::Solid(Color::TRANSPARENT)),
+                )
+                .build(ctx)
+            })
+            .collect::<Vec<_>>();
+
+        let text;
+        let root = BorderBuilder::new(
+            WidgetBuilder::new()
+                .with_visibility(false)
+                .with_width(Self::WIDTH)
+                .with_background(Brush::Solid(Color::from_rgba(0, 0, 0, 160)))
+                .with_child(
+                    StackPanelBuilder::new(
+                        WidgetBuilder::new()
+                            .with_child({
+                                text = TextBuilder::new(
+                                    WidgetBuilder::new().with_margin(Thickness::uniform(2.0)),
+                                )
+                                .build(ctx);
+                                text
+                            })
+                            .with_child(
+                                CanvasBuilder::new(
+                                    WidgetBuilder::new()
+                                        .with_height(Self::GRAPH_HEIGHT)
+                                        .with_children(&bars),
+                                )
+                                .build(ctx),
+                            ),
+                    )
+                    .build(ctx),
+                ),
+        )
+        .build(ctx);
+
+        Self {
+            root,
+            text,
+            bars,
+            frame_times: VecDeque::with_capacity(Self::BAR_COUNT),
+            last_frame: Instant::now(),
+            enabled,
+            visible: false,
+            string: String::new(),
+        }
+    }
+
+    pub fn set_enabled(&mut self, ui: &Gui, enabled: bool) {
+        self.enabled = enabled;
+        self.set_visible(ui, enabled);
+    }
+
+    /// Shows or hides overlay, does nothing if it is disabled in config.
+    pub fn toggle(&mut self, ui: &Gui) {
+        if self.enabled {
+            self.set_visible(ui, !self.visible);
+        }
+    }
+
+    fn set_visible(&mut self, ui: &Gui, visible: bool) {
+        self.visible = visible;
+        ui.send_message(WidgetMessage::visibility(
+            self.root,
+            MessageDirection::ToWidget,
+            visible,
+        ));
+    }
+
+    /// Must be called once per rendered frame.
+    pub fn update(&mut self, ui: &Gui, counters: PerformanceCounters) {
+        let now = Instant::now();
+        let frame_time = now.duration_since(self.last_frame).as_secs_f32();
+        self.last_frame = now;
+
+        if self.frame_times.len() == Self::BAR_COUNT {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(frame_time);
+
+        if !self.visible {
+            return;
+        }
+
+        let average = self.frame_times.iter().sum::<f32>() / self.frame_times.len() as f32;
+
+        self.string.clear();
+        write!(
+            self.string,
+            "FPS: {:.0}\nFrame Time: {:.2} ms\nBots: {} / {}\nProjectiles: {}\nParticle Systems: {}",
+            1.0 / average.max(f32::EPSILON),
+            frame_time * 1000.0,
+            counters.bots,
+            counters.max_bots,
+            counters.projectiles,
+            counters.particle_systems,
+        )
+        .unwrap();
+
+        ui.send_message(TextMessage::text(
+            self.text,
+            MessageDirection::ToWidget,
+            self.string.clone(),
+        ));
+
+        ui.send_message(WidgetMessage::desired_position(
+            self.root,
+            MessageDirection::ToWidget,
+            Vector2::new(
+                ui.screen_size().x - Self::WIDTH - Self::MARGIN,
+                Self::MARGIN,
+            ),
+        ));
+
+        // Bars are aligned to the right, so the newest frame is always at the right edge.
+        let offset = Self::BAR_COUNT - self.frame_times.len();
+        let bar_width = Self::WIDTH / Self::BAR_COUNT as f32;
+        for (i, (&bar, &frame_time)) in self.bars[offset..]
+            .iter()
+            .zip(self.frame_times.iter())
+            .enumerate()
+        {
+            let height = (frame_time / Self::GRAPH_MAX_FRAME_TIME).min(1.0) * Self::GRAPH_HEIGHT;
+            let color = if frame_time <= 1.0 / 55.0 {
+                Color::opaque(0, 200, 0)
+            } else if frame_time <= 1.0 / 30.0 {
+                Color::opaque(230, 200, 0)
+            } else {
+                Color::opaque(220, 0, 0)
+            };
+
+            ui.send_message(WidgetMessage::height(
+                bar,
+                MessageDirection::ToWidget,
+                height,
+            ));
+            ui.send_message(WidgetMessage::desired_position(
+                bar,
+                MessageDirection::ToWidget,
+                Vector2::new((offset + i) as f32 * bar_width, Self::GRAPH_HEIGHT - height),
+            ));
+            ui.send_message(WidgetMessage::background(
+                bar,
+                MessageDirection::ToWidget,
+                Brush::Solid(color),
+            ));
+        }
+    }
+}
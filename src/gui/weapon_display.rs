@@ -1,17 +1,17 @@
 use crate::{
-    gui::{Gui, UiNode},
+    gui::{palette::HudPalette, Gui, UiNode},
     item::ItemKind,
     player::Player,
     weapon::WeaponContainer,
 };
 use rg3d::{
-    core::{algebra::Vector2, color::Color, pool::Handle},
+    core::{algebra::Vector2, pool::Handle},
     engine::resource_manager::ResourceManager,
     gui::{
         brush::Brush,
         grid::{Column, GridBuilder, Row},
         image::ImageBuilder,
-        message::{MessageDirection, TextMessage},
+        message::{MessageDirection, TextMessage, WidgetMessage},
         text::TextBuilder,
         ttf::SharedFont,
         widget::WidgetBuilder,
@@ -27,6 +27,10 @@ pub struct WeaponDisplay {
     pub render_target: Texture,
     ammo: Handle<UiNode>,
     grenades: Handle<UiNode>,
+    battery_label: Handle<UiNode>,
+    battery: Handle<UiNode>,
+    charge_label: Handle<UiNode>,
+    charge: Handle<UiNode>,
 }
 
 impl WeaponDisplay {
@@ -40,6 +44,10 @@ impl WeaponDisplay {
 
         let ammo;
         let grenades;
+        let battery_label;
+        let battery;
+        let charge_label;
+        let charge;
         GridBuilder::new(
             WidgetBuilder::new()
                 .with_width(Self::WIDTH)
@@ -61,7 +69,7 @@ impl WeaponDisplay {
                     ammo = TextBuilder::new(
                         WidgetBuilder::new()
                             .with_vertical_alignment(VerticalAlignment::Center)
-                            .with_foreground(Brush::Solid(Color::opaque(0, 162, 232)))
+                            .with_foreground(Brush::Solid(HudPalette::default().accent))
                             .on_row(0)
                             .on_column(1),
                     )
@@ -86,19 +94,71 @@ impl WeaponDisplay {
                     grenades = TextBuilder::new(
                         WidgetBuilder::new()
                             .with_vertical_alignment(VerticalAlignment::Center)
-                            .with_foreground(Brush::Solid(Color::opaque(0, 162, 232)))
+                            .with_foreground(Brush::Solid(HudPalette::default().accent))
                             .on_row(1)
                             .on_column(1),
                     )
-                    .with_font(font)
+                    .with_font(font.clone())
                     .build(&mut ui.build_ctx());
                     grenades
+                })
+                .with_child({
+                    battery_label = TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_vertical_alignment(VerticalAlignment::Center)
+                            .with_foreground(Brush::Solid(HudPalette::default().accent))
+                            .on_row(2)
+                            .on_column(0),
+                    )
+                    .with_text("BAT")
+                    .with_font(font.clone())
+                    .build(&mut ui.build_ctx());
+                    battery_label
+                })
+                .with_child({
+                    battery = TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_vertical_alignment(VerticalAlignment::Center)
+                            .with_foreground(Brush::Solid(HudPalette::default().accent))
+                            .on_row(2)
+                            .on_column(1),
+                    )
+                    .with_font(font.clone())
+                    .build(&mut ui.build_ctx());
+                    battery
+                })
+                .with_child({
+                    charge_label = TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_vertical_alignment(VerticalAlignment::Center)
+                            .with_foreground(Brush::Solid(HudPalette::default().accent))
+                            .on_row(3)
+                            .on_column(0),
+                    )
+                    .with_text("CHG")
+                    .with_font(font.clone())
+                    .build(&mut ui.build_ctx());
+                    charge_label
+                })
+                .with_child({
+                    charge = TextBuilder::new(
+                        WidgetBuilder::new()
+                            .with_vertical_alignment(VerticalAlignment::Center)
+                            .with_foreground(Brush::Solid(HudPalette::default().accent))
+                            .on_row(3)
+                            .on_column(1),
+                    )
+                    .with_font(font)
+                    .build(&mut ui.build_ctx());
+                    charge
                 }),
         )
         .add_column(Column::auto())
         .add_column(Column::stretch())
         .add_row(Row::auto())
         .add_row(Row::auto())
+        .add_row(Row::auto())
+        .add_row(Row::auto())
         .add_row(Row::stretch())
         .build(&mut ui.build_ctx());
 
@@ -107,6 +167,27 @@ impl WeaponDisplay {
             render_target,
             ammo,
             grenades,
+            battery_label,
+            battery,
+            charge_label,
+            charge,
+        }
+    }
+
+    pub fn set_palette(&self, palette: &HudPalette) {
+        for &text in &[
+            self.ammo,
+            self.grenades,
+            self.battery_label,
+            self.battery,
+            self.charge_label,
+            self.charge,
+        ] {
+            self.ui.send_message(WidgetMessage::foreground(
+                text,
+                MessageDirection::ToWidget,
+                Brush::Solid(palette.accent),
+            ));
         }
     }
 
@@ -132,6 +213,35 @@ impl WeaponDisplay {
             MessageDirection::ToWidget,
             format!("{}", grenades),
         ));
+
+        let battery = if player.current_weapon().is_some()
+            && weapons[player.current_weapon()].has_flash_light()
+        {
+            format!(
+                "{}%",
+                (weapons[player.current_weapon()].flash_light_battery() * 100.0) as u32
+            )
+        } else {
+            "-".to_owned()
+        };
+        self.ui.send_message(TextMessage::text(
+            self.battery,
+            MessageDirection::ToWidget,
+            battery,
+        ));
+
+        let charge = if player.current_weapon().is_some()
+            && weapons[player.current_weapon()].definition.charge.is_some()
+        {
+            format!("{}%", (player.weapon_charge() * 100.0) as u32)
+        } else {
+            "-".to_owned()
+        };
+        self.ui.send_message(TextMessage::text(
+            self.charge,
+            MessageDirection::ToWidget,
+            charge,
+        ));
     }
 
     pub fn update(&mut self, delta: f32) {
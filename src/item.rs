@@ -1,4 +1,4 @@
-use crate::{message::Message, weapon::WeaponKind};
+use crate::{message::Message, status_effect::StatusEffectDefinition, weapon::WeaponKind};
 use rg3d::{
     core::{
         algebra::Vector3,
@@ -21,6 +21,7 @@ use std::{collections::HashMap, fs::File, sync::mpsc::Sender};
 pub enum ItemKind {
     Medkit,
     Medpack,
+    HealthInjector,
 
     // Ammo
     Ammo,
@@ -31,6 +32,7 @@ pub enum ItemKind {
     Ak47,
     M4,
     Glock,
+    Shotgun,
 
     // Keys
     MasterKey,
@@ -65,6 +67,8 @@ impl ItemKind {
             6 => Ok(ItemKind::M4),
             7 => Ok(ItemKind::Glock),
             8 => Ok(ItemKind::MasterKey),
+            9 => Ok(ItemKind::Shotgun),
+            10 => Ok(ItemKind::HealthInjector),
             _ => Err(format!("Unknown item kind {}", id)),
         }
     }
@@ -80,6 +84,8 @@ impl ItemKind {
             ItemKind::M4 => 6,
             ItemKind::Glock => 7,
             ItemKind::MasterKey => 8,
+            ItemKind::Shotgun => 9,
+            ItemKind::HealthInjector => 10,
         }
     }
 
@@ -89,8 +95,10 @@ impl ItemKind {
             ItemKind::Ak47 => Some(WeaponKind::Ak47),
             ItemKind::M4 => Some(WeaponKind::M4),
             ItemKind::Glock => Some(WeaponKind::Glock),
+            ItemKind::Shotgun => Some(WeaponKind::Shotgun),
             ItemKind::Medkit
             | ItemKind::Medpack
+            | ItemKind::HealthInjector
             | ItemKind::Ammo
             | ItemKind::Grenade
             | ItemKind::MasterKey => None,
@@ -132,6 +140,10 @@ pub struct ItemDefinition {
     pub name: String,
     pub consumable: bool,
     pub preview: String,
+    /// Status effect that is applied to a character that uses the item, for example healing
+    /// over time.
+    #[serde(default)]
+    pub status_effect: Option<StatusEffectDefinition>,
 }
 
 #[derive(Deserialize, Default)]
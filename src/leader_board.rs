@@ -0,0 +1,95 @@
+//! Leader board counts kills and deaths of actors. It knows nothing about bots or player,
+//! it just reacts on `ActorDied` messages.
+
+use crate::actor::Actor;
+use rg3d::core::{
+    pool::Handle,
+    visitor::{Visit, VisitResult, Visitor},
+};
+
+#[derive(Default, Copy, Clone, Debug)]
+pub struct PersonalScore {
+    pub kills: u32,
+    pub deaths: u32,
+}
+
+impl Visit for PersonalScore {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.kills.visit("Kills", visitor)?;
+        self.deaths.visit("Deaths", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct ScoreEntry {
+    actor: Handle<Actor>,
+    score: PersonalScore,
+}
+
+impl Visit for ScoreEntry {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.actor.visit("Actor", visitor)?;
+        self.score.visit("Score", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct LeaderBoard {
+    entries: Vec<ScoreEntry>,
+}
+
+impl LeaderBoard {
+    fn entry_mut(&mut self, actor: Handle<Actor>) -> &mut PersonalScore {
+        if let Some(index) = self.entries.iter().position(|e| e.actor == actor) {
+            &mut self.entries[index].score
+        } else {
+            self.entries.push(ScoreEntry {
+                actor,
+                score: Default::default(),
+            });
+            &mut self.entries.last_mut().unwrap().score
+        }
+    }
+
+    pub fn on_actor_died(&mut self, actor: Handle<Actor>, who: Handle<Actor>) {
+        self.entry_mut(actor).deaths += 1;
+
+        // Suicides and deaths from environment are not counted as kills.
+        if who.is_some() && who != actor {
+            self.entry_mut(who).kills += 1;
+        }
+    }
+
+    /// Moves score of an actor to another one, kills are reduced by given penalty.
+    pub fn transfer(&mut self, from: Handle<Actor>, to: Handle<Actor>, kill_penalty: u32) {
+        let mut score = self.score_of(from);
+        score.kills = score.kills.saturating_sub(kill_penalty);
+        self.entries.retain(|e| e.actor != from);
+        *self.entry_mut(to) = score;
+    }
+
+    pub fn score_of(&self, actor: Handle<Actor>) -> PersonalScore {
+        self.entries
+            .iter()
+            .find(|e| e.actor == actor)
+            .map_or_else(Default::default, |e| e.score)
+    }
+}
+
+impl Visit for LeaderBoard {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.entries.visit("Entries", visitor)?;
+
+        visitor.leave_region()
+    }
+}
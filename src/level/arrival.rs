@@ -44,6 +44,7 @@ impl Visit for ArrivalLevel {
 
 impl ArrivalLevel {
     pub async fn new(
+        seed: u64,
         resource_manager: ResourceManager,
         sender: Sender<Message>,
         display_texture: Texture,
@@ -52,6 +53,7 @@ impl ArrivalLevel {
     ) -> (Level, Scene) {
         let (base_level, scene) = BaseLevel::new(
             "data/levels/arrival.rgs",
+            seed,
             resource_manager,
             sender,
             display_texture,
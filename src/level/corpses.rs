@@ -0,0 +1,57 @@
+//! Corpses of bots stay on a level for a while after dying animation has ended, the
+//! oldest corpses are removed if there are too many of them. Parameters are taken from
+//! `data/configs/corpses.ron`.
+
+use crate::{
+    actor::{Actor, ActorContainer},
+    message::Message,
+};
+use rg3d::lazy_static::lazy_static;
+use serde::Deserialize;
+use std::{fs::File, sync::mpsc::Sender};
+
+#[derive(Deserialize)]
+pub struct CorpsesDefinition {
+    /// Time (in seconds) during which a corpse stays on a level, `None` means that corpses
+    /// are removed only when there are more than `max_corpses` of them.
+    pub linger_time: Option<f32>,
+    pub max_corpses: u32,
+}
+
+impl CorpsesDefinition {
+    pub fn new() -> Self {
+        let file = File::open("data/configs/corpses.ron").unwrap();
+        ron::de::from_reader(file).unwrap()
+    }
+}
+
+lazy_static! {
+    static ref DEFINITION: CorpsesDefinition = CorpsesDefinition::new();
+}
+
+/// Requests removal of corpses that lived long enough as well as the oldest corpses
+/// that exceed the cap.
+pub fn remove_stale_corpses(actors: &ActorContainer, sender: &Sender<Message>) {
+    let definition = &*DEFINITION;
+
+    let mut corpses = actors
+        .pair_iter()
+        .filter_map(|(handle, actor)| match actor {
+            Actor::Bot(bot) => bot.corpse_time().map(|time| (handle, time)),
+            Actor::Player(_) => None,
+        })
+        .collect::<Vec<_>>();
+
+    // Oldest first.
+    corpses.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap());
+
+    let excess = corpses
+        .len()
+        .saturating_sub(definition.max_corpses as usize);
+
+    for (i, &(actor, time)) in corpses.iter().enumerate() {
+        if i < excess || definition.linger_time.map_or(false, |t| time >= t) {
+            sender.send(Message::RemoveActor { actor }).unwrap();
+        }
+    }
+}
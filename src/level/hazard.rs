@@ -0,0 +1,269 @@
+//! Damage volumes are designer-placed zones (lava, toxic gas, exposed wires) that periodically
+//! damage every actor inside them. Volumes are meshes in a level with a tag in
+//! `DamageVolume:Kind:damage_per_second` format, damage is optional and taken from
+//! `data/configs/hazards.ron` if omitted. The mesh itself is hidden, only its bounds are used.
+//! Lava and toxic volumes apply their damage as status effects (burning and poison), so it
+//! lingers for a while after an actor leaves the volume.
+
+use crate::{
+    actor::Actor,
+    actor::ActorContainer,
+    message::Message,
+    status_effect::{StatusEffect, StatusEffectKind},
+    weapon::projectile::DamageType,
+};
+use rg3d::{
+    core::{
+        algebra::Vector3,
+        math::aabb::AxisAlignedBoundingBox,
+        pool::Handle,
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    lazy_static::lazy_static,
+    scene::Scene,
+    utils::log::{Log, MessageKind},
+};
+use serde::Deserialize;
+use std::{collections::HashMap, fs::File, path::PathBuf, sync::mpsc::Sender};
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Deserialize)]
+pub enum HazardKind {
+    Lava,
+    Toxic,
+    Electric,
+}
+
+impl Default for HazardKind {
+    fn default() -> Self {
+        Self::Lava
+    }
+}
+
+impl HazardKind {
+    fn id(self) -> u32 {
+        match self {
+            HazardKind::Lava => 0,
+            HazardKind::Toxic => 1,
+            HazardKind::Electric => 2,
+        }
+    }
+
+    fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(HazardKind::Lava),
+            1 => Ok(HazardKind::Toxic),
+            2 => Ok(HazardKind::Electric),
+            _ => Err(format!("Invalid hazard kind {}", id)),
+        }
+    }
+
+    pub fn from_description(description: &str) -> Result<Self, String> {
+        match description {
+            "Lava" => Ok(HazardKind::Lava),
+            "Toxic" => Ok(HazardKind::Toxic),
+            "Electric" => Ok(HazardKind::Electric),
+            _ => Err(format!("Unknown hazard kind {}", description)),
+        }
+    }
+
+    /// Status effect that deals damage of the hazard, `None` means that damage is dealt
+    /// directly.
+    fn status_effect(self) -> Option<StatusEffectKind> {
+        match self {
+            HazardKind::Lava => Some(StatusEffectKind::Burning),
+            HazardKind::Toxic => Some(StatusEffectKind::Poison),
+            HazardKind::Electric => None,
+        }
+    }
+}
+
+impl Visit for HazardKind {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut id = self.id();
+        id.visit(name, visitor)?;
+        if visitor.is_reading() {
+            *self = Self::from_id(id)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+pub struct HazardDefinition {
+    pub damage_per_second: f32,
+    /// Type of damage that is dealt directly, damage of status effects is always
+    /// environmental.
+    pub damage_type: DamageType,
+    /// Sound that is played when an actor enters the volume (coughing in toxic gas, etc.)
+    #[serde(default)]
+    pub enter_sound: Option<String>,
+    #[serde(default)]
+    pub enter_caption: Option<String>,
+    /// Time (in seconds) during which an actor keeps taking damage after leaving the volume.
+    #[serde(default)]
+    pub linger_time: f32,
+    /// Movement speed multiplier for actors inside the volume.
+    #[serde(default)]
+    pub slow: Option<f32>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct HazardDefinitionContainer {
+    map: HashMap<HazardKind, HazardDefinition>,
+}
+
+impl HazardDefinitionContainer {
+    pub fn new() -> Self {
+        let file = File::open("data/configs/hazards.ron").unwrap();
+        ron::de::from_reader(file).unwrap()
+    }
+}
+
+lazy_static! {
+    static ref DEFINITIONS: HazardDefinitionContainer = HazardDefinitionContainer::new();
+}
+
+#[derive(Default)]
+pub struct DamageVolume {
+    pub bounds: AxisAlignedBoundingBox,
+    kind: HazardKind,
+    damage_per_second: f32,
+    tick_timer: f32,
+    /// Actors that were inside the volume on previous tick, used to detect entering.
+    occupants: Vec<Handle<Actor>>,
+}
+
+impl DamageVolume {
+    /// Damage is applied in discrete ticks, so actors won't play pain sounds each frame.
+    const TICK_INTERVAL: f32 = 0.5;
+
+    pub fn get_definition(kind: HazardKind) -> &'static HazardDefinition {
+        DEFINITIONS.map.get(&kind).unwrap()
+    }
+
+    pub fn from_tag(bounds: AxisAlignedBoundingBox, tag: &str) -> Self {
+        let mut params = tag.split(':').skip(1);
+
+        let kind = match params.next().map(HazardKind::from_description) {
+            Some(Ok(kind)) => kind,
+            Some(Err(e)) => {
+                Log::writeln(
+                    MessageKind::Warning,
+                    format!("Invalid damage volume tag {}: {}", tag, e),
+                );
+                Default::default()
+            }
+            None => Default::default(),
+        };
+
+        let damage_per_second = params
+            .next()
+            .and_then(|p| p.parse::<f32>().ok())
+            .unwrap_or_else(|| Self::get_definition(kind).damage_per_second);
+
+        Self {
+            bounds,
+            kind,
+            damage_per_second,
+            ..Default::default()
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        scene: &Scene,
+        actors: &ActorContainer,
+        sender: &Sender<Message>,
+        dt: f32,
+    ) {
+        self.tick_timer -= dt;
+        if self.tick_timer > 0.0 {
+            return;
+        }
+        self.tick_timer = Self::TICK_INTERVAL;
+
+        let definition = Self::get_definition(self.kind);
+
+        let mut occupants = Vec::new();
+        for (handle, actor) in actors.pair_iter() {
+            let position = actor.position(&scene.graph);
+            if actor.is_dead() || !self.bounds.is_contains_point(position) {
+                continue;
+            }
+
+            if !self.occupants.contains(&handle) {
+                if let Some(sound) = definition.enter_sound.as_ref() {
+                    sender
+                        .send(Message::PlaySound {
+                            path: PathBuf::from(sound),
+                            position: position + Vector3::new(0.0, 1.5, 0.0),
+                            gain: 1.0,
+                            rolloff_factor: 1.0,
+                            radius: 2.0,
+                            caption: definition.enter_caption.clone(),
+                        })
+                        .unwrap();
+                }
+            }
+
+            match self.kind.status_effect() {
+                Some(kind) => {
+                    sender
+                        .send(Message::ApplyStatusEffect {
+                            actor: handle,
+                            effect: StatusEffect::new(
+                                kind,
+                                self.damage_per_second,
+                                definition.linger_time + Self::TICK_INTERVAL,
+                            ),
+                        })
+                        .unwrap();
+                }
+                None => {
+                    sender
+                        .send(Message::DamageActor {
+                            actor: handle,
+                            who: Default::default(),
+                            amount: self.damage_per_second * Self::TICK_INTERVAL,
+                            damage_type: definition.damage_type,
+                            critical: false,
+                            hit_box: None,
+                        })
+                        .unwrap();
+                }
+            }
+
+            if let Some(slow) = definition.slow {
+                // Lasts a bit longer than a tick, so there are no gaps while actor is inside.
+                sender
+                    .send(Message::ApplyStatusEffect {
+                        actor: handle,
+                        effect: StatusEffect::new(
+                            StatusEffectKind::Slow,
+                            slow,
+                            Self::TICK_INTERVAL * 1.5,
+                        ),
+                    })
+                    .unwrap();
+            }
+
+            occupants.push(handle);
+        }
+
+        self.occupants = occupants;
+    }
+}
+
+impl Visit for DamageVolume {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.bounds.visit("Bounds", visitor)?;
+        self.kind.visit("Kind", visitor)?;
+        self.damage_per_second.visit("DamagePerSecond", visitor)?;
+        self.tick_timer.visit("TickTimer", visitor)?;
+        self.occupants.visit("Occupants", visitor)?;
+
+        visitor.leave_region()
+    }
+}
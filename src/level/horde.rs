@@ -0,0 +1,184 @@
+//! Horde mode - endless waves of bots with short breathers in between. Wave definitions
+//! are taken from `data/configs/horde.ron`, bots are spawned using `SpawnBotAtRandomPoint`
+//! message.
+
+use crate::{actor::Actor, bot::BotKind, message::Message, utils::rng::GameRng};
+use rg3d::{
+    core::{
+        pool::Handle,
+        rand::seq::SliceRandom,
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    lazy_static::lazy_static,
+};
+use serde::Deserialize;
+use std::{fs::File, sync::mpsc::Sender};
+
+#[derive(Deserialize)]
+pub struct WaveGroupDefinition {
+    pub kind: BotKind,
+    pub count: u32,
+}
+
+#[derive(Deserialize)]
+pub struct WaveDefinition {
+    pub groups: Vec<WaveGroupDefinition>,
+    /// Time between spawns of two bots of the wave.
+    pub spawn_interval: f32,
+    /// Time between the end of this wave and the start of next one.
+    pub breather: f32,
+}
+
+#[derive(Deserialize)]
+pub struct HordeDefinition {
+    pub initial_delay: f32,
+    pub waves: Vec<WaveDefinition>,
+    pub endless_count_multiplier: f32,
+    /// Amount of kills taken from the player when they respawn after death.
+    #[serde(default)]
+    pub respawn_penalty: u32,
+}
+
+impl HordeDefinition {
+    pub fn new() -> Self {
+        let file = File::open("data/configs/horde.ron").unwrap();
+        ron::de::from_reader(file).unwrap()
+    }
+
+    fn wave(&self, index: u32) -> &WaveDefinition {
+        let last = self.waves.len() - 1;
+        &self.waves[(index as usize).min(last)]
+    }
+
+    fn bot_count(&self, index: u32, group: &WaveGroupDefinition) -> u32 {
+        let extra_waves = (index as usize).saturating_sub(self.waves.len() - 1);
+        (group.count as f32 * self.endless_count_multiplier.powi(extra_waves as i32)).round() as u32
+    }
+}
+
+lazy_static! {
+    static ref DEFINITION: HordeDefinition = HordeDefinition::new();
+}
+
+pub struct HordeController {
+    /// Index of current wave.
+    wave: u32,
+    /// Time left before next wave starts, wave is in progress if it is zero.
+    breather_timer: f32,
+    spawn_timer: f32,
+    spawn_queue: Vec<BotKind>,
+    /// Amount of spawn requests that wasn't handled yet.
+    pending: u32,
+    alive: Vec<Handle<Actor>>,
+}
+
+impl Default for HordeController {
+    fn default() -> Self {
+        Self {
+            wave: 0,
+            breather_timer: DEFINITION.initial_delay,
+            spawn_timer: 0.0,
+            spawn_queue: Default::default(),
+            pending: 0,
+            alive: Default::default(),
+        }
+    }
+}
+
+impl HordeController {
+    pub fn get_definition() -> &'static HordeDefinition {
+        &DEFINITION
+    }
+
+    /// Returns number of current wave, starting from one.
+    pub fn wave_number(&self) -> u32 {
+        self.wave + 1
+    }
+
+    pub fn is_breather(&self) -> bool {
+        self.breather_timer > 0.0
+    }
+
+    pub fn breather_time_left(&self) -> f32 {
+        self.breather_timer
+    }
+
+    pub fn remaining_enemies(&self) -> usize {
+        self.alive.len() + self.spawn_queue.len() + self.pending as usize
+    }
+
+    fn start_wave(&mut self, rng: &mut GameRng) {
+        let definition = Self::get_definition();
+
+        for group in definition.wave(self.wave).groups.iter() {
+            for _ in 0..definition.bot_count(self.wave, group) {
+                self.spawn_queue.push(group.kind);
+            }
+        }
+        self.spawn_queue.shuffle(rng);
+        self.spawn_timer = 0.0;
+    }
+
+    pub fn update(&mut self, dt: f32, sender: &Sender<Message>, rng: &mut GameRng) {
+        if self.is_breather() {
+            self.breather_timer -= dt;
+            if self.breather_timer <= 0.0 {
+                self.breather_timer = 0.0;
+                self.start_wave(rng);
+            }
+            return;
+        }
+
+        self.spawn_timer -= dt;
+        if self.spawn_timer <= 0.0 {
+            if let Some(kind) = self.spawn_queue.pop() {
+                sender
+                    .send(Message::SpawnBotAtRandomPoint { kind })
+                    .unwrap();
+                self.pending += 1;
+                self.spawn_timer = Self::get_definition().wave(self.wave).spawn_interval;
+            }
+        }
+
+        if self.remaining_enemies() == 0 {
+            self.breather_timer = Self::get_definition().wave(self.wave).breather.max(0.01);
+            self.wave += 1;
+        }
+    }
+
+    pub fn on_bot_spawned(&mut self, bot: Handle<Actor>) {
+        self.pending = self.pending.saturating_sub(1);
+        self.alive.push(bot);
+    }
+
+    pub fn on_spawn_failed(&mut self) {
+        self.pending = self.pending.saturating_sub(1);
+    }
+
+    /// Puts a bot back to the queue when its spawn was rejected because of the limit of live
+    /// bots, so the bot will be spawned later when some of the others die.
+    pub fn on_spawn_postponed(&mut self, kind: BotKind) {
+        self.pending = self.pending.saturating_sub(1);
+        self.spawn_queue.push(kind);
+    }
+
+    pub fn on_actor_died(&mut self, actor: Handle<Actor>) {
+        self.alive.retain(|&bot| bot != actor);
+    }
+}
+
+impl Visit for HordeController {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.wave.visit("Wave", visitor)?;
+        self.breather_timer.visit("BreatherTimer", visitor)?;
+        self.spawn_timer.visit("SpawnTimer", visitor)?;
+        self.spawn_queue.visit("SpawnQueue", visitor)?;
+        self.alive.visit("Alive", visitor)?;
+        // Bots that are waiting in the level's spawn queue are saved with the level.
+        self.pending.visit("Pending", visitor)?;
+
+        visitor.leave_region()
+    }
+}
@@ -0,0 +1,86 @@
+//! Jump links connect parts of navmesh that are separated by gaps or ledges, bots cannot walk
+//! between such parts, but they can jump. A link is placed in a level editor as a node with
+//! `JumpLink` tag, the first child of the node marks the landing point. Links are one-way, a
+//! gap that must be crossed in both directions needs two links.
+
+use rg3d::{
+    core::{
+        algebra::Vector3,
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    scene::{graph::Graph, node::Node},
+};
+
+#[derive(Default, Copy, Clone, Debug)]
+pub struct JumpLink {
+    pub begin: Vector3<f32>,
+    pub end: Vector3<f32>,
+}
+
+impl JumpLink {
+    pub fn from_node(node: &Node, graph: &Graph) -> Option<Self> {
+        node.children().first().map(|&child| Self {
+            begin: node.global_position(),
+            end: graph[child].global_position(),
+        })
+    }
+}
+
+impl Visit for JumpLink {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.begin.visit("Begin", visitor)?;
+        self.end.visit("End", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+#[derive(Default)]
+pub struct JumpLinkContainer {
+    links: Vec<JumpLink>,
+}
+
+impl JumpLinkContainer {
+    /// Distance (in meters) between the end of a path and its destination at which the
+    /// destination is considered unreachable by walking.
+    pub const UNREACHABLE_DISTANCE: f32 = 1.0;
+
+    pub fn add(&mut self, link: JumpLink) {
+        self.links.push(link);
+    }
+
+    /// Looks for a link that brings an actor closer to a destination that it cannot reach
+    /// by walking. `reachable` is the closest point to the destination that the actor can
+    /// reach, links that land farther from the destination are useless. Returns the link
+    /// with the shortest total route.
+    pub fn find_route(
+        &self,
+        position: Vector3<f32>,
+        reachable: Vector3<f32>,
+        destination: Vector3<f32>,
+    ) -> Option<JumpLink> {
+        let remaining = reachable.metric_distance(&destination);
+        self.links
+            .iter()
+            .filter(|link| link.end.metric_distance(&destination) < remaining)
+            .map(|link| {
+                let route =
+                    position.metric_distance(&link.begin) + link.end.metric_distance(&destination);
+                (link, route)
+            })
+            .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+            .map(|(link, _)| *link)
+    }
+}
+
+impl Visit for JumpLinkContainer {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.links.visit("Links", visitor)?;
+
+        visitor.leave_region()
+    }
+}
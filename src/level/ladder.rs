@@ -0,0 +1,49 @@
+//! Ladders are climbable volumes, the player that walks into a ladder volume moves along the
+//! ladder vertically instead of walking. A ladder is placed in a level editor as a mesh with
+//! `Ladder` tag, its bounds define the volume. The first child of the mesh marks the point
+//! where the player gets off at the top of the ladder, without it the player gets off at
+//! the top of the volume.
+
+use rg3d::{
+    core::{
+        algebra::Vector3,
+        math::aabb::AxisAlignedBoundingBox,
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    scene::{graph::Graph, node::Node},
+};
+
+#[derive(Default, Copy, Clone)]
+pub struct Ladder {
+    pub bounds: AxisAlignedBoundingBox,
+    pub top: Vector3<f32>,
+}
+
+impl Ladder {
+    pub fn from_node(node: &Node, graph: &Graph) -> Self {
+        let bounds = node.as_mesh().world_bounding_box();
+        let top = match node.children().first() {
+            Some(&child) => graph[child].global_position(),
+            None => {
+                let center = bounds.center();
+                Vector3::new(center.x, bounds.max.y, center.z)
+            }
+        };
+        Self { bounds, top }
+    }
+
+    pub fn contains(&self, position: Vector3<f32>) -> bool {
+        self.bounds.is_contains_point(position)
+    }
+}
+
+impl Visit for Ladder {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.bounds.visit("Bounds", visitor)?;
+        self.top.visit("Top", visitor)?;
+
+        visitor.leave_region()
+    }
+}
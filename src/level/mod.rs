@@ -1,20 +1,34 @@
 use crate::level::arrival::ArrivalLevel;
+use crate::level::hazard::DamageVolume;
+use crate::level::horde::HordeController;
+use crate::level::jump_link::{JumpLink, JumpLinkContainer};
 use crate::level::lab::LabLevel;
+use crate::level::ladder::Ladder;
+use crate::level::objective::{Objective, ObjectiveTracker};
+use crate::level::spawn::{PendingBot, SpawnPoint, SpawnPointContainer, SpawnQueue};
 use crate::{
     actor::{Actor, ActorContainer},
     bot::{Bot, BotKind},
+    config::{Config, Difficulty},
     control_scheme::ControlScheme,
     door::{Door, DoorContainer, DoorState},
-    effects::{self, EffectKind},
+    effects::{self, EffectKind, EffectPool, ParticleBudget},
+    gui::compass::{Compass, CompassMarker, CompassMarkerKind},
     item::{Item, ItemContainer, ItemKind},
-    light::{Light, LightContainer},
-    message::Message,
+    leader_board::LeaderBoard,
+    light::{Flicker, Light, LightContainer},
+    message::{CustomMessageHandlers, Message},
     player::Player,
     sound::{SoundKind, SoundManager},
+    status_effect::{StatusEffect, StatusEffectKind},
+    utils::rng::GameRng,
     vector_to_quat,
     weapon::{
-        projectile::{Damage, Projectile, ProjectileContainer, ProjectileKind, ProjectileOwner},
-        ray_hit, Weapon, WeaponContainer, WeaponKind,
+        penetrating_ray_hits,
+        projectile::{
+            Damage, DamageType, Projectile, ProjectileContainer, ProjectileKind, ProjectileOwner,
+        },
+        ray_hit, Hit, Weapon, WeaponContainer, WeaponKind,
     },
     GameEngine, GameTime,
 };
@@ -41,13 +55,18 @@ use rg3d::{
     scene::{
         self,
         base::BaseBuilder,
+        graph::Graph,
+        light::Light as SceneLight,
         mesh::{Mesh, MeshBuilder, RenderPath},
         node::Node,
         physics::RayCastOptions,
         transform::TransformBuilder,
         ColliderHandle, Scene,
     },
-    utils::navmesh::Navmesh,
+    utils::{
+        log::{Log, MessageKind},
+        navmesh::Navmesh,
+    },
 };
 use std::ops::{Deref, DerefMut};
 use std::{
@@ -56,7 +75,31 @@ use std::{
 };
 
 pub mod arrival;
+pub mod corpses;
+pub mod hazard;
+pub mod horde;
+pub mod jump_link;
 pub mod lab;
+pub mod ladder;
+pub mod objective;
+pub mod physics;
+pub mod spawn;
+
+/// Bots spawned at random spawn point won't appear closer than this distance to the player.
+const MIN_SPAWN_DISTANCE_TO_PLAYER: f32 = 15.0;
+/// Knockback speed (in m/s) of an actor right at the center of an explosion, it fades out
+/// towards the edge of the blast radius.
+const SPLASH_KNOCKBACK: f32 = 10.0;
+/// Hits that deal at least this much damage push the victim away from the attacker.
+const HEAVY_HIT_DAMAGE: f32 = 40.0;
+/// Knockback speed of a hit that deals exactly `HEAVY_HIT_DAMAGE`.
+const HEAVY_HIT_KNOCKBACK: f32 = 3.0;
+/// Explosions shake the camera of the player within this many blast radii.
+const SPLASH_SHAKE_RADIUS_FACTOR: f32 = 4.0;
+/// Heavy weapons shake the camera of the player within this distance (in meters).
+const WEAPON_SHAKE_RADIUS: f32 = 8.0;
+/// Time (in seconds) after which gibs of severed limbs disappear.
+const GIB_LIFETIME: f32 = 30.0;
 
 pub enum Level {
     Unknown,
@@ -137,10 +180,15 @@ pub struct BaseLevel {
     pub actors: ActorContainer,
     weapons: WeaponContainer,
     items: ItemContainer,
-    spawn_points: Vec<SpawnPoint>,
+    spawn_points: SpawnPointContainer,
     sender: Option<Sender<Message>>,
     pub navmesh: Handle<Navmesh>,
     death_zones: Vec<DeathZone>,
+    damage_volumes: Vec<DamageVolume>,
+    jump_links: JumpLinkContainer,
+    ladders: Vec<Ladder>,
+    objectives: ObjectiveTracker,
+    player_spawn_position: Vector3<f32>,
     time: f32,
     sound_manager: SoundManager,
     proximity_events_receiver: Option<crossbeam::channel::Receiver<IntersectionEvent>>,
@@ -149,6 +197,20 @@ pub struct BaseLevel {
     trails: ShotTrailContainer,
     doors: DoorContainer,
     lights: LightContainer,
+    leader_board: LeaderBoard,
+    horde: Option<HordeController>,
+    /// Source of gameplay randomness, saved with the level so runs are reproducible.
+    rng: GameRng,
+    particle_budget: ParticleBudget,
+    effect_pool: EffectPool,
+    spawn_queue: SpawnQueue,
+    /// Whether actors of the same faction can damage each other, taken from config.
+    friendly_fire: bool,
+    /// Whether blood effects are shown and limbs of bots can be severed, taken from config.
+    gore: bool,
+    difficulty: Difficulty,
+    /// Maximum amount of live bots, requests to spawn more are rejected. Taken from config.
+    max_bots: usize,
 }
 
 impl Default for BaseLevel {
@@ -165,6 +227,11 @@ impl Default for BaseLevel {
             sender: None,
             navmesh: Default::default(),
             death_zones: Default::default(),
+            damage_volumes: Default::default(),
+            jump_links: Default::default(),
+            ladders: Default::default(),
+            objectives: Default::default(),
+            player_spawn_position: Default::default(),
             time: 0.0,
             sound_manager: Default::default(),
             proximity_events_receiver: None,
@@ -173,6 +240,16 @@ impl Default for BaseLevel {
             trails: Default::default(),
             doors: Default::default(),
             lights: Default::default(),
+            leader_board: Default::default(),
+            horde: None,
+            rng: Default::default(),
+            particle_budget: Default::default(),
+            effect_pool: Default::default(),
+            spawn_queue: Default::default(),
+            friendly_fire: false,
+            gore: true,
+            difficulty: Default::default(),
+            max_bots: 64,
         }
     }
 }
@@ -189,6 +266,12 @@ impl Visit for BaseLevel {
         self.weapons.visit("Weapons", visitor)?;
         self.spawn_points.visit("SpawnPoints", visitor)?;
         self.death_zones.visit("DeathZones", visitor)?;
+        self.damage_volumes.visit("DamageVolumes", visitor)?;
+        self.jump_links.visit("JumpLinks", visitor)?;
+        self.ladders.visit("Ladders", visitor)?;
+        self.objectives.visit("Objectives", visitor)?;
+        self.player_spawn_position
+            .visit("PlayerSpawnPosition", visitor)?;
         self.time.visit("Time", visitor)?;
         self.sound_manager.visit("SoundManager", visitor)?;
         self.items.visit("Items", visitor)?;
@@ -196,6 +279,11 @@ impl Visit for BaseLevel {
         self.trails.visit("Trails", visitor)?;
         self.doors.visit("Doors", visitor)?;
         self.lights.visit("Lights", visitor)?;
+        self.leader_board.visit("LeaderBoard", visitor)?;
+        self.horde.visit("Horde", visitor)?;
+        self.rng.visit("Rng", visitor)?;
+        self.effect_pool.visit("EffectPool", visitor)?;
+        self.spawn_queue.visit("SpawnQueue", visitor)?;
 
         if visitor.is_reading() {
             self.beam = Some(make_beam());
@@ -294,13 +382,20 @@ pub struct UpdateContext<'a> {
     pub items: &'a ItemContainer,
     pub navmesh: Handle<Navmesh>,
     pub weapons: &'a WeaponContainer,
+    pub rng: &'a mut GameRng,
+    pub jump_links: &'a JumpLinkContainer,
+    pub ladders: &'a [Ladder],
+    pub difficulty: Difficulty,
 }
 
 #[derive(Default)]
 pub struct AnalysisResult {
     items: ItemContainer,
     death_zones: Vec<DeathZone>,
-    spawn_points: Vec<SpawnPoint>,
+    damage_volumes: Vec<DamageVolume>,
+    jump_links: JumpLinkContainer,
+    ladders: Vec<Ladder>,
+    spawn_points: SpawnPointContainer,
     player_spawn_position: Vector3<f32>,
     doors: DoorContainer,
     lights: LightContainer,
@@ -342,6 +437,29 @@ pub fn footstep_ray_check(
     }
 }
 
+/// Leaves a piece of a severed limb on the floor below given position, the piece disappears
+/// after some time.
+fn spawn_gib(scene: &mut Scene, position: Vector3<f32>) {
+    let position = pick(scene, position, position - Vector3::new(0.0, 5.0, 0.0));
+
+    MeshBuilder::new(
+        BaseBuilder::new()
+            .with_lifetime(GIB_LIFETIME)
+            .with_local_transform(
+                TransformBuilder::new()
+                    .with_local_position(position)
+                    .with_local_scale(Vector3::new(0.1, 0.05, 0.15))
+                    .build(),
+            ),
+    )
+    .with_surfaces(vec![SurfaceBuilder::new(Arc::new(RwLock::new(
+        SurfaceSharedData::make_sphere(8, 8, 1.0),
+    )))
+    .with_color(Color::opaque(90, 10, 10))
+    .build()])
+    .build(&mut scene.graph);
+}
+
 fn make_beam() -> Arc<RwLock<SurfaceSharedData>> {
     Arc::new(RwLock::new(SurfaceSharedData::make_cylinder(
         6,
@@ -360,8 +478,10 @@ pub async fn analyze(
     let mut result = AnalysisResult::default();
 
     let mut items = Vec::new();
-    let mut spawn_points = Vec::new();
+    let mut spawn_points = SpawnPointContainer::default();
     let mut death_zones = Vec::new();
+    let mut damage_volumes = Vec::new();
+    let mut ladders = Vec::new();
     let mut player_spawn_position = Default::default();
 
     for (handle, node) in scene.graph.pair_iter() {
@@ -369,26 +489,11 @@ pub async fn analyze(
         let name = node.name();
 
         if name.starts_with("Zombie") {
-            spawn_points.push(SpawnPoint {
-                position: node.global_position(),
-                rotation: **node.local_transform().rotation(),
-                bot_kind: BotKind::Zombie,
-                spawned: false,
-            })
+            spawn_points.add(SpawnPoint::new(node, vec![BotKind::Zombie], true))
         } else if name.starts_with("Mutant") {
-            spawn_points.push(SpawnPoint {
-                position: node.global_position(),
-                rotation: **node.local_transform().rotation(),
-                bot_kind: BotKind::Mutant,
-                spawned: false,
-            })
+            spawn_points.add(SpawnPoint::new(node, vec![BotKind::Mutant], true))
         } else if name.starts_with("Parasite") {
-            spawn_points.push(SpawnPoint {
-                position: node.global_position(),
-                rotation: **node.local_transform().rotation(),
-                bot_kind: BotKind::Parasite,
-                spawned: false,
-            })
+            spawn_points.add(SpawnPoint::new(node, vec![BotKind::Parasite], true))
         } else if name.starts_with("PlayerSpawnPoint") {
             player_spawn_position = node.global_position();
         } else if name.starts_with("DeathZone") {
@@ -397,6 +502,14 @@ pub async fn analyze(
             }
         }
 
+        if let Node::Light(light) = node {
+            if !matches!(light, SceneLight::Directional(_)) {
+                result
+                    .lights
+                    .add_shadow_caster(handle, light.is_cast_shadows());
+            }
+        }
+
         match node.tag() {
             "SideDoor" => {
                 result
@@ -414,14 +527,39 @@ pub async fn analyze(
                     .add(Door::new(handle, &scene.graph, DoorState::Locked));
             }
             "FlashingLight" => result.lights.add(Light::new(handle)),
+            "Ladder" => {
+                if let Node::Mesh(_) = node {
+                    ladders.push(handle);
+                }
+            }
+            "JumpLink" => {
+                if let Some(link) = JumpLink::from_node(node, &scene.graph) {
+                    result.jump_links.add(link);
+                }
+            }
+            tag if tag.starts_with("DamageVolume") => {
+                if let Node::Mesh(_) = node {
+                    damage_volumes.push((handle, tag.to_owned()));
+                }
+            }
+            tag if tag.starts_with("BotSpawnPoint") => {
+                spawn_points.add(SpawnPoint::from_tag(node, tag))
+            }
+            tag if tag.starts_with("FlickeringLight") => result.lights.add(Light::new_flickering(
+                handle,
+                Flicker::from_tag(tag),
+                &scene.graph,
+            )),
             "Medkit" => items.push((ItemKind::Medkit, position)),
             "Medpack" => items.push((ItemKind::Medpack, position)),
+            "HealthInjector" => items.push((ItemKind::HealthInjector, position)),
             "Ammo" => items.push((ItemKind::Ammo, position)),
             "Grenade" => items.push((ItemKind::Grenade, position)),
             "PlasmaGun" => items.push((ItemKind::PlasmaGun, position)),
             "Ak47" => items.push((ItemKind::Ak47, position)),
             "M4" => items.push((ItemKind::M4, position)),
             "Glock" => items.push((ItemKind::Glock, position)),
+            "Shotgun" => items.push((ItemKind::Shotgun, position)),
             "MasterKey" => items.push((ItemKind::MasterKey, position)),
             _ => (),
         }
@@ -447,6 +585,20 @@ pub async fn analyze(
             bounds: node.as_mesh().world_bounding_box(),
         });
     }
+    for (handle, tag) in damage_volumes {
+        let node = &mut scene.graph[handle];
+        node.set_visibility(false);
+        result.damage_volumes.push(DamageVolume::from_tag(
+            node.as_mesh().world_bounding_box(),
+            &tag,
+        ));
+    }
+    for handle in ladders {
+        result
+            .ladders
+            .push(Ladder::from_node(&scene.graph[handle], &scene.graph));
+        scene.graph[handle].set_visibility(false);
+    }
     result.spawn_points = spawn_points;
     result.player_spawn_position = player_spawn_position;
 
@@ -527,21 +679,24 @@ async fn give_new_weapon(
 
 async fn spawn_bot(
     spawn_point: &mut SpawnPoint,
+    kind: BotKind,
     actors: &mut ActorContainer,
     resource_manager: ResourceManager,
     sender: Sender<Message>,
     scene: &mut Scene,
+    rng: &mut GameRng,
 ) -> Handle<Actor> {
-    spawn_point.spawned = true;
+    spawn_point.set_spawned();
 
     let bot = add_bot(
-        spawn_point.bot_kind,
+        kind,
         spawn_point.position,
         spawn_point.rotation,
         actors,
         resource_manager,
         sender,
         scene,
+        rng,
     )
     .await;
 
@@ -556,6 +711,7 @@ async fn add_bot(
     resource_manager: ResourceManager,
     sender: Sender<Message>,
     scene: &mut Scene,
+    rng: &mut GameRng,
 ) -> Handle<Actor> {
     let bot = Bot::new(
         kind,
@@ -566,7 +722,24 @@ async fn add_bot(
         sender.clone(),
     )
     .await;
-    actors.add(Actor::Bot(bot))
+    let definition = bot.definition;
+    let can_use_weapons = bot.can_use_weapons();
+    let bot = actors.add(Actor::Bot(bot));
+
+    // Give bot a random weapon from the set of possible weapons, this adds some variety
+    // to encounters with the same kind of bots.
+    if can_use_weapons {
+        if let Some(&weapon) = definition.possible_weapons.choose(rng) {
+            sender
+                .send(Message::GiveNewWeapon {
+                    actor: bot,
+                    kind: weapon,
+                })
+                .unwrap();
+        }
+    }
+
+    bot
 }
 
 async fn spawn_item(
@@ -617,6 +790,7 @@ fn pick(scene: &mut Scene, from: Vector3<f32>, to: Vector3<f32>) -> Vector3<f32>
 impl BaseLevel {
     pub async fn new(
         map: &str,
+        seed: u64,
         resource_manager: ResourceManager,
         sender: Sender<Message>,
         display_texture: Texture,
@@ -625,6 +799,8 @@ impl BaseLevel {
     ) -> (Self, Scene) {
         let mut scene = Scene::new();
 
+        physics::apply_definition(&mut scene.physics);
+
         scene.ambient_lighting_color = Color::opaque(45, 45, 45);
 
         let (proximity_events_sender, proximity_events_receiver) = crossbeam::channel::unbounded();
@@ -646,6 +822,9 @@ impl BaseLevel {
         let AnalysisResult {
             items,
             death_zones,
+            damage_volumes,
+            jump_links,
+            ladders,
             mut spawn_points,
             player_spawn_position,
             doors,
@@ -653,14 +832,18 @@ impl BaseLevel {
         } = analyze(&mut scene, resource_manager.clone(), sender.clone()).await;
         let mut actors = ActorContainer::new();
         let mut weapons = WeaponContainer::new();
+        let mut rng = GameRng::new(seed);
 
-        for pt in spawn_points.iter_mut() {
+        for pt in spawn_points.auto_spawn_points_mut() {
+            let kind = pt.random_kind(&mut rng);
             spawn_bot(
                 pt,
+                kind,
                 &mut actors,
                 resource_manager.clone(),
                 sender.clone(),
                 &mut scene,
+                &mut rng,
             )
             .await;
         }
@@ -684,6 +867,11 @@ impl BaseLevel {
             items,
             lights,
             death_zones,
+            damage_volumes,
+            jump_links,
+            ladders,
+            objectives: ObjectiveTracker::new(map, &scene.graph),
+            player_spawn_position,
             spawn_points,
             navmesh: scene.navmeshes.handle_from_index(0),
             scene: Handle::NONE, // Filled when scene will be moved to engine.
@@ -696,6 +884,16 @@ impl BaseLevel {
             beam: Some(make_beam()),
             trails: Default::default(),
             doors,
+            leader_board: Default::default(),
+            horde: None,
+            rng,
+            particle_budget: Default::default(),
+            effect_pool: Default::default(),
+            spawn_queue: Default::default(),
+            friendly_fire: false,
+            gore: true,
+            difficulty: Default::default(),
+            max_bots: 64,
         };
 
         (level, scene)
@@ -728,6 +926,35 @@ impl BaseLevel {
         self.player
     }
 
+    /// Returns seed of gameplay random number generator, a new game started with the same
+    /// seed will play out the same way.
+    pub fn rng_seed(&self) -> u64 {
+        self.rng.seed()
+    }
+
+    pub fn leader_board(&self) -> &LeaderBoard {
+        &self.leader_board
+    }
+
+    /// Turns the level into horde mode arena, waves of bots will start to spawn after
+    /// a short delay.
+    pub fn enable_horde_mode(&mut self) {
+        self.horde = Some(Default::default());
+    }
+
+    pub fn is_shadow_culling(&self) -> bool {
+        self.lights.is_shadow_culling()
+    }
+
+    pub fn horde(&self) -> Option<&HordeController> {
+        self.horde.as_ref()
+    }
+
+    /// Returns amount of time (in seconds) spent on the level.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
     pub fn process_input_event(
         &mut self,
         event: &Event<()>,
@@ -736,8 +963,25 @@ impl BaseLevel {
         control_scheme: &ControlScheme,
     ) {
         if self.player.is_some() {
+            let aim_assist_targets = if control_scheme.aim_assist > 0.0 || control_scheme.aim_snap {
+                self.actors
+                    .iter()
+                    .filter(|actor| matches!(actor, Actor::Bot(_)) && !actor.is_dead())
+                    .map(|actor| actor.position(&scene.graph))
+                    .collect::<Vec<_>>()
+            } else {
+                Vec::new()
+            };
+
             if let Actor::Player(player) = self.actors.get_mut(self.player) {
-                player.process_input_event(event, dt, scene, &self.weapons, control_scheme);
+                player.process_input_event(
+                    event,
+                    dt,
+                    scene,
+                    &self.weapons,
+                    control_scheme,
+                    &aim_assist_targets,
+                );
             }
         }
     }
@@ -746,6 +990,49 @@ impl BaseLevel {
         &self.actors
     }
 
+    pub fn projectiles(&self) -> &ProjectileContainer {
+        &self.projectiles
+    }
+
+    /// Applies user settings to the level and its player. Settings are not saved with the
+    /// level, so they must be applied each time when a level is created or loaded, when the
+    /// player is respawned and when settings are changed.
+    pub fn apply_settings(&mut self, config: &Config) {
+        self.particle_budget
+            .set_budget(config.effects.particle_budget);
+        self.friendly_fire = config.gameplay.friendly_fire;
+        self.gore = config.accessibility.gore;
+        self.difficulty = config.gameplay.difficulty;
+        self.max_bots = config.gameplay.max_bots;
+
+        if self.actors.contains(self.player) {
+            if let Actor::Player(player) = self.actors.get_mut(self.player) {
+                let accessibility = &config.accessibility;
+                player.set_palette(&accessibility.color_blind_mode.palette());
+                player.set_health_regen(accessibility.health_regen);
+                player.set_camera_shake(accessibility.camera_shake_scale());
+                player.set_weapon_inspect(accessibility.weapon_inspect);
+            }
+        }
+    }
+
+    pub fn max_bots(&self) -> usize {
+        self.max_bots
+    }
+
+    /// Amount of bots that are alive or waiting in the spawn queue.
+    pub fn live_bot_count(&self) -> usize {
+        self.actors
+            .iter()
+            .filter(|a| matches!(a, Actor::Bot(_)) && !a.is_dead())
+            .count()
+            + self.spawn_queue.count()
+    }
+
+    fn is_bot_limit_reached(&self) -> bool {
+        self.live_bot_count() >= self.max_bots
+    }
+
     pub fn actors_mut(&mut self) -> &mut ActorContainer {
         &mut self.actors
     }
@@ -756,10 +1043,11 @@ impl BaseLevel {
 
     fn remove_weapon(&mut self, engine: &mut GameEngine, weapon: Handle<Weapon>) {
         for projectile in self.projectiles.iter_mut() {
-            if let ProjectileOwner::Weapon(ref mut owner) = projectile.owner {
-                // Reset owner because handle to weapon will be invalid after weapon freed.
-                if *owner == weapon {
-                    *owner = Handle::NONE;
+            if let ProjectileOwner::Weapon(owner) = projectile.owner {
+                // Handle to weapon will be invalid after weapon freed, so credit owner of
+                // the weapon directly.
+                if owner == weapon {
+                    projectile.owner = ProjectileOwner::Actor(self.weapons[weapon].owner());
                 }
             }
         }
@@ -776,6 +1064,11 @@ impl BaseLevel {
 
             if let Some(i) = actor.weapons.iter().position(|&w| w == weapon) {
                 actor.weapons.remove(i);
+
+                // Keep index pointing to the same weapon.
+                if actor.current_weapon as usize > i {
+                    actor.current_weapon -= 1;
+                }
             }
         }
 
@@ -798,6 +1091,7 @@ impl BaseLevel {
             engine.resource_manager.clone(),
             self.sender.clone().unwrap(),
             &mut engine.scenes[self.scene],
+            &mut self.rng,
         )
         .await
     }
@@ -814,6 +1108,45 @@ impl BaseLevel {
         }
     }
 
+    /// Replaces dead player by a new one at the spawn position of the level, it is used in
+    /// horde mode where death of the player doesn't end the match. Score of the player is
+    /// kept, but some of the kills are taken away as a penalty.
+    pub async fn respawn_player(
+        &mut self,
+        engine: &mut GameEngine,
+        display_texture: Texture,
+        inventory_texture: Texture,
+        item_texture: Texture,
+    ) {
+        let old_player = self.player;
+        if self.actors.contains(old_player) {
+            let weapons = self.actors.get(old_player).weapons().to_vec();
+            for weapon in weapons {
+                self.remove_weapon(engine, weapon);
+            }
+        }
+        self.remove_actor(engine, old_player).await;
+
+        self.player = spawn_player(
+            self.player_spawn_position,
+            &mut self.actors,
+            &mut self.weapons,
+            self.sender.clone().unwrap(),
+            engine.resource_manager.clone(),
+            &mut engine.scenes[self.scene],
+            display_texture,
+            inventory_texture,
+            item_texture,
+        )
+        .await;
+
+        self.leader_board.transfer(
+            old_player,
+            self.player,
+            HordeController::get_definition().respawn_penalty,
+        );
+    }
+
     async fn drop_items(
         &mut self,
         engine: &mut GameEngine,
@@ -821,6 +1154,10 @@ impl BaseLevel {
         item: ItemKind,
         count: u32,
     ) {
+        if !self.actors.contains(actor) {
+            return;
+        }
+
         let character = self.actors.get_mut(actor);
         let scene = &engine.scenes[self.scene];
 
@@ -853,13 +1190,27 @@ impl BaseLevel {
         if self.actors.contains(actor) {
             let character = self.actors.get_mut(actor);
             match kind {
-                ItemKind::Medkit => character.heal(40.0),
-                ItemKind::Medpack => character.heal(20.0),
+                ItemKind::Medkit => {
+                    character.heal(40.0);
+                    character.remove_status_effect(StatusEffectKind::Poison);
+                }
+                ItemKind::Medpack => {
+                    character.heal(20.0);
+                    character.remove_status_effect(StatusEffectKind::Poison);
+                }
+                ItemKind::HealthInjector => {
+                    if let Some(effect) = Item::get_definition(kind).status_effect.as_ref() {
+                        character.apply_status_effect(
+                            StatusEffect::from_definition(effect).with_source(actor),
+                        );
+                    }
+                }
                 // Non-consumable items.
                 ItemKind::Ak47
                 | ItemKind::PlasmaGun
                 | ItemKind::M4
                 | ItemKind::Glock
+                | ItemKind::Shotgun
                 | ItemKind::Ammo
                 | ItemKind::Grenade
                 | ItemKind::MasterKey => (),
@@ -891,15 +1242,71 @@ impl BaseLevel {
                     gain: 1.0,
                     rolloff_factor: 3.0,
                     radius: 2.0,
+                    caption: None,
                 })
                 .unwrap();
 
+            self.give_item(engine, actor, kind).await;
+        }
+    }
+
+    async fn loot_corpse(
+        &mut self,
+        engine: &mut GameEngine,
+        actor: Handle<Actor>,
+        corpse: Handle<Actor>,
+    ) {
+        if self.actors.contains(actor) && self.actors.contains(corpse) {
+            let position = self
+                .actors
+                .get(corpse)
+                .position(&engine.scenes[self.scene].graph);
+
+            let loot = match self.actors.get_mut(corpse) {
+                Actor::Bot(bot) if bot.is_dead() => bot.take_loot(),
+                _ => return,
+            };
+
+            if loot.is_empty() {
+                return;
+            }
+
+            self.sender
+                .as_ref()
+                .unwrap()
+                .send(Message::PlaySound {
+                    path: PathBuf::from("data/sounds/item_pickup.ogg"),
+                    position,
+                    gain: 1.0,
+                    rolloff_factor: 3.0,
+                    radius: 2.0,
+                    caption: None,
+                })
+                .unwrap();
+
+            for kind in loot {
+                self.give_item(engine, actor, kind).await;
+            }
+        }
+    }
+
+    /// Puts an item of given kind into inventory of an actor, weapon items give new weapon
+    /// (or ammo if the actor already has such weapon).
+    async fn give_item(&mut self, engine: &mut GameEngine, actor: Handle<Actor>, kind: ItemKind) {
+        if self.actors.contains(actor) {
             let character = self.actors.get_mut(actor);
 
             match kind {
                 ItemKind::Medkit => character.inventory_mut().add_item(ItemKind::Medkit, 1),
                 ItemKind::Medpack => character.inventory_mut().add_item(ItemKind::Medpack, 1),
-                ItemKind::Ak47 | ItemKind::PlasmaGun | ItemKind::M4 | ItemKind::Glock => {
+                ItemKind::HealthInjector => character
+                    .inventory_mut()
+                    .add_item(ItemKind::HealthInjector, 1),
+                ItemKind::Ak47
+                | ItemKind::PlasmaGun
+                | ItemKind::M4
+                | ItemKind::Glock
+                | ItemKind::Shotgun => {
                     let weapon_kind = kind.associated_weapon().unwrap();
 
                     let mut found = false;
@@ -913,6 +1320,22 @@ impl BaseLevel {
                     if found {
                         character.inventory_mut().add_item(ItemKind::Ammo, 20);
                     } else {
+                        // Swap current weapon with the new one if there is no free slot.
+                        if !character.has_free_weapon_slot() && character.current_weapon().is_some()
+                        {
+                            self.sender
+                                .as_ref()
+                                .unwrap()
+                                .send(Message::DropItems {
+                                    actor,
+                                    item: self.weapons[character.current_weapon()]
+                                        .get_kind()
+                                        .associated_item(),
+                                    count: 1,
+                                })
+                                .unwrap();
+                        }
+
                         // Finally if actor does not have such weapon, give new one to him.
                         self.give_new_weapon(engine, actor, weapon_kind).await;
                     }
@@ -938,6 +1361,7 @@ impl BaseLevel {
         direction: Vector3<f32>,
         initial_velocity: Vector3<f32>,
         owner: ProjectileOwner,
+        damage_scale: f32,
     ) {
         let scene = &mut engine.scenes[self.scene];
         let projectile = Projectile::new(
@@ -948,10 +1372,15 @@ impl BaseLevel {
             position,
             owner,
             initial_velocity,
+            damage_scale,
             self.sender.as_ref().unwrap().clone(),
         )
         .await;
-        self.projectiles.add(projectile);
+        self.projectiles.add(
+            projectile,
+            &mut scene.graph,
+            engine.resource_manager.clone(),
+        );
     }
 
     async fn shoot_weapon(
@@ -960,6 +1389,7 @@ impl BaseLevel {
         weapon_handle: Handle<Weapon>,
         time: GameTime,
         direction: Option<Vector3<f32>>,
+        damage_scale: f32,
     ) {
         if self.weapons.contains(weapon_handle) {
             let scene = &mut engine.scenes[self.scene];
@@ -970,23 +1400,63 @@ impl BaseLevel {
                 time,
                 engine.resource_manager.clone(),
                 direction,
+                damage_scale,
+                &mut self.rng,
             );
+
+            let shake = weapon.definition.camera_shake;
+            if shake > 0.0 {
+                let position = weapon.get_shot_position(&scene.graph);
+                self.shake_camera(&scene.graph, position, shake, WEAPON_SHAKE_RADIUS);
+            }
+        }
+    }
+
+    /// Whether there is at least one bot that fights or searches for a target.
+    fn is_fight_going(&self) -> bool {
+        self.actors.iter().any(|actor| match actor {
+            Actor::Bot(bot) => bot.is_alerted(),
+            Actor::Player(_) => false,
+        })
+    }
+
+    /// Shakes the camera of the player if it is closer than `radius` to the source of shake,
+    /// the shake weakens with distance.
+    fn shake_camera(&mut self, graph: &Graph, position: Vector3<f32>, strength: f32, radius: f32) {
+        if !self.actors.contains(self.player) {
+            return;
+        }
+
+        if let Actor::Player(player) = self.actors.get_mut(self.player) {
+            let distance = player.position(graph).metric_distance(&position);
+            let k = 1.0 - (distance / radius).min(1.0);
+            if k > 0.0 {
+                player.shake_camera(strength * k);
+            }
         }
     }
 
     fn show_weapon(&mut self, engine: &mut GameEngine, weapon_handle: Handle<Weapon>, state: bool) {
-        self.weapons[weapon_handle].set_visibility(state, &mut engine.scenes[self.scene].graph)
+        if self.weapons.contains(weapon_handle) {
+            self.weapons[weapon_handle].set_visibility(state, &mut engine.scenes[self.scene].graph)
+        }
     }
 
+    /// Applies damage to an actor, returns amount of damage that was dealt. Zero means that
+    /// the damage was ignored (friendly fire, invulnerable or already dead actor).
     fn damage_actor(
         &mut self,
         engine: &GameEngine,
         actor_handle: Handle<Actor>,
         who: Handle<Actor>,
         amount: f32,
-    ) {
-        if self.actors.contains(actor_handle)
-            && (who.is_none() || who.is_some() && self.actors.contains(who))
+        damage_type: DamageType,
+        critical: bool,
+        hit_box: Option<ColliderHandle>,
+    ) -> f32 {
+        if let Some(who) = self
+            .actors
+            .resolve_damage(actor_handle, who, self.friendly_fire)
         {
             let scene = &engine.scenes[self.scene];
 
@@ -998,13 +1468,65 @@ impl BaseLevel {
             let actor = self.actors.get_mut(actor_handle);
 
             if !actor.is_dead() {
-                if let Actor::Bot(bot) = actor {
-                    if let Some(who_position) = who_position {
-                        bot.set_target(actor_handle, who_position);
+                let amount = match actor {
+                    Actor::Bot(bot) => {
+                        if let Some(who_position) = who_position {
+                            bot.set_target(who, who_position);
+                        }
+                        amount * bot.definition.damage_scale(damage_type)
                     }
-                }
+                    Actor::Player(player) if player.is_invulnerable() => return 0.0,
+                    Actor::Player(_) => amount,
+                };
                 actor.damage(amount);
 
+                if let (true, Actor::Bot(bot), Some(hit_box)) = (self.gore, &mut *actor, hit_box) {
+                    if bot.damage_hit_box(hit_box, amount) {
+                        self.sender
+                            .as_ref()
+                            .unwrap()
+                            .send(Message::SeverHitBox {
+                                actor: actor_handle,
+                                hit_box,
+                            })
+                            .unwrap();
+                    }
+                }
+
+                if who.is_some() && who == self.player && actor_handle != who {
+                    self.sender
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::ShowHitMarker {
+                            kill: actor.is_dead(),
+                        })
+                        .unwrap();
+
+                    if let Actor::Bot(_) = actor {
+                        self.sender
+                            .as_ref()
+                            .unwrap()
+                            .send(Message::ShowDamageNumber {
+                                position: actor.position(&scene.graph)
+                                    + Vector3::new(0.0, 2.0, 0.0),
+                                amount,
+                                critical,
+                            })
+                            .unwrap();
+                    }
+                }
+
+                if actor.is_dead() {
+                    self.sender
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::ActorDied {
+                            actor: actor_handle,
+                            who,
+                        })
+                        .unwrap();
+                }
+
                 match actor {
                     Actor::Bot(bot) => {
                         if let Some(grunt_sound) =
@@ -1019,12 +1541,50 @@ impl BaseLevel {
                                     gain: 0.8,
                                     rolloff_factor: 1.0,
                                     radius: 0.6,
+                                    caption: Some(format!(
+                                        "[{} screams in pain]",
+                                        bot.kind().description()
+                                    )),
                                 })
                                 .unwrap();
                         }
                     }
                     Actor::Player(_) => {}
                 }
+
+                amount
+            } else {
+                0.0
+            }
+        } else {
+            0.0
+        }
+    }
+
+    fn sever_hit_box(
+        &mut self,
+        engine: &mut GameEngine,
+        actor: Handle<Actor>,
+        hit_box: ColliderHandle,
+    ) {
+        if !self.actors.contains(actor) {
+            return;
+        }
+
+        let scene = &mut engine.scenes[self.scene];
+        if let Actor::Bot(bot) = self.actors.get_mut(actor) {
+            if let Some(position) = bot.sever_hit_box(hit_box, scene) {
+                effects::create(
+                    EffectKind::BloodSpray,
+                    &mut scene.graph,
+                    engine.resource_manager.clone(),
+                    position,
+                    Default::default(),
+                    self.gore,
+                    &mut self.particle_budget,
+                    &mut self.effect_pool,
+                );
+                spawn_gib(scene, position);
             }
         }
     }
@@ -1064,6 +1624,9 @@ impl BaseLevel {
                             actor: handle,
                             who: Default::default(),
                             amount: 99999.0,
+                            damage_type: DamageType::Environment,
+                            critical: false,
+                            hit_box: None,
                         })
                         .unwrap();
                 }
@@ -1083,9 +1646,106 @@ impl BaseLevel {
         }
     }
 
+    /// Completes current objective when the player reaches it or holds action control at it
+    /// long enough.
+    fn update_objectives(&mut self, scene: &Scene, dt: f32) {
+        if !self.actors.contains(self.player) {
+            return;
+        }
+        let actor = self.actors.get(self.player);
+        let position = actor.position(&scene.graph);
+        let holding = match actor {
+            Actor::Player(player) => player.is_action_held() && !player.is_dead(),
+            Actor::Bot(_) => false,
+        };
+        let held = self
+            .objectives
+            .update_hold(position, holding, actor.health, dt);
+        if held || self.objectives.is_reached(position) {
+            self.sender
+                .as_ref()
+                .unwrap()
+                .send(Message::AdvanceObjective)
+                .unwrap();
+        }
+    }
+
+    pub fn current_objective(&self) -> Option<&Objective> {
+        self.objectives.current()
+    }
+
+    /// Returns progress of holding action control at current objective in [0; 1] range.
+    pub fn objective_hold_progress(&self) -> Option<f32> {
+        self.objectives.hold_progress()
+    }
+
+    /// Returns markers of current objective and of bots that the player can see on the
+    /// compass, visibility of bots depends on difficulty.
+    pub fn compass_markers(&self, graph: &Graph) -> Vec<CompassMarker> {
+        let mut markers = self
+            .objectives
+            .current()
+            .map(|objective| CompassMarker {
+                position: objective.position,
+                kind: CompassMarkerKind::Objective,
+            })
+            .into_iter()
+            .collect::<Vec<_>>();
+
+        if self.actors.contains(self.player) {
+            let player_position = self.actors.get(self.player).position(graph);
+            for actor in self.actors.iter() {
+                if let Actor::Bot(bot) = actor {
+                    let position = bot.position(graph);
+                    if !bot.is_dead()
+                        && self.difficulty.shows_on_compass(bot.is_alerted())
+                        && position.metric_distance(&player_position) <= Compass::ENEMY_RANGE
+                    {
+                        markers.push(CompassMarker {
+                            position,
+                            kind: CompassMarkerKind::Enemy,
+                        });
+                    }
+                }
+            }
+        }
+
+        markers
+    }
+
+    /// Instantiates queued bots whose resources are loaded, so this never waits for loading.
+    fn spawn_queued_bots(&mut self, engine: &mut GameEngine) {
+        for pending in self.spawn_queue.take_ready(&engine.resource_manager) {
+            let bot = rg3d::futures::executor::block_on(self.add_bot(
+                engine,
+                pending.kind,
+                pending.position,
+                pending.rotation,
+            ));
+
+            if pending.horde {
+                if let Some(horde) = self.horde.as_mut() {
+                    horde.on_bot_spawned(bot);
+                }
+            }
+
+            Log::writeln(
+                MessageKind::Information,
+                format!(
+                    "{} spawned {:.3} s after request",
+                    pending.kind.description(),
+                    self.time - pending.queued_at
+                ),
+            );
+        }
+    }
+
     pub fn update(&mut self, engine: &mut GameEngine, time: GameTime) {
         self.time += time.delta;
+        self.spawn_queued_bots(engine);
         let scene = &mut engine.scenes[self.scene];
+        // Physics uses the same time step as gameplay, so it slows down in slow motion too.
+        scene.physics.integration_parameters.dt = time.delta;
         while self
             .proximity_events_receiver
             .as_ref()
@@ -1097,6 +1757,17 @@ impl BaseLevel {
         }
 
         self.update_death_zones(scene);
+        for damage_volume in self.damage_volumes.iter_mut() {
+            damage_volume.update(
+                scene,
+                &self.actors,
+                self.sender.as_ref().unwrap(),
+                time.delta,
+            );
+        }
+        if let Some(horde) = self.horde.as_mut() {
+            horde.update(time.delta, self.sender.as_ref().unwrap(), &mut self.rng);
+        }
         self.weapons.update(scene, &self.actors, time.delta);
         self.projectiles
             .update(scene, &self.actors, &self.weapons, time);
@@ -1106,10 +1777,18 @@ impl BaseLevel {
             items: &self.items,
             navmesh: self.navmesh,
             weapons: &self.weapons,
+            rng: &mut self.rng,
+            jump_links: &self.jump_links,
+            ladders: &self.ladders,
+            difficulty: self.difficulty,
         };
         self.actors.update(&mut ctx);
+        corpses::remove_stale_corpses(&self.actors, self.sender.as_ref().unwrap());
         self.trails.update(time.delta, scene);
+        self.particle_budget.update(time.delta);
+        self.effect_pool.update(&mut scene.graph, time.delta);
         self.update_game_ending(scene);
+        self.update_objectives(scene, time.delta);
         self.doors.update(
             &self.actors,
             self.sender.clone().unwrap(),
@@ -1117,6 +1796,21 @@ impl BaseLevel {
             time.delta,
         );
         self.lights.update(scene, time.delta);
+        let camera = if self.actors.contains(self.player) {
+            match self.actors.get(self.player) {
+                Actor::Player(player) => Some(player.camera_controller().camera()),
+                Actor::Bot(_) => None,
+            }
+        } else {
+            None
+        };
+        match camera {
+            Some(camera) if self.lights.is_shadow_culling() => {
+                self.lights
+                    .cull_shadows(scene, camera, &engine.renderer.get_quality_settings())
+            }
+            _ => self.lights.restore_shadows(scene),
+        }
         self.items.update(time.delta, &mut scene.graph);
     }
 
@@ -1127,94 +1821,60 @@ impl BaseLevel {
         begin: Vector3<f32>,
         end: Vector3<f32>,
         damage: Damage,
+        damage_type: DamageType,
+        impact_effect: EffectKind,
     ) {
         let scene = &mut engine.scenes[self.scene];
 
-        // Do immediate intersection test and solve it.
-        let trail_len = if let Some(hit) = ray_hit(
-            begin,
-            end,
-            ProjectileOwner::Weapon(weapon),
-            &self.weapons,
-            &self.actors,
-            &mut scene.physics,
-            Default::default(),
-        ) {
-            // Just send new messages, instead of doing everything manually here.
-            self.sender
-                .as_ref()
-                .unwrap()
-                .send(Message::CreateEffect {
-                    kind: if hit.actor.is_some() {
-                        EffectKind::BloodSpray
-                    } else {
-                        EffectKind::BulletImpact
-                    },
-                    position: hit.position,
-                    orientation: vector_to_quat(hit.normal),
-                })
-                .unwrap();
-
-            self.sender
-                .as_ref()
-                .unwrap()
-                .send(Message::PlayEnvironmentSound {
-                    collider: hit.collider,
-                    feature: hit.feature,
-                    position: hit.position,
-                    sound_kind: SoundKind::Impact,
-                    gain: 1.0,
-                    rolloff_factor: 1.0,
-                    radius: 0.5,
-                })
-                .unwrap();
+        let penetration = self
+            .weapons
+            .try_get(weapon)
+            .and_then(|w| w.definition.penetration);
 
-            self.sender
-                .as_ref()
-                .unwrap()
-                .send(Message::DamageActor {
-                    actor: hit.actor,
-                    who: hit.who,
-                    amount: damage
-                        .scale(hit.hit_box.map_or(1.0, |h| h.damage_factor))
-                        .amount(),
-                })
-                .unwrap();
-
-            let dir = hit.position - begin;
-
-            if let Some(collider) = scene.physics.colliders.get(hit.collider.into()) {
-                scene
-                    .physics
-                    .bodies
-                    .get_mut(collider.parent())
-                    .unwrap()
-                    .apply_force_at_point(
-                        dir.try_normalize(std::f32::EPSILON)
-                            .unwrap_or_default()
-                            .scale(30.0),
-                        Point3::from(hit.position),
-                        true,
-                    );
-            }
+        // Do immediate intersection test and solve it.
+        let (hits, stopped) = match penetration.as_ref() {
+            Some(penetration) => penetrating_ray_hits(
+                begin,
+                end,
+                ProjectileOwner::Weapon(weapon),
+                &self.weapons,
+                &self.actors,
+                &mut scene.physics,
+                penetration,
+            ),
+            None => (
+                ray_hit(
+                    begin,
+                    end,
+                    ProjectileOwner::Weapon(weapon),
+                    &self.weapons,
+                    &self.actors,
+                    &mut scene.physics,
+                    Default::default(),
+                )
+                .into_iter()
+                .collect(),
+                true,
+            ),
+        };
 
-            if hit.actor.is_some() {
-                if let Actor::Bot(actor) = self.actors.get_mut(hit.actor) {
-                    let body = scene
-                        .physics
-                        .colliders
-                        .get(hit.collider.into())
-                        .unwrap()
-                        .parent();
-                    actor
-                        .impact_handler
-                        .handle_impact(scene, body.into(), hit.position, dir);
-                }
-            }
+        // Each subsequent hit of a penetrating shot does less damage.
+        let mut damage_scale = 1.0;
+        for hit in hits.iter() {
+            self.apply_ray_hit(
+                scene,
+                hit,
+                begin,
+                damage.scale(damage_scale),
+                damage_type,
+                impact_effect,
+            );
+            damage_scale *= penetration.map_or(1.0, |p| p.damage_falloff);
+        }
 
-            dir.norm()
-        } else {
-            100.0
+        let trail_len = match hits.last() {
+            Some(hit) if stopped => hit.position.metric_distance(&begin),
+            _ => 100.0,
         };
 
         let trail_radius = 0.0014;
@@ -1245,6 +1905,92 @@ impl BaseLevel {
         });
     }
 
+    /// Creates impact effects and damages an actor (if any) at a hit of a ray.
+    fn apply_ray_hit(
+        &mut self,
+        scene: &mut Scene,
+        hit: &Hit,
+        begin: Vector3<f32>,
+        damage: Damage,
+        damage_type: DamageType,
+        impact_effect: EffectKind,
+    ) {
+        // Just send new messages, instead of doing everything manually here.
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(Message::CreateEffect {
+                kind: if hit.actor.is_some() {
+                    EffectKind::BloodSpray
+                } else {
+                    impact_effect
+                },
+                position: hit.position,
+                orientation: vector_to_quat(hit.normal),
+            })
+            .unwrap();
+
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(Message::PlayEnvironmentSound {
+                collider: hit.collider,
+                feature: hit.feature,
+                position: hit.position,
+                sound_kind: SoundKind::Impact,
+                gain: 1.0,
+                rolloff_factor: 1.0,
+                radius: 0.5,
+            })
+            .unwrap();
+
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(Message::DamageActor {
+                actor: hit.actor,
+                who: hit.who,
+                amount: damage
+                    .scale(hit.hit_box.map_or(1.0, |h| h.damage_factor))
+                    .amount(),
+                damage_type,
+                critical: hit.hit_box.map_or(false, |h| h.critical),
+                hit_box: hit.hit_box.map(|h| h.collider),
+            })
+            .unwrap();
+
+        let dir = hit.position - begin;
+
+        if let Some(collider) = scene.physics.colliders.get(hit.collider.into()) {
+            scene
+                .physics
+                .bodies
+                .get_mut(collider.parent())
+                .unwrap()
+                .apply_force_at_point(
+                    dir.try_normalize(std::f32::EPSILON)
+                        .unwrap_or_default()
+                        .scale(30.0),
+                    Point3::from(hit.position),
+                    true,
+                );
+        }
+
+        if hit.actor.is_some() {
+            if let Actor::Bot(actor) = self.actors.get_mut(hit.actor) {
+                let body = scene
+                    .physics
+                    .colliders
+                    .get(hit.collider.into())
+                    .unwrap()
+                    .parent();
+                actor
+                    .impact_handler
+                    .handle_impact(scene, body.into(), hit.position, dir);
+            }
+        }
+    }
+
     fn apply_splash_damage(
         &mut self,
         engine: &mut GameEngine,
@@ -1252,23 +1998,71 @@ impl BaseLevel {
         radius: f32,
         center: Vector3<f32>,
         who: Handle<Actor>,
+        damage_type: DamageType,
+        effect: Option<StatusEffect>,
     ) {
-        let scene = &mut engine.scenes[self.scene];
-        // Just find out actors which must be damaged and re-cast damage message for each.
-        for (actor_handle, actor) in self.actors.pair_iter() {
-            // TODO: Add occlusion test. This will hit actors through walls.
-            let position = actor.position(&scene.graph);
-            if position.metric_distance(&center) <= radius {
+        let scene = &engine.scenes[self.scene];
+        self.shake_camera(
+            &scene.graph,
+            center,
+            1.0,
+            radius * SPLASH_SHAKE_RADIUS_FACTOR,
+        );
+
+        // Just find out actors which must be damaged and damage each of them.
+        for actor in self.actors.actors_in_radius(scene, center, radius, true) {
+            let position = self.actors.get(actor).position(&scene.graph);
+
+            // Allies and invulnerable actors are not affected by the blast at all.
+            if self.damage_actor(engine, actor, who, amount, damage_type, false, None) <= 0.0 {
+                continue;
+            }
+
+            if let Some(effect) = effect {
                 self.sender
                     .as_ref()
                     .unwrap()
-                    .send(Message::DamageActor {
-                        actor: actor_handle,
-                        who,
-                        amount,
+                    .send(Message::ApplyStatusEffect {
+                        actor,
+                        effect: effect.with_source(who),
                     })
                     .unwrap();
             }
+
+            // Actors closer to the center are pushed harder.
+            let offset = position - center;
+            let k = 1.0 - (offset.norm() / radius).min(1.0);
+            if let Some(direction) = offset.try_normalize(std::f32::EPSILON) {
+                self.actors
+                    .get_mut(actor)
+                    .apply_knockback(direction.scale(SPLASH_KNOCKBACK * k));
+            }
+        }
+    }
+
+    /// Pushes an actor away from the attacker if a hit was heavy enough.
+    fn apply_hit_knockback(
+        &mut self,
+        engine: &GameEngine,
+        actor: Handle<Actor>,
+        who: Handle<Actor>,
+        amount: f32,
+    ) {
+        if amount < HEAVY_HIT_DAMAGE
+            || who.is_none()
+            || actor == who
+            || !self.actors.contains(actor)
+            || !self.actors.contains(who)
+        {
+            return;
+        }
+
+        let graph = &engine.scenes[self.scene].graph;
+        let offset = self.actors.get(actor).position(graph) - self.actors.get(who).position(graph);
+        if let Some(direction) = offset.try_normalize(std::f32::EPSILON) {
+            self.actors
+                .get_mut(actor)
+                .apply_knockback(direction.scale(HEAVY_HIT_KNOCKBACK * amount / HEAVY_HIT_DAMAGE));
         }
     }
 
@@ -1277,6 +2071,7 @@ impl BaseLevel {
         engine: &mut GameEngine,
         message: &Message,
         time: GameTime,
+        custom_handlers: &mut CustomMessageHandlers,
     ) {
         self.sound_manager
             .handle_message(engine.resource_manager.clone(), &message)
@@ -1286,12 +2081,39 @@ impl BaseLevel {
             &Message::GiveNewWeapon { actor, kind } => {
                 self.give_new_weapon(engine, actor, kind).await;
             }
-            Message::AddBot {
+            // Spawn requests beyond the limit are rejected, so misconfigured waves or console
+            // spam won't bring the game to its knees.
+            &Message::AddBot { .. } | &Message::SpawnBot { .. } if self.is_bot_limit_reached() => {
+                Log::writeln(
+                    MessageKind::Warning,
+                    format!(
+                        "Unable to spawn a bot: limit of {} live bots is reached!",
+                        self.max_bots
+                    ),
+                );
+            }
+            &Message::SpawnBotAtRandomPoint { kind } if self.is_bot_limit_reached() => {
+                Log::writeln(
+                    MessageKind::Warning,
+                    format!(
+                        "Unable to spawn {}: limit of {} live bots is reached!",
+                        kind.description(),
+                        self.max_bots
+                    ),
+                );
+                if let Some(horde) = self.horde.as_mut() {
+                    horde.on_spawn_postponed(kind);
+                }
+            }
+            &Message::AddBot {
                 kind,
                 position,
                 rotation,
             } => {
-                self.add_bot(engine, *kind, *position, *rotation).await;
+                self.spawn_queue.push(
+                    PendingBot::new(kind, position, rotation, false, self.time),
+                    &engine.resource_manager,
+                );
             }
             &Message::RemoveActor { actor } => self.remove_actor(engine, actor).await,
             &Message::UseItem { actor, kind } => {
@@ -1300,8 +2122,16 @@ impl BaseLevel {
             &Message::PickUpItem { actor, item } => {
                 self.pickup_item(engine, actor, item).await;
             }
-            &Message::ShootWeapon { weapon, direction } => {
-                self.shoot_weapon(engine, weapon, time, direction).await
+            &Message::LootCorpse { actor, corpse } => {
+                self.loot_corpse(engine, actor, corpse).await;
+            }
+            &Message::ShootWeapon {
+                weapon,
+                direction,
+                damage_scale,
+            } => {
+                self.shoot_weapon(engine, weapon, time, direction, damage_scale)
+                    .await
             }
             &Message::CreateProjectile {
                 kind,
@@ -1309,21 +2139,80 @@ impl BaseLevel {
                 direction,
                 initial_velocity,
                 owner,
+                damage_scale,
             } => {
-                self.create_projectile(engine, kind, position, direction, initial_velocity, owner)
-                    .await
+                self.create_projectile(
+                    engine,
+                    kind,
+                    position,
+                    direction,
+                    initial_velocity,
+                    owner,
+                    damage_scale,
+                )
+                .await
             }
             &Message::ShowWeapon { weapon, state } => self.show_weapon(engine, weapon, state),
             &Message::SpawnBot { spawn_point_id } => {
                 if let Some(spawn_point) = self.spawn_points.get_mut(spawn_point_id) {
-                    spawn_bot(
-                        spawn_point,
-                        &mut self.actors,
-                        engine.resource_manager.clone(),
-                        self.sender.clone().unwrap(),
-                        &mut engine.scenes[self.scene],
-                    )
-                    .await;
+                    let kind = spawn_point.random_kind(&mut self.rng);
+                    spawn_point.set_spawned();
+                    self.spawn_queue.push(
+                        PendingBot::new(
+                            kind,
+                            spawn_point.position,
+                            spawn_point.rotation,
+                            false,
+                            self.time,
+                        ),
+                        &engine.resource_manager,
+                    );
+                } else {
+                    Log::writeln(
+                        MessageKind::Warning,
+                        format!(
+                            "Unable to spawn bot: invalid spawn point {}!",
+                            spawn_point_id
+                        ),
+                    );
+                }
+            }
+            &Message::SpawnBotAtRandomPoint { kind } => {
+                let player_position = if self.actors.contains(self.player) {
+                    self.actors
+                        .get(self.player)
+                        .position(&engine.scenes[self.scene].graph)
+                } else {
+                    Default::default()
+                };
+                if let Some(spawn_point) = self.spawn_points.random_point_for(
+                    kind,
+                    player_position,
+                    MIN_SPAWN_DISTANCE_TO_PLAYER,
+                    &mut self.rng,
+                ) {
+                    spawn_point.set_spawned();
+                    self.spawn_queue.push(
+                        PendingBot::new(
+                            kind,
+                            spawn_point.position,
+                            spawn_point.rotation,
+                            true,
+                            self.time,
+                        ),
+                        &engine.resource_manager,
+                    );
+                } else {
+                    Log::writeln(
+                        MessageKind::Warning,
+                        format!(
+                            "Unable to spawn {}: there is no suitable spawn point!",
+                            kind.description()
+                        ),
+                    );
+                    if let Some(horde) = self.horde.as_mut() {
+                        horde.on_spawn_failed();
+                    }
                 }
             }
             &Message::ApplySplashDamage {
@@ -1331,10 +2220,109 @@ impl BaseLevel {
                 radius,
                 center,
                 who,
-            } => self.apply_splash_damage(engine, amount, radius, center, who),
-            &Message::DamageActor { actor, who, amount } => {
-                self.damage_actor(engine, actor, who, amount);
+                damage_type,
+                effect,
+            } => self.apply_splash_damage(engine, amount, radius, center, who, damage_type, effect),
+            &Message::DamageActor {
+                actor,
+                who,
+                amount,
+                damage_type,
+                critical,
+                hit_box,
+            } => {
+                if self.damage_actor(engine, actor, who, amount, damage_type, critical, hit_box)
+                    > 0.0
+                {
+                    self.apply_hit_knockback(engine, actor, who, amount);
+                }
             }
+            &Message::GrenadeWarning {
+                position,
+                time,
+                radius,
+            } => {
+                let graph = &engine.scenes[self.scene].graph;
+                for actor in self.actors.iter_mut() {
+                    if let Actor::Bot(bot) = actor {
+                        bot.on_grenade_warning(graph, position, time, radius, &mut self.rng);
+                    }
+                }
+            }
+            &Message::BotScream {
+                bot,
+                position,
+                loudness,
+                target_position,
+            } => {
+                if self.actors.contains(bot) {
+                    let faction = self.actors.get(bot).faction;
+                    let scene = &engine.scenes[self.scene];
+                    for handle in self
+                        .actors
+                        .actors_in_radius(scene, position, loudness, false)
+                    {
+                        if let (true, Actor::Bot(other)) =
+                            (handle != bot, self.actors.get_mut(handle))
+                        {
+                            other.on_scream(faction, target_position);
+                        }
+                    }
+                }
+            }
+            &Message::SetGodMode { enabled } => {
+                if self.actors.contains(self.player) {
+                    if let Actor::Player(player) = self.actors.get_mut(self.player) {
+                        player.set_god_mode(enabled);
+                    }
+                }
+            }
+            &Message::SetShadowCulling { enabled } => {
+                self.lights.set_shadow_culling(enabled);
+            }
+            &Message::SetNoclip { enabled } => {
+                if self.actors.contains(self.player) {
+                    if let Actor::Player(player) = self.actors.get_mut(self.player) {
+                        player.set_noclip(&mut engine.scenes[self.scene].physics, enabled);
+                    }
+                }
+            }
+            &Message::TeleportActor { actor, position } => {
+                if self.actors.contains(actor) {
+                    let scene = &mut engine.scenes[self.scene];
+                    self.actors
+                        .get_mut(actor)
+                        .set_position(&mut scene.physics, position);
+                }
+            }
+            &Message::ApplyStatusEffect { actor, effect } => {
+                if self.actors.contains(actor) {
+                    self.actors.get_mut(actor).apply_status_effect(effect);
+                }
+            }
+            Message::AdvanceObjective => {
+                self.objectives.advance();
+                // Reaching an objective is a checkpoint.
+                self.sender
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::Autosave)
+                    .unwrap();
+            }
+            &Message::ActorDied { actor, who } => {
+                self.leader_board.on_actor_died(actor, who);
+                if let Some(horde) = self.horde.as_mut() {
+                    horde.on_actor_died(actor);
+                }
+                if who.is_some() && who == self.player && actor != who && !self.is_fight_going() {
+                    self.sender
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::FinalKill)
+                        .unwrap();
+                }
+            }
+            &Message::SeverHitBox { actor, hit_box } => self.sever_hit_box(engine, actor, hit_box),
             &Message::CreateEffect {
                 kind,
                 position,
@@ -1346,6 +2334,9 @@ impl BaseLevel {
                     engine.resource_manager.clone(),
                     position,
                     orientation,
+                    self.gore,
+                    &mut self.particle_budget,
+                    &mut self.effect_pool,
                 );
             }
             &Message::SpawnItem {
@@ -1358,8 +2349,18 @@ impl BaseLevel {
                 begin,
                 end,
                 damage,
+                damage_type,
+                impact_effect,
             } => {
-                self.shoot_ray(engine, *weapon, *begin, *end, *damage);
+                self.shoot_ray(
+                    engine,
+                    *weapon,
+                    *begin,
+                    *end,
+                    *damage,
+                    *damage_type,
+                    *impact_effect,
+                );
             }
             &Message::GrabWeapon { kind, actor } => {
                 if self.actors.contains(actor) {
@@ -1372,9 +2373,18 @@ impl BaseLevel {
                     self.weapons[weapon].switch_flash_light(&mut engine.scenes[self.scene].graph);
                 }
             }
+            &Message::SwitchLaserSight { weapon } => {
+                if self.weapons.contains(weapon) {
+                    self.weapons[weapon].switch_laser_sight(&mut engine.scenes[self.scene].graph);
+                }
+            }
             &Message::DropItems { actor, item, count } => {
                 self.drop_items(engine, actor, item, count).await;
             }
+            Message::Custom(custom) => {
+                let sender = self.sender.clone().unwrap();
+                custom_handlers.dispatch(custom, self, engine, &sender);
+            }
             _ => (),
         }
     }
@@ -1462,41 +2472,13 @@ impl BaseLevel {
         for death_zone in self.death_zones.iter() {
             drawing_context.draw_aabb(&death_zone.bounds, Color::opaque(0, 0, 200));
         }
-    }
-}
-
-pub struct SpawnPoint {
-    position: Vector3<f32>,
-    rotation: UnitQuaternion<f32>,
-    bot_kind: BotKind,
-    spawned: bool,
-}
 
-impl Default for SpawnPoint {
-    fn default() -> Self {
-        Self {
-            position: Default::default(),
-            rotation: Default::default(),
-            bot_kind: BotKind::Zombie,
-            spawned: false,
+        for damage_volume in self.damage_volumes.iter() {
+            drawing_context.draw_aabb(&damage_volume.bounds, Color::opaque(200, 100, 0));
         }
-    }
-}
 
-impl Visit for SpawnPoint {
-    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
-        visitor.enter_region(name)?;
-
-        self.position.visit("Position", visitor)?;
-        self.rotation.visit("Rotation", visitor)?;
-        self.spawned.visit("Spawned", visitor)?;
-
-        let mut kind_id = self.bot_kind.id();
-        kind_id.visit("BotKind", visitor)?;
-        if visitor.is_reading() {
-            self.bot_kind = BotKind::from_id(kind_id)?;
+        for ladder in self.ladders.iter() {
+            drawing_context.draw_aabb(&ladder.bounds, Color::opaque(0, 200, 200));
         }
-
-        visitor.leave_region()
     }
 }
@@ -0,0 +1,212 @@
+//! Objectives guide the player through a level, only one objective is active at a time.
+//! Objectives of each level are listed in `data/configs/objectives.ron` by path of the map,
+//! location of an objective is marked by a node of the map with given name or tag. An
+//! objective is completed when the player reaches it, manual objectives are completed only
+//! by `AdvanceObjective` message. Objectives with hold time (planting a charge, hacking a
+//! terminal) require the player to hold action control at them, any damage or releasing the
+//! control cancels holding.
+
+use rg3d::{
+    core::{
+        algebra::Vector3,
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    lazy_static::lazy_static,
+    scene::graph::Graph,
+    utils::log::{Log, MessageKind},
+};
+use serde::Deserialize;
+use std::{collections::HashMap, fs::File};
+
+#[derive(Deserialize)]
+pub struct ObjectiveDefinition {
+    pub description: String,
+    /// Name or tag of a node of the map that marks location of the objective.
+    pub marker: String,
+    #[serde(default)]
+    pub manual: bool,
+    /// Time (in seconds) for which the player must hold action control at the objective to
+    /// complete it, zero means that reaching the objective is enough.
+    #[serde(default)]
+    pub hold_time: f32,
+}
+
+#[derive(Deserialize, Default)]
+pub struct ObjectiveDefinitionContainer {
+    /// Objectives of levels by path of the map, in order of completion.
+    levels: HashMap<String, Vec<ObjectiveDefinition>>,
+}
+
+impl ObjectiveDefinitionContainer {
+    pub fn new() -> Self {
+        let file = File::open("data/configs/objectives.ron").unwrap();
+        ron::de::from_reader(file).unwrap()
+    }
+}
+
+lazy_static! {
+    static ref DEFINITIONS: ObjectiveDefinitionContainer = ObjectiveDefinitionContainer::new();
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct Objective {
+    pub description: String,
+    pub position: Vector3<f32>,
+    pub manual: bool,
+    pub hold_time: f32,
+}
+
+impl Visit for Objective {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.description.visit("Description", visitor)?;
+        self.position.visit("Position", visitor)?;
+        self.manual.visit("Manual", visitor)?;
+        self.hold_time.visit("HoldTime", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+#[derive(Default)]
+pub struct ObjectiveTracker {
+    objectives: Vec<Objective>,
+    /// Index of current objective, equals to amount of objectives when all of them are
+    /// completed.
+    current: u32,
+    /// Time for which the player holds action control at current objective. Not saved,
+    /// holding is cancelled on load.
+    hold_progress: f32,
+    /// Health of the player on previous update, used to detect damage that cancels holding.
+    last_health: f32,
+}
+
+impl ObjectiveTracker {
+    /// Distance (in meters) at which the player reaches an objective.
+    const REACH_DISTANCE: f32 = 2.0;
+
+    pub fn new(map: &str, graph: &Graph) -> Self {
+        let objectives = DEFINITIONS
+            .levels
+            .get(map)
+            .map(|definitions| {
+                definitions
+                    .iter()
+                    .filter_map(|definition| {
+                        let marker = graph.pair_iter().find(|(_, node)| {
+                            node.name() == definition.marker || node.tag() == definition.marker
+                        });
+                        match marker {
+                            Some((_, node)) => Some(Objective {
+                                description: definition.description.clone(),
+                                position: node.global_position(),
+                                manual: definition.manual,
+                                hold_time: definition.hold_time,
+                            }),
+                            None => {
+                                Log::writeln(
+                                    MessageKind::Warning,
+                                    format!(
+                                        "Marker {} of objective \"{}\" is not found in {}!",
+                                        definition.marker, definition.description, map
+                                    ),
+                                );
+                                None
+                            }
+                        }
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            objectives,
+            current: 0,
+            hold_progress: 0.0,
+            last_health: 0.0,
+        }
+    }
+
+    pub fn current(&self) -> Option<&Objective> {
+        self.objectives.get(self.current as usize)
+    }
+
+    pub fn advance(&mut self) {
+        if self.current().is_some() {
+            self.current += 1;
+        }
+    }
+
+    fn is_in_reach(objective: &Objective, position: Vector3<f32>) -> bool {
+        objective.position.metric_distance(&position) <= Self::REACH_DISTANCE
+    }
+
+    /// Whether current objective is completed by reaching given position.
+    pub fn is_reached(&self, position: Vector3<f32>) -> bool {
+        self.current().map_or(false, |objective| {
+            !objective.manual
+                && objective.hold_time <= 0.0
+                && Self::is_in_reach(objective, position)
+        })
+    }
+
+    /// Advances holding of action control at current objective, returns true when the
+    /// objective is held long enough to be completed.
+    pub fn update_hold(
+        &mut self,
+        position: Vector3<f32>,
+        holding: bool,
+        health: f32,
+        dt: f32,
+    ) -> bool {
+        let damaged = health < self.last_health;
+        self.last_health = health;
+
+        let hold_time = match self.current() {
+            Some(objective)
+                if !objective.manual
+                    && objective.hold_time > 0.0
+                    && Self::is_in_reach(objective, position) =>
+            {
+                objective.hold_time
+            }
+            _ => 0.0,
+        };
+
+        if hold_time <= 0.0 || !holding || damaged {
+            self.hold_progress = 0.0;
+            return false;
+        }
+
+        self.hold_progress += dt;
+        if self.hold_progress >= hold_time {
+            self.hold_progress = 0.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns progress of holding at current objective in [0; 1] range, or none if the
+    /// player doesn't hold action control at it.
+    pub fn hold_progress(&self) -> Option<f32> {
+        let objective = self.current()?;
+        if self.hold_progress > 0.0 && objective.hold_time > 0.0 {
+            Some((self.hold_progress / objective.hold_time).min(1.0))
+        } else {
+            None
+        }
+    }
+}
+
+impl Visit for ObjectiveTracker {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.objectives.visit("Objectives", visitor)?;
+        self.current.visit("Current", visitor)?;
+
+        visitor.leave_region()
+    }
+}
@@ -0,0 +1,27 @@
+//! Global physics parameters of levels, taken from `data/configs/physics.ron`.
+
+use rg3d::{core::algebra::Vector3, lazy_static::lazy_static, scene::physics::Physics};
+use serde::Deserialize;
+use std::fs::File;
+
+#[derive(Deserialize)]
+pub struct PhysicsDefinition {
+    /// Acceleration of free fall (in meters per second squared), directed down.
+    pub gravity: f32,
+}
+
+impl PhysicsDefinition {
+    pub fn new() -> Self {
+        let file = File::open("data/configs/physics.ron").unwrap();
+        ron::de::from_reader(file).unwrap()
+    }
+}
+
+lazy_static! {
+    static ref DEFINITION: PhysicsDefinition = PhysicsDefinition::new();
+}
+
+/// Applies parameters from the definition to physics of a level.
+pub fn apply_definition(physics: &mut Physics) {
+    physics.gravity = Vector3::new(0.0, -DEFINITION.gravity, 0.0);
+}
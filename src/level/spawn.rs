@@ -0,0 +1,246 @@
+//! Spawn points are places on a level where bots can appear. They're taken from level data:
+//! nodes named `Zombie`, `Mutant` or `Parasite` will spawn bot of respective kind at start of
+//! the level, and nodes with `BotSpawnPoint:Kind:Kind:..` tag are used to spawn bots later on
+//! (by `SpawnBot` or `SpawnBotAtRandomPoint` messages). Empty list of kinds in tag means that
+//! any kind of bot can be spawned on such point.
+//!
+//! Bots that are spawned during the game are put in [`SpawnQueue`] first, they appear on the
+//! level only when their models and animations are loaded, so spawning won't stall a frame.
+
+use crate::bot::{Bot, BotKind};
+use rg3d::{
+    core::{
+        algebra::{UnitQuaternion, Vector3},
+        rand::{
+            seq::{IteratorRandom, SliceRandom},
+            Rng,
+        },
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    engine::resource_manager::ResourceManager,
+    resource::{model::Model, ResourceState},
+    scene::node::Node,
+    utils::log::{Log, MessageKind},
+};
+
+#[derive(Default)]
+pub struct SpawnPoint {
+    pub position: Vector3<f32>,
+    pub rotation: UnitQuaternion<f32>,
+    allowed_kinds: Vec<BotKind>,
+    /// Whether bot should be spawned on this point at the start of a level.
+    auto_spawn: bool,
+    spawned: bool,
+}
+
+impl SpawnPoint {
+    pub fn new(node: &Node, allowed_kinds: Vec<BotKind>, auto_spawn: bool) -> Self {
+        Self {
+            position: node.global_position(),
+            rotation: **node.local_transform().rotation(),
+            allowed_kinds,
+            auto_spawn,
+            spawned: false,
+        }
+    }
+
+    /// Parses list of allowed bot kinds from a tag in `BotSpawnPoint:Kind:Kind:..` format.
+    pub fn from_tag(node: &Node, tag: &str) -> Self {
+        let mut allowed_kinds = Vec::new();
+
+        for name in tag.split(':').skip(1).filter(|n| !n.is_empty()) {
+            match BotKind::from_description(name) {
+                Ok(kind) => allowed_kinds.push(kind),
+                Err(e) => Log::writeln(
+                    MessageKind::Warning,
+                    format!("Invalid spawn point tag {}: {}", tag, e),
+                ),
+            }
+        }
+
+        if allowed_kinds.is_empty() {
+            allowed_kinds = BotKind::ALL.to_vec();
+        }
+
+        Self::new(node, allowed_kinds, false)
+    }
+
+    pub fn allows(&self, kind: BotKind) -> bool {
+        self.allowed_kinds.contains(&kind)
+    }
+
+    /// Returns random kind of bot that allowed on this spawn point.
+    pub fn random_kind<R: Rng>(&self, rng: &mut R) -> BotKind {
+        self.allowed_kinds.choose(rng).copied().unwrap_or_default()
+    }
+
+    pub fn set_spawned(&mut self) {
+        self.spawned = true;
+    }
+}
+
+impl Visit for SpawnPoint {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.position.visit("Position", visitor)?;
+        self.rotation.visit("Rotation", visitor)?;
+        self.spawned.visit("Spawned", visitor)?;
+        self.allowed_kinds.visit("AllowedKinds", visitor)?;
+        self.auto_spawn.visit("AutoSpawn", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+#[derive(Default)]
+pub struct SpawnPointContainer {
+    points: Vec<SpawnPoint>,
+}
+
+impl SpawnPointContainer {
+    pub fn add(&mut self, spawn_point: SpawnPoint) {
+        self.points.push(spawn_point);
+    }
+
+    pub fn get_mut(&mut self, id: usize) -> Option<&mut SpawnPoint> {
+        self.points.get_mut(id)
+    }
+
+    pub fn auto_spawn_points_mut(&mut self) -> impl Iterator<Item = &mut SpawnPoint> {
+        self.points.iter_mut().filter(|pt| pt.auto_spawn)
+    }
+
+    /// Picks random spawn point that allows given kind of bot and which is far enough
+    /// from given position, so bots won't appear right in front of the player.
+    pub fn random_point_for<R: Rng>(
+        &mut self,
+        kind: BotKind,
+        away_from: Vector3<f32>,
+        min_distance: f32,
+        rng: &mut R,
+    ) -> Option<&mut SpawnPoint> {
+        self.points
+            .iter_mut()
+            .filter(|pt| pt.allows(kind) && pt.position.metric_distance(&away_from) >= min_distance)
+            .choose(rng)
+    }
+}
+
+impl Visit for SpawnPointContainer {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.points.visit("Points", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+#[derive(Default)]
+pub struct PendingBot {
+    pub kind: BotKind,
+    pub position: Vector3<f32>,
+    pub rotation: UnitQuaternion<f32>,
+    /// Whether the bot must be counted by horde controller once it is spawned.
+    pub horde: bool,
+    /// Level time at which the bot was queued.
+    pub queued_at: f32,
+    resources: Vec<Model>,
+}
+
+impl PendingBot {
+    pub fn new(
+        kind: BotKind,
+        position: Vector3<f32>,
+        rotation: UnitQuaternion<f32>,
+        horde: bool,
+        queued_at: f32,
+    ) -> Self {
+        Self {
+            kind,
+            position,
+            rotation,
+            horde,
+            queued_at,
+            resources: Default::default(),
+        }
+    }
+
+    fn request_resources(&mut self, resource_manager: &ResourceManager) {
+        self.resources = Bot::get_definition(self.kind)
+            .resource_paths()
+            .into_iter()
+            .map(|path| resource_manager.request_model(path))
+            .collect();
+    }
+
+    /// Returns true if every resource has finished loading, failed resources are counted as
+    /// loaded, `Bot::new` will report them.
+    fn is_ready(&self) -> bool {
+        self.resources
+            .iter()
+            .all(|r| !matches!(*r.state(), ResourceState::Pending { .. }))
+    }
+}
+
+impl Visit for PendingBot {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.kind.visit("Kind", visitor)?;
+        self.position.visit("Position", visitor)?;
+        self.rotation.visit("Rotation", visitor)?;
+        self.horde.visit("Horde", visitor)?;
+        self.queued_at.visit("QueuedAt", visitor)?;
+
+        // Resources are not saved, they are requested again on next update.
+
+        visitor.leave_region()
+    }
+}
+
+#[derive(Default)]
+pub struct SpawnQueue {
+    pending: Vec<PendingBot>,
+}
+
+impl SpawnQueue {
+    pub fn push(&mut self, mut bot: PendingBot, resource_manager: &ResourceManager) {
+        bot.request_resources(resource_manager);
+        self.pending.push(bot);
+    }
+
+    pub fn count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns bots whose resources are loaded, they can be instantiated without waiting.
+    pub fn take_ready(&mut self, resource_manager: &ResourceManager) -> Vec<PendingBot> {
+        let mut ready = Vec::new();
+        let mut i = 0;
+        while i < self.pending.len() {
+            let bot = &mut self.pending[i];
+            if bot.resources.is_empty() {
+                // Queue was loaded from a save.
+                bot.request_resources(resource_manager);
+            }
+            if bot.is_ready() {
+                ready.push(self.pending.remove(i));
+            } else {
+                i += 1;
+            }
+        }
+        ready
+    }
+}
+
+impl Visit for SpawnQueue {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.pending.visit("Pending", visitor)?;
+
+        visitor.leave_region()
+    }
+}
@@ -1,34 +1,216 @@
 use rg3d::{
     core::{
+        color::Color,
+        math::frustum::Frustum,
         pool::Handle,
         pool::Pool,
         rand::Rng,
         visitor::{Visit, VisitResult, Visitor},
     },
     rand::thread_rng,
-    scene::{node::Node, Scene},
+    renderer::QualitySettings,
+    scene::{graph::Graph, light::Light as SceneLight, node::Node, Scene},
+    utils::log::{Log, MessageKind},
 };
 
+/// Noise-driven change of light intensity, used to make broken lights stutter.
+#[derive(Copy, Clone, Debug)]
+pub struct Flicker {
+    /// How many times per second intensity changes.
+    pub frequency: f32,
+    pub min_intensity: f32,
+    pub max_intensity: f32,
+}
+
+impl Default for Flicker {
+    fn default() -> Self {
+        Self {
+            frequency: 8.0,
+            min_intensity: 0.1,
+            max_intensity: 1.0,
+        }
+    }
+}
+
+impl Flicker {
+    /// Parses flicker parameters from a tag in `FlickeringLight:frequency:min:max` format,
+    /// every parameter is optional.
+    pub fn from_tag(tag: &str) -> Self {
+        let mut flicker = Self::default();
+
+        for (i, param) in tag.split(':').skip(1).enumerate() {
+            match param.parse::<f32>() {
+                Ok(value) => match i {
+                    0 => flicker.frequency = value,
+                    1 => flicker.min_intensity = value,
+                    2 => flicker.max_intensity = value,
+                    _ => (),
+                },
+                Err(_) => Log::writeln(
+                    MessageKind::Warning,
+                    format!("Invalid flicker parameter {} in {} tag!", param, tag),
+                ),
+            }
+        }
+
+        flicker
+    }
+
+    pub fn intensity(&self, time: f32) -> f32 {
+        let t = time * self.frequency;
+        // Two octaves of noise, second one adds small fast stutters.
+        let noise = (0.7 * value_noise(t) + 0.3 * value_noise(t * 3.7 + 17.0)).min(1.0);
+        self.min_intensity + (self.max_intensity - self.min_intensity) * noise
+    }
+}
+
+impl Visit for Flicker {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.frequency.visit("Frequency", visitor)?;
+        self.min_intensity.visit("MinIntensity", visitor)?;
+        self.max_intensity.visit("MaxIntensity", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+fn hash(n: i32) -> f32 {
+    let mut x = n as u32;
+    x = (x ^ 61) ^ (x >> 16);
+    x = x.wrapping_add(x << 3);
+    x ^= x >> 4;
+    x = x.wrapping_mul(0x27d4_eb2d);
+    x ^= x >> 15;
+    x as f32 / u32::MAX as f32
+}
+
+/// Smooth 1D noise in [0; 1] range.
+fn value_noise(t: f32) -> f32 {
+    let i = t.floor();
+    let f = t - i;
+    let k = f * f * (3.0 - 2.0 * f);
+    let a = hash(i as i32);
+    let b = hash(i as i32 + 1);
+    a + (b - a) * k
+}
+
+pub enum LightKind {
+    /// Randomly turns on and off.
+    Flashing,
+    Flickering {
+        flicker: Flicker,
+        base_color: Color,
+    },
+}
+
+impl Default for LightKind {
+    fn default() -> Self {
+        Self::Flashing
+    }
+}
+
+impl LightKind {
+    fn id(&self) -> u32 {
+        match self {
+            LightKind::Flashing => 0,
+            LightKind::Flickering { .. } => 1,
+        }
+    }
+
+    fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(Self::Flashing),
+            1 => Ok(Self::Flickering {
+                flicker: Default::default(),
+                base_color: Color::WHITE,
+            }),
+            _ => Err(format!("Invalid light kind id {}!", id)),
+        }
+    }
+}
+
+impl Visit for LightKind {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut id = self.id();
+        id.visit("Id", visitor)?;
+        if visitor.is_reading() {
+            *self = Self::from_id(id)?;
+        }
+
+        if let LightKind::Flickering {
+            flicker,
+            base_color,
+        } = self
+        {
+            flicker.visit("Flicker", visitor)?;
+            base_color.visit("BaseColor", visitor)?;
+        }
+
+        visitor.leave_region()
+    }
+}
+
 #[derive(Default)]
 pub struct Light {
     node: Handle<Node>,
     timer: f32,
+    kind: LightKind,
 }
 
 impl Light {
     pub fn new(node: Handle<Node>) -> Self {
-        Self { node, timer: 0.0 }
+        Self {
+            node,
+            timer: 0.0,
+            kind: LightKind::Flashing,
+        }
+    }
+
+    pub fn new_flickering(node: Handle<Node>, flicker: Flicker, graph: &Graph) -> Self {
+        Self {
+            node,
+            timer: 0.0,
+            kind: LightKind::Flickering {
+                flicker,
+                base_color: graph[node].as_light().color(),
+            },
+        }
     }
 
     pub fn update(&mut self, scene: &mut Scene, dt: f32) {
-        self.timer -= dt;
+        match self.kind {
+            LightKind::Flashing => {
+                self.timer -= dt;
 
-        if self.timer < 0.0 {
-            let node = &mut scene.graph[self.node];
-            let new_visibility = !node.visibility();
-            node.set_visibility(new_visibility);
+                if self.timer < 0.0 {
+                    let node = &mut scene.graph[self.node];
+                    let new_visibility = !node.visibility();
+                    node.set_visibility(new_visibility);
 
-            self.timer = thread_rng().gen_range(0.1..0.5);
+                    self.timer = thread_rng().gen_range(0.1..0.5);
+                }
+            }
+            LightKind::Flickering {
+                flicker,
+                base_color,
+            } => {
+                // Timer is used as time accumulator here.
+                self.timer += dt;
+
+                let k = flicker.intensity(self.timer).max(0.0);
+                let scale = |c: u8| (c as f32 * k).min(255.0) as u8;
+                scene.graph[self.node]
+                    .as_light_mut()
+                    .set_color(Color::opaque(
+                        scale(base_color.r),
+                        scale(base_color.g),
+                        scale(base_color.b),
+                    ));
+            }
         }
     }
 }
@@ -39,14 +221,48 @@ impl Visit for Light {
 
         self.node.visit("Node", visitor)?;
         self.timer.visit("Timer", visitor)?;
+        self.kind.visit("Kind", visitor)?;
 
         visitor.leave_region()
     }
 }
 
+/// Point or spot light of a level together with its shadows setting as the level was
+/// designed. Shadows are switched on and off depending on camera position, so original
+/// setting must be remembered.
 #[derive(Default)]
+struct ShadowCaster {
+    node: Handle<Node>,
+    cast_shadows: bool,
+}
+
+impl Visit for ShadowCaster {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.node.visit("Node", visitor)?;
+        self.cast_shadows.visit("CastShadows", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
 pub struct LightContainer {
     lights: Pool<Light>,
+    shadow_casters: Vec<ShadowCaster>,
+    /// Whether shadows are culled, it can be switched off from debug console to compare
+    /// frame time with and without culling. It is not saved.
+    shadow_culling: bool,
+}
+
+impl Default for LightContainer {
+    fn default() -> Self {
+        Self {
+            lights: Default::default(),
+            shadow_casters: Default::default(),
+            shadow_culling: true,
+        }
+    }
 }
 
 impl LightContainer {
@@ -54,11 +270,75 @@ impl LightContainer {
         let _ = self.lights.spawn(light);
     }
 
+    pub fn add_shadow_caster(&mut self, node: Handle<Node>, cast_shadows: bool) {
+        self.shadow_casters
+            .push(ShadowCaster { node, cast_shadows });
+    }
+
+    pub fn is_shadow_culling(&self) -> bool {
+        self.shadow_culling
+    }
+
+    pub fn set_shadow_culling(&mut self, enabled: bool) {
+        self.shadow_culling = enabled;
+    }
+
     pub fn update(&mut self, scene: &mut Scene, dt: f32) {
         for light in self.lights.iter_mut() {
             light.update(scene, dt);
         }
     }
+
+    /// Disables shadows of lights that are further than shadow distance from the camera or
+    /// whose light volume is out of the camera frustum, the renderer then skips their shadow
+    /// maps entirely. Lights that were designed without shadows are left as is.
+    pub fn cull_shadows(
+        &self,
+        scene: &mut Scene,
+        camera: Handle<Node>,
+        settings: &QualitySettings,
+    ) {
+        let (camera_position, frustum) = match &scene.graph[camera] {
+            Node::Camera(camera) => (
+                camera.global_position(),
+                Frustum::from(camera.view_projection_matrix()),
+            ),
+            _ => return,
+        };
+
+        for caster in self.shadow_casters.iter().filter(|c| c.cast_shadows) {
+            if let Node::Light(light) = &mut scene.graph[caster.node] {
+                let position = light.global_position();
+                let (radius, shadows_distance) = match light {
+                    SceneLight::Point(point) => (point.radius(), settings.point_shadows_distance),
+                    SceneLight::Spot(spot) => (spot.distance(), settings.spot_shadows_distance),
+                    SceneLight::Directional(_) => continue,
+                };
+
+                let in_range = position.metric_distance(&camera_position) <= shadows_distance;
+                let in_frustum = frustum
+                    .as_ref()
+                    .map_or(true, |f| f.is_intersects_sphere(position, radius));
+
+                let cast_shadows = in_range && in_frustum;
+                if light.is_cast_shadows() != cast_shadows {
+                    light.set_cast_shadows(cast_shadows);
+                }
+            }
+        }
+    }
+
+    /// Restores shadows of every light as the level was designed, it is used while shadows
+    /// are not culled.
+    pub fn restore_shadows(&self, scene: &mut Scene) {
+        for caster in self.shadow_casters.iter() {
+            if let Node::Light(light) = &mut scene.graph[caster.node] {
+                if light.is_cast_shadows() != caster.cast_shadows {
+                    light.set_cast_shadows(caster.cast_shadows);
+                }
+            }
+        }
+    }
 }
 
 impl Visit for LightContainer {
@@ -66,6 +346,7 @@ impl Visit for LightContainer {
         visitor.enter_region(name)?;
 
         self.lights.visit("Lights", visitor)?;
+        self.shadow_casters.visit("ShadowCasters", visitor)?;
 
         visitor.leave_region()
     }
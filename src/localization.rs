@@ -0,0 +1,97 @@
+//! String tables of UI. Each language has its own table in `data/localization` that maps
+//! keys to translated strings, English table is used for keys that are missing in the table
+//! of selected language. Strings are resolved when widgets are built, so changing language
+//! takes effect after restart.
+
+use rg3d::utils::log::{Log, MessageKind};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fs::File};
+
+#[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Language {
+    English,
+    German,
+}
+
+impl Default for Language {
+    fn default() -> Self {
+        Self::English
+    }
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::German];
+
+    /// Name of the language in the language itself, so everyone can find their language in
+    /// the list.
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::German => "Deutsch",
+        }
+    }
+
+    pub fn index(self) -> usize {
+        Self::ALL.iter().position(|l| *l == self).unwrap()
+    }
+
+    fn path(self) -> &'static str {
+        match self {
+            Language::English => "data/localization/english.ron",
+            Language::German => "data/localization/german.ron",
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+struct StringTableDefinition {
+    strings: HashMap<String, String>,
+}
+
+impl StringTableDefinition {
+    fn load(language: Language) -> Self {
+        let result = File::open(language.path())
+            .map_err(|e| e.to_string())
+            .and_then(|file| ron::de::from_reader(file).map_err(|e| e.to_string()));
+        match result {
+            Ok(definition) => definition,
+            Err(e) => {
+                Log::writeln(
+                    MessageKind::Error,
+                    format!(
+                        "Failed to load string table of {:?} language. Reason: {}",
+                        language, e
+                    ),
+                );
+                Default::default()
+            }
+        }
+    }
+}
+
+pub struct StringTable {
+    strings: HashMap<String, String>,
+    /// English strings, they're used for keys that are missing in the table.
+    fallback: HashMap<String, String>,
+}
+
+impl StringTable {
+    pub fn new(language: Language) -> Self {
+        let fallback = StringTableDefinition::load(Language::English).strings;
+        let strings = if language == Language::English {
+            Default::default()
+        } else {
+            StringTableDefinition::load(language).strings
+        };
+        Self { strings, fallback }
+    }
+
+    /// Returns string of given key, the key itself is returned if there is no such string
+    /// even in English table, so missing strings are easy to spot.
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.strings
+            .get(key)
+            .or_else(|| self.fallback.get(key))
+            .map_or(key, |s| s.as_str())
+    }
+}
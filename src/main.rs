@@ -14,27 +14,44 @@ pub mod effects;
 pub mod gui;
 pub mod inventory;
 pub mod item;
+pub mod leader_board;
 pub mod level;
 pub mod light;
+pub mod localization;
 pub mod menu;
 pub mod message;
 pub mod options_menu;
 pub mod player;
+pub mod slow_motion;
 pub mod sound;
+pub mod status_effect;
 pub mod utils;
 pub mod weapon;
 
 use crate::{
     actor::Actor,
     config::Config,
-    control_scheme::ControlScheme,
+    control_scheme::ControlButton,
     gui::{
-        inventory::InventoryInterface, item_display::ItemDisplay, weapon_display::WeaponDisplay,
-        BuildContext, CustomUiMessage, CustomUiNode, DeathScreen, GuiMessage, UiNode, UiNodeHandle,
+        compass::Compass,
+        console::{Console, ConsoleCommand},
+        crosshair::Crosshair,
+        damage_numbers::DamageNumbers,
+        hold_progress::HoldProgress,
+        inventory::InventoryInterface,
+        item_display::ItemDisplay,
+        make_hud_font,
+        objective_marker::ObjectiveMarker,
+        perf_overlay::{PerformanceCounters, PerformanceOverlay},
+        weapon_display::WeaponDisplay,
+        BuildContext, CustomUiMessage, CustomUiNode, DeathScreen, GuiMessage, HitMarker, HordeHud,
+        PauseMenu, SubtitleDisplay, UiNode, UiNodeHandle,
     },
     level::{arrival::ArrivalLevel, Level},
     menu::Menu,
-    message::Message,
+    message::{CustomMessageHandler, CustomMessageHandlers, Message},
+    slow_motion::SlowMotion,
+    utils::rng::GameRng,
 };
 use rg3d::{
     animation::{
@@ -42,11 +59,11 @@ use rg3d::{
         Animation,
     },
     core::{
-        algebra::{UnitQuaternion, Vector3},
+        algebra::{UnitQuaternion, Vector2, Vector3},
         pool::Handle,
         visitor::{Visit, VisitResult, Visitor},
     },
-    dpi::LogicalSize,
+    dpi::{LogicalSize, PhysicalPosition},
     engine::{resource_manager::ResourceManager, Engine},
     event::{ElementState, Event, VirtualKeyCode, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
@@ -69,6 +86,7 @@ use rg3d::{
         log::{Log, MessageKind},
         translate_event,
     },
+    window::Window,
 };
 use std::{
     collections::HashMap,
@@ -85,6 +103,9 @@ use std::{
 };
 
 const FIXED_FPS: f32 = 60.0;
+const SAVE_PATH: &str = "save.bin";
+/// Autosave is made on checkpoints, the player gets back to it after death.
+const AUTOSAVE_PATH: &str = "autosave.bin";
 
 // Define type aliases for engine structs.
 pub type GameEngine = Engine<CustomUiMessage, CustomUiNode>;
@@ -161,16 +182,42 @@ pub struct Game {
     debug_string: String,
     last_tick_time: time::Instant,
     running: bool,
-    control_scheme: ControlScheme,
+    /// User settings. Controls and settings that must be applied again to each created or
+    /// loaded level are kept up to date when they are changed in options, other settings
+    /// are applied directly to the engine and HUD.
+    config: Config,
     time: GameTime,
     events_receiver: Receiver<Message>,
     events_sender: Sender<Message>,
     load_context: Option<Arc<Mutex<LoadContext>>>,
     loading_screen: LoadingScreen,
     death_screen: DeathScreen,
+    pause_menu: PauseMenu,
     weapon_display: WeaponDisplay,
     inventory_interface: InventoryInterface,
     item_display: ItemDisplay,
+    horde_hud: HordeHud,
+    /// Whether last started game was in horde mode, used to restart a match.
+    horde_mode: bool,
+    subtitles: SubtitleDisplay,
+    /// Seed for gameplay randomness of new games, random seed is used if not set. Can be
+    /// set from command line using `--seed <number>` to reproduce a specific run.
+    rng_seed: Option<u64>,
+    hit_marker: HitMarker,
+    damage_numbers: DamageNumbers,
+    crosshair: Crosshair,
+    hold_progress: HoldProgress,
+    compass: Compass,
+    objective_marker: ObjectiveMarker,
+    performance_overlay: PerformanceOverlay,
+    console: Console,
+    /// Multiplier of time step of gameplay, can be changed only by a cheat of the console.
+    time_scale: f32,
+    slow_motion: SlowMotion,
+    custom_message_handlers: CustomMessageHandlers,
+    /// Time left until window size and position are saved, the timer restarts on each
+    /// resize or move so the config is not written many times while the window is dragged.
+    window_save_timer: Option<f32>,
 }
 
 struct LoadingScreen {
@@ -229,6 +276,37 @@ pub struct GameTime {
     delta: f32,
 }
 
+impl GameTime {
+    /// Smallest time step used for gameplay movement, it guards divisions by time step.
+    pub const MIN_MOVEMENT_DELTA: f32 = 1.0 / 240.0;
+    /// Largest time step used for gameplay movement, so frame spike or long pause won't
+    /// produce huge velocities.
+    pub const MAX_MOVEMENT_DELTA: f32 = 0.1;
+
+    /// Returns time step clamped to a sane range, it must be used for movement calculations
+    /// where something is divided by time step.
+    pub fn movement_delta(&self) -> f32 {
+        self.delta
+            .max(Self::MIN_MOVEMENT_DELTA)
+            .min(Self::MAX_MOVEMENT_DELTA)
+    }
+
+    /// Returns velocity that moves by given offset during this time step. Velocity is also
+    /// clamped to given speed, so a time step that is out of sane range won't produce a huge
+    /// velocity.
+    pub fn velocity_from_offset(&self, offset: Vector3<f32>, max_speed: f32) -> Vector3<f32> {
+        let velocity = offset.scale(1.0 / self.movement_delta());
+        if velocity.norm() > max_speed {
+            velocity
+                .try_normalize(std::f32::EPSILON)
+                .unwrap_or_default()
+                .scale(max_speed)
+        } else {
+            velocity
+        }
+    }
+}
+
 #[repr(u16)]
 pub enum CollisionGroups {
     ActorCapsule = 1 << 0,
@@ -240,10 +318,21 @@ pub struct LoadContext {
 }
 
 impl Game {
+    /// Delay (in seconds) between last resize or move of the window and saving its state.
+    const WINDOW_SAVE_DELAY: f32 = 1.0;
+
     pub fn run() {
         let events_loop = EventLoop::<()>::new();
 
-        let inner_size = if let Some(primary_monitor) = events_loop.primary_monitor() {
+        let config = Config::load();
+
+        let inner_size = if let Some(size) = config.as_ref().ok().and_then(|c| c.window_size()) {
+            size.to_logical::<f32>(
+                events_loop
+                    .primary_monitor()
+                    .map_or(1.0, |monitor| monitor.scale_factor()),
+            )
+        } else if let Some(primary_monitor) = events_loop.primary_monitor() {
             let mut monitor_dimensions = primary_monitor.size();
             monitor_dimensions.height = (monitor_dimensions.height as f32 * 0.7) as u32;
             monitor_dimensions.width = (monitor_dimensions.width as f32 * 0.7) as u32;
@@ -277,10 +366,14 @@ impl Game {
 
         let mut engine = GameEngine::new(window_builder, &events_loop, false).unwrap();
 
-        let mut control_scheme = ControlScheme::default();
+        if let Some(&Some(position)) = config.as_ref().ok().map(|c| &c.window_position) {
+            restore_window_position(engine.get_window(), position);
+        }
 
-        match Config::load() {
+        let config = match config {
             Ok(config) => {
+                config.display_mode.apply(engine.get_window());
+
                 match engine
                     .renderer
                     .set_quality_settings(&config.graphics_settings)
@@ -297,7 +390,7 @@ impl Game {
                     ),
                 }
 
-                control_scheme = config.controls;
+                config
             }
             Err(e) => {
                 Log::writeln(
@@ -307,8 +400,11 @@ impl Game {
                         e
                     ),
                 );
+                Config::default()
             }
-        }
+        };
+        let hud = config.hud;
+        let accessibility = config.accessibility;
 
         let fixed_timestep = 1.0 / FIXED_FPS;
 
@@ -318,6 +414,8 @@ impl Game {
             delta: fixed_timestep,
         };
 
+        let hud_font = make_hud_font(hud.hud_scale);
+
         let (tx, rx) = mpsc::channel();
 
         let mut game = Game {
@@ -329,12 +427,62 @@ impl Game {
             running: true,
             menu: rg3d::futures::executor::block_on(Menu::new(
                 &mut engine,
-                &control_scheme,
+                &config.controls,
+                accessibility,
+                hud,
+                config.effects,
                 tx.clone(),
                 font.clone(),
             )),
             death_screen: DeathScreen::new(&mut engine.user_interface, font.clone(), tx.clone()),
-            control_scheme,
+            pause_menu: PauseMenu::new(&mut engine.user_interface, font.clone(), tx.clone()),
+            horde_hud: HordeHud::new(&mut engine.user_interface, hud_font.clone(), hud.hud_scale),
+            horde_mode: false,
+            subtitles: SubtitleDisplay::new(
+                &mut engine.user_interface,
+                hud_font.clone(),
+                accessibility.subtitles,
+                hud.hud_scale,
+            ),
+            hit_marker: HitMarker::new(
+                &mut engine.user_interface,
+                hud_font.clone(),
+                accessibility.hit_markers,
+            ),
+            damage_numbers: DamageNumbers::new(hud_font.clone(), hud.damage_numbers, hud.hud_scale),
+            compass: Compass::new(
+                &mut engine.user_interface,
+                hud_font.clone(),
+                hud.compass,
+                hud.hud_scale,
+            ),
+            objective_marker: ObjectiveMarker::new(
+                &mut engine.user_interface,
+                hud_font,
+                hud.hud_scale,
+            ),
+            crosshair: Crosshair::new(
+                &mut engine.user_interface,
+                hud.crosshair_style,
+                hud.crosshair_color(),
+                hud.hud_scale,
+            ),
+            hold_progress: HoldProgress::new(&mut engine.user_interface, hud.hud_scale),
+            performance_overlay: PerformanceOverlay::new(
+                &mut engine.user_interface,
+                hud.performance_overlay,
+            ),
+            console: Console::new(
+                &mut engine.user_interface,
+                smaller_font.clone(),
+                config.gameplay.cheats,
+            ),
+            time_scale: 1.0,
+            slow_motion: Default::default(),
+            custom_message_handlers: Default::default(),
+            rng_seed: parse_seed_argument(),
+            window_save_timer: None,
+            config,
             debug_text: Handle::NONE,
             weapon_display: WeaponDisplay::new(font, engine.resource_manager.clone()),
             item_display: ItemDisplay::new(smaller_font),
@@ -350,6 +498,7 @@ impl Game {
         };
 
         game.create_debug_ui();
+        game.apply_settings();
 
         events_loop.run(move |event, _, control_flow| {
             game.process_input_event(&event);
@@ -392,6 +541,10 @@ impl Game {
                     }
                     WindowEvent::Resized(new_size) => {
                         game.engine.renderer.set_frame_size(new_size.into());
+                        game.window_save_timer = Some(Game::WINDOW_SAVE_DELAY);
+                    }
+                    WindowEvent::Moved(_) => {
+                        game.window_save_timer = Some(Game::WINDOW_SAVE_DELAY);
                     }
                     _ => (),
                 },
@@ -408,10 +561,12 @@ impl Game {
             &mut self.engine,
             self.level.as_ref(),
             &message,
-            &mut self.control_scheme,
+            &mut self.config.controls,
         );
 
         self.death_screen.handle_ui_message(message);
+        self.pause_menu.handle_ui_message(message);
+        self.console.handle_ui_message(message);
 
         if matches!(message.data(), UiMessageData::Button(ButtonMessage::Click))
             || (matches!(
@@ -423,6 +578,7 @@ impl Game {
                 .send(Message::Play2DSound {
                     path: PathBuf::from("data/sounds/click.ogg"),
                     gain: 0.8,
+                    caption: None,
                 })
                 .unwrap();
         }
@@ -467,7 +623,7 @@ impl Game {
             .build(&mut self.engine.user_interface.build_ctx());
     }
 
-    pub fn save_game(&mut self) -> VisitResult {
+    pub fn save_game(&mut self, path: &Path) -> VisitResult {
         let mut visitor = Visitor::new();
 
         // Visit engine state first.
@@ -479,16 +635,16 @@ impl Game {
             file.write_all(visitor.save_text().as_bytes()).unwrap();
         }
 
-        visitor.save_binary(Path::new("save.bin"))
+        visitor.save_binary(path)
     }
 
-    pub fn load_game(&mut self) -> VisitResult {
+    pub fn load_game(&mut self, path: &Path) -> VisitResult {
         Log::writeln(
             MessageKind::Information,
             "Attempting load a save...".to_owned(),
         );
 
-        let mut visitor = Visitor::load_binary(Path::new("save.bin"))?;
+        let mut visitor = Visitor::load_binary(path)?;
 
         // Clean up.
         self.destroy_level();
@@ -510,9 +666,12 @@ impl Game {
         self.set_menu_visible(false);
         self.death_screen
             .set_visible(&self.engine.user_interface, false);
+        self.pause_menu
+            .set_visible(&self.engine.user_interface, false);
 
         // Set control scheme for player.
         if let Some(level) = &mut self.level {
+            self.horde_mode = level.horde().is_some();
             level.resolve(
                 &mut self.engine,
                 self.events_sender.clone(),
@@ -521,6 +680,7 @@ impl Game {
                 self.item_display.render_target.clone(),
             );
         }
+        self.apply_settings();
 
         self.time.elapsed = self.time.clock.elapsed().as_secs_f64();
 
@@ -528,8 +688,12 @@ impl Game {
     }
 
     fn destroy_level(&mut self) {
+        self.slow_motion.stop();
         if let Some(ref mut level) = self.level.take() {
             level.destroy(&mut self.engine);
+            self.damage_numbers.clear(&self.engine.user_interface);
+            self.horde_hud
+                .sync_to_model(&self.engine.user_interface, None);
             Log::writeln(
                 MessageKind::Information,
                 "Current level destroyed!".to_owned(),
@@ -537,9 +701,11 @@ impl Game {
         }
     }
 
-    pub fn start_new_game(&mut self) {
+    pub fn start_new_game(&mut self, horde_mode: bool) {
         self.destroy_level();
 
+        self.horde_mode = horde_mode;
+
         let ctx = Arc::new(Mutex::new(LoadContext { level: None }));
 
         self.load_context = Some(ctx.clone());
@@ -552,6 +718,10 @@ impl Game {
                 true,
             ));
         self.menu.set_visible(&mut self.engine, false);
+        self.death_screen
+            .set_visible(&self.engine.user_interface, false);
+        self.pause_menu
+            .set_visible(&self.engine.user_interface, false);
 
         let resource_manager = self.engine.resource_manager.clone();
         let sender = self.events_sender.clone();
@@ -559,8 +729,17 @@ impl Game {
         let inventory_texture = self.inventory_interface.render_target.clone();
         let item_texture = self.item_display.render_target.clone();
 
+        let seed = self
+            .rng_seed
+            .unwrap_or_else(|| GameRng::from_entropy().seed());
+        Log::writeln(
+            MessageKind::Information,
+            format!("Starting new game with seed {}", seed),
+        );
+
         std::thread::spawn(move || {
-            let level = rg3d::futures::executor::block_on(ArrivalLevel::new(
+            let mut level = rg3d::futures::executor::block_on(ArrivalLevel::new(
+                seed,
                 resource_manager,
                 sender,
                 display_texture,
@@ -568,17 +747,115 @@ impl Game {
                 item_texture,
             ));
 
+            if horde_mode {
+                level.0.enable_horde_mode();
+            }
+
             ctx.lock().unwrap().level = Some(level);
         });
     }
 
+    pub fn restart_level(&mut self) {
+        // Only arrival level can be loaded from scratch for now. Level can be already
+        // destroyed if the match has ended, in this case restart the last started game.
+        if !matches!(self.level, Some(Level::Lab(_))) {
+            self.start_new_game(self.horde_mode);
+        } else {
+            Log::writeln(
+                MessageKind::Warning,
+                "Current level cannot be restarted!".to_owned(),
+            );
+        }
+    }
+
+    /// Horde mode respawns the player at the spawn point with a penalty, otherwise the last
+    /// autosave is loaded. The level is restarted if there is no autosave.
+    fn respawn_player(&mut self) {
+        if self.horde_mode {
+            if let Some(level) = self.level.as_mut() {
+                rg3d::futures::executor::block_on(level.respawn_player(
+                    &mut self.engine,
+                    self.weapon_display.render_target.clone(),
+                    self.inventory_interface.render_target.clone(),
+                    self.item_display.render_target.clone(),
+                ));
+                self.death_screen
+                    .set_visible(&self.engine.user_interface, false);
+                // New player gets settings of the old one.
+                self.apply_settings();
+                return;
+            }
+        } else if Path::new(AUTOSAVE_PATH).exists() {
+            match self.load_game(Path::new(AUTOSAVE_PATH)) {
+                Ok(_) => return,
+                Err(e) => Log::writeln(
+                    MessageKind::Error,
+                    format!("Failed to load autosave. Reason: {:?}", e),
+                ),
+            }
+        }
+        self.restart_level();
+    }
+
+    /// Applies user settings to HUD and to current level.
+    fn apply_settings(&mut self) {
+        self.apply_palette();
+        if let Some(level) = self.level.as_mut() {
+            level.apply_settings(&self.config);
+        }
+    }
+
+    /// Registers a handler of [`Message::Custom`] messages, handlers live as long as the game
+    /// and receive custom messages of every level.
+    pub fn register_custom_message_handler(&mut self, handler: Box<dyn CustomMessageHandler>) {
+        self.custom_message_handlers.register(handler);
+    }
+
+    fn set_hud_scale(&mut self, scale: f32) {
+        let ui = &self.engine.user_interface;
+        let font = make_hud_font(scale);
+        self.horde_hud.set_scale(ui, font.clone(), scale);
+        self.subtitles.set_scale(ui, font.clone(), scale);
+        self.hit_marker.set_font(ui, font.clone());
+        self.damage_numbers.set_scale(font.clone(), scale);
+        self.crosshair.set_scale(ui, scale);
+        self.hold_progress.set_scale(ui, scale);
+        self.compass.set_scale(ui, font.clone(), scale);
+        self.objective_marker.set_scale(ui, font, scale);
+    }
+
+    /// Applies HUD colors of current color blind mode to every HUD element.
+    fn apply_palette(&mut self) {
+        let palette = self.config.accessibility.color_blind_mode.palette();
+
+        self.weapon_display.set_palette(&palette);
+        self.death_screen
+            .set_palette(&self.engine.user_interface, &palette);
+        self.horde_hud
+            .set_palette(&self.engine.user_interface, &palette);
+        self.hit_marker.set_palette(&palette);
+        self.damage_numbers.set_palette(&palette);
+        self.compass.set_palette(&palette);
+        self.objective_marker
+            .set_palette(&self.engine.user_interface, &palette);
+        self.hold_progress
+            .set_palette(&self.engine.user_interface, &palette);
+    }
+
     pub fn set_menu_visible(&mut self, visible: bool) {
         self.menu.set_visible(&mut self.engine, visible);
     }
 
+    pub fn is_paused(&self) -> bool {
+        self.menu.is_visible(&self.engine.user_interface)
+            || self.pause_menu.is_visible(&self.engine.user_interface)
+    }
+
     pub fn is_any_menu_visible(&self) -> bool {
         self.menu.is_visible(&self.engine.user_interface)
             || self.death_screen.is_visible(&self.engine.user_interface)
+            || self.pause_menu.is_visible(&self.engine.user_interface)
+            || self.console.is_visible(&self.engine.user_interface)
     }
 
     pub fn update(&mut self, time: GameTime) {
@@ -602,6 +879,9 @@ impl Game {
                             false,
                         ));
                     self.menu.sync_to_model(&mut self.engine, true);
+                    self.apply_settings();
+                    // Start of a level is the first checkpoint.
+                    self.events_sender.send(Message::Autosave).unwrap();
                 } else {
                     self.engine
                         .user_interface
@@ -614,36 +894,139 @@ impl Game {
             }
         }
 
+        let paused = self.is_paused();
+        // Slow motion affects gameplay and scenes only, menus and HUD run in real time.
+        let mut gameplay_time = time;
+        if !paused {
+            gameplay_time.delta *= self.slow_motion.update(time.delta) * self.time_scale;
+        }
+        let mut crosshair_spread = None;
+        let mut hold_progress = None;
         if let Some(ref mut level) = self.level {
-            let menu_visible = self.menu.is_visible(&self.engine.user_interface);
-            if !menu_visible {
-                level.update(&mut self.engine, time);
+            if !paused {
+                level.update(&mut self.engine, gameplay_time);
                 let player = level.get_player();
                 if player.is_some() {
                     if let Actor::Player(player) = level.actors().get(player) {
                         self.weapon_display.sync_to_model(player, level.weapons());
+                        if !player.is_dead() {
+                            crosshair_spread = Some(player.accuracy_spread());
+                            hold_progress = level.objective_hold_progress();
+                        }
                     }
                 }
             }
-            self.engine.scenes[level.scene].enabled = !menu_visible;
+            self.horde_hud
+                .sync_to_model(&self.engine.user_interface, level.horde());
+            self.engine.scenes[level.scene].enabled = !paused;
         }
+        self.crosshair
+            .sync_to_model(&self.engine.user_interface, crosshair_spread);
+        self.hold_progress
+            .sync_to_model(&self.engine.user_interface, hold_progress);
 
-        self.menu.scene.update(&mut self.engine, time.delta);
+        self.menu.update(&mut self.engine, time.delta);
         self.weapon_display.update(time.delta);
         self.inventory_interface.update(time.delta);
         self.item_display.update(time.delta);
+        self.subtitles
+            .update(&self.engine.user_interface, time.delta);
+        self.hit_marker
+            .update(&self.engine.user_interface, time.delta);
+        let camera = self.level.as_ref().and_then(|level| {
+            let player = level.get_player();
+            if player.is_none() {
+                return None;
+            }
+            match level.actors().get(player) {
+                Actor::Player(player) => {
+                    match &self.engine.scenes[level.scene].graph
+                        [player.camera_controller().camera()]
+                    {
+                        Node::Camera(camera) => Some(camera),
+                        _ => None,
+                    }
+                }
+                Actor::Bot(_) => None,
+            }
+        });
+        self.damage_numbers
+            .update(&self.engine.user_interface, camera, time.delta);
+        let compass_markers = match self.level.as_ref() {
+            Some(level) => level.compass_markers(&self.engine.scenes[level.scene].graph),
+            None => Vec::new(),
+        };
+        self.compass
+            .sync_to_model(&self.engine.user_interface, camera, &compass_markers);
+        self.objective_marker.sync_to_model(
+            &self.engine.user_interface,
+            camera,
+            self.level
+                .as_ref()
+                .and_then(|level| level.current_objective()),
+        );
+        // Menus and HUD run in real time, so the engine is updated with real time step. Scene
+        // of the level is updated separately: its animations, particles and physics are slowed
+        // down together with gameplay.
+        let mut level_scene = None;
+        if let Some(level) = self.level.as_ref() {
+            let scene = &mut self.engine.scenes[level.scene];
+            if scene.enabled {
+                scene.enabled = false;
+                level_scene = Some(level.scene);
+            }
+        }
         self.engine.update(time.delta);
+        if let Some(scene) = level_scene {
+            let scene = &mut self.engine.scenes[scene];
+            scene.enabled = true;
+            // Zero time scale freezes the scene, physics can't do a step of zero length.
+            if gameplay_time.delta > 0.0 {
+                let frame_size = self.engine.renderer.get_frame_size();
+                scene.update(
+                    Vector2::new(frame_size.0 as f32, frame_size.1 as f32),
+                    gameplay_time.delta,
+                );
+            }
+        }
+
+        self.update_window_save_timer(time.delta);
 
         self.handle_messages(time);
     }
 
+    fn update_window_save_timer(&mut self, dt: f32) {
+        if let Some(timer) = self.window_save_timer.as_mut() {
+            *timer -= dt;
+            if *timer <= 0.0 {
+                self.window_save_timer = None;
+
+                let window = self.engine.get_window();
+                // Size of fullscreen window must not replace size of windowed mode.
+                if window.fullscreen().is_none() {
+                    let size = window.inner_size();
+                    let position = window.outer_position().ok().map(|p| (p.x, p.y));
+                    if let Err(e) = Config::save_window_state(size.width, size.height, position) {
+                        Log::writeln(
+                            MessageKind::Error,
+                            format!("Failed to save window state. Reason: {:?}", e),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
     fn handle_messages(&mut self, time: GameTime) {
         while let Ok(message) = self.events_receiver.try_recv() {
             match &message {
                 Message::StartNewGame => {
-                    self.start_new_game();
+                    self.start_new_game(false);
+                }
+                Message::StartHordeMode => {
+                    self.start_new_game(true);
                 }
-                Message::SaveGame => match self.save_game() {
+                Message::SaveGame => match self.save_game(Path::new(SAVE_PATH)) {
                     Ok(_) => {
                         Log::writeln(MessageKind::Information, "Successfully saved".to_owned())
                     }
@@ -653,7 +1036,7 @@ impl Game {
                     ),
                 },
                 Message::LoadGame => {
-                    if let Err(e) = self.load_game() {
+                    if let Err(e) = self.load_game(Path::new(SAVE_PATH)) {
                         Log::writeln(
                             MessageKind::Error,
                             format!("Failed to load saved game. Reason: {:?}", e),
@@ -664,11 +1047,47 @@ impl Game {
                     self.destroy_level();
                     self.running = false;
                 }
+                Message::FinalKill => {
+                    if self.config.gameplay.final_kill_slow_motion {
+                        self.slow_motion.start();
+                    }
+                }
+                Message::Autosave => {
+                    // Horde mode has no checkpoints, the player respawns in place there.
+                    if !self.horde_mode {
+                        if let Err(e) = self.save_game(Path::new(AUTOSAVE_PATH)) {
+                            Log::writeln(
+                                MessageKind::Error,
+                                format!("Failed to make an autosave, reason: {}", e),
+                            );
+                        }
+                    }
+                }
+                Message::RespawnPlayer => {
+                    self.respawn_player();
+                }
                 Message::EndMatch => {
-                    self.destroy_level();
-                    self.death_screen
-                        .set_visible(&self.engine.user_interface, true);
-                    self.menu.sync_to_model(&mut self.engine, false);
+                    // Level keeps running in horde mode while the death screen is shown, so
+                    // the message comes every frame until the player respawns.
+                    if !self.death_screen.is_visible(&self.engine.user_interface) {
+                        if let Some(level) = self.level.as_ref() {
+                            self.death_screen.sync_to_model(
+                                &self.engine.user_interface,
+                                level.leader_board().score_of(level.get_player()),
+                                level.time(),
+                            );
+                        }
+                        if !self.horde_mode {
+                            self.destroy_level();
+                            self.menu.sync_to_model(&mut self.engine, false);
+                        }
+                        self.death_screen
+                            .set_visible(&self.engine.user_interface, true);
+                    }
+                }
+                &Message::SetColorBlindMode { mode } => {
+                    self.config.accessibility.color_blind_mode = mode;
+                    self.apply_settings();
                 }
                 Message::SetMusicVolume { volume } => {
                     self.engine.scenes[self.menu.scene.scene]
@@ -681,12 +1100,34 @@ impl Game {
                     self.menu.set_visible(&mut self.engine, true);
                     self.death_screen
                         .set_visible(&self.engine.user_interface, false);
+                    self.pause_menu
+                        .set_visible(&self.engine.user_interface, false);
+                }
+                &Message::SetTimeScale { scale } => {
+                    self.time_scale = scale.max(0.0);
+                }
+                &Message::SetPaused { paused } => {
+                    self.pause_menu
+                        .set_visible(&self.engine.user_interface, paused);
+                }
+                Message::RestartLevel => {
+                    self.restart_level();
+                }
+                Message::QuitToMainMenu => {
+                    self.destroy_level();
+                    self.pause_menu
+                        .set_visible(&self.engine.user_interface, false);
+                    self.set_menu_visible(true);
+                    self.menu.sync_to_model(&mut self.engine, false);
                 }
                 Message::SyncInventory => {
                     if let Some(ref mut level) = self.level {
-                        if let Actor::Player(player) = level.actors().get(level.get_player()) {
-                            self.inventory_interface
-                                .sync_to_model(self.engine.resource_manager.clone(), player);
+                        let player = level.get_player();
+                        if level.actors().contains(player) {
+                            if let Actor::Player(player) = level.actors().get(player) {
+                                self.inventory_interface
+                                    .sync_to_model(self.engine.resource_manager.clone(), player);
+                            }
                         }
                     }
                 }
@@ -697,7 +1138,96 @@ impl Game {
                         count,
                     );
                 }
-                Message::Play2DSound { path, gain } => {
+                &Message::SetSubtitlesEnabled { enabled } => {
+                    self.subtitles
+                        .set_enabled(&self.engine.user_interface, enabled);
+                }
+                &Message::SetCrosshairStyle { style } => {
+                    self.crosshair.set_style(&self.engine.user_interface, style);
+                }
+                &Message::SetCrosshairColor { color } => {
+                    self.crosshair.set_color(&self.engine.user_interface, color);
+                }
+                &Message::SetHudScale { scale } => {
+                    self.set_hud_scale(scale);
+                }
+                &Message::SetPerformanceOverlayEnabled { enabled } => {
+                    self.performance_overlay
+                        .set_enabled(&self.engine.user_interface, enabled);
+                }
+                &Message::SetParticleBudget { budget } => {
+                    self.config.effects.particle_budget = budget;
+                    self.apply_settings();
+                }
+                &Message::SetHealthRegenEnabled { enabled } => {
+                    self.config.accessibility.health_regen = enabled;
+                    self.apply_settings();
+                }
+                &Message::SetCameraShake { enabled, intensity } => {
+                    self.config.accessibility.camera_shake = enabled;
+                    self.config.accessibility.camera_shake_intensity = intensity;
+                    self.apply_settings();
+                }
+                &Message::SetGoreEnabled { enabled } => {
+                    self.config.accessibility.gore = enabled;
+                    self.apply_settings();
+                }
+                &Message::SetWeaponInspectEnabled { enabled } => {
+                    self.config.accessibility.weapon_inspect = enabled;
+                    self.apply_settings();
+                }
+                &Message::SetHitMarkersEnabled { enabled } => {
+                    self.hit_marker
+                        .set_enabled(&self.engine.user_interface, enabled);
+                }
+                &Message::SetDamageNumbersEnabled { enabled } => {
+                    self.damage_numbers
+                        .set_enabled(&self.engine.user_interface, enabled);
+                }
+                &Message::SetCompassEnabled { enabled } => {
+                    self.compass.set_enabled(enabled);
+                }
+                &Message::ShowDamageNumber {
+                    position,
+                    amount,
+                    critical,
+                } => {
+                    self.damage_numbers.show(
+                        &mut self.engine.user_interface,
+                        position,
+                        amount,
+                        critical,
+                    );
+                }
+                &Message::ShowHitMarker { kill } => {
+                    self.hit_marker.show(&self.engine.user_interface, kill);
+                    if kill && self.hit_marker.is_enabled() {
+                        self.events_sender
+                            .send(Message::Play2DSound {
+                                path: PathBuf::from("data/sounds/bullet_impact_body.ogg"),
+                                gain: 0.6,
+                                caption: None,
+                            })
+                            .unwrap();
+                    }
+                }
+                Message::PlaySound {
+                    caption: Some(caption),
+                    ..
+                } => {
+                    self.subtitles
+                        .add_caption(&self.engine.user_interface, caption);
+                }
+                Message::Play2DSound {
+                    path,
+                    gain,
+                    caption,
+                } => {
+                    if let Some(caption) = caption {
+                        self.subtitles
+                            .add_caption(&self.engine.user_interface, caption);
+                    }
+
                     if let Ok(buffer) = rg3d::futures::executor::block_on(
                         self.engine
                             .resource_manager
@@ -724,6 +1254,7 @@ impl Game {
                     &mut self.engine,
                     &message,
                     time,
+                    &mut self.custom_message_handlers,
                 ));
             }
         }
@@ -750,6 +1281,23 @@ impl Game {
             MessageDirection::ToWidget,
             self.debug_string.clone(),
         ));
+
+        let counters = if let Some(level) = self.level.as_ref() {
+            PerformanceCounters {
+                bots: level.live_bot_count(),
+                max_bots: level.max_bots(),
+                projectiles: level.projectiles().count(),
+                particle_systems: self.engine.scenes[level.scene]
+                    .graph
+                    .pair_iter()
+                    .filter(|(_, n)| matches!(n, Node::ParticleSystem(_)))
+                    .count(),
+            }
+        } else {
+            Default::default()
+        };
+        self.performance_overlay
+            .update(&self.engine.user_interface, counters);
     }
 
     pub fn limit_fps(&mut self, value: f64) {
@@ -770,6 +1318,10 @@ impl Game {
         if let Event::WindowEvent { event, .. } = event {
             if let Some(event) = translate_event(event) {
                 self.engine.user_interface.process_os_event(&event);
+                self.inventory_interface.process_os_event(&event);
+            }
+
+            if let Some((button, ElementState::Pressed)) = ControlButton::from_window_event(event) {
                 if let Some(level) = self.level.as_mut() {
                     let player_handle = level.get_player();
                     let player =
@@ -778,9 +1330,9 @@ impl Game {
                         } else {
                             unreachable!()
                         };
-                    self.inventory_interface.process_os_event(
-                        &event,
-                        &self.control_scheme,
+                    self.inventory_interface.process_button(
+                        button,
+                        &self.config.controls,
                         player_handle,
                         player,
                     );
@@ -791,33 +1343,221 @@ impl Game {
         if !self.is_any_menu_visible() {
             if let Some(ref mut level) = self.level {
                 let scene = &mut self.engine.scenes[level.scene];
-                level.process_input_event(event, scene, self.time.delta, &self.control_scheme);
+                level.process_input_event(event, scene, self.time.delta, &self.config.controls);
             }
         }
     }
 
+    /// Translates a command of the console to messages, spawned things appear in front of
+    /// the player.
+    fn execute_console_command(&mut self, command: ConsoleCommand) {
+        let ui = &self.engine.user_interface;
+        let level = match self.level.as_ref() {
+            Some(level) if level.actors().contains(level.get_player()) => level,
+            _ => {
+                self.console.print(ui, "No level is loaded");
+                return;
+            }
+        };
+        let player = level.get_player();
+        let (god_mode, noclip, front) = match level.actors().get(player) {
+            Actor::Player(player) => {
+                let camera =
+                    &self.engine.scenes[level.scene].graph[player.camera_controller().camera()];
+                (
+                    player.is_god_mode(),
+                    player.is_noclip(),
+                    camera.global_position() + camera.look_vector().scale(2.0),
+                )
+            }
+            Actor::Bot(_) => unreachable!(),
+        };
+
+        let message = match command {
+            ConsoleCommand::SpawnBot(kind) => Message::AddBot {
+                kind,
+                position: front,
+                rotation: Default::default(),
+            },
+            ConsoleCommand::SpawnItem(kind) => Message::SpawnItem {
+                kind,
+                position: front,
+                adjust_height: true,
+            },
+            ConsoleCommand::GiveWeapon(kind) => Message::GiveNewWeapon {
+                actor: player,
+                kind,
+            },
+            ConsoleCommand::GodMode => {
+                self.console.print(
+                    ui,
+                    if god_mode {
+                        "God mode off"
+                    } else {
+                        "God mode on"
+                    },
+                );
+                Message::SetGodMode { enabled: !god_mode }
+            }
+            ConsoleCommand::Noclip => {
+                self.console
+                    .print(ui, if noclip { "Noclip off" } else { "Noclip on" });
+                Message::SetNoclip { enabled: !noclip }
+            }
+            ConsoleCommand::Teleport(position) => Message::TeleportActor {
+                actor: player,
+                position,
+            },
+            ConsoleCommand::TimeScale(scale) => Message::SetTimeScale { scale },
+            ConsoleCommand::ShadowCulling => {
+                let enabled = !level.is_shadow_culling();
+                self.console.print(
+                    ui,
+                    if enabled {
+                        "Shadow culling on"
+                    } else {
+                        "Shadow culling off"
+                    },
+                );
+                Message::SetShadowCulling { enabled }
+            }
+        };
+        self.events_sender.send(message).unwrap();
+    }
+
     pub fn process_input_event(&mut self, event: &Event<()>) {
         self.process_dispatched_event(event);
 
-        if let Event::WindowEvent {
-            event: WindowEvent::KeyboardInput { input, .. },
-            ..
-        } = event
-        {
-            if let ElementState::Pressed = input.state {
-                if let Some(key) = input.virtual_keycode {
-                    if key == VirtualKeyCode::Escape && self.level.is_some() {
-                        self.set_menu_visible(!self.is_any_menu_visible());
+        if let Event::WindowEvent { event, .. } = event {
+            if let Some((button, ElementState::Pressed)) = ControlButton::from_window_event(event) {
+                // A button that is being bound to an action in options must not trigger
+                // anything.
+                if !self.menu.is_binding() {
+                    if button == self.config.controls.toggle_performance_overlay.button {
+                        self.performance_overlay.toggle(&self.engine.user_interface);
+                    }
+
+                    if button == self.config.controls.toggle_console.button {
+                        self.console.toggle(&self.engine.user_interface);
+                    } else if button == ControlButton::Key(VirtualKeyCode::Return)
+                        && self.console.is_visible(&self.engine.user_interface)
+                    {
+                        if let Some(command) = self.console.submit(&self.engine.user_interface) {
+                            self.execute_console_command(command);
+                        }
+                    }
+
+                    if button == self.config.controls.pause.button && self.level.is_some() {
+                        let ui = &self.engine.user_interface;
+                        if self.menu.is_visible(ui) {
+                            self.set_menu_visible(false);
+                        } else if !self.death_screen.is_visible(ui) {
+                            self.events_sender
+                                .send(Message::SetPaused {
+                                    paused: !self.pause_menu.is_visible(ui),
+                                })
+                                .unwrap();
+                        }
                     }
                 }
             }
         }
 
         self.menu
-            .process_input_event(&mut self.engine, &event, &mut self.control_scheme);
+            .process_input_event(&mut self.engine, &event, &mut self.config.controls);
+    }
+}
+
+/// Parses `--seed <number>` command line argument.
+fn parse_seed_argument() -> Option<u64> {
+    let mut args = std::env::args().skip_while(|arg| arg != "--seed").skip(1);
+    let seed = args.next()?;
+    match seed.parse() {
+        Ok(seed) => Some(seed),
+        Err(e) => {
+            Log::writeln(
+                MessageKind::Warning,
+                format!("Invalid seed {}: {:?}", seed, e),
+            );
+            None
+        }
+    }
+}
+
+/// Moves window to saved position, the window is centered on primary monitor instead if the
+/// position is off-screen (for example when the monitor it was on is disconnected).
+fn restore_window_position(window: &Window, position: (i32, i32)) {
+    let (x, y) = position;
+    let on_screen = window.available_monitors().any(|monitor| {
+        let origin = monitor.position();
+        let size = monitor.size();
+        x >= origin.x
+            && y >= origin.y
+            && x < origin.x + size.width as i32
+            && y < origin.y + size.height as i32
+    });
+
+    if on_screen {
+        window.set_outer_position(PhysicalPosition::new(x, y));
+    } else if let Some(monitor) = window.primary_monitor() {
+        let origin = monitor.position();
+        let monitor_size = monitor.size();
+        let window_size = window.outer_size();
+        window.set_outer_position(PhysicalPosition::new(
+            origin.x + (monitor_size.width as i32 - window_size.width as i32) / 2,
+            origin.y + (monitor_size.height as i32 - window_size.height as i32) / 2,
+        ));
     }
 }
 
 fn main() {
     Game::run();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time_with_delta(delta: f32) -> GameTime {
+        GameTime {
+            clock: time::Instant::now(),
+            elapsed: 0.0,
+            delta,
+        }
+    }
+
+    #[test]
+    fn movement_delta_is_clamped() {
+        assert_eq!(
+            time_with_delta(0.0).movement_delta(),
+            GameTime::MIN_MOVEMENT_DELTA
+        );
+        assert_eq!(
+            time_with_delta(-1.0).movement_delta(),
+            GameTime::MIN_MOVEMENT_DELTA
+        );
+        assert_eq!(
+            time_with_delta(30.0).movement_delta(),
+            GameTime::MAX_MOVEMENT_DELTA
+        );
+        assert_eq!(time_with_delta(1.0 / 60.0).movement_delta(), 1.0 / 60.0);
+    }
+
+    #[test]
+    fn velocity_stays_bounded_on_pathological_delta() {
+        let offset = Vector3::new(0.5, 0.0, 0.5);
+        let max_speed = 5.0;
+        for &delta in &[0.0, 1.0e-9, std::f32::NAN, 1000.0] {
+            let velocity = time_with_delta(delta).velocity_from_offset(offset, max_speed);
+            assert!(velocity.norm().is_finite());
+            assert!(velocity.norm() <= max_speed * 1.001);
+        }
+    }
+
+    #[test]
+    fn velocity_is_not_changed_on_sane_delta() {
+        let offset = Vector3::new(0.1, 0.0, 0.1);
+        let velocity = time_with_delta(0.1).velocity_from_offset(offset, 5.0);
+        assert!(velocity.metric_distance(&Vector3::new(1.0, 0.0, 1.0)) < 1.0e-4);
+    }
+}
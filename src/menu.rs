@@ -1,7 +1,14 @@
 use crate::level::Level;
 use crate::{
-    control_scheme::ControlScheme, gui::Gui, gui::GuiMessage, gui::UiNode, message::Message,
-    options_menu::OptionsMenu, utils::create_camera, GameEngine,
+    config::{AccessibilityConfig, EffectsConfig, HudConfig},
+    control_scheme::ControlScheme,
+    gui::Gui,
+    gui::GuiMessage,
+    gui::UiNode,
+    message::Message,
+    options_menu::OptionsMenu,
+    utils::create_camera,
+    GameEngine,
 };
 use rg3d::{
     core::{
@@ -29,6 +36,7 @@ pub struct Menu {
     sender: Sender<Message>,
     root: Handle<UiNode>,
     btn_new_game: Handle<UiNode>,
+    btn_horde_mode: Handle<UiNode>,
     btn_save_game: Handle<UiNode>,
     btn_settings: Handle<UiNode>,
     btn_load_game: Handle<UiNode>,
@@ -108,6 +116,9 @@ impl Menu {
     pub async fn new(
         engine: &mut GameEngine,
         control_scheme: &ControlScheme,
+        accessibility: AccessibilityConfig,
+        hud: HudConfig,
+        effects: EffectsConfig,
         sender: Sender<Message>,
         font: SharedFont,
     ) -> Self {
@@ -118,6 +129,7 @@ impl Menu {
         let ctx = &mut engine.user_interface.build_ctx();
 
         let btn_new_game;
+        let btn_horde_mode;
         let btn_settings;
         let btn_save_game;
         let btn_load_game;
@@ -149,10 +161,22 @@ impl Menu {
                                         btn_new_game
                                     })
                                     .with_child({
-                                        btn_save_game = ButtonBuilder::new(
+                                        btn_horde_mode = ButtonBuilder::new(
                                             WidgetBuilder::new()
                                                 .on_column(0)
                                                 .on_row(1)
+                                                .with_margin(Thickness::uniform(4.0)),
+                                        )
+                                        .with_text("Horde Mode")
+                                        .with_font(font.clone())
+                                        .build(ctx);
+                                        btn_horde_mode
+                                    })
+                                    .with_child({
+                                        btn_save_game = ButtonBuilder::new(
+                                            WidgetBuilder::new()
+                                                .on_column(0)
+                                                .on_row(2)
                                                 .with_enabled(false)
                                                 .with_margin(Thickness::uniform(4.0)),
                                         )
@@ -165,7 +189,7 @@ impl Menu {
                                         btn_load_game = ButtonBuilder::new(
                                             WidgetBuilder::new()
                                                 .on_column(0)
-                                                .on_row(2)
+                                                .on_row(3)
                                                 .with_margin(Thickness::uniform(4.0)),
                                         )
                                         .with_text("Load Game")
@@ -177,7 +201,7 @@ impl Menu {
                                         btn_settings = ButtonBuilder::new(
                                             WidgetBuilder::new()
                                                 .on_column(0)
-                                                .on_row(3)
+                                                .on_row(4)
                                                 .with_margin(Thickness::uniform(4.0)),
                                         )
                                         .with_text("Settings")
@@ -189,7 +213,7 @@ impl Menu {
                                         btn_quit_game = ButtonBuilder::new(
                                             WidgetBuilder::new()
                                                 .on_column(0)
-                                                .on_row(4)
+                                                .on_row(5)
                                                 .with_margin(Thickness::uniform(4.0)),
                                         )
                                         .with_text("Quit")
@@ -204,6 +228,7 @@ impl Menu {
                             .add_row(Row::strict(75.0))
                             .add_row(Row::strict(75.0))
                             .add_row(Row::strict(75.0))
+                            .add_row(Row::strict(75.0))
                             .build(ctx),
                         )
                         .build(ctx),
@@ -220,14 +245,27 @@ impl Menu {
             sender: sender.clone(),
             root,
             btn_new_game,
+            btn_horde_mode,
             btn_settings,
             btn_save_game,
             btn_load_game,
             btn_quit_game,
-            options_menu: OptionsMenu::new(engine, control_scheme, sender),
+            options_menu: OptionsMenu::new(
+                engine,
+                control_scheme,
+                accessibility,
+                hud,
+                effects,
+                sender,
+            ),
         }
     }
 
+    pub fn update(&mut self, engine: &mut GameEngine, dt: f32) {
+        self.scene.update(engine, dt);
+        self.options_menu.update(engine, dt);
+    }
+
     pub fn set_visible(&mut self, engine: &mut GameEngine, visible: bool) {
         engine.scenes[self.scene.scene].enabled = visible;
 
@@ -250,6 +288,10 @@ impl Menu {
         ui.node(self.root).visibility()
     }
 
+    pub fn is_binding(&self) -> bool {
+        self.options_menu.is_binding()
+    }
+
     pub fn process_input_event(
         &mut self,
         engine: &mut GameEngine,
@@ -295,6 +337,8 @@ impl Menu {
         if let UiMessageData::Button(ButtonMessage::Click) = message.data() {
             if message.destination() == self.btn_new_game {
                 self.sender.send(Message::StartNewGame).unwrap();
+            } else if message.destination() == self.btn_horde_mode {
+                self.sender.send(Message::StartHordeMode).unwrap();
             } else if message.destination() == self.btn_save_game {
                 self.sender.send(Message::SaveGame).unwrap();
             } else if message.destination() == self.btn_load_game {
@@ -8,27 +8,49 @@
 //! board know nothing about bots, it just knows the fact that bot died. In other way bot knows
 //! nothing about leader board - its can just die. Not sure if this mechanism is suitable for
 //! all kinds of games, but at least it very useful for first-person shooters.
+//!
+//! New gameplay can use its own messages without editing [`Message`]: wrap any payload in
+//! [`CustomMessage`], send it as [`Message::Custom`] and register a [`CustomMessageHandler`]
+//! in the game. Ownership and threading rules of custom messages are the same as for the
+//! rest of messages:
+//!
+//! - Payload must be `Send`, because messages are sent through `Sender<Message>` and levels
+//!   are loaded on a separate thread with a clone of the sender.
+//! - Handlers are owned by the game and always run on the main thread, while the game pumps
+//!   its message queue. A handler has exclusive access to the level and the engine for the
+//!   duration of the call, it must not hold references to them after the call.
+//! - Messages sent by a handler are processed within the same pump, so a handler that keeps
+//!   sending messages to itself will hang the game.
 
 use crate::{
     actor::Actor,
     bot::BotKind,
     effects::EffectKind,
+    gui::{crosshair::CrosshairStyle, palette::ColorBlindMode},
     item::{Item, ItemKind},
     sound::SoundKind,
+    status_effect::StatusEffect,
     weapon::{
-        projectile::{Damage, ProjectileKind, ProjectileOwner},
+        projectile::{Damage, DamageType, ProjectileKind, ProjectileOwner},
         Weapon, WeaponKind,
     },
 };
+use crate::{level::BaseLevel, GameEngine};
 use rg3d::{
     core::{
         algebra::{UnitQuaternion, Vector3},
+        color::Color,
         pool::Handle,
     },
     physics::parry::shape::FeatureId,
     scene::ColliderHandle,
 };
-use std::path::PathBuf;
+use std::{
+    any::Any,
+    fmt::{Debug, Formatter},
+    path::PathBuf,
+    sync::mpsc::Sender,
+};
 
 #[derive(Debug)]
 pub enum Message {
@@ -36,6 +58,7 @@ pub enum Message {
         actor: Handle<Actor>,
         kind: WeaponKind,
     },
+    /// Queues a bot, it appears on the level once its resources are loaded.
     AddBot {
         kind: BotKind,
         position: Vector3<f32>,
@@ -44,9 +67,15 @@ pub enum Message {
     RemoveActor {
         actor: Handle<Actor>,
     },
+    /// Spawns bot of a random allowed kind on a spawn point with given index.
     SpawnBot {
         spawn_point_id: usize,
     },
+    /// Spawns bot of a given kind on a random suitable spawn point which is far enough
+    /// from the player.
+    SpawnBotAtRandomPoint {
+        kind: BotKind,
+    },
     /// Gives item of specified kind to a given actor. Basically it means that actor will take
     /// item and consume it immediately (heal itself, add ammo, etc.)
     UseItem {
@@ -59,6 +88,11 @@ pub enum Message {
         actor: Handle<Actor>,
         item: Handle<Item>,
     },
+    /// Moves every item from the corpse of a bot into inventory of given actor.
+    LootCorpse {
+        actor: Handle<Actor>,
+        corpse: Handle<Actor>,
+    },
     SpawnItem {
         kind: ItemKind,
         position: Vector3<f32>,
@@ -70,16 +104,23 @@ pub enum Message {
         direction: Vector3<f32>,
         initial_velocity: Vector3<f32>,
         owner: ProjectileOwner,
+        /// Multiplier of damage of the projectile.
+        damage_scale: f32,
     },
     ShootWeapon {
         weapon: Handle<Weapon>,
         direction: Option<Vector3<f32>>,
+        /// Multiplier of damage of the shot, charged shots do more damage.
+        damage_scale: f32,
     },
     ShootRay {
         weapon: Handle<Weapon>,
         begin: Vector3<f32>,
         end: Vector3<f32>,
         damage: Damage,
+        damage_type: DamageType,
+        /// Effect that will be created if the ray hits the environment.
+        impact_effect: EffectKind,
     },
     PlaySound {
         path: PathBuf,
@@ -87,10 +128,13 @@ pub enum Message {
         gain: f32,
         rolloff_factor: f32,
         radius: f32,
+        /// Text that will be shown in subtitles (if enabled) when sound is played.
+        caption: Option<String>,
     },
     Play2DSound {
         path: PathBuf,
         gain: f32,
+        caption: Option<String>,
     },
     /// Plays environment-specific sound. It also handles foot step sounds.
     PlayEnvironmentSound {
@@ -114,38 +158,182 @@ pub enum Message {
     SwitchFlashLight {
         weapon: Handle<Weapon>,
     },
+    SwitchLaserSight {
+        weapon: Handle<Weapon>,
+    },
     DamageActor {
         /// Which actor should be damaged.
         actor: Handle<Actor>,
         /// Actor who damaged target actor, can be Handle::NONE if damage came from environment
         /// or not from any actor.
         who: Handle<Actor>,
-        /// Numeric value of damage.
+        /// Numeric value of damage, it is scaled by resistance of the actor to the type of
+        /// damage.
         amount: f32,
+        damage_type: DamageType,
+        /// Whether damage was dealt to a weak spot (head shot).
+        critical: bool,
+        /// Collider of a hit box that took the hit, it is used to sever limbs.
+        hit_box: Option<ColliderHandle>,
+    },
+    /// Severs a limb of a bot, see [`crate::bot::DismembermentDefinition`].
+    SeverHitBox {
+        actor: Handle<Actor>,
+        hit_box: ColliderHandle,
+    },
+    /// Applies a status effect (poison, burning, etc.) to an actor, effect of the same kind
+    /// that the actor already has is refreshed.
+    ApplyStatusEffect {
+        actor: Handle<Actor>,
+        effect: StatusEffect,
+    },
+    /// Notifies that an actor has just died.
+    ActorDied {
+        actor: Handle<Actor>,
+        /// Actor who killed the actor, can be Handle::NONE if actor was killed by environment.
+        who: Handle<Actor>,
     },
     CreateEffect {
         kind: EffectKind,
         position: Vector3<f32>,
         orientation: UnitQuaternion<f32>,
     },
+    /// Notifies that an explosive projectile (grenade) will explode in given time, bots
+    /// nearby can run away from it.
+    GrenadeWarning {
+        position: Vector3<f32>,
+        time: f32,
+        radius: f32,
+    },
+    /// Bot has screamed on spotting a target, calm bots of its faction within `loudness`
+    /// meters go to investigate position of the target.
+    BotScream {
+        bot: Handle<Actor>,
+        position: Vector3<f32>,
+        loudness: f32,
+        target_position: Vector3<f32>,
+    },
     ApplySplashDamage {
         amount: f32,
         radius: f32,
         center: Vector3<f32>,
         /// Damage initiator
         who: Handle<Actor>,
+        damage_type: DamageType,
+        /// Status effect that is applied to every damaged actor, for example burning.
+        effect: Option<StatusEffect>,
     },
     /// Save game state to a file. TODO: Add filename field.
     SaveGame,
     /// Loads game state from a file. TODO: Add filename field.
     LoadGame,
     StartNewGame,
+    /// Starts new game in horde mode, where player have to survive endless waves of bots.
+    StartHordeMode,
     QuitGame,
     ToggleMainMenu,
+    /// Makes the player immune to damage, it is a cheat of debug console.
+    SetGodMode {
+        enabled: bool,
+    },
+    /// Lets the player fly through walls, it is a cheat of debug console.
+    SetNoclip {
+        enabled: bool,
+    },
+    /// Switches culling of shadows of distant and off-screen lights, it is a command of
+    /// debug console to compare frame time with and without culling.
+    SetShadowCulling {
+        enabled: bool,
+    },
+    /// Instantly moves an actor to given position.
+    TeleportActor {
+        actor: Handle<Actor>,
+        position: Vector3<f32>,
+    },
+    /// Sets multiplier of time step of gameplay, it is a cheat of debug console.
+    SetTimeScale {
+        scale: f32,
+    },
+    /// Pauses or resumes gameplay simulation.
+    SetPaused {
+        paused: bool,
+    },
+    /// Reloads current level from scratch.
+    RestartLevel,
+    /// Destroys current level and shows main menu.
+    QuitToMainMenu,
+    /// Changes HUD colors to a palette suitable for given kind of color blindness.
+    SetColorBlindMode {
+        mode: ColorBlindMode,
+    },
+    SetSubtitlesEnabled {
+        enabled: bool,
+    },
+    SetHitMarkersEnabled {
+        enabled: bool,
+    },
+    SetHealthRegenEnabled {
+        enabled: bool,
+    },
+    /// Enables or disables camera shake and sets its strength multiplier in [0; 1] range.
+    SetCameraShake {
+        enabled: bool,
+        intensity: f32,
+    },
+    /// Enables or disables blood effects and severing of limbs of bots.
+    SetGoreEnabled {
+        enabled: bool,
+    },
+    /// Enables or disables idle inspection of the weapon of the player.
+    SetWeaponInspectEnabled {
+        enabled: bool,
+    },
+    SetCrosshairStyle {
+        style: CrosshairStyle,
+    },
+    SetCrosshairColor {
+        color: Color,
+    },
+    /// Sets multiplier of size of HUD elements.
+    SetHudScale {
+        scale: f32,
+    },
+    SetPerformanceOverlayEnabled {
+        enabled: bool,
+    },
+    SetParticleBudget {
+        budget: u32,
+    },
+    /// Shows floating number of damage that player has dealt to an actor.
+    ShowDamageNumber {
+        position: Vector3<f32>,
+        amount: f32,
+        critical: bool,
+    },
+    SetDamageNumbersEnabled {
+        enabled: bool,
+    },
+    SetCompassEnabled {
+        enabled: bool,
+    },
+    /// Notifies that player's shot has damaged some actor.
+    ShowHitMarker {
+        /// Whether the actor was killed by the shot.
+        kill: bool,
+    },
     SetMusicVolume {
         volume: f32,
     },
     EndMatch,
+    /// Brings the player back after death. In horde mode the player respawns at the spawn
+    /// point of the level, otherwise the last autosave is loaded.
+    RespawnPlayer,
+    /// Saves the game to the autosave slot, it is sent on checkpoints.
+    Autosave,
+    /// Player has killed the last alerted bot, so the fight is over.
+    FinalKill,
+    /// Completes current objective of the level and makes next one current.
+    AdvanceObjective,
     SyncInventory,
     ShowItemDisplay {
         item: ItemKind,
@@ -156,4 +344,63 @@ pub enum Message {
         item: ItemKind,
         count: u32,
     },
+    /// Message that is not known to the core game, it is passed to registered custom
+    /// message handlers.
+    Custom(CustomMessage),
+}
+
+/// Arbitrary payload of [`Message::Custom`].
+pub struct CustomMessage(Box<dyn Any + Send>);
+
+impl CustomMessage {
+    pub fn new<T: Any + Send>(payload: T) -> Self {
+        Self(Box::new(payload))
+    }
+
+    /// Returns the payload if it has type `T`.
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        self.0.downcast_ref()
+    }
+}
+
+impl Debug for CustomMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CustomMessage")
+    }
+}
+
+pub trait CustomMessageHandler {
+    /// Called for every custom message while a level is active. A handler must check type
+    /// of the payload and ignore messages that it does not know.
+    fn handle(
+        &mut self,
+        message: &CustomMessage,
+        level: &mut BaseLevel,
+        engine: &mut GameEngine,
+        sender: &Sender<Message>,
+    );
+}
+
+#[derive(Default)]
+pub struct CustomMessageHandlers {
+    handlers: Vec<Box<dyn CustomMessageHandler>>,
+}
+
+impl CustomMessageHandlers {
+    pub fn register(&mut self, handler: Box<dyn CustomMessageHandler>) {
+        self.handlers.push(handler);
+    }
+
+    /// Passes a message to every handler in order of registration.
+    pub fn dispatch(
+        &mut self,
+        message: &CustomMessage,
+        level: &mut BaseLevel,
+        engine: &mut GameEngine,
+        sender: &Sender<Message>,
+    ) {
+        for handler in self.handlers.iter_mut() {
+            handler.handle(message, level, engine, sender);
+        }
+    }
 }
@@ -1,14 +1,19 @@
 use crate::{
-    config::Config,
-    control_scheme::{ControlButton, ControlScheme},
-    gui::{create_check_box, create_scroll_bar, BuildContext, GuiMessage, ScrollBarData, UiNode},
+    config::{AccessibilityConfig, Config, EffectsConfig, HudConfig},
+    control_scheme::{ActivationMode, ControlButton, ControlScheme},
+    gui::{
+        create_check_box, create_scroll_bar, crosshair::CrosshairStyle, palette::ColorBlindMode,
+        BuildContext, GuiMessage, ScrollBarData, UiNode,
+    },
     level::Level,
+    localization::{Language, StringTable},
     message::Message,
+    sound::MasterGainRamp,
     GameEngine,
 };
 use rg3d::{
     core::{algebra::Vector2, pool::Handle},
-    event::{Event, MouseButton, MouseScrollDelta, WindowEvent},
+    event::{ElementState, Event},
     gui::{
         border::BorderBuilder,
         button::ButtonBuilder,
@@ -31,7 +36,7 @@ use rg3d::{
     renderer::ShadowMapPrecision,
     scene::Scene,
     utils::log::{Log, MessageKind},
-    window::Fullscreen,
+    window::{Fullscreen, Window},
 };
 use std::sync::mpsc::Sender;
 
@@ -39,6 +44,7 @@ pub struct OptionsMenu {
     pub window: Handle<UiNode>,
     sender: Sender<Message>,
     sound_volume: Handle<UiNode>,
+    master_gain: MasterGainRamp,
     pub music_volume: Handle<UiNode>,
     video_mode: Handle<UiNode>,
     spot_shadows: Handle<UiNode>,
@@ -54,12 +60,40 @@ pub struct OptionsMenu {
     control_scheme_buttons: Vec<Handle<UiNode>>,
     active_control_button: Option<usize>,
     mouse_sens: Handle<UiNode>,
+    mouse_vertical_sens: Handle<UiNode>,
     mouse_y_inverse: Handle<UiNode>,
+    mouse_smoothing: Handle<UiNode>,
+    mouse_acceleration: Handle<UiNode>,
+    toggle_aim: Handle<UiNode>,
+    toggle_run: Handle<UiNode>,
     reset_control_scheme: Handle<UiNode>,
     use_hrtf: Handle<UiNode>,
     reset_audio_settings: Handle<UiNode>,
     point_shadows_quality: Handle<UiNode>,
     spot_shadows_quality: Handle<UiNode>,
+    color_blind_mode: Handle<UiNode>,
+    subtitles: Handle<UiNode>,
+    hit_markers: Handle<UiNode>,
+    health_regen: Handle<UiNode>,
+    camera_shake: Handle<UiNode>,
+    camera_shake_intensity: Handle<UiNode>,
+    gore: Handle<UiNode>,
+    weapon_inspect: Handle<UiNode>,
+    performance_overlay: Handle<UiNode>,
+    damage_numbers: Handle<UiNode>,
+    hud_scale: Handle<UiNode>,
+    compass: Handle<UiNode>,
+    aim_assist: Handle<UiNode>,
+    aim_snap: Handle<UiNode>,
+    aim_snap_cooldown: Handle<UiNode>,
+    language: Handle<UiNode>,
+    accessibility: AccessibilityConfig,
+    crosshair_style: Handle<UiNode>,
+    crosshair_color: [Handle<UiNode>; 3],
+    hud: HudConfig,
+    particle_budget: Handle<UiNode>,
+    effects: EffectsConfig,
+    strings: StringTable,
 }
 
 fn make_text_mark(text: &str, row: usize, ctx: &mut BuildContext) -> Handle<UiNode> {
@@ -112,6 +146,7 @@ fn make_video_mode_item(video_mode: &VideoMode, ctx: &mut BuildContext) -> Handl
 
 fn make_shadows_quality_drop_down(
     ctx: &mut BuildContext,
+    strings: &StringTable,
     row: usize,
     current: usize,
 ) -> Handle<UiNode> {
@@ -122,13 +157,13 @@ fn make_shadows_quality_drop_down(
             .with_margin(Thickness::uniform(1.0)),
     )
     .with_items({
-        ["Low", "Medium", "High", "Ultra"]
+        ["low", "medium", "high", "ultra"]
             .iter()
-            .map(|o| {
+            .map(|key| {
                 DecoratorBuilder::new(BorderBuilder::new(
                     WidgetBuilder::new().with_child(
                         TextBuilder::new(WidgetBuilder::new())
-                            .with_text(o)
+                            .with_text(strings.get(key))
                             .build(ctx),
                     ),
                 ))
@@ -140,6 +175,70 @@ fn make_shadows_quality_drop_down(
     .build(ctx)
 }
 
+fn make_color_blind_mode_drop_down(
+    ctx: &mut BuildContext,
+    row: usize,
+    current: ColorBlindMode,
+) -> Handle<UiNode> {
+    make_named_drop_down(
+        ctx,
+        row,
+        ColorBlindMode::ALL.iter().map(|mode| mode.name()),
+        current.index(),
+    )
+}
+
+fn make_crosshair_style_drop_down(
+    ctx: &mut BuildContext,
+    row: usize,
+    current: CrosshairStyle,
+) -> Handle<UiNode> {
+    make_named_drop_down(
+        ctx,
+        row,
+        CrosshairStyle::ALL.iter().map(|style| style.name()),
+        current.index(),
+    )
+}
+
+fn make_named_drop_down<'a>(
+    ctx: &mut BuildContext,
+    row: usize,
+    names: impl Iterator<Item = &'a str>,
+    selected: usize,
+) -> Handle<UiNode> {
+    DropdownListBuilder::new(
+        WidgetBuilder::new()
+            .on_row(row)
+            .on_column(1)
+            .with_margin(Thickness::uniform(1.0)),
+    )
+    .with_items({
+        names
+            .map(|name| {
+                DecoratorBuilder::new(BorderBuilder::new(
+                    WidgetBuilder::new().with_child(
+                        TextBuilder::new(WidgetBuilder::new())
+                            .with_text(name)
+                            .build(ctx),
+                    ),
+                ))
+                .build(ctx)
+            })
+            .collect::<Vec<_>>()
+    })
+    .with_selected(selected)
+    .build(ctx)
+}
+
+fn activation_mode(toggle: bool) -> ActivationMode {
+    if toggle {
+        ActivationMode::Toggle
+    } else {
+        ActivationMode::Hold
+    }
+}
+
 fn shadows_quality(size: usize) -> usize {
     if size < 256 {
         0
@@ -165,6 +264,9 @@ impl OptionsMenu {
     pub fn new(
         engine: &mut GameEngine,
         control_scheme: &ControlScheme,
+        accessibility: AccessibilityConfig,
+        hud: HudConfig,
+        effects: EffectsConfig,
         sender: Sender<Message>,
     ) -> Self {
         let video_modes: Vec<VideoMode> = engine
@@ -175,6 +277,8 @@ impl OptionsMenu {
             .filter(|vm| vm.size().width > 800 && vm.size().height > 600 && vm.bit_depth() == 32)
             .collect();
 
+        let strings = StringTable::new(accessibility.language);
+
         let ctx = &mut engine.user_interface.build_ctx();
 
         let common_row = Row::strict(36.0);
@@ -193,7 +297,12 @@ impl OptionsMenu {
         let point_shadow_distance;
         let spot_shadow_distance;
         let mouse_sens;
+        let mouse_vertical_sens;
         let mouse_y_inverse;
+        let mouse_smoothing;
+        let mouse_acceleration;
+        let toggle_aim;
+        let toggle_run;
         let reset_control_scheme;
         let mut control_scheme_buttons = Vec::new();
         let use_hrtf;
@@ -203,16 +312,35 @@ impl OptionsMenu {
         let ssao;
         let point_shadows_quality;
         let spot_shadows_quality;
+        let particle_budget;
+        let color_blind_mode;
+        let subtitles;
+        let hit_markers;
+        let health_regen;
+        let camera_shake;
+        let camera_shake_intensity;
+        let gore;
+        let weapon_inspect;
+        let performance_overlay;
+        let damage_numbers;
+        let hud_scale;
+        let compass;
+        let aim_assist;
+        let aim_snap;
+        let aim_snap_cooldown;
+        let language;
+        let crosshair_style;
+        let mut crosshair_color = [Handle::NONE; 3];
 
         let graphics_tab = TabDefinition {
-            header: make_tab_header("Graphics", ctx),
+            header: make_tab_header(strings.get("graphics"), ctx),
             content: {
                 ScrollViewerBuilder::new(WidgetBuilder::new())
                     .with_content(
                         GridBuilder::new(
                             WidgetBuilder::new()
                                 .with_margin(Thickness::uniform(5.0))
-                                .with_child(make_text_mark("Resolution", 0, ctx))
+                                .with_child(make_text_mark(strings.get("resolution"), 0, ctx))
                                 .with_child({
                                     video_mode = DropdownListBuilder::new(
                                         WidgetBuilder::new()
@@ -222,15 +350,19 @@ impl OptionsMenu {
                                     )
                                     .with_selected(0)
                                     .with_items({
-                                        let mut modes =
-                                            vec![DecoratorBuilder::new(BorderBuilder::new(
-                                                WidgetBuilder::new().with_child(
-                                                    TextBuilder::new(WidgetBuilder::new())
-                                                        .with_text("Windowed")
-                                                        .build(ctx),
-                                                ),
-                                            ))
-                                            .build(ctx)];
+                                        let mut modes = ["windowed", "borderless"]
+                                            .iter()
+                                            .map(|key| {
+                                                DecoratorBuilder::new(BorderBuilder::new(
+                                                    WidgetBuilder::new().with_child(
+                                                        TextBuilder::new(WidgetBuilder::new())
+                                                            .with_text(strings.get(key))
+                                                            .build(ctx),
+                                                    ),
+                                                ))
+                                                .build(ctx)
+                                            })
+                                            .collect::<Vec<_>>();
                                         modes.extend(video_modes.iter().map(|video_mode| {
                                             make_video_mode_item(video_mode, ctx)
                                         }));
@@ -240,21 +372,29 @@ impl OptionsMenu {
                                     video_mode
                                 })
                                 // Spot Shadows Enabled
-                                .with_child(make_text_mark("Spot Shadows", 1, ctx))
+                                .with_child(make_text_mark(strings.get("spot_shadows"), 1, ctx))
                                 .with_child({
                                     spot_shadows =
                                         create_check_box(ctx, 1, 1, settings.spot_shadows_enabled);
                                     spot_shadows
                                 })
                                 // Soft Spot Shadows
-                                .with_child(make_text_mark("Soft Spot Shadows", 2, ctx))
+                                .with_child(make_text_mark(
+                                    strings.get("soft_spot_shadows"),
+                                    2,
+                                    ctx,
+                                ))
                                 .with_child({
                                     soft_spot_shadows =
                                         create_check_box(ctx, 2, 1, settings.spot_soft_shadows);
                                     soft_spot_shadows
                                 })
                                 // Spot Shadows Distance
-                                .with_child(make_text_mark("Spot Shadows Distance", 3, ctx))
+                                .with_child(make_text_mark(
+                                    strings.get("spot_shadows_distance"),
+                                    3,
+                                    ctx,
+                                ))
                                 .with_child({
                                     spot_shadow_distance = create_scroll_bar(
                                         ctx,
@@ -273,21 +413,29 @@ impl OptionsMenu {
                                     spot_shadow_distance
                                 })
                                 // Point Shadows Enabled
-                                .with_child(make_text_mark("Point Shadows", 4, ctx))
+                                .with_child(make_text_mark(strings.get("point_shadows"), 4, ctx))
                                 .with_child({
                                     point_shadows =
                                         create_check_box(ctx, 4, 1, settings.point_shadows_enabled);
                                     point_shadows
                                 })
                                 // Soft Point Shadows
-                                .with_child(make_text_mark("Soft Point Shadows", 5, ctx))
+                                .with_child(make_text_mark(
+                                    strings.get("soft_point_shadows"),
+                                    5,
+                                    ctx,
+                                ))
                                 .with_child({
                                     soft_point_shadows =
                                         create_check_box(ctx, 5, 1, settings.point_soft_shadows);
                                     soft_point_shadows
                                 })
                                 // Point Shadows Distance
-                                .with_child(make_text_mark("Point Shadows Distance", 6, ctx))
+                                .with_child(make_text_mark(
+                                    strings.get("point_shadows_distance"),
+                                    6,
+                                    ctx,
+                                ))
                                 .with_child({
                                     point_shadow_distance = create_scroll_bar(
                                         ctx,
@@ -305,39 +453,71 @@ impl OptionsMenu {
                                     );
                                     point_shadow_distance
                                 })
-                                .with_child(make_text_mark("Use Light Scatter", 7, ctx))
+                                .with_child(make_text_mark(
+                                    strings.get("use_light_scatter"),
+                                    7,
+                                    ctx,
+                                ))
                                 .with_child({
                                     use_light_scatter =
                                         create_check_box(ctx, 7, 1, settings.light_scatter_enabled);
                                     use_light_scatter
                                 })
-                                .with_child(make_text_mark("FXAA", 8, ctx))
+                                .with_child(make_text_mark(strings.get("fxaa"), 8, ctx))
                                 .with_child({
                                     fxaa = create_check_box(ctx, 8, 1, settings.fxaa);
                                     fxaa
                                 })
-                                .with_child(make_text_mark("SSAO", 9, ctx))
+                                .with_child(make_text_mark(strings.get("ssao"), 9, ctx))
                                 .with_child({
                                     ssao = create_check_box(ctx, 9, 1, settings.fxaa);
                                     ssao
                                 })
-                                .with_child(make_text_mark("Point Shadows Quality", 10, ctx))
+                                .with_child(make_text_mark(
+                                    strings.get("point_shadows_quality"),
+                                    10,
+                                    ctx,
+                                ))
                                 .with_child({
                                     point_shadows_quality = make_shadows_quality_drop_down(
                                         ctx,
+                                        &strings,
                                         10,
                                         shadows_quality(settings.point_shadow_map_size),
                                     );
                                     point_shadows_quality
                                 })
-                                .with_child(make_text_mark("Spot Shadows Quality", 11, ctx))
+                                .with_child(make_text_mark(
+                                    strings.get("spot_shadows_quality"),
+                                    11,
+                                    ctx,
+                                ))
                                 .with_child({
                                     spot_shadows_quality = make_shadows_quality_drop_down(
                                         ctx,
+                                        &strings,
                                         11,
                                         shadows_quality(settings.spot_shadow_map_size),
                                     );
                                     spot_shadows_quality
+                                })
+                                .with_child(make_text_mark(strings.get("particle_budget"), 12, ctx))
+                                .with_child({
+                                    particle_budget = create_scroll_bar(
+                                        ctx,
+                                        ScrollBarData {
+                                            min: 500.0,
+                                            max: 20000.0,
+                                            value: effects.particle_budget as f32,
+                                            step: 500.0,
+                                            row: 12,
+                                            column: 1,
+                                            margin,
+                                            show_value: true,
+                                            orientation: Orientation::Horizontal,
+                                        },
+                                    );
+                                    particle_budget
                                 }),
                         )
                         .add_row(common_row)
@@ -352,6 +532,7 @@ impl OptionsMenu {
                         .add_row(common_row)
                         .add_row(common_row)
                         .add_row(common_row)
+                        .add_row(common_row)
                         .add_column(Column::strict(250.0))
                         .add_column(Column::stretch())
                         .build(ctx),
@@ -361,13 +542,13 @@ impl OptionsMenu {
         };
 
         let sound_tab = TabDefinition {
-            header: make_tab_header("Sound", ctx),
+            header: make_tab_header(strings.get("sound"), ctx),
             content: {
                 ScrollViewerBuilder::new(WidgetBuilder::new())
                     .with_content(
                         GridBuilder::new(
                             WidgetBuilder::new()
-                                .with_child(make_text_mark("Sound Volume", 0, ctx))
+                                .with_child(make_text_mark(strings.get("sound_volume"), 0, ctx))
                                 .with_child({
                                     sound_volume = create_scroll_bar(
                                         ctx,
@@ -385,7 +566,7 @@ impl OptionsMenu {
                                     );
                                     sound_volume
                                 })
-                                .with_child(make_text_mark("Music Volume", 1, ctx))
+                                .with_child(make_text_mark(strings.get("music_volume"), 1, ctx))
                                 .with_child({
                                     music_volume = create_scroll_bar(
                                         ctx,
@@ -403,7 +584,7 @@ impl OptionsMenu {
                                     );
                                     music_volume
                                 })
-                                .with_child(make_text_mark("Use HRTF", 2, ctx))
+                                .with_child(make_text_mark(strings.get("use_hrtf"), 2, ctx))
                                 .with_child({
                                     use_hrtf = create_check_box(ctx, 2, 1, true);
                                     use_hrtf
@@ -412,7 +593,7 @@ impl OptionsMenu {
                                     reset_audio_settings = ButtonBuilder::new(
                                         WidgetBuilder::new().on_row(4).with_margin(margin),
                                     )
-                                    .with_text("Reset")
+                                    .with_text(strings.get("reset"))
                                     .build(ctx);
                                     reset_audio_settings
                                 }),
@@ -431,7 +612,7 @@ impl OptionsMenu {
         };
 
         let controls_tab = TabDefinition {
-            header: make_tab_header("Controls", ctx),
+            header: make_tab_header(strings.get("controls"), ctx),
             content: {
                 ScrollViewerBuilder::new(WidgetBuilder::new())
                     .with_content({
@@ -439,7 +620,7 @@ impl OptionsMenu {
 
                         for (row, button) in control_scheme.buttons().iter().enumerate() {
                             // Offset by total amount of rows that goes before
-                            let row = row + 2;
+                            let row = row + 7;
 
                             children.push(make_text_mark(button.description.as_str(), row, ctx));
 
@@ -457,7 +638,11 @@ impl OptionsMenu {
 
                         GridBuilder::new(
                             WidgetBuilder::new()
-                                .with_child(make_text_mark("Mouse Sensitivity", 0, ctx))
+                                .with_child(make_text_mark(
+                                    strings.get("horizontal_mouse_sensitivity"),
+                                    0,
+                                    ctx,
+                                ))
                                 .with_child({
                                     mouse_sens = create_scroll_bar(
                                         ctx,
@@ -475,19 +660,81 @@ impl OptionsMenu {
                                     );
                                     mouse_sens
                                 })
-                                .with_child(make_text_mark("Inverse Mouse Y", 1, ctx))
+                                .with_child(make_text_mark(
+                                    strings.get("vertical_mouse_sensitivity"),
+                                    1,
+                                    ctx,
+                                ))
+                                .with_child({
+                                    mouse_vertical_sens = create_scroll_bar(
+                                        ctx,
+                                        ScrollBarData {
+                                            min: 0.05,
+                                            max: 2.0,
+                                            value: control_scheme.mouse_vertical_sens(),
+                                            step: 0.05,
+                                            row: 1,
+                                            column: 1,
+                                            margin,
+                                            show_value: true,
+                                            orientation: Orientation::Horizontal,
+                                        },
+                                    );
+                                    mouse_vertical_sens
+                                })
+                                .with_child(make_text_mark(strings.get("inverse_mouse_y"), 2, ctx))
                                 .with_child({
                                     mouse_y_inverse =
-                                        create_check_box(ctx, 1, 1, control_scheme.mouse_y_inverse);
+                                        create_check_box(ctx, 2, 1, control_scheme.mouse_y_inverse);
                                     mouse_y_inverse
                                 })
+                                .with_child(make_text_mark(strings.get("mouse_smoothing"), 3, ctx))
+                                .with_child({
+                                    mouse_smoothing =
+                                        create_check_box(ctx, 3, 1, control_scheme.mouse_smoothing);
+                                    mouse_smoothing
+                                })
+                                .with_child(make_text_mark(
+                                    strings.get("mouse_acceleration"),
+                                    4,
+                                    ctx,
+                                ))
+                                .with_child({
+                                    mouse_acceleration = create_check_box(
+                                        ctx,
+                                        4,
+                                        1,
+                                        control_scheme.mouse_acceleration,
+                                    );
+                                    mouse_acceleration
+                                })
+                                .with_child(make_text_mark(strings.get("toggle_aim"), 5, ctx))
+                                .with_child({
+                                    toggle_aim = create_check_box(
+                                        ctx,
+                                        5,
+                                        1,
+                                        control_scheme.aim_mode == ActivationMode::Toggle,
+                                    );
+                                    toggle_aim
+                                })
+                                .with_child(make_text_mark(strings.get("toggle_run"), 6, ctx))
+                                .with_child({
+                                    toggle_run = create_check_box(
+                                        ctx,
+                                        6,
+                                        1,
+                                        control_scheme.run_mode == ActivationMode::Toggle,
+                                    );
+                                    toggle_run
+                                })
                                 .with_child({
                                     reset_control_scheme = ButtonBuilder::new(
                                         WidgetBuilder::new()
-                                            .on_row(2 + control_scheme.buttons().len())
+                                            .on_row(7 + control_scheme.buttons().len())
                                             .with_margin(margin),
                                     )
-                                    .with_text("Reset")
+                                    .with_text(strings.get("reset"))
                                     .build(ctx);
                                     reset_control_scheme
                                 })
@@ -497,6 +744,11 @@ impl OptionsMenu {
                         .add_column(Column::stretch())
                         .add_row(common_row)
                         .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
                         .add_rows(
                             (0..control_scheme.buttons().len())
                                 .map(|_| common_row)
@@ -509,10 +761,253 @@ impl OptionsMenu {
             },
         };
 
+        let accessibility_tab = TabDefinition {
+            header: make_tab_header(strings.get("accessibility"), ctx),
+            content: {
+                ScrollViewerBuilder::new(WidgetBuilder::new())
+                    .with_content(
+                        GridBuilder::new(
+                            WidgetBuilder::new()
+                                .with_child(make_text_mark(strings.get("color_blind_mode"), 0, ctx))
+                                .with_child({
+                                    color_blind_mode = make_color_blind_mode_drop_down(
+                                        ctx,
+                                        0,
+                                        accessibility.color_blind_mode,
+                                    );
+                                    color_blind_mode
+                                })
+                                .with_child(make_text_mark(strings.get("subtitles"), 1, ctx))
+                                .with_child({
+                                    subtitles =
+                                        create_check_box(ctx, 1, 1, accessibility.subtitles);
+                                    subtitles
+                                })
+                                .with_child(make_text_mark(strings.get("aim_assist"), 2, ctx))
+                                .with_child({
+                                    aim_assist = create_scroll_bar(
+                                        ctx,
+                                        ScrollBarData {
+                                            min: 0.0,
+                                            max: 1.0,
+                                            value: control_scheme.aim_assist,
+                                            step: 0.05,
+                                            row: 2,
+                                            column: 1,
+                                            margin,
+                                            show_value: true,
+                                            orientation: Orientation::Horizontal,
+                                        },
+                                    );
+                                    aim_assist
+                                })
+                                .with_child(make_text_mark(strings.get("aim_snap"), 3, ctx))
+                                .with_child({
+                                    aim_snap = create_check_box(ctx, 3, 1, control_scheme.aim_snap);
+                                    aim_snap
+                                })
+                                .with_child(make_text_mark(
+                                    strings.get("aim_snap_cooldown"),
+                                    4,
+                                    ctx,
+                                ))
+                                .with_child({
+                                    aim_snap_cooldown = create_scroll_bar(
+                                        ctx,
+                                        ScrollBarData {
+                                            min: 0.5,
+                                            max: 10.0,
+                                            value: control_scheme.aim_snap_cooldown,
+                                            step: 0.5,
+                                            row: 4,
+                                            column: 1,
+                                            margin,
+                                            show_value: true,
+                                            orientation: Orientation::Horizontal,
+                                        },
+                                    );
+                                    aim_snap_cooldown
+                                })
+                                .with_child(make_text_mark(strings.get("hit_markers"), 5, ctx))
+                                .with_child({
+                                    hit_markers =
+                                        create_check_box(ctx, 5, 1, accessibility.hit_markers);
+                                    hit_markers
+                                })
+                                .with_child(make_text_mark(
+                                    strings.get("health_regeneration"),
+                                    6,
+                                    ctx,
+                                ))
+                                .with_child({
+                                    health_regen =
+                                        create_check_box(ctx, 6, 1, accessibility.health_regen);
+                                    health_regen
+                                })
+                                .with_child(make_text_mark(strings.get("camera_shake"), 7, ctx))
+                                .with_child({
+                                    camera_shake =
+                                        create_check_box(ctx, 7, 1, accessibility.camera_shake);
+                                    camera_shake
+                                })
+                                .with_child(make_text_mark(
+                                    strings.get("camera_shake_intensity"),
+                                    8,
+                                    ctx,
+                                ))
+                                .with_child({
+                                    camera_shake_intensity = create_scroll_bar(
+                                        ctx,
+                                        ScrollBarData {
+                                            min: 0.0,
+                                            max: 1.0,
+                                            value: accessibility.camera_shake_intensity,
+                                            step: 0.05,
+                                            row: 8,
+                                            column: 1,
+                                            margin,
+                                            show_value: true,
+                                            orientation: Orientation::Horizontal,
+                                        },
+                                    );
+                                    camera_shake_intensity
+                                })
+                                .with_child(make_text_mark(strings.get("gore"), 9, ctx))
+                                .with_child({
+                                    gore = create_check_box(ctx, 9, 1, accessibility.gore);
+                                    gore
+                                })
+                                .with_child(make_text_mark(strings.get("weapon_inspect"), 10, ctx))
+                                .with_child({
+                                    weapon_inspect =
+                                        create_check_box(ctx, 10, 1, accessibility.weapon_inspect);
+                                    weapon_inspect
+                                })
+                                .with_child(make_text_mark(strings.get("language"), 11, ctx))
+                                .with_child({
+                                    language = make_named_drop_down(
+                                        ctx,
+                                        11,
+                                        Language::ALL.iter().map(|language| language.name()),
+                                        accessibility.language.index(),
+                                    );
+                                    language
+                                }),
+                        )
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_column(Column::strict(250.0))
+                        .add_column(Column::stretch())
+                        .build(ctx),
+                    )
+                    .build(ctx)
+            },
+        };
+
+        let hud_tab = TabDefinition {
+            header: make_tab_header(strings.get("hud"), ctx),
+            content: {
+                let mut grid = WidgetBuilder::new()
+                    .with_child(make_text_mark(strings.get("crosshair"), 0, ctx))
+                    .with_child({
+                        crosshair_style =
+                            make_crosshair_style_drop_down(ctx, 0, hud.crosshair_style);
+                        crosshair_style
+                    });
+
+                for (i, (name, component)) in ["crosshair_red", "crosshair_green", "crosshair_blue"]
+                    .iter()
+                    .zip(hud.crosshair_color.iter())
+                    .enumerate()
+                {
+                    let row = i + 1;
+                    crosshair_color[i] = create_scroll_bar(
+                        ctx,
+                        ScrollBarData {
+                            min: 0.0,
+                            max: 255.0,
+                            value: *component as f32,
+                            step: 1.0,
+                            row,
+                            column: 1,
+                            margin,
+                            show_value: true,
+                            orientation: Orientation::Horizontal,
+                        },
+                    );
+                    grid = grid
+                        .with_child(make_text_mark(strings.get(name), row, ctx))
+                        .with_child(crosshair_color[i]);
+                }
+
+                performance_overlay = create_check_box(ctx, 4, 1, hud.performance_overlay);
+                grid = grid
+                    .with_child(make_text_mark(strings.get("performance_overlay"), 4, ctx))
+                    .with_child(performance_overlay);
+
+                damage_numbers = create_check_box(ctx, 5, 1, hud.damage_numbers);
+                grid = grid
+                    .with_child(make_text_mark(strings.get("damage_numbers"), 5, ctx))
+                    .with_child(damage_numbers);
+
+                hud_scale = create_scroll_bar(
+                    ctx,
+                    ScrollBarData {
+                        min: 0.5,
+                        max: 2.0,
+                        value: hud.hud_scale,
+                        step: 0.1,
+                        row: 6,
+                        column: 1,
+                        margin,
+                        show_value: true,
+                        orientation: Orientation::Horizontal,
+                    },
+                );
+                grid = grid
+                    .with_child(make_text_mark(strings.get("hud_scale"), 6, ctx))
+                    .with_child(hud_scale);
+
+                compass = create_check_box(ctx, 7, 1, hud.compass);
+                grid = grid
+                    .with_child(make_text_mark(strings.get("compass"), 7, ctx))
+                    .with_child(compass);
+
+                ScrollViewerBuilder::new(WidgetBuilder::new())
+                    .with_content(
+                        GridBuilder::new(grid)
+                            .add_row(common_row)
+                            .add_row(common_row)
+                            .add_row(common_row)
+                            .add_row(common_row)
+                            .add_row(common_row)
+                            .add_row(common_row)
+                            .add_row(common_row)
+                            .add_row(common_row)
+                            .add_column(Column::strict(250.0))
+                            .add_column(Column::stretch())
+                            .build(ctx),
+                    )
+                    .build(ctx)
+            },
+        };
+
         let tab_control = TabControlBuilder::new(WidgetBuilder::new())
             .with_tab(graphics_tab)
             .with_tab(sound_tab)
             .with_tab(controls_tab)
+            .with_tab(accessibility_tab)
+            .with_tab(hud_tab)
             .build(ctx);
 
         let options_window: Handle<UiNode> = WindowBuilder::new(
@@ -521,7 +1016,7 @@ impl OptionsMenu {
                 .with_width(500.0),
         )
         .can_minimize(false)
-        .with_title(WindowTitle::text("Options"))
+        .with_title(WindowTitle::text(strings.get("options")))
         .open(false)
         .with_content(tab_control)
         .build(ctx);
@@ -530,6 +1025,7 @@ impl OptionsMenu {
             sender,
             window: options_window,
             sound_volume,
+            master_gain: Default::default(),
             music_volume,
             video_mode,
             spot_shadows,
@@ -542,7 +1038,12 @@ impl OptionsMenu {
             control_scheme_buttons,
             active_control_button: None,
             mouse_sens,
+            mouse_vertical_sens,
             mouse_y_inverse,
+            mouse_smoothing,
+            mouse_acceleration,
+            toggle_aim,
+            toggle_run,
             reset_control_scheme,
             use_hrtf,
             reset_audio_settings,
@@ -551,18 +1052,66 @@ impl OptionsMenu {
             fxaa,
             ssao,
             spot_shadows_quality,
+            color_blind_mode,
+            subtitles,
+            hit_markers,
+            health_regen,
+            camera_shake,
+            camera_shake_intensity,
+            gore,
+            weapon_inspect,
+            performance_overlay,
+            damage_numbers,
+            hud_scale,
+            compass,
+            aim_assist,
+            aim_snap,
+            aim_snap_cooldown,
+            language,
+            accessibility,
+            crosshair_style,
+            crosshair_color,
+            hud,
+            particle_budget,
+            effects,
+            strings,
         }
     }
 
+    /// Returns index of the item in resolution list that corresponds to current window mode.
+    fn video_mode_index(&self, window: &Window) -> usize {
+        match window.fullscreen() {
+            None => 0,
+            Some(Fullscreen::Borderless(_)) => 1,
+            Some(Fullscreen::Exclusive(video_mode)) => self
+                .available_video_modes
+                .iter()
+                .position(|vm| *vm == video_mode)
+                .map_or(0, |i| i + 2),
+        }
+    }
+
+    pub fn update(&mut self, engine: &mut GameEngine, dt: f32) {
+        self.master_gain.update(&engine.sound_engine, dt);
+    }
+
     pub fn sync_to_model(
         &mut self,
         scene: Handle<Scene>,
         engine: &mut GameEngine,
         control_scheme: &ControlScheme,
     ) {
+        let video_mode_index = self.video_mode_index(engine.get_window());
+
         let ui = &mut engine.user_interface;
         let settings = engine.renderer.get_quality_settings();
 
+        ui.send_message(DropdownListMessage::selection(
+            self.video_mode,
+            MessageDirection::ToWidget,
+            Some(video_mode_index),
+        ));
+
         let sync_check_box = |handle: Handle<UiNode>, value: bool| {
             ui.send_message(CheckBoxMessage::checked(
                 handle,
@@ -578,6 +1127,17 @@ impl OptionsMenu {
         sync_check_box(self.ssao, settings.use_ssao);
         sync_check_box(self.fxaa, settings.fxaa);
         sync_check_box(self.mouse_y_inverse, control_scheme.mouse_y_inverse);
+        sync_check_box(self.mouse_smoothing, control_scheme.mouse_smoothing);
+        sync_check_box(self.mouse_acceleration, control_scheme.mouse_acceleration);
+        sync_check_box(self.aim_snap, control_scheme.aim_snap);
+        sync_check_box(
+            self.toggle_aim,
+            control_scheme.aim_mode == ActivationMode::Toggle,
+        );
+        sync_check_box(
+            self.toggle_run,
+            control_scheme.run_mode == ActivationMode::Toggle,
+        );
         let is_hrtf = if scene.is_some() {
             matches!(
                 engine.scenes[scene].sound_context.state().renderer(),
@@ -599,9 +1159,16 @@ impl OptionsMenu {
         sync_scroll_bar(self.point_shadow_distance, settings.point_shadows_distance);
         sync_scroll_bar(self.spot_shadow_distance, settings.spot_shadows_distance);
         sync_scroll_bar(self.mouse_sens, control_scheme.mouse_sens);
+        sync_scroll_bar(self.aim_assist, control_scheme.aim_assist);
+        sync_scroll_bar(self.aim_snap_cooldown, control_scheme.aim_snap_cooldown);
+        sync_scroll_bar(self.hud_scale, self.hud.hud_scale);
+        sync_scroll_bar(
+            self.mouse_vertical_sens,
+            control_scheme.mouse_vertical_sens(),
+        );
         sync_scroll_bar(
             self.sound_volume,
-            engine.sound_engine.lock().unwrap().master_gain(),
+            self.master_gain.gain(&engine.sound_engine),
         );
 
         for (btn, def) in self
@@ -626,60 +1193,38 @@ impl OptionsMenu {
         control_scheme: &mut ControlScheme,
     ) {
         if let Event::WindowEvent { event, .. } = event {
-            let mut control_button = None;
-
-            match event {
-                WindowEvent::MouseWheel {
-                    delta: MouseScrollDelta::LineDelta(_, y),
-                    ..
-                } => {
-                    if *y != 0.0 {
-                        control_button = if *y >= 0.0 {
-                            Some(ControlButton::WheelUp)
-                        } else {
-                            Some(ControlButton::WheelDown)
-                        };
-                    }
-                }
-                WindowEvent::KeyboardInput { input, .. } => {
-                    if let Some(code) = input.virtual_keycode {
-                        control_button = Some(ControlButton::Key(code));
-                    }
-                }
-                WindowEvent::MouseInput { button, .. } => {
-                    let index = match button {
-                        MouseButton::Left => 1,
-                        MouseButton::Right => 2,
-                        MouseButton::Middle => 3,
-                        MouseButton::Other(i) => *i,
-                    };
-
-                    control_button = Some(ControlButton::Mouse(index));
-                }
-                _ => {}
-            }
+            if let Some((control_button, ElementState::Pressed)) =
+                ControlButton::from_window_event(event)
+            {
+                if let Some(active_control_button) = self.active_control_button.take() {
+                    let conflict = control_scheme.rebind(active_control_button, control_button);
 
-            if let Some(control_button) = control_button {
-                if let Some(active_control_button) = self.active_control_button {
-                    if let UINode::Button(button) = engine
-                        .user_interface
-                        .node(self.control_scheme_buttons[active_control_button])
-                    {
-                        engine.user_interface.send_message(TextMessage::text(
-                            button.content(),
-                            MessageDirection::ToWidget,
-                            control_button.name().to_owned(),
-                        ));
+                    // Action that was bound to the same button got previous button of the
+                    // rebound action.
+                    let buttons = control_scheme.buttons();
+                    for index in std::iter::once(active_control_button).chain(conflict) {
+                        if let UINode::Button(button) = engine
+                            .user_interface
+                            .node(self.control_scheme_buttons[index])
+                        {
+                            engine.user_interface.send_message(TextMessage::text(
+                                button.content(),
+                                MessageDirection::ToWidget,
+                                buttons[index].button.name().to_owned(),
+                            ));
+                        }
                     }
-
-                    control_scheme.buttons_mut()[active_control_button].button = control_button;
-
-                    self.active_control_button = None;
                 }
             }
         }
     }
 
+    /// Whether the menu waits for a button to bind to an action, the button must not
+    /// trigger anything else then.
+    pub fn is_binding(&self) -> bool {
+        self.active_control_button.is_some()
+    }
+
     #[allow(clippy::cognitive_complexity)]
     pub fn handle_ui_event(
         &mut self,
@@ -698,11 +1243,7 @@ impl OptionsMenu {
                 if message.direction() == MessageDirection::FromWidget =>
             {
                 if message.destination() == self.sound_volume {
-                    engine
-                        .sound_engine
-                        .lock()
-                        .unwrap()
-                        .set_master_gain(*new_value);
+                    self.master_gain.set_target(*new_value);
                     changed = true;
                 } else if message.destination() == self.point_shadow_distance {
                     settings.point_shadows_distance = *new_value;
@@ -713,6 +1254,50 @@ impl OptionsMenu {
                 } else if message.destination() == self.mouse_sens {
                     control_scheme.mouse_sens = *new_value;
                     changed = true;
+                } else if let Some(i) = self
+                    .crosshair_color
+                    .iter()
+                    .position(|&c| c == message.destination())
+                {
+                    self.hud.crosshair_color[i] = new_value.round().max(0.0).min(255.0) as u8;
+                    self.sender
+                        .send(Message::SetCrosshairColor {
+                            color: self.hud.crosshair_color(),
+                        })
+                        .unwrap();
+                    changed = true;
+                } else if message.destination() == self.aim_assist {
+                    control_scheme.aim_assist = *new_value;
+                    changed = true;
+                } else if message.destination() == self.aim_snap_cooldown {
+                    control_scheme.aim_snap_cooldown = *new_value;
+                    changed = true;
+                } else if message.destination() == self.camera_shake_intensity {
+                    self.accessibility.camera_shake_intensity = *new_value;
+                    self.sender
+                        .send(Message::SetCameraShake {
+                            enabled: self.accessibility.camera_shake,
+                            intensity: self.accessibility.camera_shake_intensity,
+                        })
+                        .unwrap();
+                    changed = true;
+                } else if message.destination() == self.hud_scale {
+                    self.hud.hud_scale = *new_value;
+                    self.sender
+                        .send(Message::SetHudScale { scale: *new_value })
+                        .unwrap();
+                    changed = true;
+                } else if message.destination() == self.mouse_vertical_sens {
+                    control_scheme.mouse_vertical_sens = Some(*new_value);
+                    changed = true;
+                } else if message.destination() == self.particle_budget {
+                    self.effects.particle_budget = new_value.round().max(0.0) as u32;
+                    self.sender
+                        .send(Message::SetParticleBudget {
+                            budget: self.effects.particle_budget,
+                        })
+                        .unwrap();
+                    changed = true;
                 } else if message.destination() == self.music_volume {
                     self.sender
                         .send(Message::SetMusicVolume { volume: *new_value })
@@ -721,17 +1306,28 @@ impl OptionsMenu {
                 }
             }
             UiMessageData::DropdownList(DropdownListMessage::SelectionChanged(Some(index))) => {
-                if message.destination() == self.video_mode {
-                    // -1 here because we have Windowed item in the list.
-                    if let Some(video_mode) = self.available_video_modes.get(*index - 1) {
-                        engine
+                // Selection is also changed from sync_to_model, do not switch to the same mode
+                // again.
+                if message.destination() == self.video_mode
+                    && *index != self.video_mode_index(engine.get_window())
+                {
+                    match *index {
+                        0 => engine.get_window().set_fullscreen(None),
+                        1 => engine
                             .get_window()
-                            .set_fullscreen(Some(Fullscreen::Exclusive(video_mode.clone())));
-                        changed = true;
-                    } else {
-                        engine.get_window().set_fullscreen(None);
-                        changed = true;
+                            .set_fullscreen(Some(Fullscreen::Borderless(None))),
+                        // -2 here because we have Windowed and Borderless items in the list.
+                        _ => {
+                            if let Some(video_mode) = self.available_video_modes.get(*index - 2) {
+                                engine
+                                    .get_window()
+                                    .set_fullscreen(Some(Fullscreen::Exclusive(
+                                        video_mode.clone(),
+                                    )));
+                            }
+                        }
                     }
+                    changed = true;
                 } else if message.destination() == self.spot_shadows_quality {
                     settings.spot_shadow_map_size = index_to_shadow_map_size(*index);
                     if *index > 0 {
@@ -748,6 +1344,33 @@ impl OptionsMenu {
                         settings.point_shadow_map_precision = ShadowMapPrecision::Half;
                     }
                     changed = true;
+                } else if message.destination() == self.color_blind_mode {
+                    if let Some(&mode) = ColorBlindMode::ALL.get(*index) {
+                        self.accessibility.color_blind_mode = mode;
+                        self.sender
+                            .send(Message::SetColorBlindMode { mode })
+                            .unwrap();
+                        changed = true;
+                    }
+                } else if message.destination() == self.language {
+                    if let Some(&language) = Language::ALL.get(*index) {
+                        if language != self.accessibility.language {
+                            self.accessibility.language = language;
+                            Log::writeln(
+                                MessageKind::Information,
+                                format!("Language is set to {:?}, restart to apply", language),
+                            );
+                            changed = true;
+                        }
+                    }
+                } else if message.destination() == self.crosshair_style {
+                    if let Some(&style) = CrosshairStyle::ALL.get(*index) {
+                        self.hud.crosshair_style = style;
+                        self.sender
+                            .send(Message::SetCrosshairStyle { style })
+                            .unwrap();
+                        changed = true;
+                    }
                 }
             }
             UiMessageData::CheckBox(msg) => {
@@ -768,6 +1391,21 @@ impl OptionsMenu {
                 } else if message.destination() == self.mouse_y_inverse {
                     control_scheme.mouse_y_inverse = value;
                     changed = true;
+                } else if message.destination() == self.mouse_smoothing {
+                    control_scheme.mouse_smoothing = value;
+                    changed = true;
+                } else if message.destination() == self.mouse_acceleration {
+                    control_scheme.mouse_acceleration = value;
+                    changed = true;
+                } else if message.destination() == self.aim_snap {
+                    control_scheme.aim_snap = value;
+                    changed = true;
+                } else if message.destination() == self.toggle_aim {
+                    control_scheme.aim_mode = activation_mode(value);
+                    changed = true;
+                } else if message.destination() == self.toggle_run {
+                    control_scheme.run_mode = activation_mode(value);
+                    changed = true;
                 } else if message.destination() == self.use_light_scatter {
                     settings.light_scatter_enabled = value;
                     changed = true;
@@ -777,6 +1415,63 @@ impl OptionsMenu {
                 } else if message.destination() == self.ssao {
                     settings.use_ssao = value;
                     changed = true;
+                } else if message.destination() == self.subtitles {
+                    self.accessibility.subtitles = value;
+                    self.sender
+                        .send(Message::SetSubtitlesEnabled { enabled: value })
+                        .unwrap();
+                    changed = true;
+                } else if message.destination() == self.hit_markers {
+                    self.accessibility.hit_markers = value;
+                    self.sender
+                        .send(Message::SetHitMarkersEnabled { enabled: value })
+                        .unwrap();
+                    changed = true;
+                } else if message.destination() == self.health_regen {
+                    self.accessibility.health_regen = value;
+                    self.sender
+                        .send(Message::SetHealthRegenEnabled { enabled: value })
+                        .unwrap();
+                    changed = true;
+                } else if message.destination() == self.camera_shake {
+                    self.accessibility.camera_shake = value;
+                    self.sender
+                        .send(Message::SetCameraShake {
+                            enabled: self.accessibility.camera_shake,
+                            intensity: self.accessibility.camera_shake_intensity,
+                        })
+                        .unwrap();
+                    changed = true;
+                } else if message.destination() == self.gore {
+                    self.accessibility.gore = value;
+                    self.sender
+                        .send(Message::SetGoreEnabled { enabled: value })
+                        .unwrap();
+                    changed = true;
+                } else if message.destination() == self.weapon_inspect {
+                    self.accessibility.weapon_inspect = value;
+                    self.sender
+                        .send(Message::SetWeaponInspectEnabled { enabled: value })
+                        .unwrap();
+                    changed = true;
+                } else if message.destination() == self.performance_overlay {
+                    self.hud.performance_overlay = value;
+                    self.sender
+                        .send(Message::SetPerformanceOverlayEnabled { enabled: value })
+                        .unwrap();
+                    changed = true;
+                } else if message.destination() == self.damage_numbers {
+                    self.hud.damage_numbers = value;
+                    self.sender
+                        .send(Message::SetDamageNumbersEnabled { enabled: value })
+                        .unwrap();
+                    changed = true;
+                } else if message.destination() == self.compass {
+                    self.hud.compass = value;
+                    self.sender
+                        .send(Message::SetCompassEnabled { enabled: value })
+                        .unwrap();
+                    changed = true;
                 }
             }
             UiMessageData::Button(ButtonMessage::Click) => {
@@ -789,7 +1484,7 @@ impl OptionsMenu {
                     );
                     changed = true;
                 } else if message.destination() == self.reset_audio_settings {
-                    engine.sound_engine.lock().unwrap().set_master_gain(1.0);
+                    self.master_gain.set_target(1.0);
                     self.sync_to_model(
                         level.map_or(Default::default(), |m| m.scene),
                         engine,
@@ -804,7 +1499,7 @@ impl OptionsMenu {
                             engine.user_interface.send_message(TextMessage::text(
                                 button.content(),
                                 MessageDirection::ToWidget,
-                                "[WAITING INPUT]".to_owned(),
+                                self.strings.get("waiting_input").to_owned(),
                             ))
                         }
 
@@ -825,7 +1520,15 @@ impl OptionsMenu {
         }
 
         if changed {
-            match Config::save(engine, control_scheme.clone(), Default::default()) {
+            match Config::save(
+                engine,
+                control_scheme.clone(),
+                self.master_gain.gain(&engine.sound_engine),
+                Default::default(),
+                self.accessibility,
+                self.hud,
+                self.effects,
+            ) {
                 Ok(_) => {
                     Log::writeln(MessageKind::Information, "Settings saved!".to_string());
                 }
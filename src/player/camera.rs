@@ -31,6 +31,11 @@ pub struct CameraController {
     shake_offset: Vector3<f32>,
     target_shake_offset: Vector3<f32>,
     shake_timer: f32,
+    /// Strength of shake caused by explosions and heavy weapons in [0; 1] range, it decays
+    /// over time.
+    trauma: f32,
+    /// Strength multiplier of every shake, set from settings so it is not saved.
+    shake_scale: f32,
     query_buffer: Vec<Intersection>,
 }
 
@@ -72,6 +77,8 @@ impl CameraController {
             shake_offset: Default::default(),
             target_shake_offset: Default::default(),
             shake_timer: 0.0,
+            trauma: 0.0,
+            shake_scale: 1.0,
             query_buffer: Default::default(),
         }
     }
@@ -80,10 +87,26 @@ impl CameraController {
         self.camera
     }
 
+    /// Maximum offset (in meters) of the camera at full trauma.
+    const MAX_TRAUMA_OFFSET: f32 = 0.08;
+    /// Maximum roll angle (in degrees) of the camera at full trauma.
+    const MAX_TRAUMA_ANGLE: f32 = 3.0;
+    /// Amount of trauma that is lost per second.
+    const TRAUMA_DECAY: f32 = 1.5;
+
     pub fn request_shake_camera(&mut self) {
         self.shake_timer = 0.24;
     }
 
+    /// Shakes the camera additionally to current shake, `amount` is in [0; 1] range.
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.0);
+    }
+
+    pub fn set_shake_scale(&mut self, scale: f32) {
+        self.shake_scale = scale;
+    }
+
     pub fn update(
         &mut self,
         position: Vector3<f32>,
@@ -109,9 +132,10 @@ impl CameraController {
         self.target_camera_offset.z = if is_aiming { 0.2 } else { 0.8 };
 
         self.update_shake(time.delta);
+        let (trauma_offset, trauma_roll) = self.update_trauma(time.delta);
         self.check_occlusion(owner_collider, scene);
 
-        self.target_camera_offset += self.shake_offset;
+        self.target_camera_offset += self.shake_offset.scale(self.shake_scale) + trauma_offset;
 
         self.camera_offset.follow(&self.target_camera_offset, 0.2);
 
@@ -126,6 +150,10 @@ impl CameraController {
                 self.camera_offset.x,
                 self.camera_offset.y,
                 -self.camera_offset.z,
+            ))
+            .set_rotation(UnitQuaternion::from_axis_angle(
+                &Vector3::z_axis(),
+                trauma_roll,
             ));
 
         // Rotate camera hinge - this will make camera move up and down while look at character
@@ -195,6 +223,27 @@ impl CameraController {
         }
         self.shake_offset.follow(&self.target_shake_offset, 0.5);
     }
+
+    /// Returns offset and roll angle of the camera caused by trauma. Shake is proportional
+    /// to square of trauma, so weak shakes fade out smoothly.
+    fn update_trauma(&mut self, dt: f32) -> (Vector3<f32>, f32) {
+        self.trauma = (self.trauma - Self::TRAUMA_DECAY * dt).max(0.0);
+
+        let shake = self.trauma * self.trauma * self.shake_scale;
+        if shake <= 0.0 {
+            return (Default::default(), 0.0);
+        }
+
+        let mut rnd = rand::thread_rng();
+        let offset = Vector3::new(
+            rnd.gen_range(-1.0..1.0),
+            rnd.gen_range(-1.0..1.0),
+            rnd.gen_range(-1.0..1.0),
+        )
+        .scale(Self::MAX_TRAUMA_OFFSET * shake);
+        let roll = rnd.gen_range(-1.0..1.0) * Self::MAX_TRAUMA_ANGLE.to_radians() * shake;
+        (offset, roll)
+    }
 }
 
 impl Visit for CameraController {
@@ -211,6 +260,7 @@ impl Visit for CameraController {
         self.target_shake_offset
             .visit("TargetShakeOffset", visitor)?;
         self.shake_timer.visit("ShakeTimer", visitor)?;
+        self.trauma.visit("Trauma", visitor)?;
 
         visitor.leave_region()
     }
@@ -1,19 +1,22 @@
 use crate::player::camera::CameraController;
 use crate::{
-    actor::Actor,
-    character::{find_hit_boxes, Character},
+    actor::{is_line_of_sight_clear, Actor, CorpseDescriptor},
+    character::{find_hit_boxes, Character, Faction},
     control_scheme::{ControlButton, ControlScheme},
+    gui::palette::HudPalette,
     inventory::Inventory,
     item::ItemKind,
-    level::UpdateContext,
+    level::{ladder::Ladder, UpdateContext},
     message::Message,
     player::{
         lower_body::{LowerBodyMachine, LowerBodyMachineInput},
         upper_body::{CombatWeaponKind, UpperBodyMachine, UpperBodyMachineInput},
+        weapon_inspect::WeaponInspect,
     },
+    utils::rng::GameRng,
     weapon::{
         projectile::{ProjectileKind, ProjectileOwner},
-        WeaponContainer, WeaponKind,
+        Weapon, WeaponContainer, WeaponKind,
     },
     CollisionGroups,
 };
@@ -27,17 +30,18 @@ use rg3d::{
     },
     core::{
         algebra::Matrix4,
-        algebra::{Isometry3, UnitQuaternion, Vector3},
+        algebra::{Isometry3, UnitQuaternion, Vector2, Vector3},
         color::Color,
         color_gradient::{ColorGradient, ColorGradientBuilder, GradientPoint},
-        math::{self, SmoothAngle, Vector3Ext},
+        math::{self, frustum::Frustum, SmoothAngle, Vector3Ext},
         pool::Handle,
         visitor::{Visit, VisitResult, Visitor},
     },
     engine::resource_manager::ResourceManager,
     event::{DeviceEvent, ElementState, Event, MouseScrollDelta, WindowEvent},
+    lazy_static::lazy_static,
     physics::{
-        dynamics::{CoefficientCombineRule, RigidBodyBuilder},
+        dynamics::{BodyStatus, CoefficientCombineRule, RigidBodyBuilder},
         geometry::{ColliderBuilder, InteractionGroups},
     },
     renderer::surface::{SurfaceBuilder, SurfaceSharedData},
@@ -46,12 +50,16 @@ use rg3d::{
         base::BaseBuilder,
         mesh::{MeshBuilder, RenderPath},
         node::Node,
+        physics::Physics,
         sprite::SpriteBuilder,
         transform::TransformBuilder,
         ColliderHandle, Scene,
     },
+    utils::log::{Log, MessageKind},
 };
+use serde::Deserialize;
 use std::{
+    fs::File,
     ops::{Deref, DerefMut},
     sync::{mpsc::Sender, Arc, RwLock},
 };
@@ -59,6 +67,7 @@ use std::{
 mod camera;
 mod lower_body;
 mod upper_body;
+mod weapon_inspect;
 
 pub struct WalkStateDefinition {
     state: Handle<State>,
@@ -171,6 +180,11 @@ pub struct InputController {
     shoot: bool,
     run: bool,
     action: bool,
+    last_mouse_delta: Vector2<f32>,
+    /// Physical state of aim and run buttons, these differ from `aim` and `run` when the
+    /// actions are toggled.
+    aim_pressed: bool,
+    run_pressed: bool,
 }
 
 impl Deref for Player {
@@ -244,6 +258,62 @@ impl Default for RequiredWeapon {
     }
 }
 
+#[derive(Deserialize)]
+pub struct HealthRegenDefinition {
+    /// Time (in seconds) without taking damage after which health starts to regenerate.
+    pub delay: f32,
+    /// Health restored per second.
+    pub rate: f32,
+    /// Health does not regenerate above this value.
+    pub cap: f32,
+}
+
+#[derive(Deserialize)]
+pub struct MovementDefinition {
+    /// Walking speed (in meters per second).
+    pub walk_speed: f32,
+    /// Running speed is `walk_speed` multiplied by this value.
+    pub run_speed_multiplier: f32,
+    /// Fraction (from 0 to 1) of difference between current and desired velocity that is
+    /// applied on each step, lower values make the player feel heavier.
+    pub acceleration: f32,
+    /// Fraction (from 0 to 1) of speed that the player can control while falling.
+    pub air_control: f32,
+    /// Height (in meters) of a jump.
+    pub jump_height: f32,
+    /// Vertical speed (in meters per second) on ladders.
+    pub climb_speed: f32,
+}
+
+impl MovementDefinition {
+    /// Returns vertical speed required to reach jump height with given gravity.
+    pub fn jump_speed(&self, gravity: f32) -> f32 {
+        (2.0 * gravity.abs() * self.jump_height).sqrt()
+    }
+}
+
+#[derive(Deserialize)]
+pub struct PlayerDefinition {
+    pub pain_sounds: Vec<String>,
+    pub weapon_slots: u32,
+    pub health_regen: HealthRegenDefinition,
+    pub movement: MovementDefinition,
+    /// Time (in seconds) after spawn during which the player takes no damage.
+    #[serde(default)]
+    pub spawn_protection: f32,
+}
+
+impl PlayerDefinition {
+    pub fn new() -> Self {
+        let file = File::open("data/configs/player.ron").unwrap();
+        ron::de::from_reader(file).unwrap()
+    }
+}
+
+lazy_static! {
+    static ref DEFINITION: PlayerDefinition = PlayerDefinition::new();
+}
+
 #[derive(Default)]
 pub struct Player {
     character: Character,
@@ -275,6 +345,33 @@ pub struct Player {
     health_color_gradient: ColorGradient,
     v_recoil: SmoothAngle,
     h_recoil: SmoothAngle,
+    /// Accuracy spread in [0; 1] range, recalculated every frame so it is not saved.
+    spread: f32,
+    /// Charge of a shot of current weapon in [0; 1] range, only weapons with charge use it.
+    /// Not saved, charging shot is cancelled on load.
+    charge: f32,
+    /// Whether shoot control must be released before next shot, used by burst weapons.
+    trigger_locked: bool,
+    /// Whether health regeneration is enabled in settings, it is set by the game after a
+    /// level is loaded, so it is not saved.
+    health_regen: bool,
+    /// Time left until health starts to regenerate.
+    regen_delay: f32,
+    /// Health on previous frame, used to detect damage.
+    regen_last_health: f32,
+    regenerating: bool,
+    /// Ladder that the player is climbing, it replaces walking with vertical movement.
+    climbing: Option<Ladder>,
+    /// Player takes no damage, it is a cheat so it is not saved.
+    god_mode: bool,
+    /// Player flies freely through walls, the body is kinematic while it is enabled. It is a
+    /// cheat so it is not saved.
+    noclip: bool,
+    /// Time left until the end of invulnerability after spawn.
+    spawn_protection: f32,
+    weapon_inspect: WeaponInspect,
+    /// Time left until aim can be snapped to a target again.
+    aim_snap_cooldown: f32,
 }
 
 impl Visit for Player {
@@ -312,23 +409,98 @@ impl Visit for Player {
         self.item_display.visit("ItemDisplay", visitor)?;
         self.v_recoil.visit("VRecoil", visitor)?;
         self.h_recoil.visit("HRecoil", visitor)?;
+        self.climbing.visit("Climbing", visitor)?;
+        self.spawn_protection.visit("SpawnProtection", visitor)?;
+        self.weapon_inspect.visit("WeaponInspect", visitor)?;
+        self.aim_snap_cooldown.visit("AimSnapCooldown", visitor)?;
 
         if visitor.is_reading() {
-            self.health_color_gradient = make_color_gradient();
+            self.health_color_gradient = make_color_gradient(&Default::default());
         }
 
         visitor.leave_region()
     }
 }
 
-fn make_color_gradient() -> ColorGradient {
+/// Returns yaw and pitch offsets that turn `look` direction to `dir` direction, both are
+/// expected to be normalized.
+fn aim_offsets(look: Vector3<f32>, dir: Vector3<f32>) -> (f32, f32) {
+    let mut yaw_offset = dir.x.atan2(dir.z) - look.x.atan2(look.z);
+    if yaw_offset > std::f32::consts::PI {
+        yaw_offset -= 2.0 * std::f32::consts::PI;
+    } else if yaw_offset < -std::f32::consts::PI {
+        yaw_offset += 2.0 * std::f32::consts::PI;
+    }
+    // Positive pitch means looking down.
+    let pitch_offset = look.y.asin() - dir.y.asin();
+    (yaw_offset, pitch_offset)
+}
+
+fn make_color_gradient(palette: &HudPalette) -> ColorGradient {
+    let with_alpha = |c: Color| Color::from_rgba(c.r, c.g, c.b, 200);
     ColorGradientBuilder::new()
-        .with_point(GradientPoint::new(0.0, Color::from_rgba(255, 0, 0, 200)))
-        .with_point(GradientPoint::new(1.0, Color::from_rgba(0, 255, 0, 200)))
+        .with_point(GradientPoint::new(0.0, with_alpha(palette.health_empty)))
+        .with_point(GradientPoint::new(1.0, with_alpha(palette.health_full)))
         .build()
 }
 
 impl Player {
+    /// Horizontal distance from the player to a weapon at which it is picked up.
+    const WEAPON_WALK_OVER_DISTANCE: f32 = 0.4;
+    /// Maximum height difference between center of the body and a weapon lying on the
+    /// floor, the body center is about half a meter above the feet.
+    const WEAPON_WALK_OVER_HEIGHT: f32 = 1.0;
+    const MOUSE_ACCELERATION: f32 = 0.05;
+    const MAX_MOUSE_ACCELERATION: f32 = 3.0;
+    const AIM_ASSIST_CONE_ANGLE: f32 = 0.17; // ~10 degrees
+    const AIM_ASSIST_SLOWDOWN_ANGLE: f32 = 0.05;
+    /// Multiplier of walk speed when flying in noclip mode.
+    const NOCLIP_SPEED_MULTIPLIER: f32 = 3.0;
+    const AIM_ASSIST_MAX_DISTANCE: f32 = 30.0;
+    const AIM_ASSIST_MAGNETISM: f32 = 0.05;
+    const AIM_SNAP_MAX_DISTANCE: f32 = 50.0;
+    const LOOT_DISTANCE: f32 = 1.5;
+    const LOOT_CONE_ANGLE: f32 = 0.7; // ~40 degrees
+
+    /// Returns closest corpse with loot that is within reach and in front of the camera.
+    fn find_lootable_corpse<'a>(
+        &self,
+        scene: &Scene,
+        corpses: &'a [CorpseDescriptor],
+    ) -> Option<&'a CorpseDescriptor> {
+        let self_position = scene.graph[self.pivot].global_position();
+        let look = scene.graph[self.camera_controller.camera()].look_vector();
+        let look = Vector3::new(look.x, 0.0, look.z).try_normalize(f32::EPSILON)?;
+
+        corpses
+            .iter()
+            .filter_map(|corpse| {
+                let to_corpse = corpse.position - self_position;
+                let distance = to_corpse.norm();
+                // Corpse lies on the floor, so only horizontal direction matters.
+                let dir = Vector3::new(to_corpse.x, 0.0, to_corpse.z)
+                    .try_normalize(f32::EPSILON)
+                    .unwrap_or(look);
+                if distance < Self::LOOT_DISTANCE
+                    && look.dot(&dir).min(1.0).acos() < Self::LOOT_CONE_ANGLE
+                {
+                    Some((corpse, distance))
+                } else {
+                    None
+                }
+            })
+            .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+            .map(|(corpse, _)| corpse)
+    }
+
+    pub fn get_definition() -> &'static PlayerDefinition {
+        &DEFINITION
+    }
+
+    pub fn set_palette(&mut self, palette: &HudPalette) {
+        self.health_color_gradient = make_color_gradient(palette);
+    }
+
     pub async fn new(
         scene: &mut Scene,
         resource_manager: ResourceManager,
@@ -387,6 +559,17 @@ impl Player {
         let hand = scene
             .graph
             .find_by_name(model_handle, "mixamorig:RightHand");
+        // Keep weapons attached to the model if the hand is missing, otherwise they would be
+        // floating at the origin of the scene.
+        let hand = if hand.is_some() {
+            hand
+        } else {
+            Log::writeln(
+                MessageKind::Warning,
+                "Right hand bone not found, weapons will be attached to the model!".to_owned(),
+            );
+            model_handle
+        };
 
         let hand_scale = scene.graph.global_scale(hand);
 
@@ -490,6 +673,8 @@ impl Player {
                 sender: Some(sender),
                 hit_boxes: find_hit_boxes(pivot, scene),
                 inventory,
+                weapon_slots: Self::get_definition().weapon_slots,
+                faction: Faction::Player,
                 ..Default::default()
             },
             camera_controller: CameraController::new(resource_manager.clone(), &mut scene.graph)
@@ -508,7 +693,8 @@ impl Player {
                 target: 0.0,
                 speed: 10.0,
             },
-            move_speed: 0.65,
+            move_speed: DEFINITION.movement.walk_speed,
+            spawn_protection: DEFINITION.spawn_protection,
             spine_pitch: SmoothAngle {
                 angle: 0.0,
                 target: 0.0,
@@ -533,7 +719,7 @@ impl Player {
             target_velocity: Default::default(),
             weapon_display,
             last_health: 100.0,
-            health_color_gradient: make_color_gradient(),
+            health_color_gradient: make_color_gradient(&Default::default()),
             item_display,
             v_recoil: SmoothAngle {
                 angle: 0.0,
@@ -545,6 +731,174 @@ impl Player {
                 target: 0.0,
                 speed: 1.5, // rad/s
             },
+            spread: 0.0,
+            charge: 0.0,
+            trigger_locked: false,
+            health_regen: false,
+            regen_delay: 0.0,
+            regen_last_health: 100.0,
+            regenerating: false,
+            climbing: None,
+            god_mode: false,
+            noclip: false,
+            spawn_protection: 0.0,
+            weapon_inspect: Default::default(),
+            aim_snap_cooldown: 0.0,
+        }
+    }
+
+    /// Returns accuracy spread in [0; 1] range, it grows when player moves or shoots.
+    pub fn accuracy_spread(&self) -> f32 {
+        self.spread
+    }
+
+    pub fn set_health_regen(&mut self, enabled: bool) {
+        self.health_regen = enabled;
+    }
+
+    /// Whether action control is held, some objectives must be held to complete them.
+    pub fn is_action_held(&self) -> bool {
+        self.controller.action
+    }
+
+    pub fn set_weapon_inspect(&mut self, enabled: bool) {
+        self.weapon_inspect.set_enabled(enabled);
+    }
+
+    pub fn is_god_mode(&self) -> bool {
+        self.god_mode
+    }
+
+    /// Whether the player ignores damage, either because of god mode or spawn protection.
+    pub fn is_invulnerable(&self) -> bool {
+        self.god_mode || self.spawn_protection > 0.0
+    }
+
+    /// Makes the player invulnerable for a while, so bots that already aim at a spawn point
+    /// won't kill the player right after spawn.
+    pub fn protect_spawn(&mut self) {
+        self.spawn_protection = DEFINITION.spawn_protection;
+    }
+
+    pub fn set_god_mode(&mut self, enabled: bool) {
+        self.god_mode = enabled;
+    }
+
+    pub fn is_noclip(&self) -> bool {
+        self.noclip
+    }
+
+    /// Kinematic body is not affected by gravity and is not pushed by walls, so the player
+    /// is able to fly through them.
+    pub fn set_noclip(&mut self, physics: &mut Physics, enabled: bool) {
+        self.noclip = enabled;
+        if let Some(body) = physics.bodies.get_mut(self.body.into()) {
+            body.set_body_status(if enabled {
+                BodyStatus::Kinematic
+            } else {
+                BodyStatus::Dynamic
+            });
+            body.set_linvel(Default::default(), true);
+        }
+    }
+
+    pub fn set_camera_shake(&mut self, scale: f32) {
+        self.camera_controller.set_shake_scale(scale);
+    }
+
+    /// Shakes the camera of the player, `amount` is in [0; 1] range.
+    pub fn shake_camera(&mut self, amount: f32) {
+        self.camera_controller.add_trauma(amount);
+    }
+
+    fn update_health_regen(&mut self, dt: f32) {
+        let definition = &Self::get_definition().health_regen;
+
+        let was_damaged = self.health < self.regen_last_health;
+        if was_damaged {
+            self.regen_delay = definition.delay;
+        } else {
+            self.regen_delay -= dt;
+        }
+
+        self.regenerating = self.health_regen
+            && !self.is_dead()
+            && self.regen_delay <= 0.0
+            && self.health < definition.cap;
+        if self.regenerating {
+            self.health = (self.health + definition.rate * dt).min(definition.cap);
+        }
+
+        self.regen_last_health = self.health;
+    }
+
+    /// Grabs a ladder when the player walks forward into a ladder volume, lets it go when
+    /// the player climbs down to the ground or leaves the volume. Returns a position to
+    /// which the player must be moved when it climbs up to the top of the ladder. To climb
+    /// down from the top the player steps onto the ladder and then moves backward.
+    fn update_climbing(
+        &mut self,
+        ladders: &[Ladder],
+        position: Vector3<f32>,
+        is_on_ground: bool,
+    ) -> Option<Vector3<f32>> {
+        match self.climbing {
+            None => {
+                if self.controller.walk_forward {
+                    self.climbing = ladders.iter().find(|l| l.contains(position)).copied();
+                }
+                None
+            }
+            Some(ladder) => {
+                if self.controller.walk_forward && position.y >= ladder.top.y {
+                    self.climbing = None;
+                    Some(ladder.top)
+                } else {
+                    if !ladder.contains(position) || (self.controller.walk_backward && is_on_ground)
+                    {
+                        self.climbing = None;
+                    }
+                    None
+                }
+            }
+        }
+    }
+
+    /// Returns charge of a shot of current weapon in [0; 1] range.
+    pub fn weapon_charge(&self) -> f32 {
+        self.charge
+    }
+
+    fn shoot(
+        &mut self,
+        weapon_handle: Handle<Weapon>,
+        weapon: &Weapon,
+        damage_scale: f32,
+        rng: &mut GameRng,
+    ) {
+        let ammo_per_shot = weapon.definition.ammo_consumption_per_shot;
+
+        if self
+            .inventory
+            .try_extract_exact_items(ItemKind::Ammo, ammo_per_shot)
+            == ammo_per_shot
+        {
+            self.character
+                .sender
+                .as_ref()
+                .unwrap()
+                .send(Message::ShootWeapon {
+                    weapon: weapon_handle,
+                    direction: None,
+                    damage_scale,
+                })
+                .unwrap();
+
+            self.camera_controller.request_shake_camera();
+            self.v_recoil
+                .set_target(weapon.definition.gen_v_recoil_angle(rng));
+            self.h_recoil
+                .set_target(weapon.definition.gen_h_recoil_angle(rng));
         }
     }
 
@@ -556,22 +910,64 @@ impl Player {
         self.health <= 0.0
     }
 
-    pub fn update(&mut self, self_handle: Handle<Actor>, context: &mut UpdateContext) {
+    pub fn update(
+        &mut self,
+        self_handle: Handle<Actor>,
+        context: &mut UpdateContext,
+        corpses: &[CorpseDescriptor],
+    ) {
         let UpdateContext { time, scene, .. } = context;
 
+        self.character.update_weapon_switch(time.delta);
+        self.character
+            .update_status_effects(self_handle, time.delta);
+        self.character.update_knockback(time.delta);
+        self.update_health_regen(time.delta);
+        self.spawn_protection -= time.delta;
+        self.aim_snap_cooldown = (self.aim_snap_cooldown - time.delta).max(0.0);
+
+        let mut health_color = self.health_color_gradient.get_color(self.health / 100.0);
+        if self.spawn_protection > 0.0 {
+            // Fast shimmer of the health bar tells that the player is invulnerable.
+            let k = 0.5 + 0.5 * (time.elapsed as f32 * 20.0).sin();
+            let shimmer = |c: u8| (c as f32 + (255.0 - c as f32) * k) as u8;
+            health_color = Color::from_rgba(
+                shimmer(health_color.r),
+                shimmer(health_color.g),
+                shimmer(health_color.b),
+                health_color.a,
+            );
+        }
+        if self.regenerating {
+            // Subtle pulse of the health bar tells that health is regenerating.
+            let k = 0.15 * (1.0 + (time.elapsed as f32 * 6.0).sin());
+            let brighten = |c: u8| (c as f32 + (255.0 - c as f32) * k) as u8;
+            health_color = Color::from_rgba(
+                brighten(health_color.r),
+                brighten(health_color.g),
+                brighten(health_color.b),
+                health_color.a,
+            );
+        }
         let mesh = scene.graph[self.health_cylinder].as_mesh_mut();
         mesh.surfaces_mut()
             .first_mut()
             .unwrap()
-            .set_color(self.health_color_gradient.get_color(self.health / 100.0));
+            .set_color(health_color);
 
         let has_ground_contact = self.has_ground_contact(&scene.physics);
+        let is_on_ground = self.is_on_ground(&scene.physics);
+
+        let position = self.position(&scene.graph);
+        if let Some(exit) = self.update_climbing(context.ladders, position, is_on_ground) {
+            self.character.set_position(&mut scene.physics, exit);
+        }
 
         let is_walking = self.controller.walk_backward
             || self.controller.walk_forward
             || self.controller.walk_right
             || self.controller.walk_left;
-        let is_jumping = has_ground_contact && self.controller.jump;
+        let is_jumping = is_on_ground && self.controller.jump && self.climbing.is_none();
 
         let should_be_stunned = if self.last_health - self.health >= 15.0 {
             for &animation in self
@@ -591,9 +987,10 @@ impl Player {
 
         let weapon_kind = if self.current_weapon().is_some() {
             match context.weapons[self.current_weapon()].get_kind() {
-                WeaponKind::M4 | WeaponKind::Ak47 | WeaponKind::PlasmaRifle => {
-                    CombatWeaponKind::Rifle
-                }
+                WeaponKind::M4
+                | WeaponKind::Ak47
+                | WeaponKind::PlasmaRifle
+                | WeaponKind::Shotgun => CombatWeaponKind::Rifle,
                 WeaponKind::Glock => CombatWeaponKind::Pistol,
             }
         } else {
@@ -647,6 +1044,7 @@ impl Player {
             }
             self.run_factor += (self.target_run_factor - self.run_factor) * 0.1;
 
+            let gravity = scene.physics.gravity.y;
             let body = scene.physics.bodies.get_mut(self.body.into()).unwrap();
 
             let pivot = &scene.graph[self.pivot];
@@ -678,25 +1076,41 @@ impl Player {
                 self.target_velocity -= look_vector;
             }
 
-            let can_move = self.lower_body_machine.machine.active_state()
-                != self.lower_body_machine.fall_state
+            let movement = &DEFINITION.movement;
+
+            let is_falling = self.lower_body_machine.machine.active_state()
+                == self.lower_body_machine.fall_state;
+            let can_move = !is_falling
                 && self.lower_body_machine.machine.active_state()
                     != self.lower_body_machine.land_state
                 && !stunned;
 
-            let speed = if can_move {
-                math::lerpf(self.move_speed, self.move_speed * 4.0, self.run_factor) * time.delta
+            let control = if self.climbing.is_some() {
+                0.0
+            } else if can_move {
+                1.0
+            } else if is_falling && !stunned {
+                movement.air_control
             } else {
                 0.0
             };
 
+            let speed = math::lerpf(
+                self.move_speed,
+                self.move_speed * movement.run_speed_multiplier,
+                self.run_factor,
+            ) * control
+                * self.character.movement_speed_factor()
+                * time.movement_delta();
+
             self.target_velocity = self
                 .target_velocity
                 .try_normalize(std::f32::EPSILON)
                 .map(|v| v.scale(speed))
                 .unwrap_or_default();
 
-            self.velocity.follow(&self.target_velocity, 0.15);
+            self.velocity
+                .follow(&self.target_velocity, movement.acceleration);
 
             let mut new_y_vel = None;
             while let Some(event) = scene
@@ -704,7 +1118,10 @@ impl Player {
                 .get_mut(self.lower_body_machine.jump_animation)
                 .pop_event()
             {
+                // Impulse is applied only when standing on the ground, so the player won't
+                // jump again in the air.
                 if event.signal_id == LowerBodyMachine::JUMP_SIGNAL
+                    && is_on_ground
                     && (self.lower_body_machine.machine.active_transition()
                         == self.lower_body_machine.idle_to_jump
                         || self.lower_body_machine.machine.active_transition()
@@ -712,7 +1129,7 @@ impl Player {
                         || self.lower_body_machine.machine.active_state()
                             == self.lower_body_machine.jump_state)
                 {
-                    new_y_vel = Some(3.0 * time.delta);
+                    new_y_vel = Some(movement.jump_speed(gravity) * time.movement_delta());
                 }
             }
 
@@ -774,6 +1191,7 @@ impl Player {
                                 direction,
                                 initial_velocity: direction.scale(15.0),
                                 owner: ProjectileOwner::Actor(self_handle),
+                                damage_scale: 1.0,
                             })
                             .unwrap();
                     }
@@ -782,24 +1200,68 @@ impl Player {
 
             let quat_yaw = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), self.controller.yaw);
 
+            let knockback = self.character.knockback_velocity();
+
             body.wake_up(true);
             body.set_angvel(Default::default(), true);
-            if let Some(new_y_vel) = new_y_vel {
+            if self.noclip {
+                // Fly where the camera looks, jump control lifts the player up.
+                let camera = &scene.graph[self.camera_controller.camera()];
+                let mut direction = Vector3::default();
+                if self.controller.walk_forward {
+                    direction += camera.look_vector();
+                }
+                if self.controller.walk_backward {
+                    direction -= camera.look_vector();
+                }
+                if self.controller.walk_left {
+                    direction += camera.side_vector();
+                }
+                if self.controller.walk_right {
+                    direction -= camera.side_vector();
+                }
+                if self.controller.jump {
+                    direction += Vector3::y();
+                }
+                let speed = self.move_speed * Self::NOCLIP_SPEED_MULTIPLIER;
+                let mut next_position = *body.position();
+                next_position.translation.vector += direction
+                    .try_normalize(std::f32::EPSILON)
+                    .unwrap_or_default()
+                    .scale(speed * time.delta);
+                body.set_next_kinematic_position(next_position);
+            } else if self.climbing.is_some() {
+                let direction = match (self.controller.walk_forward, self.controller.walk_backward)
+                {
+                    (true, false) => 1.0,
+                    (false, true) => -1.0,
+                    _ => 0.0,
+                };
+                // Compensate gravity that will be applied on next physics step, so the player
+                // won't slide down the ladder.
                 body.set_linvel(
                     Vector3::new(
-                        self.velocity.x / time.delta,
-                        new_y_vel / time.delta,
-                        self.velocity.z / time.delta,
+                        0.0,
+                        movement.climb_speed * direction - gravity * time.delta,
+                        0.0,
                     ),
                     true,
                 );
             } else {
+                // Player can't move faster than running speed.
+                let max_speed = self.move_speed
+                    * movement.run_speed_multiplier
+                    * self.character.movement_speed_factor();
+                let velocity = time.velocity_from_offset(
+                    Vector3::new(self.velocity.x, 0.0, self.velocity.z),
+                    max_speed,
+                );
+                let y_vel = match new_y_vel {
+                    Some(new_y_vel) => new_y_vel / time.movement_delta(),
+                    None => body.linvel().y,
+                };
                 body.set_linvel(
-                    Vector3::new(
-                        self.velocity.x / time.delta,
-                        body.linvel().y,
-                        self.velocity.z / time.delta,
-                    ),
+                    Vector3::new(velocity.x + knockback.x, y_vel, velocity.z + knockback.z),
                     true,
                 );
             }
@@ -937,10 +1399,21 @@ impl Player {
                 self.weapon_pitch_correction.set_target(8.0f32.to_radians());
             }
 
+            // Weapon is inspected only while the player stands still.
+            if self.controller.aim
+                || is_walking
+                || !has_ground_contact
+                || self.climbing.is_some()
+                || should_be_stunned
+            {
+                self.weapon_inspect.interrupt();
+            }
+
             if can_move {
                 let yaw_correction_angle = self.weapon_yaw_correction.update(time.delta).angle();
                 let pitch_correction_angle =
                     self.weapon_pitch_correction.update(time.delta).angle();
+                let inspect_rotation = self.weapon_inspect.update(time.delta);
                 scene.graph[self.weapon_pivot]
                     .local_transform_mut()
                     .set_rotation(
@@ -948,7 +1421,8 @@ impl Player {
                             * UnitQuaternion::from_axis_angle(
                                 &Vector3::x_axis(),
                                 pitch_correction_angle,
-                            ),
+                            )
+                            * inspect_rotation,
                     );
             }
 
@@ -964,7 +1438,7 @@ impl Player {
                 *time,
             );
 
-            if has_ground_contact {
+            if has_ground_contact || self.climbing.is_some() {
                 self.in_air_time = 0.0;
             } else {
                 self.in_air_time += time.delta;
@@ -983,6 +1457,7 @@ impl Player {
 
             scene.graph[self.item_display].set_visibility(false);
 
+            let mut item_in_reach = false;
             for (item_handle, item) in context.items.pair_iter() {
                 let self_position = scene.graph[self.pivot].global_position();
                 let item_position = scene.graph[item.get_pivot()].global_position();
@@ -998,7 +1473,14 @@ impl Player {
                         })
                         .unwrap();
 
-                    if self.controller.action {
+                    // Weapons are picked up by simply walking over them.
+                    let offset = item_position - self_position;
+                    let walked_over_weapon = item.get_kind().associated_weapon().is_some()
+                        && Vector2::new(offset.x, offset.z).norm()
+                            < Self::WEAPON_WALK_OVER_DISTANCE
+                        && offset.y.abs() < Self::WEAPON_WALK_OVER_HEIGHT;
+
+                    if self.controller.action || walked_over_weapon {
                         self.sender
                             .as_ref()
                             .unwrap()
@@ -1021,10 +1503,40 @@ impl Player {
                         .set_position(item_position + Vector3::new(0.0, 0.2, 0.0));
                     display.set_visibility(true);
 
+                    item_in_reach = true;
                     break;
                 }
             }
 
+            if !item_in_reach {
+                if let Some(corpse) = self.find_lootable_corpse(scene, corpses) {
+                    let sender = self.sender.as_ref().unwrap();
+
+                    sender
+                        .send(Message::ShowItemDisplay {
+                            item: corpse.item,
+                            count: corpse.count,
+                        })
+                        .unwrap();
+
+                    if self.controller.action {
+                        sender
+                            .send(Message::LootCorpse {
+                                actor: self_handle,
+                                corpse: corpse.handle,
+                            })
+                            .unwrap();
+                        sender.send(Message::SyncInventory).unwrap();
+                    }
+
+                    let display = &mut scene.graph[self.item_display];
+                    display
+                        .local_transform_mut()
+                        .set_position(corpse.position + Vector3::new(0.0, 0.5, 0.0));
+                    display.set_visibility(true);
+                }
+            }
+
             if let Some(&current_weapon_handle) = self
                 .character
                 .weapons
@@ -1034,43 +1546,49 @@ impl Player {
                     == self.upper_body_machine.aim_state
                 {
                     let weapon = &context.weapons[current_weapon_handle];
-                    weapon.laser_sight().set_visible(true, &mut scene.graph);
+                    weapon
+                        .laser_sight()
+                        .set_visible(true, true, &mut scene.graph);
                     scene.graph[self.weapon_display]
                         .set_visibility(true)
                         .local_transform_mut()
                         .set_position(weapon.definition.ammo_indicator_offset());
 
-                    if self.controller.shoot && weapon.can_shoot(context.time) {
-                        let ammo_per_shot = context.weapons[current_weapon_handle]
-                            .definition
-                            .ammo_consumption_per_shot;
-
-                        if self
-                            .inventory
-                            .try_extract_exact_items(ItemKind::Ammo, ammo_per_shot)
-                            == ammo_per_shot
-                        {
-                            self.character
-                                .sender
-                                .as_ref()
-                                .unwrap()
-                                .send(Message::ShootWeapon {
-                                    weapon: current_weapon_handle,
-                                    direction: None,
-                                })
-                                .unwrap();
-
-                            self.camera_controller.request_shake_camera();
-                            self.v_recoil
-                                .set_target(weapon.definition.gen_v_recoil_angle());
-                            self.h_recoil
-                                .set_target(weapon.definition.gen_h_recoil_angle());
+                    let can_shoot =
+                        !self.character.is_switching_weapon() && weapon.can_shoot(context.time);
+
+                    if let Some(charge) = weapon.definition.charge {
+                        // Charge while shoot control is held, fire on release.
+                        if self.controller.shoot && can_shoot {
+                            self.charge = (self.charge + context.time.delta / charge.time).min(1.0);
+                        } else if !self.controller.shoot && self.charge > 0.0 {
+                            if self.charge >= charge.min_charge && can_shoot {
+                                self.shoot(
+                                    current_weapon_handle,
+                                    weapon,
+                                    charge.damage_scale(self.charge),
+                                    context.rng,
+                                );
+                            }
+                            self.charge = 0.0;
+                        }
+                    } else {
+                        self.charge = 0.0;
+                        if weapon.is_bursting() {
+                            if can_shoot {
+                                self.shoot(current_weapon_handle, weapon, 1.0, context.rng);
+                            }
+                        } else if self.controller.shoot && can_shoot && !self.trigger_locked {
+                            self.shoot(current_weapon_handle, weapon, 1.0, context.rng);
+                            // Next burst requires the trigger to be released first.
+                            self.trigger_locked = weapon.definition.burst.is_some();
                         }
                     }
                 } else {
+                    self.charge = 0.0;
                     context.weapons[current_weapon_handle]
                         .laser_sight()
-                        .set_visible(false, &mut scene.graph);
+                        .set_visible(false, false, &mut scene.graph);
                     scene.graph[self.weapon_display].set_visibility(false);
                 }
             }
@@ -1078,6 +1596,15 @@ impl Player {
             self.v_recoil.update(context.time.delta);
             self.h_recoil.update(context.time.delta);
 
+            let max_speed = self.move_speed
+                * DEFINITION.movement.run_speed_multiplier
+                * context.time.movement_delta();
+            let movement = (self.velocity.norm() / max_speed).min(1.0);
+            let recoil = ((self.v_recoil.angle().abs() + self.h_recoil.angle().abs())
+                / 5.0f32.to_radians())
+            .min(1.0);
+            self.spread = (0.6 * movement + 0.4 * recoil).min(1.0);
+
             let spine_transform = scene.graph[self.spine].local_transform_mut();
             let rotation = **spine_transform.rotation();
             spine_transform.set_rotation(
@@ -1097,6 +1624,65 @@ impl Player {
         }
     }
 
+    /// Searches for a visible target closest to the line of sight of the camera. Returns
+    /// yaw and pitch offsets that should be applied to look directly at the target.
+    fn find_aim_assist_target(
+        &self,
+        scene: &Scene,
+        targets: &[Vector3<f32>],
+    ) -> Option<(f32, f32)> {
+        let camera = &scene.graph[self.camera_controller.camera()];
+        let origin = camera.global_position();
+        let look = camera.look_vector().try_normalize(f32::EPSILON)?;
+
+        let mut closest = None;
+        let mut closest_angle = Self::AIM_ASSIST_CONE_ANGLE;
+        for &target in targets {
+            let dir = target - origin;
+            let distance = dir.norm();
+            if distance > Self::AIM_ASSIST_MAX_DISTANCE || distance <= f32::EPSILON {
+                continue;
+            }
+            let dir = dir.scale(1.0 / distance);
+
+            let angle = look.dot(&dir).min(1.0).acos();
+            if angle >= closest_angle || !is_line_of_sight_clear(&scene.physics, target, origin) {
+                continue;
+            }
+
+            closest_angle = angle;
+            closest = Some(dir);
+        }
+
+        closest.map(|dir| aim_offsets(look, dir))
+    }
+
+    /// Searches for the nearest target that is in view of the camera and not occluded.
+    /// Returns yaw and pitch offsets that should be applied to look directly at the target.
+    fn find_aim_snap_target(&self, scene: &Scene, targets: &[Vector3<f32>]) -> Option<(f32, f32)> {
+        let camera = &scene.graph[self.camera_controller.camera()];
+        let origin = camera.global_position();
+        let look = camera.look_vector().try_normalize(f32::EPSILON)?;
+        let frustum = Frustum::from(camera.view_projection_matrix())?;
+
+        targets
+            .iter()
+            .filter_map(|&target| {
+                let distance = target.metric_distance(&origin);
+                if distance > Self::AIM_SNAP_MAX_DISTANCE
+                    || distance <= f32::EPSILON
+                    || !frustum.is_contains_point(target)
+                    || !is_line_of_sight_clear(&scene.physics, target, origin)
+                {
+                    None
+                } else {
+                    Some((target, distance))
+                }
+            })
+            .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
+            .map(|(target, distance)| aim_offsets(look, (target - origin).scale(1.0 / distance)))
+    }
+
     pub fn process_input_event(
         &mut self,
         event: &Event<()>,
@@ -1104,6 +1690,7 @@ impl Player {
         scene: &mut Scene,
         weapons: &WeaponContainer,
         control_scheme: &ControlScheme,
+        aim_assist_targets: &[Vector3<f32>],
     ) {
         let button_state = match event {
             Event::WindowEvent { event, .. } => {
@@ -1136,9 +1723,52 @@ impl Player {
                     Some((ControlButton::Mouse(button as u16), state))
                 }
                 DeviceEvent::MouseMotion { delta } => {
-                    let mouse_sens = control_scheme.mouse_sens * dt;
-                    self.controller.yaw -= (delta.0 as f32) * mouse_sens;
-                    self.controller.pitch = (self.controller.pitch + (delta.1 as f32) * mouse_sens)
+                    self.weapon_inspect.interrupt();
+
+                    let mut delta = Vector2::new(delta.0 as f32, delta.1 as f32);
+                    if control_scheme.mouse_smoothing {
+                        let raw_delta = delta;
+                        delta = (delta + self.controller.last_mouse_delta).scale(0.5);
+                        self.controller.last_mouse_delta = raw_delta;
+                    }
+
+                    let acceleration = if control_scheme.mouse_acceleration {
+                        (1.0 + Self::MOUSE_ACCELERATION * delta.norm())
+                            .min(Self::MAX_MOUSE_ACCELERATION)
+                    } else {
+                        1.0
+                    };
+                    let y_sign = if control_scheme.mouse_y_inverse {
+                        -1.0
+                    } else {
+                        1.0
+                    };
+                    let mut horizontal_sens = control_scheme.mouse_sens * acceleration * dt;
+                    let mut vertical_sens =
+                        control_scheme.mouse_vertical_sens() * acceleration * dt * y_sign;
+
+                    if control_scheme.aim_assist > 0.0 && delta.norm() > 0.0 {
+                        if let Some((yaw_offset, pitch_offset)) =
+                            self.find_aim_assist_target(scene, aim_assist_targets)
+                        {
+                            // Slow down when reticle is over target, so it is easier to keep aim.
+                            if yaw_offset.abs().max(pitch_offset.abs())
+                                < Self::AIM_ASSIST_SLOWDOWN_ANGLE
+                            {
+                                let slowdown = 1.0 - 0.5 * control_scheme.aim_assist;
+                                horizontal_sens *= slowdown;
+                                vertical_sens *= slowdown;
+                            }
+
+                            // Slightly pull the view towards target while player moves it.
+                            let magnetism = Self::AIM_ASSIST_MAGNETISM * control_scheme.aim_assist;
+                            self.controller.yaw += yaw_offset * magnetism;
+                            self.controller.pitch += pitch_offset * magnetism;
+                        }
+                    }
+
+                    self.controller.yaw -= delta.x * horizontal_sens;
+                    self.controller.pitch = (self.controller.pitch + delta.y * vertical_sens)
                         .max(-90.0f32.to_radians())
                         .min(90.0f32.to_radians());
                     None
@@ -1148,6 +1778,10 @@ impl Player {
             _ => None,
         };
 
+        if button_state.is_some() {
+            self.weapon_inspect.interrupt();
+        }
+
         let can_change_weapon = self.weapon_change_direction.is_none()
             && scene.animations[self.upper_body_machine.grab_animation].has_ended();
 
@@ -1161,8 +1795,14 @@ impl Player {
 
         if let Some((button, state)) = button_state {
             if button == control_scheme.aim.button {
-                self.controller.aim = state == ElementState::Pressed;
-                if state == ElementState::Pressed {
+                let pressed = state == ElementState::Pressed;
+                self.controller.aim = control_scheme.aim_mode.apply(
+                    self.controller.aim,
+                    self.controller.aim_pressed,
+                    pressed,
+                );
+                self.controller.aim_pressed = pressed;
+                if self.controller.aim {
                     scene.graph[self.inventory_display].set_visibility(false);
                 }
             } else if button == control_scheme.move_forward.button {
@@ -1175,7 +1815,13 @@ impl Player {
                 self.controller.walk_right = state == ElementState::Pressed;
             } else if button == control_scheme.jump.button {
                 let jump_anim = scene.animations.get(self.lower_body_machine.jump_animation);
-                let can_jump = !jump_anim.is_enabled() || jump_anim.has_ended();
+                let can_jump = (!jump_anim.is_enabled() || jump_anim.has_ended())
+                    && self.is_on_ground(&scene.physics);
+
+                // Jump lets go of a ladder.
+                if state == ElementState::Pressed {
+                    self.climbing = None;
+                }
 
                 if state == ElementState::Pressed && can_jump {
                     // Rewind jump animation to beginning before jump.
@@ -1192,8 +1838,29 @@ impl Player {
                 }
 
                 self.controller.jump = state == ElementState::Pressed && can_jump;
+            } else if button == control_scheme.snap_aim.button {
+                if state == ElementState::Pressed
+                    && control_scheme.aim_snap
+                    && self.aim_snap_cooldown <= 0.0
+                {
+                    if let Some((yaw_offset, pitch_offset)) =
+                        self.find_aim_snap_target(scene, aim_assist_targets)
+                    {
+                        self.controller.yaw += yaw_offset;
+                        self.controller.pitch = (self.controller.pitch + pitch_offset)
+                            .max(-90.0f32.to_radians())
+                            .min(90.0f32.to_radians());
+                        self.aim_snap_cooldown = control_scheme.aim_snap_cooldown;
+                    }
+                }
             } else if button == control_scheme.run.button {
-                self.controller.run = state == ElementState::Pressed;
+                let pressed = state == ElementState::Pressed;
+                self.controller.run = control_scheme.run_mode.apply(
+                    self.controller.run,
+                    self.controller.run_pressed,
+                    pressed,
+                );
+                self.controller.run_pressed = pressed;
             } else if button == control_scheme.flash_light.button {
                 if state == ElementState::Pressed {
                     let current_weapon = self.current_weapon();
@@ -1205,6 +1872,17 @@ impl Player {
                         })
                         .unwrap();
                 }
+            } else if button == control_scheme.laser_sight.button {
+                if state == ElementState::Pressed {
+                    let current_weapon = self.current_weapon();
+                    self.sender
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::SwitchLaserSight {
+                            weapon: current_weapon,
+                        })
+                        .unwrap();
+                }
             } else if button == control_scheme.grab_ak47.button && can_change_weapon {
                 if current_weapon_kind.map_or(false, |k| k != WeaponKind::Ak47) {
                     weapon_change_direction = Some(RequiredWeapon::Specific(WeaponKind::Ak47));
@@ -1223,14 +1901,11 @@ impl Player {
                     weapon_change_direction = Some(RequiredWeapon::Specific(WeaponKind::Glock));
                 }
             } else if button == control_scheme.next_weapon.button {
-                if state == ElementState::Pressed
-                    && self.current_weapon < self.weapons.len() as u32 - 1
-                    && can_change_weapon
-                {
+                if state == ElementState::Pressed && self.weapons.len() > 1 && can_change_weapon {
                     weapon_change_direction = Some(RequiredWeapon::Next);
                 }
             } else if button == control_scheme.prev_weapon.button {
-                if state == ElementState::Pressed && self.current_weapon > 0 && can_change_weapon {
+                if state == ElementState::Pressed && self.weapons.len() > 1 && can_change_weapon {
                     weapon_change_direction = Some(RequiredWeapon::Previous);
                 }
             } else if button == control_scheme.toss_grenade.button {
@@ -1246,6 +1921,9 @@ impl Player {
                 }
             } else if button == control_scheme.shoot.button {
                 self.controller.shoot = state == ElementState::Pressed;
+                if !self.controller.shoot {
+                    self.trigger_locked = false;
+                }
             } else if button == control_scheme.action.button {
                 self.controller.action = state == ElementState::Pressed;
             } else if button == control_scheme.inventory.button
@@ -1294,6 +1972,9 @@ impl Player {
         inventory_texture: Texture,
         item_texture: Texture,
     ) {
+        // Noclip is not saved, so the body must be dynamic after loading.
+        self.set_noclip(&mut scene.physics, false);
+
         scene.graph[self.weapon_display]
             .as_mesh_mut()
             .surfaces_mut()
@@ -0,0 +1,107 @@
+//! Idle fidget of the weapon of the player: after some time without input the player turns
+//! the weapon in the hand to inspect it. It is purely cosmetic, any action interrupts it and
+//! the weapon smoothly returns to its usual pose.
+
+use rg3d::core::{
+    algebra::{UnitQuaternion, Vector3},
+    visitor::{Visit, VisitResult, Visitor},
+};
+
+pub struct WeaponInspect {
+    /// Whether inspection is enabled in settings, it is set by the game after a level is
+    /// loaded, so it is not saved.
+    enabled: bool,
+    /// Time (in seconds) since last action of the player.
+    idle_time: f32,
+    inspecting: bool,
+    /// Time since the beginning of inspection, it is frozen when inspection is interrupted so
+    /// the weapon returns from the pose it had.
+    time: f32,
+    /// Weight of inspection pose in [0; 1] range.
+    weight: f32,
+}
+
+impl Default for WeaponInspect {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            idle_time: 0.0,
+            inspecting: false,
+            time: 0.0,
+            weight: 0.0,
+        }
+    }
+}
+
+impl Visit for WeaponInspect {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.idle_time.visit("IdleTime", visitor)?;
+        self.inspecting.visit("Inspecting", visitor)?;
+        self.time.visit("Time", visitor)?;
+        self.weight.visit("Weight", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+impl WeaponInspect {
+    /// Time (in seconds) without input after which the weapon is inspected.
+    const IDLE_DELAY: f32 = 10.0;
+    const DURATION: f32 = 3.5;
+    /// Speed (per second) of blending in and out of inspection pose.
+    const BLEND_SPEED: f32 = 4.0;
+    const MAX_ROLL: f32 = 70.0;
+    const MAX_YAW: f32 = 25.0;
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.interrupt();
+        }
+    }
+
+    /// Stops inspection and restarts countdown to the next one.
+    pub fn interrupt(&mut self) {
+        self.idle_time = 0.0;
+        self.inspecting = false;
+    }
+
+    /// Advances inspection and returns additional rotation of the weapon.
+    pub fn update(&mut self, dt: f32) -> UnitQuaternion<f32> {
+        if self.inspecting {
+            self.time += dt;
+            if self.time >= Self::DURATION {
+                self.interrupt();
+            }
+        } else if self.enabled {
+            self.idle_time += dt;
+            if self.idle_time >= Self::IDLE_DELAY {
+                self.inspecting = true;
+                self.time = 0.0;
+            }
+        }
+
+        let target_weight = if self.inspecting { 1.0 } else { 0.0 };
+        let step = Self::BLEND_SPEED * dt;
+        self.weight = if self.weight < target_weight {
+            (self.weight + step).min(target_weight)
+        } else {
+            (self.weight - step).max(target_weight)
+        };
+
+        self.pose()
+    }
+
+    fn pose(&self) -> UnitQuaternion<f32> {
+        let t = (self.time / Self::DURATION).min(1.0);
+        // Weapon is rolled to look at its side and turned back and forth meanwhile, both
+        // motions start and end at rest pose.
+        let envelope = (std::f32::consts::PI * t).sin() * self.weight;
+        let roll = Self::MAX_ROLL.to_radians() * envelope;
+        let yaw = Self::MAX_YAW.to_radians() * (2.0 * std::f32::consts::PI * t).sin() * envelope;
+        UnitQuaternion::from_axis_angle(&Vector3::z_axis(), roll)
+            * UnitQuaternion::from_axis_angle(&Vector3::y_axis(), yaw)
+    }
+}
@@ -0,0 +1,36 @@
+//! Brief slow motion that is played when the player kills the last bot of an encounter. It
+//! produces a time scale that multiplies time step of gameplay, the scale smoothly returns
+//! to normal at the end of the effect.
+
+#[derive(Default)]
+pub struct SlowMotion {
+    /// Real time (in seconds) left until the end of the effect.
+    timer: f32,
+}
+
+impl SlowMotion {
+    const DURATION: f32 = 1.5;
+    /// Time scale at the peak of the effect.
+    const SCALE: f32 = 0.3;
+    /// Time (in seconds) at the end of the effect during which time scale returns to normal.
+    const EASE_OUT_TIME: f32 = 0.5;
+
+    pub fn start(&mut self) {
+        self.timer = Self::DURATION;
+    }
+
+    pub fn stop(&mut self) {
+        self.timer = 0.0;
+    }
+
+    /// Advances the effect by real time step and returns current time scale.
+    pub fn update(&mut self, dt: f32) -> f32 {
+        if self.timer <= 0.0 {
+            return 1.0;
+        }
+        self.timer = (self.timer - dt).max(0.0);
+
+        let k = (self.timer / Self::EASE_OUT_TIME).min(1.0);
+        1.0 + (Self::SCALE - 1.0) * k
+    }
+}
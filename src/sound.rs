@@ -12,11 +12,12 @@ use rg3d::{
     sound::{
         context::{self, Context},
         effects::{BaseEffect, Effect, EffectInput},
+        engine::SoundEngine,
         source::{generic::GenericSourceBuilder, spatial::SpatialSourceBuilder, Status},
     },
 };
 use serde::Deserialize;
-use std::{collections::HashMap, fs::File, ops::Range, path::Path, time::Duration};
+use std::{collections::HashMap, fs::File, ops::Range, path::Path, sync::Mutex, time::Duration};
 
 #[derive(Debug)]
 pub struct TriangleRange {
@@ -205,6 +206,7 @@ impl SoundManager {
                 gain,
                 rolloff_factor,
                 radius,
+                ..
             } => {
                 self.play_sound(
                     path,
@@ -286,3 +288,39 @@ impl Visit for SoundManager {
         visitor.leave_region()
     }
 }
+
+/// Smoothly changes master gain of the sound engine, instant changes cause audible pops when
+/// volume slider is dragged quickly.
+#[derive(Default)]
+pub struct MasterGainRamp {
+    target: Option<f32>,
+}
+
+impl MasterGainRamp {
+    /// Time (in seconds) needed to change gain from zero to one.
+    const RAMP_TIME: f32 = 0.05;
+
+    pub fn set_target(&mut self, gain: f32) {
+        self.target = Some(gain);
+    }
+
+    /// Returns gain the sound engine will have when the ramp is finished.
+    pub fn gain(&self, sound_engine: &Mutex<SoundEngine>) -> f32 {
+        self.target
+            .unwrap_or_else(|| sound_engine.lock().unwrap().master_gain())
+    }
+
+    pub fn update(&mut self, sound_engine: &Mutex<SoundEngine>, dt: f32) {
+        if let Some(target) = self.target {
+            let mut sound_engine = sound_engine.lock().unwrap();
+            let current = sound_engine.master_gain();
+            let max_step = dt / Self::RAMP_TIME;
+            if (target - current).abs() <= max_step {
+                sound_engine.set_master_gain(target);
+                self.target = None;
+            } else {
+                sound_engine.set_master_gain(current + max_step.copysign(target - current));
+            }
+        }
+    }
+}
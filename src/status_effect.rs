@@ -0,0 +1,167 @@
+//! Status effects are applied to a character for some time and act on it periodically, for
+//! example poison from toxic gas, burning after an explosion, slowdown or healing from an
+//! injector. Every effect has a kind, strength (its meaning depends on the kind), remaining
+//! time and the actor that applied it, so kills made by effects are credited to it.
+
+use crate::actor::Actor;
+use rg3d::core::{
+    pool::Handle,
+    visitor::{Visit, VisitResult, Visitor},
+};
+use serde::Deserialize;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Deserialize)]
+pub enum StatusEffectKind {
+    /// Restores `strength` health per second.
+    Heal,
+    /// Deals `strength` damage per second.
+    Poison,
+    /// Deals `strength` damage per second.
+    Burning,
+    /// Multiplies movement speed by `strength`.
+    Slow,
+}
+
+impl Default for StatusEffectKind {
+    fn default() -> Self {
+        Self::Heal
+    }
+}
+
+impl StatusEffectKind {
+    fn id(self) -> u32 {
+        match self {
+            StatusEffectKind::Heal => 0,
+            StatusEffectKind::Poison => 1,
+            StatusEffectKind::Burning => 2,
+            StatusEffectKind::Slow => 3,
+        }
+    }
+
+    fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(StatusEffectKind::Heal),
+            1 => Ok(StatusEffectKind::Poison),
+            2 => Ok(StatusEffectKind::Burning),
+            3 => Ok(StatusEffectKind::Slow),
+            _ => Err(format!("Invalid status effect kind {}", id)),
+        }
+    }
+
+    /// Time (in seconds) between two applications of an effect, zero means that an effect
+    /// is applied every frame.
+    pub fn tick_interval(self) -> f32 {
+        match self {
+            StatusEffectKind::Heal | StatusEffectKind::Slow => 0.0,
+            // Damage is applied in discrete ticks, so actors won't play pain sounds each frame.
+            StatusEffectKind::Poison | StatusEffectKind::Burning => 0.5,
+        }
+    }
+
+    /// Whether an effect is cancelled when the character takes damage.
+    pub fn is_interrupted_by_damage(self) -> bool {
+        match self {
+            StatusEffectKind::Heal => true,
+            StatusEffectKind::Poison | StatusEffectKind::Burning | StatusEffectKind::Slow => false,
+        }
+    }
+}
+
+impl Visit for StatusEffectKind {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        let mut id = self.id();
+        id.visit(name, visitor)?;
+        if visitor.is_reading() {
+            *self = Self::from_id(id)?;
+        }
+        Ok(())
+    }
+}
+
+/// Description of a status effect in configs, for example burning that is applied by
+/// an explosion.
+#[derive(Copy, Clone, Debug, Deserialize)]
+pub struct StatusEffectDefinition {
+    pub kind: StatusEffectKind,
+    pub strength: f32,
+    pub duration: f32,
+}
+
+#[derive(Default, Copy, Clone, Debug)]
+pub struct StatusEffect {
+    pub kind: StatusEffectKind,
+    pub strength: f32,
+    pub time_left: f32,
+    tick_timer: f32,
+    /// Actor that applied the effect, can be Handle::NONE if the effect was applied by
+    /// environment.
+    pub source: Handle<Actor>,
+}
+
+impl StatusEffect {
+    pub fn new(kind: StatusEffectKind, strength: f32, duration: f32) -> Self {
+        Self {
+            kind,
+            strength,
+            time_left: duration,
+            tick_timer: 0.0,
+            source: Handle::NONE,
+        }
+    }
+
+    pub fn from_definition(definition: &StatusEffectDefinition) -> Self {
+        Self::new(definition.kind, definition.strength, definition.duration)
+    }
+
+    pub fn with_source(mut self, source: Handle<Actor>) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Prolongs the effect using the other effect of the same kind, so an effect that is
+    /// re-applied continuously (like burning in lava) won't restart its ticks.
+    pub fn refresh(&mut self, other: &StatusEffect) {
+        self.strength = self.strength.max(other.strength);
+        self.time_left = self.time_left.max(other.time_left);
+        self.source = other.source;
+    }
+
+    /// Advances the effect and returns the amount of time that must be handled by per-tick
+    /// action of the effect, or `None` if there is no tick on this frame.
+    pub fn update(&mut self, dt: f32) -> Option<f32> {
+        // Do not apply more than remaining time of an effect on last frame.
+        let step = dt.min(self.time_left.max(0.0));
+        self.time_left -= dt;
+
+        let interval = self.kind.tick_interval();
+        if interval <= 0.0 {
+            return Some(step);
+        }
+
+        self.tick_timer += step;
+        if self.tick_timer >= interval {
+            self.tick_timer -= interval;
+            Some(interval)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.time_left <= 0.0
+    }
+}
+
+impl Visit for StatusEffect {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.kind.visit("Kind", visitor)?;
+        self.strength.visit("Strength", visitor)?;
+        self.time_left.visit("TimeLeft", visitor)?;
+        self.tick_timer.visit("TickTimer", visitor)?;
+        self.source.visit("Source", visitor)?;
+
+        visitor.leave_region()
+    }
+}
@@ -1,3 +1,5 @@
+pub mod rng;
+
 use rg3d::core::pool::Handle;
 use rg3d::engine::resource_manager::ResourceManager;
 use rg3d::resource::texture::TextureWrapMode;
@@ -7,12 +9,38 @@ use rg3d::scene::graph::Graph;
 use rg3d::scene::node::Node;
 use rg3d::scene::transform::TransformBuilder;
 use rg3d::{
-    core::algebra::{Point3, Unit, UnitQuaternion, Vector3},
+    core::{
+        algebra::{Point3, Unit, UnitQuaternion, Vector3},
+        rand::Rng,
+    },
     scene::{RigidBodyHandle, Scene},
 };
+use serde::Deserialize;
 use std::collections::HashMap;
 
+/// Tuning of reaction of body parts to impacts, it defines how long damaged limbs cripple
+/// their owner.
+#[derive(Deserialize, Copy, Clone)]
+#[serde(default)]
+pub struct BodyImpactDefinition {
+    /// Time (in seconds) for which an impact fades out.
+    pub duration: f32,
+    /// Strength of an impact (from 0 to 1, it is 1 right after the impact and fades to 0)
+    /// above which a body part counts as affected.
+    pub affected_threshold: f32,
+}
+
+impl Default for BodyImpactDefinition {
+    fn default() -> Self {
+        Self {
+            duration: 1.0,
+            affected_threshold: 0.0,
+        }
+    }
+}
+
 struct ImpactEntry {
+    /// Progress of fading out of the impact in [0; 1] range.
     k: f32,
     source: UnitQuaternion<f32>,
 }
@@ -58,10 +86,15 @@ impl BodyImpactHandler {
         }
     }
 
-    pub fn update_and_apply(&mut self, dt: f32, scene: &mut Scene) {
+    pub fn update_and_apply(
+        &mut self,
+        dt: f32,
+        scene: &mut Scene,
+        definition: &BodyImpactDefinition,
+    ) {
         for (body, entry) in self.additional_rotations.iter_mut() {
             let additional_rotation = entry.source.nlerp(&UnitQuaternion::default(), entry.k);
-            entry.k += dt;
+            entry.k += dt / definition.duration.max(std::f32::EPSILON);
             let node = scene.physics_binder.node_of(*body).unwrap();
             let transform = scene.graph[node].local_transform_mut();
             let new_rotation = **transform.rotation() * additional_rotation;
@@ -70,11 +103,43 @@ impl BodyImpactHandler {
         self.additional_rotations.retain(|_, e| e.k < 1.0);
     }
 
-    pub fn is_affected(&self, handle: RigidBodyHandle) -> bool {
-        self.additional_rotations.contains_key(&handle)
+    pub fn is_affected(&self, handle: RigidBodyHandle, definition: &BodyImpactDefinition) -> bool {
+        self.additional_rotations
+            .get(&handle)
+            .map_or(false, |e| 1.0 - e.k > definition.affected_threshold)
     }
 }
 
+/// Returns random unit vector inside a cone around given direction, `half_angle` is in radians.
+pub fn random_direction_in_cone<R: Rng>(
+    direction: Vector3<f32>,
+    half_angle: f32,
+    rng: &mut R,
+) -> Vector3<f32> {
+    let direction = direction
+        .try_normalize(std::f32::EPSILON)
+        .unwrap_or_else(Vector3::z);
+    if half_angle <= 0.0 {
+        return direction;
+    }
+
+    // Any vector that is not collinear with the direction will do here.
+    let helper = if direction.y.abs() < 0.99 {
+        Vector3::y()
+    } else {
+        Vector3::x()
+    };
+    let side = direction.cross(&helper).normalize();
+    let up = side.cross(&direction);
+
+    // Square root gives uniform distribution over the cone base.
+    let angle = half_angle * rng.gen::<f32>().sqrt();
+    let roll = rng.gen_range(0.0..2.0 * std::f32::consts::PI);
+    let offset = side.scale(roll.cos()) + up.scale(roll.sin());
+
+    direction.scale(angle.cos()) + offset.scale(angle.sin())
+}
+
 /// Creates a camera at given position with a skybox.
 pub async fn create_camera(
     resource_manager: ResourceManager,
@@ -122,3 +187,22 @@ pub async fn create_camera(
     .with_skybox(skybox)
     .build(graph)
 }
+
+/// Saves given value with a visitor and loads it back into a default value, so tests can
+/// check that state survives save/load.
+#[cfg(test)]
+pub fn visit_round_trip<T: rg3d::core::visitor::Visit + Default>(value: &mut T, name: &str) -> T {
+    use rg3d::core::visitor::Visitor;
+
+    let path = std::env::temp_dir().join(format!("station_iapetus_{}.bin", name));
+
+    let mut visitor = Visitor::new();
+    value.visit(name, &mut visitor).unwrap();
+    visitor.save_binary(&path).unwrap();
+
+    let mut loaded = T::default();
+    let mut visitor = Visitor::load_binary(&path).unwrap();
+    loaded.visit(name, &mut visitor).unwrap();
+    let _ = std::fs::remove_file(path);
+    loaded
+}
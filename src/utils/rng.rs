@@ -0,0 +1,70 @@
+//! Seeded random number generator for gameplay randomness. Unlike `thread_rng` its state is
+//! saved together with a level, so a run started with the same seed can be reproduced.
+//! Purely cosmetic randomness does not need to use it and deliberately uses `thread_rng`:
+//! choice of shot, pain and footstep sounds, muzzle flash textures, size of plasma sprites,
+//! flicker of lights and flash light, and camera shake.
+
+use rg3d::{
+    core::visitor::{Visit, VisitResult, Visitor},
+    rand::{self, Error, Rng, RngCore},
+};
+
+/// SplitMix64 generator, it is small and fast and its whole state is a single integer.
+#[derive(Default, Clone, Debug)]
+pub struct GameRng {
+    seed: u64,
+    state: u64,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, state: seed }
+    }
+
+    /// Creates generator with random seed.
+    pub fn from_entropy() -> Self {
+        Self::new(rand::thread_rng().gen())
+    }
+
+    /// Returns seed that was used to create the generator.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            let bytes = self.next_u64().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl Visit for GameRng {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.seed.visit("Seed", visitor)?;
+        self.state.visit("State", visitor)?;
+
+        visitor.leave_region()
+    }
+}
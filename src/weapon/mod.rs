@@ -3,9 +3,11 @@
 use crate::{
     actor::{Actor, ActorContainer},
     character::HitBox,
+    effects::EffectKind,
     item::ItemKind,
     message::Message,
-    weapon::projectile::{Damage, ProjectileKind, ProjectileOwner},
+    utils::{random_direction_in_cone, rng::GameRng},
+    weapon::projectile::{Damage, DamageType, ProjectileKind, ProjectileOwner},
     CollisionGroups, GameTime,
 };
 use rg3d::rand::Rng;
@@ -57,6 +59,7 @@ pub enum WeaponKind {
     Ak47 = 1,
     PlasmaRifle = 2,
     Glock = 3,
+    Shotgun = 4,
 }
 
 impl Default for WeaponKind {
@@ -76,6 +79,7 @@ impl WeaponKind {
             1 => Ok(WeaponKind::Ak47),
             2 => Ok(WeaponKind::PlasmaRifle),
             3 => Ok(WeaponKind::Glock),
+            4 => Ok(WeaponKind::Shotgun),
             _ => Err(format!("unknown weapon kind {}", id)),
         }
     }
@@ -86,6 +90,7 @@ impl WeaponKind {
             WeaponKind::Ak47 => ItemKind::Ak47,
             WeaponKind::PlasmaRifle => ItemKind::PlasmaGun,
             WeaponKind::Glock => ItemKind::Glock,
+            WeaponKind::Shotgun => ItemKind::Shotgun,
         }
     }
 }
@@ -101,15 +106,47 @@ impl Visit for WeaponKind {
     }
 }
 
-#[derive(Default)]
+#[derive(Deserialize, Copy, Clone)]
+pub struct LaserSightDefinition {
+    /// Color of the beam and the dot in RGBA format.
+    pub color: (u8, u8, u8, u8),
+    /// Whether the dot at the end of the beam is shown only for weapons of the player, bots
+    /// show only the beam then.
+    #[serde(default)]
+    pub player_only_dot: bool,
+}
+
+/// Laser sight is created only for weapons that have it in their definitions, it is shown
+/// while owner of a weapon aims, if it wasn't turned off.
 pub struct LaserSight {
     ray: Handle<Node>,
     tip: Handle<Node>,
+    enabled: bool,
+}
+
+impl Default for LaserSight {
+    fn default() -> Self {
+        Self {
+            ray: Default::default(),
+            tip: Default::default(),
+            enabled: true,
+        }
+    }
 }
 
 impl LaserSight {
-    pub fn new(scene: &mut Scene, resource_manager: ResourceManager) -> Self {
-        let color = Color::from_rgba(0, 162, 232, 200);
+    pub fn new(
+        scene: &mut Scene,
+        resource_manager: ResourceManager,
+        definition: Option<&LaserSightDefinition>,
+    ) -> Self {
+        let definition = match definition {
+            Some(definition) => definition,
+            None => return Default::default(),
+        };
+
+        let (r, g, b, a) = definition.color;
+        let color = Color::from_rgba(r, g, b, a);
 
         let ray = MeshBuilder::new(BaseBuilder::new().with_visibility(false))
             .with_surfaces(vec![SurfaceBuilder::new(Arc::new(RwLock::new(
@@ -145,7 +182,11 @@ impl LaserSight {
         .with_size(0.025)
         .build(&mut scene.graph);
 
-        Self { ray, tip }
+        Self {
+            ray,
+            tip,
+            enabled: true,
+        }
     }
 
     pub fn update(
@@ -155,6 +196,10 @@ impl LaserSight {
         direction: Vector3<f32>,
         ignore_collider: ColliderHandle,
     ) {
+        if self.ray.is_none() {
+            return;
+        }
+
         let mut intersections = ArrayVec::<[_; 64]>::new();
 
         let ray = &mut scene.graph[self.ray];
@@ -185,9 +230,28 @@ impl LaserSight {
         }
     }
 
-    pub fn set_visible(&self, visibility: bool, graph: &mut Graph) {
-        graph[self.tip].set_visibility(visibility);
-        graph[self.ray].set_visibility(visibility);
+    /// Shows or hides the laser sight, it stays hidden if it was turned off. Bots could
+    /// show the beam without the dot.
+    pub fn set_visible(&self, visibility: bool, show_dot: bool, graph: &mut Graph) {
+        if self.ray.is_some() {
+            let visibility = visibility && self.enabled;
+            graph[self.tip].set_visibility(visibility && show_dot);
+            graph[self.ray].set_visibility(visibility);
+        }
+    }
+
+    pub fn switch(&mut self, graph: &mut Graph) {
+        self.enabled = !self.enabled;
+        if !self.enabled {
+            self.set_visible(false, false, graph);
+        }
+    }
+
+    pub fn clean_up(&self, graph: &mut Graph) {
+        if self.ray.is_some() {
+            graph.remove_node(self.ray);
+            graph.remove_node(self.tip);
+        }
     }
 }
 
@@ -197,6 +261,7 @@ impl Visit for LaserSight {
 
         self.ray.visit("Ray", visitor)?;
         self.tip.visit("Tip", visitor)?;
+        self.enabled.visit("Enabled", visitor)?;
 
         visitor.leave_region()
     }
@@ -215,7 +280,10 @@ pub struct Weapon {
     pub definition: &'static WeaponDefinition,
     pub sender: Option<Sender<Message>>,
     flash_light: Handle<Node>,
+    flash_light_battery: f32,
     laser_sight: LaserSight,
+    /// Amount of shots fired in current burst, only weapons with burst use it.
+    burst_shots: u32,
 }
 
 #[derive(Copy, Clone)]
@@ -284,11 +352,7 @@ pub fn ray_hit(
                 if hit_box.collider == hit.collider {
                     is_hitbox_hit = true;
 
-                    let who = match owner {
-                        ProjectileOwner::None => Default::default(),
-                        ProjectileOwner::Actor(actor) => actor,
-                        ProjectileOwner::Weapon(weapon) => weapons[weapon].owner(),
-                    };
+                    let who = owner.actor(weapons, actors);
 
                     // Ignore intersections with owners.
                     if who == actor_handle {
@@ -326,6 +390,107 @@ pub fn ray_hit(
     }
 }
 
+/// Checks intersections of given ray with actors and environment for a shot that penetrates
+/// soft targets and thin surfaces. Returns hits in order along the ray and whether the shot was
+/// stopped by the last of them, every actor is hit at most once.
+pub fn penetrating_ray_hits(
+    begin: Vector3<f32>,
+    end: Vector3<f32>,
+    owner: ProjectileOwner,
+    weapons: &WeaponContainer,
+    actors: &ActorContainer,
+    physics: &mut Physics,
+    penetration: &PenetrationDefinition,
+) -> (Vec<Hit>, bool) {
+    // Back ray of a thick surface hits only its entry side at the very end of the ray.
+    const THICKNESS_EPSILON: f32 = 0.001;
+
+    let ray = Ray::from_two_points(begin, end);
+    let dir = ray.dir.try_normalize(f32::EPSILON).unwrap_or_default();
+    let groups = InteractionGroups::new(0xFFFF, !(CollisionGroups::ActorCapsule as u16));
+
+    let mut query_buffer = Vec::default();
+    physics.cast_ray(
+        RayCastOptions {
+            ray,
+            max_len: ray.dir.norm(),
+            groups,
+            sort_results: true,
+        },
+        &mut query_buffer,
+    );
+
+    let who = owner.actor(weapons, actors);
+    let mut hits = Vec::<Hit>::new();
+    for intersection in query_buffer.iter() {
+        let hit_box = actors.pair_iter().find_map(|(actor_handle, actor)| {
+            actor
+                .hit_boxes
+                .iter()
+                .find(|h| h.collider == intersection.collider)
+                .map(|h| (actor_handle, *h))
+        });
+
+        let stopped = match hit_box {
+            Some((actor, hit_box)) => {
+                // Ignore the owner and other hit boxes of already hit actors.
+                if actor == who || hits.iter().any(|h| h.actor == actor) {
+                    continue;
+                }
+                hits.push(Hit {
+                    actor,
+                    who,
+                    position: intersection.position.coords,
+                    normal: intersection.normal,
+                    collider: intersection.collider,
+                    feature: intersection.feature,
+                    hit_box: Some(hit_box),
+                });
+                false
+            }
+            None => {
+                hits.push(Hit {
+                    actor: Handle::NONE,
+                    who: Handle::NONE,
+                    position: intersection.position.coords,
+                    normal: intersection.normal,
+                    collider: intersection.collider,
+                    feature: intersection.feature,
+                    hit_box: None,
+                });
+                // Cast a ray back from the deepest point that the shot can reach inside the
+                // surface, it hits the surface from the other side if the surface is thin.
+                let entry = intersection.position.coords;
+                let mut back_buffer = Vec::default();
+                physics.cast_ray(
+                    RayCastOptions {
+                        ray: Ray::from_two_points(
+                            entry + dir.scale(penetration.max_thickness),
+                            entry,
+                        ),
+                        max_len: penetration.max_thickness,
+                        groups,
+                        sort_results: true,
+                    },
+                    &mut back_buffer,
+                );
+                !back_buffer.iter().any(|i| {
+                    i.collider == intersection.collider
+                        && i.toi > f32::EPSILON
+                        && i.toi < penetration.max_thickness - THICKNESS_EPSILON
+                })
+            }
+        };
+
+        // A thick surface stops the shot, so does the last hit within penetration budget.
+        if stopped || hits.len() as u32 > penetration.count {
+            return (hits, true);
+        }
+    }
+
+    (hits, false)
+}
+
 #[derive(Copy, Clone, Debug, Deserialize)]
 pub enum WeaponProjectile {
     Projectile(ProjectileKind),
@@ -333,6 +498,13 @@ pub enum WeaponProjectile {
     Ray {
         damage: Damage,
     },
+    /// Multiple rays shot at once in a cone, damage is split evenly between them.
+    Pellets {
+        damage: Damage,
+        count: u32,
+        /// Half-angle of the cone in degrees.
+        spread: f32,
+    },
 }
 
 #[derive(Deserialize)]
@@ -347,6 +519,62 @@ pub struct WeaponDefinition {
     pub ammo_consumption_per_shot: u32,
     pub v_recoil: (f32, f32),
     pub h_recoil: (f32, f32),
+    /// Effect that is created where a shot hits the environment.
+    #[serde(default)]
+    pub impact_effect: EffectKind,
+    /// Type of damage of rays and pellets, projectiles have their own type of damage.
+    #[serde(default)]
+    pub damage_type: DamageType,
+    /// Weapons with charge must be held to charge a shot, the shot is fired on release.
+    #[serde(default)]
+    pub charge: Option<ChargeDefinition>,
+    /// Weapons with burst fire a few shots per trigger pull.
+    #[serde(default)]
+    pub burst: Option<BurstDefinition>,
+    /// Strength (from 0 to 1) of camera shake of the player near the weapon when it fires,
+    /// used by heavy weapons.
+    #[serde(default)]
+    pub camera_shake: f32,
+    #[serde(default)]
+    pub laser_sight: Option<LaserSightDefinition>,
+    /// Rays of weapons with penetration pass through enemies and thin surfaces.
+    #[serde(default)]
+    pub penetration: Option<PenetrationDefinition>,
+}
+
+#[derive(Copy, Clone, Deserialize)]
+pub struct PenetrationDefinition {
+    /// Amount of enemies and surfaces that a ray can pass through.
+    pub count: u32,
+    /// Damage multiplier of each subsequent hit.
+    pub damage_falloff: f32,
+    /// Surfaces thicker than this (in meters) stop a ray.
+    pub max_thickness: f32,
+}
+
+#[derive(Copy, Clone, Deserialize)]
+pub struct BurstDefinition {
+    /// Amount of shots per trigger pull.
+    pub size: u32,
+    /// Interval (in seconds) between shots within a burst, `shoot_interval` is used as
+    /// interval between bursts.
+    pub cadence: f64,
+}
+
+#[derive(Copy, Clone, Deserialize)]
+pub struct ChargeDefinition {
+    /// Time (in seconds) needed to fully charge a shot.
+    pub time: f32,
+    /// Charge (from 0 to 1) below which a shot is not fired at all.
+    pub min_charge: f32,
+    /// Damage multiplier of fully charged shot, damage grows linearly from 1.
+    pub max_damage_scale: f32,
+}
+
+impl ChargeDefinition {
+    pub fn damage_scale(&self, charge: f32) -> f32 {
+        1.0 + (self.max_damage_scale - 1.0) * charge
+    }
 }
 
 impl WeaponDefinition {
@@ -358,14 +586,12 @@ impl WeaponDefinition {
         )
     }
 
-    pub fn gen_v_recoil_angle(&self) -> f32 {
-        rg3d::rand::thread_rng()
-            .gen_range(self.v_recoil.0.to_radians()..self.v_recoil.1.to_radians())
+    pub fn gen_v_recoil_angle(&self, rng: &mut GameRng) -> f32 {
+        rng.gen_range(self.v_recoil.0.to_radians()..self.v_recoil.1.to_radians())
     }
 
-    pub fn gen_h_recoil_angle(&self) -> f32 {
-        rg3d::rand::thread_rng()
-            .gen_range(self.h_recoil.0.to_radians()..self.h_recoil.1.to_radians())
+    pub fn gen_h_recoil_angle(&self, rng: &mut GameRng) -> f32 {
+        rng.gen_range(self.h_recoil.0.to_radians()..self.h_recoil.1.to_radians())
     }
 }
 
@@ -400,7 +626,9 @@ impl Default for Weapon {
             muzzle_flash: Default::default(),
             shot_light: Default::default(),
             flash_light: Default::default(),
+            flash_light_battery: 1.0,
             laser_sight: Default::default(),
+            burst_shots: 0,
         }
     }
 }
@@ -419,13 +647,23 @@ impl Visit for Weapon {
         self.muzzle_flash_timer.visit("MuzzleFlashTimer", visitor)?;
         self.shot_light.visit("ShotLight", visitor)?;
         self.flash_light.visit("FlashLight", visitor)?;
+        self.flash_light_battery
+            .visit("FlashLightBattery", visitor)?;
         self.laser_sight.visit("LaserSight", visitor)?;
+        self.burst_shots.visit("BurstShots", visitor)?;
 
         visitor.leave_region()
     }
 }
 
 impl Weapon {
+    /// Amount of battery charge (in fraction of full charge) drained per second.
+    const FLASH_LIGHT_DRAIN_RATE: f32 = 1.0 / 180.0;
+    /// Amount of battery charge restored per second while flash light is off.
+    const FLASH_LIGHT_RECHARGE_RATE: f32 = 1.0 / 60.0;
+    /// Flash light starts to dim and flicker below this level of charge.
+    const FLASH_LIGHT_LOW_BATTERY: f32 = 0.2;
+
     pub fn get_definition(kind: WeaponKind) -> &'static WeaponDefinition {
         DEFINITIONS.map.get(&kind).unwrap()
     }
@@ -509,7 +747,7 @@ impl Weapon {
             shot_light,
             sender: Some(sender),
             flash_light,
-            laser_sight: LaserSight::new(scene, resource_manager),
+            laser_sight: LaserSight::new(scene, resource_manager, definition.laser_sight.as_ref()),
             ..Default::default()
         }
     }
@@ -517,7 +755,7 @@ impl Weapon {
     pub fn set_visibility(&self, visibility: bool, graph: &mut Graph) {
         graph[self.model].set_visibility(visibility);
         if !visibility {
-            self.laser_sight.set_visible(visibility, graph);
+            self.laser_sight.set_visible(false, false, graph);
         }
     }
 
@@ -546,11 +784,75 @@ impl Weapon {
             }
         }
 
+        self.update_flash_light(&mut scene.graph, dt);
+
         let dir = self.get_shot_direction(&scene.graph);
         let pos = self.get_shot_position(&scene.graph);
         self.laser_sight.update(scene, pos, dir, ignored_collider)
     }
 
+    fn update_flash_light(&mut self, graph: &mut Graph, dt: f32) {
+        if self.flash_light.is_none() {
+            return;
+        }
+
+        let is_on = graph[self.model].visibility() && graph[self.flash_light].visibility();
+
+        if is_on {
+            let prev_battery = self.flash_light_battery;
+            self.flash_light_battery =
+                (self.flash_light_battery - Self::FLASH_LIGHT_DRAIN_RATE * dt).max(0.0);
+
+            if prev_battery >= Self::FLASH_LIGHT_LOW_BATTERY
+                && self.flash_light_battery < Self::FLASH_LIGHT_LOW_BATTERY
+            {
+                if let Some(sender) = self.sender.as_ref() {
+                    sender
+                        .send(Message::PlaySound {
+                            path: PathBuf::from("data/sounds/click.ogg"),
+                            position: graph[self.flash_light].global_position(),
+                            gain: 0.5,
+                            rolloff_factor: 1.0,
+                            radius: 1.0,
+                            caption: Some("[Flash light battery low]".to_owned()),
+                        })
+                        .unwrap();
+                }
+            }
+
+            let flash_light = &mut graph[self.flash_light];
+            if self.flash_light_battery <= 0.0 {
+                flash_light.set_visibility(false);
+            } else {
+                let mut brightness =
+                    0.3 + 0.7 * (self.flash_light_battery / Self::FLASH_LIGHT_LOW_BATTERY).min(1.0);
+
+                // Flicker when battery is running low.
+                let mut rng = rg3d::rand::thread_rng();
+                if self.flash_light_battery < Self::FLASH_LIGHT_LOW_BATTERY && rng.gen_bool(0.1) {
+                    brightness *= rng.gen_range(0.0..0.5);
+                }
+
+                let intensity = (255.0 * brightness) as u8;
+                flash_light
+                    .as_light_mut()
+                    .set_color(Color::opaque(intensity, intensity, intensity));
+            }
+        } else {
+            self.flash_light_battery =
+                (self.flash_light_battery + Self::FLASH_LIGHT_RECHARGE_RATE * dt).min(1.0);
+        }
+    }
+
+    /// Returns charge of the flash light battery in [0; 1] range.
+    pub fn flash_light_battery(&self) -> f32 {
+        self.flash_light_battery
+    }
+
+    pub fn has_flash_light(&self) -> bool {
+        self.flash_light.is_some()
+    }
+
     pub fn get_shot_position(&self, graph: &Graph) -> Vector3<f32> {
         if self.shot_point.is_some() {
             graph[self.shot_point].global_position()
@@ -584,7 +886,10 @@ impl Weapon {
         if self.flash_light.is_some() {
             let flash_light = &mut graph[self.flash_light];
             let enabled = flash_light.visibility();
-            flash_light.set_visibility(!enabled);
+            // Light cannot be turned on with dead battery.
+            if enabled || self.flash_light_battery > 0.0 {
+                flash_light.set_visibility(!enabled);
+            }
         }
     }
 
@@ -592,8 +897,33 @@ impl Weapon {
         &self.laser_sight
     }
 
+    pub fn switch_laser_sight(&mut self, graph: &mut Graph) {
+        self.laser_sight.switch(graph);
+    }
+
+    /// Shows laser sight of the weapon held by a bot, the dot could be reserved for the player.
+    pub fn show_laser_sight_for_bot(&self, visibility: bool, graph: &mut Graph) {
+        let show_dot = self
+            .definition
+            .laser_sight
+            .map_or(false, |laser| !laser.player_only_dot);
+        self.laser_sight.set_visible(visibility, show_dot, graph);
+    }
+
     pub fn can_shoot(&self, time: GameTime) -> bool {
-        time.elapsed - self.last_shot_time >= self.definition.shoot_interval
+        let interval = match self.definition.burst {
+            Some(burst) if self.is_bursting() => burst.cadence,
+            _ => self.definition.shoot_interval,
+        };
+        time.elapsed - self.last_shot_time >= interval
+    }
+
+    /// Returns true if the weapon has started a burst which is not finished yet, the rest of
+    /// the burst is fired regardless of the trigger.
+    pub fn is_bursting(&self) -> bool {
+        self.definition.burst.map_or(false, |burst| {
+            self.burst_shots > 0 && self.burst_shots < burst.size
+        })
     }
 
     pub fn shoot(
@@ -603,9 +933,18 @@ impl Weapon {
         time: GameTime,
         resource_manager: ResourceManager,
         direction: Option<Vector3<f32>>,
+        damage_scale: f32,
+        rng: &mut GameRng,
     ) {
         self.last_shot_time = time.elapsed;
 
+        if let Some(burst) = self.definition.burst {
+            if self.burst_shots >= burst.size {
+                self.burst_shots = 0;
+            }
+            self.burst_shots += 1;
+        }
+
         let position = self.get_shot_position(&scene.graph);
 
         if let Some(random_shot_sound) = self
@@ -622,6 +961,7 @@ impl Weapon {
                     gain: 1.0,
                     rolloff_factor: 5.0,
                     radius: 3.0,
+                    caption: None,
                 })
                 .unwrap();
         }
@@ -663,6 +1003,7 @@ impl Weapon {
                     direction,
                     owner: ProjectileOwner::Weapon(self_handle),
                     initial_velocity: Default::default(),
+                    damage_scale,
                 })
                 .unwrap(),
             WeaponProjectile::Ray { damage } => {
@@ -673,17 +1014,40 @@ impl Weapon {
                         weapon: self_handle,
                         begin: position,
                         end: position + direction.scale(1000.0),
-                        damage,
+                        damage: damage.scale(damage_scale),
+                        damage_type: self.definition.damage_type,
+                        impact_effect: self.definition.impact_effect,
                     })
                     .unwrap();
             }
+            WeaponProjectile::Pellets {
+                damage,
+                count,
+                spread,
+            } => {
+                let damage = damage.scale(damage_scale / count.max(1) as f32);
+                for _ in 0..count {
+                    let direction = random_direction_in_cone(direction, spread.to_radians(), rng);
+                    self.sender
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::ShootRay {
+                            weapon: self_handle,
+                            begin: position,
+                            end: position + direction.scale(1000.0),
+                            damage,
+                            damage_type: self.definition.damage_type,
+                            impact_effect: self.definition.impact_effect,
+                        })
+                        .unwrap();
+                }
+            }
         }
     }
 
     pub fn clean_up(&mut self, scene: &mut Scene) {
         scene.graph.remove_node(self.model);
-        scene.graph.remove_node(self.laser_sight.ray);
-        scene.graph.remove_node(self.laser_sight.tip);
+        self.laser_sight.clean_up(&mut scene.graph);
     }
 }
 
@@ -1,8 +1,9 @@
 use crate::actor::Actor;
 use crate::{
     actor::ActorContainer,
-    effects::EffectKind,
+    effects::{EffectKind, SmokeTrailPool},
     message::Message,
+    status_effect::{StatusEffect, StatusEffectDefinition},
     vector_to_quat,
     weapon::{ray_hit, Hit, Weapon, WeaponContainer},
     GameTime,
@@ -75,6 +76,23 @@ impl Default for ProjectileOwner {
 }
 
 impl ProjectileOwner {
+    /// Returns actor that must be credited for damage dealt by a projectile, `NONE` if the
+    /// owner or its weapon was removed while the projectile was in flight. Damage of such
+    /// projectile is still dealt, but nobody gets a frag for it.
+    pub fn actor(&self, weapons: &WeaponContainer, actors: &ActorContainer) -> Handle<Actor> {
+        let actor = match *self {
+            Self::None => Handle::NONE,
+            Self::Actor(actor) => actor,
+            Self::Weapon(weapon) if weapons.contains(weapon) => weapons[weapon].owner(),
+            Self::Weapon(_) => Handle::NONE,
+        };
+        if actors.contains(actor) {
+            actor
+        } else {
+            Handle::NONE
+        }
+    }
+
     fn id(&self) -> u32 {
         match self {
             Self::None => 0,
@@ -112,6 +130,24 @@ impl Visit for ProjectileOwner {
     }
 }
 
+/// Kind of damage, bots can resist some kinds of damage or be vulnerable to them.
+#[derive(Deserialize, Copy, Clone, Hash, PartialEq, Eq, Debug)]
+pub enum DamageType {
+    Ballistic,
+    Explosive,
+    Energy,
+    /// Bites, claws and other close combat attacks.
+    Melee,
+    /// Hazards of levels, for example lava or toxic gas.
+    Environment,
+}
+
+impl Default for DamageType {
+    fn default() -> Self {
+        Self::Ballistic
+    }
+}
+
 #[derive(Deserialize, Copy, Clone, Debug)]
 pub enum Damage {
     Splash { radius: f32, amount: f32 },
@@ -204,6 +240,14 @@ pub struct Projectile {
     definition: &'static ProjectileDefinition,
     pub sender: Option<Sender<Message>>,
     hits: HashSet<Hit>,
+    /// Smoke emitter that follows the projectile, taken from [`SmokeTrailPool`].
+    smoke_trail: Handle<Node>,
+    /// Multiplier of damage from the definition, charged shots do more damage.
+    damage_scale: f32,
+    /// Whether bots were warned about imminent explosion of the projectile.
+    warned: bool,
+    /// Distance (in meters) that projectile has travelled so far.
+    distance: f32,
 }
 
 impl Default for Projectile {
@@ -221,6 +265,10 @@ impl Default for Projectile {
             definition: Self::get_definition(ProjectileKind::Plasma),
             sender: None,
             hits: Default::default(),
+            smoke_trail: Default::default(),
+            damage_scale: 1.0,
+            warned: false,
+            distance: 0.0,
         }
     }
 }
@@ -229,11 +277,32 @@ impl Default for Projectile {
 pub struct ProjectileDefinition {
     damage: Damage,
     speed: f32,
+    /// Time (in seconds) after which projectile despawns if it hasn't hit anything, for
+    /// bouncing projectiles this is the fuse time.
     lifetime: f32,
+    /// Distance (in meters) after which projectile despawns if it hasn't hit anything,
+    /// `None` means that only lifetime limits the projectile.
+    #[serde(default)]
+    max_range: Option<f32>,
     /// Means that movement of projectile controlled by code, not physics.
     /// However projectile still could have rigid body to detect collisions.
     is_kinematic: bool,
     impact_sound: String,
+    /// Subtitle for impact sound, only important sounds (like explosions) should have it.
+    #[serde(default)]
+    impact_caption: Option<String>,
+    /// Whether projectile leaves a smoke trail behind.
+    #[serde(default)]
+    smoke_trail: bool,
+    #[serde(default)]
+    damage_type: DamageType,
+    /// Status effect that is applied to actors caught by splash damage, for example burning.
+    #[serde(default)]
+    splash_effect: Option<StatusEffectDefinition>,
+    /// Whether projectile bounces off level geometry instead of exploding on contact, such
+    /// projectiles explode when their lifetime (fuse) ends or when they hit an actor.
+    #[serde(default)]
+    bounces: bool,
 }
 
 #[derive(Deserialize, Default)]
@@ -266,6 +335,7 @@ impl Projectile {
         position: Vector3<f32>,
         owner: ProjectileOwner,
         initial_velocity: Vector3<f32>,
+        damage_scale: f32,
         sender: Sender<Message>,
     ) -> Self {
         let definition = Self::get_definition(kind);
@@ -273,7 +343,8 @@ impl Projectile {
         let (model, body) = {
             match &kind {
                 ProjectileKind::Plasma => {
-                    let size = rand::thread_rng().gen_range(0.09..0.12);
+                    // Charged plasma balls are bigger.
+                    let size = rand::thread_rng().gen_range(0.09..0.12) * damage_scale.sqrt();
 
                     let color = Color::opaque(0, 162, 232);
                     let model = SpriteBuilder::new(
@@ -333,10 +404,15 @@ impl Projectile {
             owner,
             definition,
             sender: Some(sender),
+            damage_scale,
             ..Default::default()
         }
     }
 
+    /// Time (in seconds) before explosion of a bouncing projectile when bots are warned
+    /// about it.
+    const WARNING_TIME: f32 = 1.5;
+
     pub fn is_dead(&self) -> bool {
         self.lifetime <= 0.0
     }
@@ -375,8 +451,10 @@ impl Projectile {
         );
 
         if let Some(hit) = ray_hit {
-            self.hits.insert(hit);
-            self.kill();
+            if !self.definition.bounces || hit.actor.is_some() {
+                self.hits.insert(hit);
+                self.kill();
+            }
         }
 
         // Movement of kinematic projectiles are controlled explicitly.
@@ -402,6 +480,12 @@ impl Projectile {
             }
         }
 
+        if self.smoke_trail.is_some() {
+            scene.graph[self.smoke_trail]
+                .local_transform_mut()
+                .set_position(position);
+        }
+
         if let Node::Sprite(sprite) = &mut scene.graph[self.model] {
             sprite.set_rotation(self.rotation_angle);
             self.rotation_angle += 1.5;
@@ -413,69 +497,114 @@ impl Projectile {
 
         self.lifetime -= time.delta;
 
+        self.distance += position.metric_distance(&self.last_position);
+        if let Some(max_range) = self.definition.max_range {
+            if self.distance >= max_range {
+                self.kill();
+            }
+        }
+
+        // Give bots a chance to get away from the explosion.
+        if self.definition.bounces
+            && !self.warned
+            && self.lifetime > 0.0
+            && self.lifetime <= Self::WARNING_TIME
+        {
+            if let Damage::Splash { radius, .. } = self.definition.damage {
+                self.sender
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::GrenadeWarning {
+                        position,
+                        time: self.lifetime,
+                        radius,
+                    })
+                    .unwrap();
+            }
+            self.warned = true;
+        }
+
         if self.lifetime <= 0.0 {
-            let (pos, normal, effect_kind) = ray_hit.map_or_else(
-                || {
-                    (
-                        self.get_position(&scene.graph),
-                        Vector3::y(),
-                        EffectKind::BulletImpact,
-                    )
-                },
-                |h| {
-                    (
-                        h.position,
-                        h.normal,
-                        if h.actor.is_some() {
-                            EffectKind::BloodSpray
-                        } else {
-                            EffectKind::BulletImpact
-                        },
-                    )
-                },
-            );
-
-            self.sender
-                .as_ref()
-                .unwrap()
-                .send(Message::CreateEffect {
-                    kind: effect_kind,
-                    position: pos,
-                    orientation: vector_to_quat(normal),
-                })
-                .unwrap();
-
-            self.sender
-                .as_ref()
-                .unwrap()
-                .send(Message::PlaySound {
-                    path: PathBuf::from(self.definition.impact_sound.clone()),
-                    position: pos,
-                    gain: 1.0,
-                    rolloff_factor: 4.0,
-                    radius: 3.0,
-                })
-                .unwrap();
+            // Fuse of a bouncing projectile has run out without hitting anyone.
+            if self.definition.bounces && self.hits.is_empty() {
+                if let Damage::Splash { radius, amount } =
+                    self.definition.damage.scale(self.damage_scale)
+                {
+                    let who = self.owner.actor(weapons, actors);
+                    self.apply_splash_damage(amount, radius, position, who);
+                }
+            }
+
+            if ray_hit.is_none() && !self.definition.bounces {
+                // Projectile has run out of lifetime or range without hitting anything, it
+                // just fizzles out.
+                self.sender
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::CreateEffect {
+                        kind: EffectKind::Smoke,
+                        position,
+                        orientation: vector_to_quat(self.dir),
+                    })
+                    .unwrap();
+            } else {
+                // Projectiles fired from a weapon use its impact effect.
+                let impact_effect = match self.owner {
+                    ProjectileOwner::Weapon(weapon) if weapons.contains(weapon) => {
+                        weapons[weapon].definition.impact_effect
+                    }
+                    _ => EffectKind::BulletImpact,
+                };
+
+                let (pos, normal, effect_kind) = ray_hit.map_or_else(
+                    || (self.get_position(&scene.graph), Vector3::y(), impact_effect),
+                    |h| {
+                        (
+                            h.position,
+                            h.normal,
+                            if h.actor.is_some() {
+                                EffectKind::BloodSpray
+                            } else {
+                                impact_effect
+                            },
+                        )
+                    },
+                );
+
+                self.sender
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::CreateEffect {
+                        kind: effect_kind,
+                        position: pos,
+                        orientation: vector_to_quat(normal),
+                    })
+                    .unwrap();
+
+                self.sender
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::PlaySound {
+                        path: PathBuf::from(self.definition.impact_sound.clone()),
+                        position: pos,
+                        gain: 1.0,
+                        rolloff_factor: 4.0,
+                        radius: 3.0,
+                        caption: self.definition.impact_caption.clone(),
+                    })
+                    .unwrap();
+            }
         }
 
         for hit in self.hits.drain() {
             let damage = self
                 .definition
                 .damage
-                .scale(hit.hit_box.map_or(1.0, |h| h.damage_factor));
+                .scale(hit.hit_box.map_or(1.0, |h| h.damage_factor) * self.damage_scale);
 
             match damage {
                 Damage::Splash { radius, amount } => {
-                    self.sender
-                        .as_ref()
-                        .unwrap()
-                        .send(Message::ApplySplashDamage {
-                            amount,
-                            radius,
-                            center: position,
-                            who: hit.who,
-                        })
-                        .unwrap();
+                    self.apply_splash_damage(amount, radius, position, hit.who);
                 }
                 Damage::Point(amount) => {
                     self.sender
@@ -485,6 +614,9 @@ impl Projectile {
                             actor: hit.actor,
                             who: hit.who,
                             amount,
+                            damage_type: self.definition.damage_type,
+                            critical: hit.hit_box.map_or(false, |h| h.critical),
+                            hit_box: hit.hit_box.map(|h| h.collider),
                         })
                         .unwrap();
                 }
@@ -494,11 +626,40 @@ impl Projectile {
         self.last_position = position;
     }
 
+    fn apply_splash_damage(
+        &self,
+        amount: f32,
+        radius: f32,
+        center: Vector3<f32>,
+        who: Handle<Actor>,
+    ) {
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(Message::ApplySplashDamage {
+                amount,
+                radius,
+                center,
+                who,
+                damage_type: self.definition.damage_type,
+                effect: self
+                    .definition
+                    .splash_effect
+                    .as_ref()
+                    .map(StatusEffect::from_definition),
+            })
+            .unwrap();
+    }
+
     pub fn get_position(&self, graph: &Graph) -> Vector3<f32> {
         graph[self.model].global_position()
     }
 
-    fn clean_up(&mut self, scene: &mut Scene) {
+    fn clean_up(&mut self, scene: &mut Scene, smoke_trails: &mut SmokeTrailPool) {
+        if self.smoke_trail.is_some() {
+            smoke_trails.release(&mut scene.graph, self.smoke_trail);
+            self.smoke_trail = Handle::NONE;
+        }
         if self.body.is_some() {
             scene.physics.remove_body(self.body);
         }
@@ -519,13 +680,30 @@ impl Visit for Projectile {
         }
 
         self.definition = Self::get_definition(self.kind);
+        // Remaining lifetime (fuse time for grenades).
         self.lifetime.visit("Lifetime", visitor)?;
+        self.warned.visit("Warned", visitor)?;
+        self.distance.visit("Distance", visitor)?;
         self.dir.visit("Direction", visitor)?;
         self.model.visit("Model", visitor)?;
         self.body.visit("Body", visitor)?;
         self.rotation_angle.visit("RotationAngle", visitor)?;
         self.initial_velocity.visit("InitialVelocity", visitor)?;
         self.owner.visit("Owner", visitor)?;
+        // Must be saved, otherwise ray check on first frame after load will be done from
+        // origin and may hit something that is not on the projectile's path.
+        self.last_position.visit("LastPosition", visitor)?;
+        self.smoke_trail.visit("SmokeTrail", visitor)?;
+        self.damage_scale.visit("DamageScale", visitor)?;
+
+        // Position and velocity are stored in the rigid body (or in the model for
+        // projectiles without bodies) and saved together with the scene. Hits are not
+        // saved, they are gathered and handled within a single update.
+        //
+        // Effects are not owned by projectiles: particle systems of effects are scene nodes
+        // with their own lifetime that is saved together with the scene, so effects that
+        // were alive at the moment of saving play to the end after loading. Pooled effects
+        // and smoke trails are saved by their pools.
 
         visitor.leave_region()
     }
@@ -534,14 +712,28 @@ impl Visit for Projectile {
 #[derive(Default)]
 pub struct ProjectileContainer {
     pool: Pool<Projectile>,
+    smoke_trails: SmokeTrailPool,
 }
 
 impl ProjectileContainer {
     pub fn new() -> Self {
-        Self { pool: Pool::new() }
+        Self {
+            pool: Pool::new(),
+            smoke_trails: Default::default(),
+        }
     }
 
-    pub fn add(&mut self, projectile: Projectile) -> Handle<Projectile> {
+    pub fn add(
+        &mut self,
+        mut projectile: Projectile,
+        graph: &mut Graph,
+        resource_manager: ResourceManager,
+    ) -> Handle<Projectile> {
+        if projectile.definition.smoke_trail {
+            projectile.smoke_trail =
+                self.smoke_trails
+                    .acquire(graph, resource_manager, projectile.last_position);
+        }
         self.pool.spawn(projectile)
     }
 
@@ -549,6 +741,10 @@ impl ProjectileContainer {
         self.pool.iter_mut()
     }
 
+    pub fn count(&self) -> usize {
+        self.pool.alive_count()
+    }
+
     pub fn update(
         &mut self,
         scene: &mut Scene,
@@ -559,10 +755,12 @@ impl ProjectileContainer {
         for projectile in self.pool.iter_mut() {
             projectile.update(scene, actors, weapons, time);
             if projectile.is_dead() {
-                projectile.clean_up(scene);
+                projectile.clean_up(scene, &mut self.smoke_trails);
             }
         }
 
+        self.smoke_trails.update(time.delta);
+
         self.pool.retain(|proj| !proj.is_dead());
     }
 }
@@ -572,7 +770,88 @@ impl Visit for ProjectileContainer {
         visitor.enter_region(name)?;
 
         self.pool.visit("Pool", visitor)?;
+        self.smoke_trails.visit("SmokeTrails", visitor)?;
 
         visitor.leave_region()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{leader_board::LeaderBoard, utils::visit_round_trip};
+
+    #[test]
+    fn projectile_of_removed_owner_credits_nobody() {
+        let mut actors = ActorContainer::new();
+        let weapons = WeaponContainer::default();
+        let shooter = actors.add(Actor::default());
+        let victim = actors.add(Actor::default());
+        let owner = ProjectileOwner::Actor(shooter);
+        assert_eq!(owner.actor(&weapons, &actors), shooter);
+
+        // Shooter is removed while the projectile is in flight, damage is still dealt (it
+        // does not depend on the attacker), but the kill is credited to nobody.
+        actors.free(shooter);
+        let who = owner.actor(&weapons, &actors);
+        assert_eq!(who, Handle::NONE);
+        assert_eq!(
+            actors.resolve_damage(victim, who, false),
+            Some(Handle::NONE)
+        );
+
+        let mut leader_board = LeaderBoard::default();
+        leader_board.on_actor_died(victim, who);
+        assert_eq!(leader_board.score_of(victim).deaths, 1);
+        assert_eq!(leader_board.score_of(shooter).kills, 0);
+        assert_eq!(leader_board.score_of(Handle::NONE).kills, 0);
+    }
+
+    #[test]
+    fn projectile_of_removed_weapon_credits_nobody() {
+        let actors = ActorContainer::new();
+        let weapons = WeaponContainer::default();
+        let owner = ProjectileOwner::Weapon(Handle::new(1, 1));
+        assert_eq!(owner.actor(&weapons, &actors), Handle::NONE);
+        assert_eq!(ProjectileOwner::None.actor(&weapons, &actors), Handle::NONE);
+    }
+
+    #[test]
+    fn live_projectile_survives_save_and_load() {
+        let mut projectile = Projectile {
+            kind: ProjectileKind::Grenade,
+            model: Handle::new(3, 1),
+            dir: Vector3::new(0.0, 0.6, 0.8),
+            lifetime: 1.25,
+            rotation_angle: 0.5,
+            owner: ProjectileOwner::Actor(Handle::new(2, 4)),
+            initial_velocity: Vector3::new(1.0, 2.0, 3.0),
+            last_position: Vector3::new(4.0, 5.0, 6.0),
+            smoke_trail: Handle::new(7, 1),
+            damage_scale: 1.5,
+            warned: true,
+            distance: 12.0,
+            ..Default::default()
+        };
+
+        let loaded = visit_round_trip(&mut projectile, "Projectile");
+
+        assert_eq!(loaded.kind, ProjectileKind::Grenade);
+        assert_eq!(loaded.model, projectile.model);
+        assert_eq!(loaded.dir, projectile.dir);
+        assert_eq!(loaded.lifetime, 1.25);
+        assert_eq!(loaded.rotation_angle, 0.5);
+        assert_eq!(loaded.owner, projectile.owner);
+        assert_eq!(loaded.initial_velocity, projectile.initial_velocity);
+        assert_eq!(loaded.last_position, projectile.last_position);
+        assert_eq!(loaded.smoke_trail, projectile.smoke_trail);
+        assert_eq!(loaded.damage_scale, 1.5);
+        assert!(loaded.warned);
+        assert_eq!(loaded.distance, 12.0);
+        // Definition is restored from the kind.
+        assert!(std::ptr::eq(
+            loaded.definition,
+            Projectile::get_definition(ProjectileKind::Grenade)
+        ));
+    }
+}